@@ -0,0 +1,142 @@
+struct AssetIndexApiService {}
+
+pub fn service() -> Arc<dyn ApiService> {
+    Arc::new(AssetIndexApiService {})
+}
+
+impl ApiService for AssetIndexApiService {
+    fn name(&self) -> &'static str {
+        "assetindex"
+    }
+
+    fn routes(&self) -> Vec<ApiRoute> {
+        use ApiType::*;
+        const PAGE: &str = "from 1";
+        const LIMIT: &str = "20 by default, 200 at most";
+        vec![
+            ApiRoute::get("/query/asset/list", query_asset_list)
+                .summary("Indexed assets with metadata, supply and holder count")
+                .param("page", Integer, PAGE)
+                .param("limit", Integer, LIMIT)
+                .param("desc", Boolean, "newest first")
+                .returns("total", Integer, "")
+                .returns("list", Array, ""),
+            ApiRoute::get("/query/asset/holders", query_asset_holders)
+                .summary("Holder count and top holders of an asset")
+                .param_must("serial", Integer, "")
+                .param("limit", Integer, LIMIT)
+                .returns("holders", Integer, "")
+                .returns("list", Array, "largest first"),
+            ApiRoute::get("/query/asset/transfers", query_asset_transfers)
+                .summary("Indexed movements of an asset or an address")
+                .param("serial", Integer, "asset serial")
+                .param("address", String, "instead of serial, movements to or from it")
+                .param("page", Integer, PAGE)
+                .param("limit", Integer, LIMIT)
+                .param("desc", Boolean, "newest first")
+                .returns("total", Integer, "")
+                .returns("list", Array, ""),
+        ]
+    }
+}
+
+fn api_error(errmsg: &str) -> ApiResponse {
+    ApiResponse::json(json!({"ret":1,"err":errmsg}).to_string())
+}
+
+fn api_page(req: &ApiRequest) -> (usize, usize, bool) {
+    let page = req.query_usize("page", 1);
+    let limit = req.query_usize("limit", 20).clamp(1, 200);
+    let desc = matches!(req.query("desc"), Some("true" | "1"));
+    (page, limit, desc)
+}
+
+fn api_with_range(index: &AssetIndex, mut data: Value) -> ApiResponse {
+    let (start, height) = index.range();
+    data["ret"] = json!(0);
+    data["indexed"] = json!({"start":start,"height":height});
+    ApiResponse::json(data.to_string())
+}
+
+fn readable_or_null(addr: &Address) -> Value {
+    maybe!(*addr == ADDRESS_ZERO, Value::Null, json!(addr.to_readable()))
+}
+
+fn query_asset_list(_: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let index = match asset_index() {
+        Ok(i) => i,
+        Err(e) => return api_error(&e),
+    };
+    let (page, limit, desc) = api_page(&req);
+    let (total, infos) = index.assets(page, limit, desc);
+    let list: Vec<Value> = infos
+        .iter()
+        .map(|a| {
+            let m = &a.smelt;
+            let serial = m.serial.uint();
+            json!({
+                "serial": serial,
+                "ticket": m.ticket.to_readable_or_hex(),
+                "name": m.name.to_readable_or_hex(),
+                "decimal": m.decimal.uint(),
+                "issuer": m.issuer.to_readable(),
+                "supply": m.supply.uint(),
+                "holders": index.holders(serial, 0).0,
+                "height": a.height.uint(),
+                "tx": maybe!(a.tx == Hash::default(), Value::Null, json!(a.tx.to_hex())),
+            })
+        })
+        .collect();
+    api_with_range(index, json!({"total":total,"list":list}))
+}
+
+fn query_asset_holders(_: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let index = match asset_index() {
+        Ok(i) => i,
+        Err(e) => return api_error(&e),
+    };
+    let serial = req.query_u64("serial", 0);
+    if index.asset(serial).is_none() {
+        return api_error(&format!("asset {} not indexed", serial));
+    }
+    let (_, limit, _) = api_page(&req);
+    let (holders, top) = index.holders(serial, limit);
+    let list: Vec<Value> = top
+        .iter()
+        .map(|(addr, amount)| json!({"address":addr.to_readable(),"amount":amount}))
+        .collect();
+    api_with_range(index, json!({"serial":serial,"holders":holders,"list":list}))
+}
+
+fn query_asset_transfers(_: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let index = match asset_index() {
+        Ok(i) => i,
+        Err(e) => return api_error(&e),
+    };
+    let pg = api_page(&req);
+    let (total, trs) = match (req.query("address"), req.query("serial")) {
+        (Some(a), _) if !a.is_empty() => {
+            let Ok(addr) = Address::from_readable(a) else {
+                return api_error("address format invalid");
+            };
+            index.address_transfers(&addr, pg.0, pg.1, pg.2)
+        }
+        (_, Some(_)) => index.transfers(req.query_u64("serial", 0), pg.0, pg.1, pg.2),
+        _ => return api_error("serial or address must be given"),
+    };
+    let list: Vec<Value> = trs
+        .iter()
+        .map(|t| {
+            json!({
+                "height": t.height.uint(),
+                "tx": maybe!(t.tx == Hash::default(), Value::Null, json!(t.tx.to_hex())),
+                "kind": asset_act_name(t.kind.uint()),
+                "serial": t.serial.uint(),
+                "from": readable_or_null(&t.from),
+                "to": readable_or_null(&t.to),
+                "amount": t.amount.uint(),
+            })
+        })
+        .collect();
+    api_with_range(index, json!({"total":total,"list":list}))
+}
@@ -0,0 +1,325 @@
+
+static ASSET_INDEX: OnceLock<AssetIndex> = OnceLock::new();
+
+/// Install the asset index DB. Without it nothing is recorded and the index
+/// routes answer an error.
+pub fn configure_asset_index(db: Box<dyn DiskDB>) -> Rerr {
+    let index = AssetIndex::open(db)?;
+    let _ = ASSET_INDEX.set(index);
+    Ok(())
+}
+
+pub fn asset_index() -> Ret<&'static AssetIndex> {
+    ASSET_INDEX.get().ok_or_else(|| "asset index not enabled".to_owned())
+}
+
+pub const ASSET_ACT_CREATE: u8 = 1;
+pub const ASSET_ACT_TRANSFER: u8 = 2;
+pub const ASSET_ACT_TEX: u8 = 3;
+pub const ASSET_ACT_CONTRACT: u8 = 4;
+
+// `CoreState` slots of `balance` and `asset`, the first byte of their keys.
+const BALANCE_STATE_PREFIX: u8 = 11;
+const ASSET_STATE_PREFIX: u8 = 17;
+
+pub fn asset_act_name(kind: u8) -> &'static str {
+    match kind {
+        ASSET_ACT_CREATE => "create",
+        ASSET_ACT_TRANSFER => "transfer",
+        ASSET_ACT_TEX => "tex",
+        ASSET_ACT_CONTRACT => "contract",
+        _ => "unknown",
+    }
+}
+
+// A registered asset and where it was created, zeros when it was read in
+// from the state.
+combi_struct!{ AssetIndexInfo,
+    height : BlockHeight
+    tx     : Hash
+    smelt  : AssetSmelt
+}
+
+// One movement of an asset. `from` is zero for a create or a TEX get, `to`
+// for a TEX pay, as those go through the settlement ledger. A `contract`
+// movement is a balance change no action explains, from or to zero.
+combi_struct!{ AssetIndexTransfer,
+    height : BlockHeight
+    tx     : Hash
+    kind   : Uint1
+    serial : Fold64
+    from   : Address
+    to     : Address
+    amount : Fold64
+}
+
+/// What the actions of one block say about asset movements, in order.
+/// Moves inside AST branches may not have run.
+#[derive(Clone, Debug, Default)]
+pub struct AssetTouches {
+    pub creates: Vec<(Hash, AssetSmelt)>,
+    pub moves: Vec<(Hash, u8, AssetAmt, Address, Address)>, // kind, amount, from, to
+    pub branch_moves: Vec<(Hash, u8, AssetAmt, Address, Address)>,
+}
+
+/// The asset state one block wrote: registry entries, and the asset
+/// holdings of every address whose balance was written.
+#[derive(Clone, Debug, Default)]
+pub struct AssetWrites {
+    pub smelts: Vec<AssetSmelt>,
+    pub balances: Vec<(Address, Vec<(u64, u64)>)>,
+}
+
+/// Balances of one asset, also ranked by amount.
+#[derive(Default)]
+struct AssetHolders {
+    balances: HashMap<Address, u64>,
+    ranked: BTreeSet<(Reverse<u64>, [u8; Address::SIZE])>,
+}
+
+impl AssetHolders {
+    fn set(&mut self, addr: &Address, amount: u64) {
+        if let Some(old) = self.balances.remove(addr) {
+            self.ranked.remove(&(Reverse(old), addr.to_array()));
+        }
+        if amount > 0 {
+            self.balances.insert(*addr, amount);
+            self.ranked.insert((Reverse(amount), addr.to_array()));
+        }
+    }
+}
+
+/*
+    Keys:
+      height                   last indexed block
+      start                    first indexed block
+      b                        set once the assets in state were read in
+      s ++ serial              AssetIndexInfo
+      l                        registered count, Uint4
+      l ++ seq                 serial, in order of creation
+      h ++ serial ++ address   balance of a holder, Fold64
+      t ++ serial              transfer count, Uint4
+      t ++ serial ++ seq       AssetIndexTransfer
+      a ++ address             transfer count, Uint4
+      a ++ address ++ seq      AssetIndexTransfer
+*/
+pub struct AssetIndex {
+    db: Box<dyn DiskDB>,
+    holders: Mutex<HashMap<u64, AssetHolders>>,
+}
+
+impl AssetIndex {
+    /// Open the index, loading the holders of every asset into memory.
+    pub fn open(db: Box<dyn DiskDB>) -> Ret<Self> {
+        const HK: usize = 1 + 8 + Address::SIZE;
+        let mut holders: HashMap<u64, AssetHolders> = HashMap::new();
+        db.for_each(&mut |k, v| {
+            if k.len() == HK && k[0] == b'h' {
+                let serial = u64::from_be_bytes(k[1..9].try_into().unwrap());
+                let addr = Address::from(<[u8; Address::SIZE]>::try_from(&k[9..]).unwrap());
+                holders.entry(serial).or_default().set(&addr, Fold64::must(v).uint());
+            }
+            true
+        })?;
+        Ok(Self { db, holders: Mutex::new(holders) })
+    }
+
+    /// First and last indexed block heights, zeros when nothing is indexed.
+    pub fn range(&self) -> (u64, u64) {
+        side_range(self.db.as_ref())
+    }
+
+    /// Whether the assets and holders in state were read in by `backfill`.
+    pub fn backfilled(&self) -> bool {
+        self.db.read(b"b").is_some()
+    }
+
+    /// Read the registry and every holder from the state disk, as it is
+    /// after block `height`, replacing the holders kept so far. Assets not
+    /// seen created get a zero height and transaction. Runs once; block
+    /// `height` itself gets no transfers.
+    pub fn backfill(&self, height: u64, disk: &dyn DiskDB) -> Rerr {
+        if self.backfilled() {
+            return Ok(());
+        }
+        let mut writes = AssetWrites::default();
+        disk.for_each(&mut |k, v| {
+            state_asset_write(&mut writes, k, Some(v));
+            true
+        })?;
+        let mut batch = SideBatch::new(self.db.as_ref());
+        for smelt in &writes.smelts {
+            register(&mut batch, &BlockHeight::from(0), &Hash::default(), smelt);
+        }
+        let mut fresh: HashMap<u64, AssetHolders> = HashMap::new();
+        for (addr, list) in &writes.balances {
+            for (serial, amount) in list {
+                fresh.entry(*serial).or_default().set(addr, *amount);
+            }
+        }
+        let mut holders = self.holders.lock().unwrap();
+        for (serial, h) in holders.iter() {
+            for addr in h.balances.keys() {
+                batch.del(key_holder(*serial, addr));
+            }
+        }
+        for (serial, h) in &fresh {
+            for (addr, amount) in &h.balances {
+                batch.put(key_holder(*serial, addr), Fold64::from(*amount).unwrap_or_default().serialize());
+            }
+        }
+        batch.put(b"b".to_vec(), vec![1]);
+        let (_, last) = self.range();
+        batch.commit(height.max(last));
+        *holders = fresh;
+        Ok(())
+    }
+
+    /// Record block `height` from the asset state it wrote. Holders come
+    /// from `writes`; movements from `touches`, a branch move only when
+    /// both of its addresses changed that asset, and what the actions do
+    /// not explain as `contract`. Blocks at or below the last indexed are
+    /// skipped.
+    pub fn apply(&self, height: u64, touches: &AssetTouches, writes: &AssetWrites) {
+        let (_, last) = self.range();
+        if last > 0 && height <= last {
+            return;
+        }
+        let mut holders = self.holders.lock().unwrap();
+        let mut batch = SideBatch::new(self.db.as_ref());
+        let hei = BlockHeight::from(height);
+        // balances that changed, as (serial, address) -> (before, after)
+        let mut changed: BTreeMap<(u64, [u8; Address::SIZE]), (u64, u64)> = BTreeMap::new();
+        for (addr, list) in &writes.balances {
+            let mut serials: BTreeSet<u64> = list.iter().map(|(s, _)| *s).collect();
+            serials.extend(holders.iter().filter(|(_, h)| h.balances.contains_key(addr)).map(|(s, _)| *s));
+            for serial in serials {
+                let old = holders.get(&serial).and_then(|h| h.balances.get(addr)).copied().unwrap_or(0);
+                let new = list.iter().find(|(s, _)| *s == serial).map_or(0, |(_, n)| *n);
+                if old != new {
+                    changed.insert((serial, addr.to_array()), (old, new));
+                }
+            }
+        }
+        let mut net: HashMap<(u64, [u8; Address::SIZE]), i128> = HashMap::new();
+        let record = |batch: &mut SideBatch, net: &mut HashMap<(u64, [u8; Address::SIZE]), i128>, tx: &Hash, kind: u8, amt: &AssetAmt, from: &Address, to: &Address| {
+            let trs = AssetIndexTransfer {
+                height: hei,
+                tx: *tx,
+                kind: Uint1::from(kind),
+                serial: amt.serial,
+                from: *from,
+                to: *to,
+                amount: amt.amount,
+            }
+            .serialize();
+            batch.push(b"t", &amt.serial.uint().to_be_bytes(), trs.clone());
+            let n = amt.amount.uint() as i128;
+            for (adr, sign) in [(from, -1), (to, 1)] {
+                if *adr != ADDRESS_ZERO {
+                    batch.push(b"a", adr.as_ref(), trs.clone());
+                    *net.entry((amt.serial.uint(), adr.to_array())).or_default() += sign * n;
+                }
+            }
+        };
+        for smelt in &writes.smelts {
+            let serial = smelt.serial.uint();
+            if batch.get(&key_serial(b"s", serial)).is_some() {
+                register(&mut batch, &hei, &Hash::default(), smelt);
+                continue;
+            }
+            let tx = touches.creates.iter().find(|(_, m)| m.serial == smelt.serial).map_or(Hash::default(), |c| c.0);
+            register(&mut batch, &hei, &tx, smelt);
+            let amt = AssetAmt { serial: smelt.serial, amount: smelt.supply };
+            record(&mut batch, &mut net, &tx, ASSET_ACT_CREATE, &amt, &ADDRESS_ZERO, &smelt.issuer);
+        }
+        let ran = |amt: &AssetAmt, adr: &Address| {
+            *adr == ADDRESS_ZERO || changed.contains_key(&(amt.serial.uint(), adr.to_array()))
+        };
+        for (tx, kind, amt, from, to) in &touches.moves {
+            record(&mut batch, &mut net, tx, *kind, amt, from, to);
+        }
+        for (tx, kind, amt, from, to) in &touches.branch_moves {
+            if ran(amt, from) && ran(amt, to) {
+                record(&mut batch, &mut net, tx, *kind, amt, from, to);
+            }
+        }
+        for ((serial, adr), (old, new)) in &changed {
+            let addr = Address::from(*adr);
+            let rest = (*new as i128 - *old as i128) - net.get(&(*serial, *adr)).copied().unwrap_or(0);
+            if rest != 0 {
+                let amt = AssetAmt {
+                    serial: Fold64::from(*serial).unwrap_or_default(),
+                    amount: Fold64::from(rest.unsigned_abs() as u64).unwrap_or_default(),
+                };
+                let (from, to) = maybe!(rest > 0, (ADDRESS_ZERO, addr), (addr, ADDRESS_ZERO));
+                record(&mut batch, &mut net, &Hash::default(), ASSET_ACT_CONTRACT, &amt, &from, &to);
+            }
+            let k = key_holder(*serial, &addr);
+            match new {
+                0 => batch.del(k),
+                n => batch.put(k, Fold64::from(*n).unwrap_or_default().serialize()),
+            }
+        }
+        batch.commit(height);
+        for ((serial, adr), (_, new)) in &changed {
+            holders.entry(*serial).or_default().set(&Address::from(*adr), *new);
+        }
+    }
+
+    /// Page `page` (from 1) of registered assets, in order of creation.
+    pub fn assets(&self, page: usize, limit: usize, desc: bool) -> (usize, Vec<AssetIndexInfo>) {
+        let (total, serials) = side_page(self.db.as_ref(), b"l", &[], (page, limit, desc), |v| {
+            u64::from_be_bytes(v.try_into().unwrap_or_default())
+        });
+        let list = serials.into_iter().filter_map(|s| self.asset(s)).collect();
+        (total, list)
+    }
+
+    pub fn asset(&self, serial: u64) -> Option<AssetIndexInfo> {
+        self.db.read(&key_serial(b"s", serial)).map(|v| AssetIndexInfo::must(&v))
+    }
+
+    /// Holder count and the `limit` largest holders of an asset.
+    pub fn holders(&self, serial: u64, limit: usize) -> (usize, Vec<(Address, u64)>) {
+        let holders = self.holders.lock().unwrap();
+        let Some(h) = holders.get(&serial) else {
+            return (0, vec![]);
+        };
+        let top = h.ranked.iter().take(limit).map(|(n, a)| (Address::must(a), n.0)).collect();
+        (h.balances.len(), top)
+    }
+
+    /// Page `page` (from 1) of the movements of an asset.
+    pub fn transfers(&self, serial: u64, page: usize, limit: usize, desc: bool) -> (usize, Vec<AssetIndexTransfer>) {
+        side_page(self.db.as_ref(), b"t", &serial.to_be_bytes(), (page, limit, desc), AssetIndexTransfer::must)
+    }
+
+    /// Page `page` (from 1) of the asset movements to or from `addr`.
+    pub fn address_transfers(&self, addr: &Address, page: usize, limit: usize, desc: bool) -> (usize, Vec<AssetIndexTransfer>) {
+        side_page(self.db.as_ref(), b"a", addr.as_ref(), (page, limit, desc), AssetIndexTransfer::must)
+    }
+}
+
+/// Add `smelt` to the registry, or only refresh its metadata when it is
+/// already there.
+fn register(batch: &mut SideBatch, hei: &BlockHeight, tx: &Hash, smelt: &AssetSmelt) {
+    let serial = smelt.serial.uint();
+    let k = key_serial(b"s", serial);
+    let info = match batch.get(&k) {
+        Some(v) => AssetIndexInfo { smelt: smelt.clone(), ..AssetIndexInfo::must(&v) },
+        None => {
+            batch.push(b"l", &[], serial.to_be_bytes().to_vec());
+            AssetIndexInfo { height: *hei, tx: *tx, smelt: smelt.clone() }
+        }
+    };
+    batch.put(k, info.serialize());
+}
+
+fn key_serial(pre: &[u8], serial: u64) -> Vec<u8> {
+    [pre, &serial.to_be_bytes()].concat()
+}
+
+fn key_holder(serial: u64, addr: &Address) -> Vec<u8> {
+    [b"h".as_slice(), &serial.to_be_bytes(), addr.as_ref()].concat()
+}
@@ -0,0 +1,35 @@
+/*
+    Asset registry and holder index.
+
+    A local, non-consensus side DB fed by `Scaner::roll`. The chain engine
+    rolls only blocks that became the root of its block tree, which are
+    never reorganized, so the index only grows and needs no undo. For every
+    asset it keeps the metadata, the movements and the balance of each
+    holder. Registry entries and balances come from the block's state
+    writes, so changes inside AST branches and by contracts are seen;
+    movements come from the actions, and what they do not explain is kept
+    as a `contract` movement. Holders are also kept in memory ranked by
+    balance. When first enabled, the assets and holders already in state
+    are read in; movements start at that block.
+*/
+use std::cmp::Reverse;
+use std::collections::*;
+use std::sync::*;
+
+use basis::component::*;
+use basis::interface::*;
+use field::*;
+use mint::action::*;
+use protocol::action::*;
+use protocol::tex::*;
+use serde_json::{Value, json};
+use sys::*;
+
+use crate::sidedb::*;
+
+include! {"index.rs"}
+include! {"scan.rs"}
+include! {"api.rs"}
+
+#[cfg(test)]
+include! {"tests.rs"}
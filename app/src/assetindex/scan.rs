@@ -0,0 +1,147 @@
+
+/// Record the asset movements one action of `tx` names by its own fields.
+/// AST containers and contract calls name none themselves.
+pub fn action_asset_moves(tx: &dyn Transaction, act: &dyn Action, touches: &mut AssetTouches) {
+    let hx = tx.hash();
+    let main = tx.main();
+    let addrs = tx.addrs();
+    let real = |a: &AddrOrPtr| a.real(&addrs).ok();
+    let mut trs = |from: Option<Address>, to: Option<Address>, amt: &AssetAmt| {
+        if let (Some(from), Some(to)) = (from, to) {
+            touches.moves.push((hx, ASSET_ACT_TRANSFER, amt.clone(), from, to));
+        }
+    };
+    let any = act.as_any();
+    if let Some(a) = any.downcast_ref::<AssetToTrs>() {
+        trs(Some(main), real(&a.to), &a.asset);
+    } else if let Some(a) = any.downcast_ref::<AssetFromTrs>() {
+        trs(real(&a.from), Some(main), &a.asset);
+    } else if let Some(a) = any.downcast_ref::<AssetFromToTrs>() {
+        trs(real(&a.from), real(&a.to), &a.asset);
+    } else if let Some(a) = any.downcast_ref::<AssetCreate>() {
+        touches.creates.push((hx, a.metadata.clone()));
+    } else if let Some(a) = any.downcast_ref::<TexCellAct>() {
+        for cell in a.cells.as_list() {
+            let buf = cell.serialize();
+            let (asset, from, to) = match cell.kind() as u8 {
+                CellTrsAssetPay::CID => match CellTrsAssetPay::build(&buf) {
+                    Ok(c) => (c.asset, a.addr, ADDRESS_ZERO),
+                    Err(_) => continue,
+                },
+                CellTrsAssetGet::CID => match CellTrsAssetGet::build(&buf) {
+                    Ok(c) => (c.asset, ADDRESS_ZERO, a.addr),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            touches.moves.push((hx, ASSET_ACT_TEX, asset, from, to));
+        }
+    }
+}
+
+/// What the actions of a block say about asset movements, walking into
+/// every AST branch; moves found there go to `branch_moves`.
+pub fn block_asset_touches(txs: &[Box<dyn Transaction>]) -> AssetTouches {
+    fn walk(tx: &dyn Transaction, act: &dyn Action, touches: &mut AssetTouches) {
+        for sub in ast_action_childs(act) {
+            let mut inner = AssetTouches::default();
+            action_asset_moves(tx, sub, &mut inner);
+            walk(tx, sub, &mut inner);
+            touches.creates.append(&mut inner.creates);
+            touches.branch_moves.append(&mut inner.moves);
+            touches.branch_moves.append(&mut inner.branch_moves);
+        }
+    }
+    let mut touches = AssetTouches::default();
+    for tx in txs {
+        for act in tx.actions() {
+            action_asset_moves(tx.as_ref(), act.as_ref(), &mut touches);
+            walk(tx.as_ref(), act.as_ref(), &mut touches);
+        }
+    }
+    touches
+}
+
+/// The asset state a block wrote, from its state writes.
+pub fn state_asset_writes(writes: &MemMap) -> AssetWrites {
+    let mut keys: Vec<&Vec<u8>> = writes.keys().collect();
+    keys.sort();
+    let mut asw = AssetWrites::default();
+    for k in keys {
+        state_asset_write(&mut asw, k, writes[k].as_deref());
+    }
+    asw
+}
+
+/// Add one `CoreState` write to `asw` if it is an asset or a balance; a
+/// removed balance holds nothing.
+pub fn state_asset_write(asw: &mut AssetWrites, k: &[u8], v: Option<&[u8]>) {
+    match k.first() {
+        Some(&ASSET_STATE_PREFIX) => {
+            if let Some(Ok(smelt)) = v.map(AssetSmelt::build) {
+                asw.smelts.push(smelt);
+            }
+        }
+        Some(&BALANCE_STATE_PREFIX) if k.len() == 1 + Address::SIZE => {
+            let addr = Address::from(<[u8; Address::SIZE]>::try_from(&k[1..]).unwrap());
+            let list = match v.map(Balance::build) {
+                Some(Ok(bls)) => bls.assets.as_list().iter().map(|a| (a.serial.uint(), a.amount.uint())).collect(),
+                Some(Err(_)) => return,
+                None => vec![],
+            };
+            asw.balances.push((addr, list));
+        }
+        _ => {}
+    }
+}
+
+/// Feeds confirmed blocks to the asset index, if one is configured, and
+/// adds its routes; everything else goes to the wrapped scaner.
+pub struct AssetIndexScaner {
+    inner: Box<dyn Scaner>,
+}
+
+impl AssetIndexScaner {
+    pub fn wrap(inner: Box<dyn Scaner>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Scaner for AssetIndexScaner {
+    fn init(&mut self, ini: &IniObj) -> Rerr {
+        self.inner.init(ini)
+    }
+
+    fn exit(&self) {
+        self.inner.exit()
+    }
+
+    fn start(&self, worker: Worker) {
+        self.inner.start(worker)
+    }
+
+    fn serve(&self, worker: Worker) {
+        self.inner.serve(worker)
+    }
+
+    fn roll(&self, blk: Arc<dyn Block>, sta: Arc<Box<dyn State>>, disk: Arc<dyn DiskDB>) {
+        if let Ok(index) = asset_index() {
+            let height = blk.height().uint();
+            if !index.backfilled() {
+                if let Err(e) = index.backfill(height, sta.disk().as_ref()) {
+                    println!("[Asset Index] reading assets at block {} failed: {}", height, e);
+                }
+            } else {
+                let touches = block_asset_touches(blk.transactions());
+                index.apply(height, &touches, &state_asset_writes(sta.as_mem()));
+            }
+        }
+        self.inner.roll(blk, sta, disk)
+    }
+
+    fn api_services(&self) -> Vec<Arc<dyn ApiService>> {
+        let mut services = self.inner.api_services();
+        services.push(service());
+        services
+    }
+}
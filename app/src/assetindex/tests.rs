@@ -0,0 +1,149 @@
+mod tests {
+    use super::*;
+    use protocol::transaction::TransactionType2;
+    use testkit::sim::disk::MemDisk;
+
+    fn addr(s: &str) -> Address {
+        Address::from_readable(s).unwrap()
+    }
+
+    fn amt(serial: u64, amount: u64) -> AssetAmt {
+        AssetAmt { serial: Fold64::from(serial).unwrap(), amount: Fold64::from(amount).unwrap() }
+    }
+
+    fn smelt(serial: u64, supply: u64, issuer: &Address) -> AssetSmelt {
+        AssetSmelt {
+            serial: Fold64::from(serial).unwrap(),
+            supply: Fold64::from(supply).unwrap(),
+            decimal: Uint1::from(2),
+            issuer: *issuer,
+            ticket: BytesW1::from_str("USDT").unwrap(),
+            name: BytesW1::from_str("Tether").unwrap(),
+        }
+    }
+
+    fn holding(list: &[(&Address, &[(u64, u64)])]) -> AssetWrites {
+        AssetWrites { smelts: vec![], balances: list.iter().map(|(a, l)| (**a, l.to_vec())).collect() }
+    }
+
+    #[test]
+    fn index_tracks_registry_holders_and_transfers() {
+        let disk = MemDisk::default();
+        let shared = disk.clone();
+        let index = AssetIndex::open(Box::new(disk)).unwrap();
+        let a1 = addr("12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi");
+        let a2 = addr("1LsQLqkd8FQDh3R7ZhxC5fndNf92WfhM19");
+        let a3 = addr("1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9");
+        index.backfill(9, &MemDisk::default()).unwrap();
+
+        let mut t = AssetTouches::default();
+        t.creates.push((Hash::default(), smelt(5, 1000, &a1)));
+        let mut w = holding(&[(&a1, &[(5, 1000)])]);
+        w.smelts.push(smelt(5, 1000, &a1));
+        index.apply(10, &t, &w);
+
+        let mut t = AssetTouches::default();
+        t.moves.push((Hash::default(), ASSET_ACT_TRANSFER, amt(5, 300), a1, a2));
+        t.moves.push((Hash::default(), ASSET_ACT_TRANSFER, amt(5, 100), a1, a3));
+        index.apply(11, &t, &holding(&[(&a1, &[(5, 600)]), (&a2, &[(5, 300)]), (&a3, &[(5, 100)])]));
+        assert_eq!(index.holders(5, 2), (3, vec![(a1, 600), (a2, 300)]));
+
+        // a3 leaves, and a replay is skipped
+        let mut t = AssetTouches::default();
+        t.moves.push((Hash::default(), ASSET_ACT_TEX, amt(5, 100), a3, ADDRESS_ZERO));
+        index.apply(12, &t, &holding(&[(&a3, &[])]));
+        index.apply(12, &t, &holding(&[(&a3, &[(5, 77)])]));
+
+        assert_eq!(index.range(), (9, 12));
+        let (total, list) = index.assets(1, 20, false);
+        assert_eq!((total, list[0].smelt.serial.uint(), list[0].height.uint()), (1, 5, 10));
+        assert!(index.asset(6).is_none());
+        assert_eq!(index.holders(5, 20), (2, vec![(a1, 600), (a2, 300)]));
+
+        let (total, trs) = index.transfers(5, 1, 20, false);
+        assert_eq!(total, 4);
+        assert_eq!((trs[0].kind.uint(), trs[0].from, trs[0].to), (ASSET_ACT_CREATE, ADDRESS_ZERO, a1));
+        let (total, trs) = index.address_transfers(&a3, 1, 1, true);
+        assert_eq!((total, trs[0].kind.uint()), (2, ASSET_ACT_TEX));
+
+        // holders come back from the disk
+        let reopened = AssetIndex::open(Box::new(shared)).unwrap();
+        assert_eq!(reopened.holders(5, 20), (2, vec![(a1, 600), (a2, 300)]));
+    }
+
+    #[test]
+    fn backfill_and_state_writes_cover_branches_and_contracts() {
+        let index = AssetIndex::open(Box::new(MemDisk::default())).unwrap();
+        let a1 = addr("12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi");
+        let a2 = addr("1LsQLqkd8FQDh3R7ZhxC5fndNf92WfhM19");
+        let a3 = addr("1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9");
+        let ctr = Address::from([7u8; Address::SIZE]);
+        // indexed before the state was read in, a holder that left since
+        let mut w = holding(&[(&a3, &[(5, 1)])]);
+        w.smelts.push(smelt(5, 1000, &a1));
+        index.apply(5, &AssetTouches::default(), &w);
+
+        // asset 6 was created before the index
+        let disk = MemDisk::default();
+        let mut bls = Balance::default();
+        bls.asset_set(amt(5, 700)).unwrap();
+        bls.asset_set(amt(6, 50)).unwrap();
+        disk.save(&[&[BALANCE_STATE_PREFIX][..], a1.as_ref()].concat(), &bls.serialize());
+        let mut bls = Balance::default();
+        bls.asset_set(amt(5, 300)).unwrap();
+        disk.save(&[&[BALANCE_STATE_PREFIX][..], a2.as_ref()].concat(), &bls.serialize());
+        for m in [smelt(5, 1000, &a1), smelt(6, 50, &a1)] {
+            disk.save(&[&[ASSET_STATE_PREFIX][..], &m.serial.serialize()].concat(), &m.serialize());
+        }
+        index.backfill(8, &disk).unwrap();
+        assert_eq!(index.assets(1, 20, false).0, 2);
+        assert_eq!(index.asset(6).unwrap().height.uint(), 0);
+        assert_eq!(index.holders(5, 20), (2, vec![(a1, 700), (a2, 300)]));
+
+        // a branch moved 100 of asset 5, another branch that did not run
+        // names a3, and a contract took 10 of asset 6
+        let mut t = AssetTouches::default();
+        t.branch_moves.push((Hash::default(), ASSET_ACT_TRANSFER, amt(5, 100), a1, a2));
+        t.branch_moves.push((Hash::default(), ASSET_ACT_TRANSFER, amt(6, 5), a1, a3));
+        index.apply(9, &t, &holding(&[(&a1, &[(5, 600), (6, 40)]), (&a2, &[(5, 400)]), (&ctr, &[(6, 10)])]));
+        assert_eq!(index.holders(5, 20), (2, vec![(a1, 600), (a2, 400)]));
+        assert_eq!(index.holders(6, 20), (2, vec![(a1, 40), (ctr, 10)]));
+        let (total, trs) = index.transfers(6, 1, 20, false);
+        assert_eq!(total, 2);
+        assert!(trs.iter().all(|t| t.kind.uint() == ASSET_ACT_CONTRACT && t.amount.uint() == 10));
+        let (_, trs) = index.address_transfers(&a3, 1, 20, false);
+        assert!(trs.iter().all(|t| t.height.uint() == 5));
+        let (_, trs) = index.transfers(5, 1, 20, false);
+        assert_eq!(trs.iter().filter(|t| t.height.uint() == 9).count(), 1);
+    }
+
+    #[test]
+    fn actions_name_asset_moves() {
+        let main = addr("12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi");
+        let to = addr("1LsQLqkd8FQDh3R7ZhxC5fndNf92WfhM19");
+        let tx = TransactionType2::new_by(main, Amount::mei(1), 1730000000);
+        let mut touches = AssetTouches::default();
+
+        let mut trs = AssetToTrs::new();
+        trs.to = AddrOrPtr::from_addr(to);
+        trs.asset = amt(5, 10);
+        action_asset_moves(&tx, &trs, &mut touches);
+        let mut create = AssetCreate::new();
+        create.metadata = smelt(6, 100, &to);
+        action_asset_moves(&tx, &create, &mut touches);
+        action_asset_moves(&tx, &HacToTrs::new(), &mut touches);
+
+        assert_eq!(touches.moves.len(), 1);
+        let (_, kind, a, from, dest) = &touches.moves[0];
+        assert_eq!((*kind, a.amount.uint(), *from, *dest), (ASSET_ACT_TRANSFER, 10, main, to));
+        assert_eq!(touches.creates.len(), 1);
+        assert_eq!(touches.creates[0].1.serial.uint(), 6);
+
+        // moves inside an AST branch are kept apart
+        let mut tx = TransactionType2::new_by(main, Amount::mei(1), 1730000000);
+        tx.push_action(Box::new(AstSelect::create_list(vec![Box::new(trs)]))).unwrap();
+        let txs: Vec<Box<dyn Transaction>> = vec![Box::new(tx)];
+        let touches = block_asset_touches(&txs);
+        assert_eq!((touches.moves.len(), touches.branch_moves.len()), (0, 1));
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::*;
+
+use basis::interface::*;
+use mint::api::dev_mine_block;
+use sys::*;
+
+/// Block producer of the single-node dev mode: mines as soon as the pool holds
+/// a transaction, or every `[dev] block_interval` seconds when that is set.
+pub fn start_dev_block_producer(mut worker: Worker, hnode: Arc<dyn HNoder>) {
+    let eng = hnode.engine();
+    let devcnf = eng.config().dev;
+    if !devcnf.enable {
+        return
+    }
+    println!("[Dev] block producer started, {}.", match devcnf.block_interval {
+        0 => "mine on every new transaction".to_owned(),
+        n => format!("one block every {} secs", n),
+    });
+    thread::spawn(move || {
+        let tick = match devcnf.block_interval {
+            0 => Duration::from_millis(100),
+            n => Duration::from_secs(n),
+        };
+        loop {
+            if worker.sleep_or_quit(tick) {
+                break
+            }
+            if devcnf.block_interval == 0 && !pool_has_tx(hnode.txpool().as_ref()) {
+                continue
+            }
+            match dev_mine_block(&eng, hnode.as_ref()) {
+                Ok(hei) => println!("[Dev] mined block {}.", hei),
+                Err(e) => println!("[Dev] mine block failed: {}", e),
+            }
+        }
+    });
+}
+
+fn pool_has_tx(txpool: &dyn TxPool) -> bool {
+    // group 0 normal txs, group 1 diamond mints
+    (0..2).any(|g| txpool.count_at(g).unwrap_or(0) > 0)
+}
@@ -1,26 +1,17 @@
-use std::sync::Arc;
-use std::thread;
-use std::time::*;
-
-use basis::component::*;
-use basis::config::*;
-use basis::interface::*;
-use field::*;
-use mint::action::*;
-use mint::*;
-use sys::*;
-
-pub fn start_diamond_auto_bidding(mut worker: Worker, hnode: Arc<dyn HNoder>) {
+pub fn start_diamond_auto_bidding(bidcnf: &BidStrategyConf, mut worker: Worker, hnode: Arc<dyn HNoder>) {
     // check config
     let eng = hnode.engine();
     let cnf = eng.config();
     let bidmin = cnf.dmer_bid_min.clone();
     let bidmax = cnf.dmer_bid_max.clone();
     let bidstep = cnf.dmer_bid_step.clone();
-    let minstep = Amount::coin(1, 244);
+    let minstep = min_bid_step();
 
-    if !cnf.dmer_enable || !cnf.is_mainnet() {
-        return; // not enable or not mainnet
+    if !cnf.is_mainnet() {
+        return; // not mainnet
+    }
+    if !cnf.dmer_enable && !cnf.dmer_bid_account_set() {
+        return; // no bid account, the admin api cannot turn bidding on either
     }
 
     macro_rules! printerr {
@@ -32,36 +23,47 @@ pub fn start_diamond_auto_bidding(mut worker: Worker, hnode: Arc<dyn HNoder>) {
         }
     }
 
-    if bidstep < minstep {
-        printerr!("bid step amount cannot be less than {} HAC", &minstep);
-    }
+    let mut strategy = bidcnf.open(&cnf.data_dir);
+    if cnf.dmer_enable {
+        if bidstep < minstep {
+            printerr!("bid step amount cannot be less than {} HAC", &minstep);
+        }
+
+        if bidmax < bidmin {
+            printerr!(
+                "max bid fee {} cannot be less than min fee {}",
+                &bidmax,
+                &bidmin
+            );
+            panic!("");
+        }
 
-    if bidmax < bidmin {
-        printerr!(
-            "max bid fee {} cannot be less than min fee {}",
+        println!(
+            "[Diamond Auto Bidding] start with account {} min fee {} and max fee {}, strategy {}.",
+            &cnf.dmer_bid_account.readable(),
+            &bidmin,
             &bidmax,
-            &bidmin
+            strategy.name()
         );
-        panic!("");
     }
 
-    println!(
-        "[Diamond Auto Bidding] start with account {} min fee {} and max fee {}.",
-        &cnf.dmer_bid_account.readable(),
-        &bidmin,
-        &bidmax
-    );
-
-    // thread loop
-    let engcnf = cnf.clone();
+    // thread loop, settings are read each round as the admin api may change them
     thread::spawn(move || {
         if worker.sleep_or_quit(Duration::from_secs(15)) {
             return;
         }
-        let mut current_number: u32 = 0;
+        let mut round = BidRound::default();
         loop {
+            let engcnf = eng.config();
+            if !engcnf.dmer_enable {
+                if worker.sleep_or_quit(Duration::from_secs(3)) {
+                    break;
+                }
+                continue;
+            }
+            update_bid_round(eng.as_ref(), &engcnf, strategy.as_mut(), &mut round);
             let pending_height = eng.latest_block().height().uint() + 1;
-            check_bidding_step(hnode.clone(), &engcnf, pending_height, &mut current_number);
+            check_bidding_step(hnode.clone(), &engcnf, strategy.as_mut(), &mut round, pending_height);
             thread::sleep(Duration::from_millis(77));
             if worker.quit() {
                 break;
@@ -70,11 +72,45 @@ pub fn start_diamond_auto_bidding(mut worker: Worker, hnode: Arc<dyn HNoder>) {
     });
 }
 
+/// The diamond mined last, to tell rounds apart and credit wins.
+#[derive(Default)]
+struct BidRound {
+    number: u32,  // latest mined diamond
+    since: u64,   // its block time
+    bidding: u32, // diamond the last raise was printed for
+}
+
+fn update_bid_round(eng: &dyn Engine, engcnf: &EngineConf, strategy: &mut dyn BidStrategy, round: &mut BidRound) {
+    let staptr = eng.state();
+    let lastdia = CoreStateRead::wrap(staptr.as_ref().as_ref()).get_latest_diamond();
+    let number = *lastdia.number;
+    if number == round.number {
+        return;
+    }
+    let first = round.number == 0;
+    round.number = number;
+    round.since = eng
+        .store()
+        .block_data_by_height(&lastdia.born_height)
+        .and_then(|(_, dts)| build_block_package(dts).ok())
+        .map_or_else(curtimes, |blk| blk.block().timestamp().uint());
+    if !first && lastdia.miner_address == engcnf.dmer_reward_address {
+        strategy.won(number, &lastdia.bid_fee);
+        flush!(
+            "\n✵✵✵✵ Diamond Auto Bid won {}({}) paying {}\n",
+            lastdia.diamond.to_readable(),
+            number,
+            lastdia.bid_fee
+        );
+    }
+}
+
 fn check_bidding_step(
     hnode: Arc<dyn HNoder>,
     engcnf: &EngineConf,
+    strategy: &mut dyn BidStrategy,
+    round: &mut BidRound,
     pending_height: u64,
-    bidding_number: &mut u32,
 ) {
     if pending_height % 5 == 0 {
         return; // not need bid in mining block tail 5 and 10
@@ -83,12 +119,7 @@ fn check_bidding_step(
     let txpool = hnode.txpool();
     let txplptr = txpool.as_ref();
     let my_acc = &engcnf.dmer_bid_account;
-    let mut bid_step = engcnf.dmer_bid_step.clone();
-    let min_step = Amount::coin(1, 244);
     let my_addr = Address::from(*my_acc.address());
-    if bid_step < min_step {
-        bid_step = min_step;
-    }
 
     macro_rules! retry {
         ($ms: expr) => {
@@ -116,15 +147,6 @@ fn check_bidding_step(
         retry!(1); // im the first
     }
 
-    let first_bid_fee = first_bid_txp.tx().fee();
-    if *first_bid_fee > engcnf.dmer_bid_max {
-        retry!(10); // my max too low
-    }
-    let Ok(first_bid_fee) = first_bid_fee.compress(2, AmtCpr::Grow) else {
-        printerr!("cannot compress fee {} to 4 length", &first_bid_fee);
-        retry!(10); // move step fail
-    };
-
     let Some(my_bid_txp) = my_bid_txp else {
         retry!(3); // have no my tx
     };
@@ -134,33 +156,25 @@ fn check_bidding_step(
         retry!(1); // im the first
     }
 
-    let my_bid_fee = my_bid_txp.tx().fee();
-    if my_bid_fee >= &engcnf.dmer_bid_max {
-        retry!(5); // my fee up max
-    }
-
-    let Ok(new_bid_fee) = first_bid_fee.add_mode_u64(&bid_step) else {
-        printerr!("cannot add fee {} with {}", &first_bid_fee, bid_step);
-        retry!(10); // move step fail
+    let mint = pickout_diamond_mint_action(my_bid_txp.tx_read());
+    let view = BidView {
+        number: mint.as_ref().map_or(round.number + 1, |m| *m.d.number),
+        elapsed: curtimes().saturating_sub(round.since),
+        top: first_bid_txp.tx().fee().clone(),
+        ours: my_bid_txp.tx().fee().clone(),
+        limits: BidLimits::from_conf(engcnf),
     };
-    let Ok(mut new_bid_fee) = new_bid_fee.compress(2, AmtCpr::Grow) else {
-        printerr!("cannot compress fee {} to 4 length", &new_bid_fee);
-        retry!(10); // move step fail
+    let Some(new_bid_fee) = strategy.raise(&view) else {
+        retry!(10); // strategy keeps our bid
     };
-    if new_bid_fee > engcnf.dmer_bid_max {
-        new_bid_fee = engcnf.dmer_bid_max.clone()
-    }
-    if new_bid_fee <= first_bid_fee {
-        retry!(10); // my max too low
-    }
     // ok
-    if let Some(mint) = pickout_diamond_mint_action(my_bid_txp.tx_read()) {
+    if let Some(mint) = mint {
         let act = mint.d;
         let dia = act.diamond.to_readable();
         let dnum = *act.number;
         let dfee = new_bid_fee.to_fin_string();
-        if *bidding_number != dnum {
-            *bidding_number = dnum;
+        if round.bidding != dnum {
+            round.bidding = dnum;
             flush!(
                 "✵✵✵✵ Diamond Auto Bid {}({}) by {} raise fee to ⇨ {}",
                 dia,
@@ -0,0 +1,62 @@
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BidStrategyKind {
+    Fixed,
+    Percent,
+    Timed,
+}
+
+/// `[diamondminer]` keys picking how the bidder raises; the limits stay in
+/// `bid_min`, `bid_max` and `bid_step`.
+#[derive(Clone, Debug)]
+pub struct BidStrategyConf {
+    pub kind: BidStrategyKind,
+    pub percent: u64, // outbid by, for `percent`
+    pub window: u64,  // seconds until `timed` may bid up to max
+    pub budget: Option<Amount>, // for all diamonds won, no cap when unset
+}
+
+impl BidStrategyConf {
+    pub fn new(ini: &IniObj) -> Self {
+        let sec = ini_section(ini, "diamondminer");
+        let kind = match ini_must(sec, "bid_strategy", "fixed").as_str() {
+            "fixed" => BidStrategyKind::Fixed,
+            "percent" => BidStrategyKind::Percent,
+            "timed" => BidStrategyKind::Timed,
+            s => panic!("{}", exiterr!(1, "diamondminer bid_strategy '{}' not supported", s)),
+        };
+        let budget = match ini_must(sec, "bid_budget", "").as_str() {
+            "" => None,
+            _ => Some(ini_must_amount(sec, "bid_budget")),
+        };
+        Self {
+            kind,
+            percent: ini_must_u64(sec, "bid_percent", 5).max(1),
+            window: ini_must_u64(sec, "bid_window", 600),
+            budget,
+        }
+    }
+
+    /// A strategy without a budget store, for the simulator.
+    pub fn build(&self) -> Box<dyn BidStrategy> {
+        self.assemble(None)
+    }
+
+    /// A strategy keeping its spent budget in `data_dir`, for the bidder.
+    pub fn open(&self, data_dir: &str) -> Box<dyn BidStrategy> {
+        self.assemble(Some(std::path::Path::new(data_dir).join("diamond_bid.spent")))
+    }
+
+    fn assemble(&self, store: Option<std::path::PathBuf>) -> Box<dyn BidStrategy> {
+        let inner: Box<dyn BidStrategy> = match self.kind {
+            BidStrategyKind::Fixed => Box::new(FixedStepBid),
+            BidStrategyKind::Percent => Box::new(OutbidPercentBid { percent: self.percent }),
+            BidStrategyKind::Timed => Box::new(TimeWeightedBid { window: self.window }),
+        };
+        match (&self.budget, store) {
+            (Some(budget), Some(store)) => Box::new(BudgetCappedBid::open(inner, budget.clone(), store)),
+            (Some(budget), None) => Box::new(BudgetCappedBid { inner, budget: budget.clone(), spent: Amount::zero(), store: None }),
+            (None, _) => inner,
+        }
+    }
+}
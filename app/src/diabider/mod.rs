@@ -0,0 +1,31 @@
+/*
+    Diamond auto bidding.
+
+    While diamond mining is enabled the bidder watches the diamond mint
+    transactions in the tx pool and, when another bid is ahead of ours,
+    asks a `BidStrategy` how far to raise. Strategies can also be replayed
+    against recorded `/query/diamond/bidding` answers with the simulator,
+    which sends nothing.
+*/
+use std::sync::Arc;
+use std::thread;
+use std::time::*;
+
+use basis::component::*;
+use basis::config::*;
+use basis::interface::*;
+use field::*;
+use mint::action::*;
+use mint::*;
+use protocol::block::build_block_package;
+use protocol::state::*;
+use serde_json::{Value, json};
+use sys::*;
+
+include! {"strategy.rs"}
+include! {"config.rs"}
+include! {"bidder.rs"}
+include! {"simulate.rs"}
+
+#[cfg(test)]
+include! {"tests.rs"}
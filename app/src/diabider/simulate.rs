@@ -0,0 +1,162 @@
+
+/// One recorded answer of `/query/diamond/bidding?since=true`, with the unix
+/// second it was taken at in an extra `time` field.
+#[derive(Clone, Debug)]
+pub struct BidSnapshot {
+    pub time: u64,
+    pub number: u32,
+    pub since: u64,
+    pub bids: Vec<(String, Amount)>, // bidder address and fee
+}
+
+impl BidSnapshot {
+    /// Parse a JSON array of answers, or one answer per line. Snapshots
+    /// without `time` count one second after the previous one.
+    pub fn parse_history(text: &str) -> Ret<Vec<Self>> {
+        let text = text.trim();
+        let values: Vec<Value> = match text.starts_with('[') {
+            true => serde_json::from_str(text).map_err(|e| format!("bid history json error: {}", e))?,
+            false => {
+                let mut list = vec![];
+                for (i, line) in text.lines().map(|a| a.trim()).enumerate() {
+                    if line.is_empty() {
+                        continue;
+                    }
+                    let v = serde_json::from_str(line)
+                        .map_err(|e| format!("bid history line {} json error: {}", i + 1, e))?;
+                    list.push(v);
+                }
+                list
+            }
+        };
+        let mut history: Vec<Self> = vec![];
+        for (i, v) in values.iter().enumerate() {
+            let prev = history.last();
+            let snap = Self::parse(v, prev).map_err(|e| format!("bid history item {}: {}", i + 1, e))?;
+            history.push(snap);
+        }
+        Ok(history)
+    }
+
+    fn parse(v: &Value, prev: Option<&Self>) -> Ret<Self> {
+        let Some(number) = v["number"].as_u64() else {
+            return errf!("number not find");
+        };
+        let number = number as u32;
+        let same = prev.filter(|p| p.number == number);
+        let since = v["since"].as_u64().or(same.map(|p| p.since)).unwrap_or(0);
+        let time = match v["time"].as_u64() {
+            Some(t) => t,
+            None => same.map_or(since, |p| p.time + 1),
+        };
+        let mut bids = vec![];
+        for one in v["list"].as_array().map(|a| a.as_slice()).unwrap_or_default() {
+            let fee = one["fee"].as_str().unwrap_or_default();
+            let Ok(fee) = Amount::from(fee) else {
+                return errf!("fee {} is invalid", fee);
+            };
+            bids.push((one["bid"].as_str().unwrap_or_default().to_owned(), fee));
+        }
+        Ok(Self { time, number, since, bids })
+    }
+
+    /// Highest fee bid, ignoring `skip` (our own recorded bids).
+    fn top(&self, skip: &str) -> Amount {
+        let mut top = Amount::zero();
+        for (addr, fee) in &self.bids {
+            if addr != skip && *fee > top {
+                top = fee.clone();
+            }
+        }
+        top
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BidSimRound {
+    pub number: u32,
+    pub won: bool,
+    pub ours: Amount, // our last bid
+    pub top: Amount,  // highest other bid at the last snapshot
+    pub raises: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BidSimReport {
+    pub rounds: Vec<BidSimRound>,
+    pub won: usize,
+    pub spent: Amount,
+}
+
+impl BidSimReport {
+    pub fn to_json(&self) -> Value {
+        let rounds: Vec<Value> = self
+            .rounds
+            .iter()
+            .map(|r| {
+                json!({
+                    "number": r.number,
+                    "won": r.won,
+                    "ours": r.ours.to_fin_string(),
+                    "top": r.top.to_fin_string(),
+                    "raises": r.raises,
+                })
+            })
+            .collect();
+        json!({
+            "rounds": rounds,
+            "won": self.won,
+            "spent": self.spent.to_fin_string(),
+        })
+    }
+}
+
+/// Replay recorded bids against a strategy without sending anything. Each
+/// diamond starts with our bid at `min`; at every snapshot where another bid
+/// is ahead the strategy may raise once, and the diamond counts as won when
+/// our bid leads the last snapshot of its number. Bids of `skip` in the
+/// history are left out, so records of a node that was bidding can be used.
+pub fn simulate_bidding(
+    strategy: &mut dyn BidStrategy,
+    limits: &BidLimits,
+    history: &[BidSnapshot],
+    skip: &str,
+) -> BidSimReport {
+    let mut report = BidSimReport::default();
+    let mut i = 0;
+    while i < history.len() {
+        let number = history[i].number;
+        let mut ours = limits.min.clone();
+        let mut top = Amount::zero();
+        let mut raises = 0;
+        while i < history.len() && history[i].number == number {
+            let snap = &history[i];
+            i += 1;
+            top = snap.top(skip);
+            if top < ours {
+                continue; // we lead
+            }
+            let view = BidView {
+                number,
+                elapsed: snap.time.saturating_sub(snap.since),
+                top: top.clone(),
+                ours: ours.clone(),
+                limits: limits.clone(),
+            };
+            if let Some(fee) = strategy.raise(&view) {
+                ours = fee;
+                raises += 1;
+            }
+        }
+        let won = ours > top;
+        if won {
+            strategy.won(number, &ours);
+            report.won += 1;
+            if let Ok(spent) = report.spent.add_mode_bigint(&ours) {
+                report.spent = spent;
+            }
+        }
+        report.rounds.push(BidSimRound { number, won, ours, top, raises });
+    }
+    report
+}
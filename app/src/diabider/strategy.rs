@@ -0,0 +1,178 @@
+
+/// Smallest raise any strategy makes, as the tx pool needs a higher fee to
+/// replace a bid.
+fn min_bid_step() -> Amount {
+    Amount::coin(1, 244)
+}
+
+/// `[diamondminer]` bid limits. The running bidder takes them from the
+/// engine config each round, so the admin api can change them.
+#[derive(Clone, Debug)]
+pub struct BidLimits {
+    pub min: Amount,
+    pub max: Amount,
+    pub step: Amount,
+}
+
+impl BidLimits {
+    pub fn from_conf(cnf: &EngineConf) -> Self {
+        Self {
+            min: cnf.dmer_bid_min.clone(),
+            max: cnf.dmer_bid_max.clone(),
+            step: cnf.dmer_bid_step.clone(),
+        }
+    }
+
+    /// Read from `[diamondminer]` even when it is not enabled, falling back
+    /// to the engine defaults; for the simulator.
+    pub fn new(ini: &IniObj) -> Self {
+        let sec = ini_section(ini, "diamondminer");
+        let amt = |key: &str, dv: Amount| match ini_must(sec, key, "").as_str() {
+            "" => dv,
+            _ => ini_must_amount(sec, key).compress(2, AmtCpr::Grow).unwrap(),
+        };
+        Self {
+            min: amt("bid_min", Amount::small_mei(1)),
+            max: amt("bid_max", Amount::small_mei(31)),
+            step: amt("bid_step", Amount::small(5, 247)),
+        }
+    }
+}
+
+/// One bidding round as a strategy sees it.
+#[derive(Clone, Debug)]
+pub struct BidView {
+    pub number: u32,  // diamond bid for
+    pub elapsed: u64, // seconds since the previous diamond
+    pub top: Amount,  // highest bid of the others
+    pub ours: Amount, // our bid in the tx pool
+    pub limits: BidLimits,
+}
+
+pub trait BidStrategy: Send {
+    fn name(&self) -> &'static str;
+
+    /// The fee to raise our bid to, or None to keep it. Only called while
+    /// another bid is ahead of ours.
+    fn raise(&mut self, view: &BidView) -> Option<Amount>;
+
+    /// Diamond `number` was mined paying our bid of `fee`.
+    fn won(&mut self, _number: u32, _fee: &Amount) {}
+}
+
+/// `want` as a fee no higher than `cap`, if that still beats `top` and `ours`.
+fn outbid(view: &BidView, want: Amount, cap: &Amount) -> Option<Amount> {
+    let want = want.compress(2, AmtCpr::Grow).ok()?;
+    let want = maybe!(want > *cap, cap.clone(), want);
+    (want > view.top && want > view.ours).then_some(want)
+}
+
+/// Top bid plus `step`, up to `max`. The bidder's original behavior.
+pub struct FixedStepBid;
+
+impl BidStrategy for FixedStepBid {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    fn raise(&mut self, view: &BidView) -> Option<Amount> {
+        let step = maybe!(view.limits.step < min_bid_step(), min_bid_step(), view.limits.step.clone());
+        let want = view.top.add_mode_u64(&step).ok()?;
+        outbid(view, want, &view.limits.max)
+    }
+}
+
+/// Top bid plus `percent` of it, at least the smallest step, up to `max`.
+pub struct OutbidPercentBid {
+    pub percent: u64,
+}
+
+impl BidStrategy for OutbidPercentBid {
+    fn name(&self) -> &'static str {
+        "percent"
+    }
+
+    fn raise(&mut self, view: &BidView) -> Option<Amount> {
+        let mut step = view.top.ratio_floor(self.percent, 100).ok()?;
+        if step < min_bid_step() {
+            step = min_bid_step();
+        }
+        let want = view.top.add_mode_bigint(&step).ok()?;
+        outbid(view, want, &view.limits.max)
+    }
+}
+
+/// Fixed steps under a ceiling that climbs from `min` to `max` over
+/// `window` seconds after the previous diamond, so early rounds stay cheap.
+pub struct TimeWeightedBid {
+    pub window: u64,
+}
+
+impl BidStrategy for TimeWeightedBid {
+    fn name(&self) -> &'static str {
+        "timed"
+    }
+
+    fn raise(&mut self, view: &BidView) -> Option<Amount> {
+        let BidLimits { min, max, .. } = &view.limits;
+        let mut cap = max.clone();
+        if self.window > 0 && view.elapsed < self.window && max > min {
+            let span = max.sub_mode_bigint(min).ok()?;
+            let part = span.ratio_floor(view.elapsed, self.window).ok()?;
+            cap = min.add_mode_bigint(&part).ok()?.compress(2, AmtCpr::Discard).ok()?;
+        }
+        FixedStepBid.raise(view).and_then(|want| outbid(view, want, &cap))
+    }
+}
+
+/// Any strategy under a total for all diamonds won; bids stop once it is spent.
+/// With a `store` file the spent total is written on each win and read back
+/// at start, so a restart does not reset the budget.
+pub struct BudgetCappedBid {
+    pub inner: Box<dyn BidStrategy>,
+    pub budget: Amount,
+    pub spent: Amount,
+    pub store: Option<std::path::PathBuf>,
+}
+
+impl BudgetCappedBid {
+    pub fn open(inner: Box<dyn BidStrategy>, budget: Amount, store: std::path::PathBuf) -> Self {
+        let spent = std::fs::read_to_string(&store)
+            .ok()
+            .and_then(|s| Amount::from(s.trim()).ok())
+            .unwrap_or_default();
+        Self { inner, budget, spent, store: Some(store) }
+    }
+
+    pub fn remaining(&self) -> Amount {
+        self.budget.sub_mode_bigint(&self.spent).unwrap_or_default()
+    }
+}
+
+impl BidStrategy for BudgetCappedBid {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn raise(&mut self, view: &BidView) -> Option<Amount> {
+        let left = self.remaining();
+        if !left.is_positive() {
+            return None;
+        }
+        let want = self.inner.raise(view)?;
+        let left = left.compress(2, AmtCpr::Discard).ok()?;
+        outbid(view, want, &left)
+    }
+
+    fn won(&mut self, number: u32, fee: &Amount) {
+        if let Ok(spent) = self.spent.add_mode_bigint(fee) {
+            self.spent = spent;
+        }
+        if let Some(store) = &self.store {
+            if let Err(e) = std::fs::write(store, self.spent.to_fin_string()) {
+                println!("[Diamond Auto Bidding] save spent budget to {} failed: {}", store.display(), e);
+            }
+        }
+        self.inner.won(number, fee);
+    }
+}
@@ -0,0 +1,97 @@
+mod tests {
+    use super::*;
+
+    fn amt(s: &str) -> Amount {
+        Amount::from(s).unwrap()
+    }
+
+    // fees in HAC, so equal values compare equal whatever their unit
+    fn mei(a: &Amount) -> String {
+        a.to_unit_string("mei")
+    }
+
+    fn fee(a: Option<Amount>) -> Option<String> {
+        a.as_ref().map(mei)
+    }
+
+    fn fin(s: &str) -> Option<String> {
+        Some(mei(&amt(s)))
+    }
+
+    fn limits() -> BidLimits {
+        BidLimits { min: amt("1:248"), max: amt("10:248"), step: amt("5:247") }
+    }
+
+    fn view(top: &str, ours: &str, elapsed: u64) -> BidView {
+        BidView { number: 7, elapsed, top: amt(top), ours: amt(ours), limits: limits() }
+    }
+
+    #[test]
+    fn fixed_step_raises_over_top_up_to_max() {
+        let mut s = FixedStepBid;
+        assert_eq!(fee(s.raise(&view("2:248", "1:248", 0))), fin("25:247"));
+        assert_eq!(fee(s.raise(&view("98:247", "1:248", 0))), fin("10:248"));
+        assert_eq!(s.raise(&view("10:248", "1:248", 0)), None);
+    }
+
+    #[test]
+    fn percent_raises_by_share_of_top() {
+        let mut s = OutbidPercentBid { percent: 10 };
+        assert_eq!(fee(s.raise(&view("5:248", "1:248", 0))), fin("55:247"));
+        // tiny tops still move by the smallest step
+        assert_eq!(fee(s.raise(&view("5:244", "1:244", 0))), fin("6:244"));
+    }
+
+    #[test]
+    fn timed_ceiling_climbs_with_elapsed() {
+        let mut s = TimeWeightedBid { window: 100 };
+        // ceiling 1 + 9 * 10 / 100 = 1.9 HAC, below the next step
+        assert_eq!(s.raise(&view("2:248", "1:248", 10)), None);
+        assert_eq!(fee(s.raise(&view("2:248", "1:248", 50))), fin("25:247"));
+        assert_eq!(fee(s.raise(&view("98:247", "1:248", 200))), fin("10:248"));
+    }
+
+    #[test]
+    fn budget_caps_bids_and_counts_wins() {
+        let mut s = BudgetCappedBid { inner: Box::new(FixedStepBid), budget: amt("6:248"), spent: Amount::zero(), store: None };
+        assert_eq!(fee(s.raise(&view("2:248", "1:248", 0))), fin("25:247"));
+        s.won(7, &amt("4:248"));
+        assert_eq!(mei(&s.remaining()), mei(&amt("2:248")));
+        assert_eq!(s.raise(&view("3:248", "1:248", 0)), None);
+        s.won(8, &amt("2:248"));
+        assert_eq!(s.raise(&view("1:247", "1:246", 0)), None);
+    }
+
+    #[test]
+    fn budget_spent_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("diabider_budget_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = dir.join("diamond_bid.spent");
+        let _ = std::fs::remove_file(&store);
+        let mut s = BudgetCappedBid::open(Box::new(FixedStepBid), amt("6:248"), store.clone());
+        assert_eq!(mei(&s.remaining()), mei(&amt("6:248")));
+        s.won(7, &amt("4:248"));
+        let s = BudgetCappedBid::open(Box::new(FixedStepBid), amt("6:248"), store.clone());
+        assert_eq!(mei(&s.remaining()), mei(&amt("2:248")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn simulate_history_lines() {
+        let text = r#"
+            {"ret":0,"number":7,"since":1000,"time":1010,"list":[{"bid":"A","fee":"2"},{"bid":"me","fee":"9"}]}
+            {"ret":0,"number":7,"list":[{"bid":"A","fee":"3"}]}
+            {"ret":0,"number":8,"since":1100,"time":1105,"list":[{"bid":"B","fee":"20"}]}
+        "#;
+        let history = BidSnapshot::parse_history(text).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[1].time, 1011);
+        assert_eq!(history[1].since, 1000);
+        let report = simulate_bidding(&mut FixedStepBid, &limits(), &history, "me");
+        assert_eq!(report.won, 1);
+        assert_eq!(mei(&report.spent), mei(&amt("35:247")));
+        assert_eq!(report.rounds[0].raises, 2);
+        assert!(!report.rounds[1].won);
+        assert_eq!(report.rounds[1].raises, 0); // 20 HAC is over max
+    }
+}
@@ -0,0 +1,141 @@
+struct DiamondIndexApiService {}
+
+pub fn service() -> Arc<dyn ApiService> {
+    Arc::new(DiamondIndexApiService {})
+}
+
+impl ApiService for DiamondIndexApiService {
+    fn name(&self) -> &'static str {
+        "diaindex"
+    }
+
+    fn routes(&self) -> Vec<ApiRoute> {
+        use ApiType::*;
+        const PAGE: &str = "from 1";
+        const LIMIT: &str = "20 by default, 200 at most";
+        vec![
+            ApiRoute::get("/query/diamond/history", query_diamond_history)
+                .summary("Indexed owners and inscription changes of a diamond")
+                .param_must("name", String, "")
+                .param("page", Integer, PAGE)
+                .param("limit", Integer, LIMIT)
+                .param("desc", Boolean, "newest first")
+                .returns("total", Integer, "")
+                .returns("list", Array, ""),
+            ApiRoute::get("/query/diamond/held", query_diamond_held)
+                .summary("Diamonds that came to or left an address")
+                .param_must("address", String, "")
+                .param("page", Integer, PAGE)
+                .param("limit", Integer, LIMIT)
+                .param("desc", Boolean, "newest first")
+                .returns("total", Integer, "")
+                .returns("list", Array, ""),
+            ApiRoute::get("/query/diamond/inscribed", query_diamond_inscribed)
+                .summary("Diamonds carrying an inscription now")
+                .param("content", String, "inscription text")
+                .param("hex", String, "inscription bytes, instead of content")
+                .param("page", Integer, PAGE)
+                .param("limit", Integer, LIMIT)
+                .returns("total", Integer, "")
+                .returns("list", Array, "diamond names"),
+        ]
+    }
+}
+
+fn api_error(errmsg: &str) -> ApiResponse {
+    ApiResponse::json(json!({"ret":1,"err":errmsg}).to_string())
+}
+
+fn api_page(req: &ApiRequest) -> (usize, usize, bool) {
+    let page = req.query_usize("page", 1);
+    let limit = req.query_usize("limit", 20).clamp(1, 200);
+    let desc = matches!(req.query("desc"), Some("true" | "1"));
+    (page, limit, desc)
+}
+
+fn api_list(index: &DiamondIndex, total: usize, list: Vec<Value>) -> ApiResponse {
+    let (start, height) = index.range();
+    ApiResponse::json(
+        json!({"ret":0,"indexed":{"start":start,"height":height},"total":total,"list":list}).to_string(),
+    )
+}
+
+fn readable_or_null(addr: &Address) -> Value {
+    maybe!(*addr == Address::default(), Value::Null, json!(addr.to_readable()))
+}
+
+fn query_diamond_history(_: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let index = match diamond_index() {
+        Ok(i) => i,
+        Err(e) => return api_error(&e),
+    };
+    let Ok(name) = DiamondName::from_readable(req.query("name").unwrap_or_default().as_bytes()) else {
+        return api_error("diamond name format invalid");
+    };
+    let (page, limit, desc) = api_page(&req);
+    let (total, events) = index.events(&name, page, limit, desc);
+    let list = events
+        .iter()
+        .map(|e| {
+            json!({
+                "height": e.height.uint(),
+                "tx": maybe!(e.tx == Hash::default(), Value::Null, json!(e.tx.to_hex())),
+                "acts": dia_act_names(e.acts.uint()),
+                "from": readable_or_null(&e.from),
+                "owner": e.owner.to_readable(),
+                "inscripts": e.inscripts.uint(),
+            })
+        })
+        .collect();
+    api_list(index, total, list)
+}
+
+fn query_diamond_held(_: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let index = match diamond_index() {
+        Ok(i) => i,
+        Err(e) => return api_error(&e),
+    };
+    let Ok(addr) = Address::from_readable(req.query("address").unwrap_or_default()) else {
+        return api_error("address format invalid");
+    };
+    let (page, limit, desc) = api_page(&req);
+    let (total, holds) = index.holds(&addr, page, limit, desc);
+    let list = holds
+        .iter()
+        .map(|h| {
+            json!({
+                "height": h.height.uint(),
+                "name": h.diamond.to_readable(),
+                "dir": maybe!(h.dir.uint() == DIA_HOLD_IN, "in", "out"),
+            })
+        })
+        .collect();
+    api_list(index, total, list)
+}
+
+fn query_diamond_inscribed(_: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let index = match diamond_index() {
+        Ok(i) => i,
+        Err(e) => return api_error(&e),
+    };
+    let content = match (req.query("hex"), req.query("content")) {
+        (Some(h), _) if !h.is_empty() => match hex::decode(h) {
+            Ok(c) => c,
+            Err(_) => return api_error("hex format invalid"),
+        },
+        (_, Some(c)) if !c.is_empty() => c.as_bytes().to_vec(),
+        _ => return api_error("content or hex must be given"),
+    };
+    let (page, limit, _) = api_page(&req);
+    let names = match index.inscribed(&content) {
+        Ok(n) => n,
+        Err(e) => return api_error(&e),
+    };
+    let list = names
+        .iter()
+        .skip(page.max(1).saturating_sub(1).saturating_mul(limit))
+        .take(limit)
+        .map(|n| json!(n.to_readable()))
+        .collect();
+    api_list(index, names.len(), list)
+}
@@ -0,0 +1,251 @@
+
+static DIAMOND_INDEX: OnceLock<DiamondIndex> = OnceLock::new();
+
+/// Install the diamond index DB. Without it nothing is recorded and the
+/// index routes answer an error.
+pub fn configure_diamond_index(db: Box<dyn DiskDB>) {
+    let _ = DIAMOND_INDEX.set(DiamondIndex::new(db));
+}
+
+pub fn diamond_index() -> Ret<&'static DiamondIndex> {
+    DIAMOND_INDEX.get().ok_or_else(|| "diamond index not enabled".to_owned())
+}
+
+/*
+* action kinds of an index event, as bits
+*/
+pub const DIA_ACT_MINT: u16 = 1;
+pub const DIA_ACT_TRANSFER: u16 = 2;
+pub const DIA_ACT_TEX: u16 = 4;
+pub const DIA_ACT_INSC_PUSH: u16 = 8;
+pub const DIA_ACT_INSC_CLEAN: u16 = 16;
+pub const DIA_ACT_INSC_EDIT: u16 = 32;
+pub const DIA_ACT_INSC_MOVE: u16 = 64;
+pub const DIA_ACT_INSC_DROP: u16 = 128;
+pub const DIA_ACT_CONTRACT: u16 = 256; // changed with no action naming it
+
+const DIA_ACT_NAMES: [(u16, &str); 9] = [
+    (DIA_ACT_MINT, "mint"),
+    (DIA_ACT_TRANSFER, "transfer"),
+    (DIA_ACT_TEX, "tex"),
+    (DIA_ACT_INSC_PUSH, "insc_push"),
+    (DIA_ACT_INSC_CLEAN, "insc_clean"),
+    (DIA_ACT_INSC_EDIT, "insc_edit"),
+    (DIA_ACT_INSC_MOVE, "insc_move"),
+    (DIA_ACT_INSC_DROP, "insc_drop"),
+    (DIA_ACT_CONTRACT, "contract"),
+];
+
+// `CoreState` slot of `diamond`, the first byte of its keys.
+const DIAMOND_STATE_PREFIX: u8 = 13;
+
+pub fn dia_act_names(acts: u16) -> Vec<&'static str> {
+    DIA_ACT_NAMES.iter().filter(|(b, _)| acts & b != 0).map(|(_, n)| *n).collect()
+}
+
+pub const DIA_HOLD_IN: u8 = 1;
+pub const DIA_HOLD_OUT: u8 = 2;
+
+// One block that touched a diamond. `from` is zero after a mint, `tx` is
+// zero when only a contract changed it.
+combi_struct!{ DiaIndexEvent,
+    height    : BlockHeight
+    tx        : Hash // last transaction touching it in the block
+    acts      : Uint2
+    from      : Address
+    owner     : Address
+    inscripts : Uint1
+}
+
+// A diamond coming to or leaving an address.
+combi_struct!{ DiaIndexHold,
+    height  : BlockHeight
+    diamond : DiamondName
+    dir     : Uint1
+}
+
+/// The diamonds one block touched, in order of first touch.
+#[derive(Clone, Debug, Default)]
+pub struct DiaTouches {
+    pub list: Vec<(DiamondName, Hash, u16)>,
+}
+
+impl DiaTouches {
+    pub fn add(&mut self, name: &DiamondName, tx: &Hash, acts: u16) {
+        match self.list.iter_mut().find(|(n, ..)| n == name) {
+            Some((_, t, a)) => {
+                *t = *tx;
+                *a |= acts;
+            }
+            None => self.list.push((*name, *tx, acts)),
+        }
+    }
+}
+
+/*
+    Keys:
+      height                   last indexed block
+      start                    first indexed block
+      d ++ name                event count, Uint4
+      d ++ name ++ seq         DiaIndexEvent
+      o ++ name                DiamondSto as last indexed
+      a ++ address             hold count, Uint4
+      a ++ address ++ seq      DiaIndexHold
+      i ++ sha3(content)       DiamondOwnedForm of the diamonds carrying it
+      b                        set once the diamonds in state were read in
+*/
+pub struct DiamondIndex {
+    db: Box<dyn DiskDB>,
+}
+
+impl DiamondIndex {
+    pub fn new(db: Box<dyn DiskDB>) -> Self {
+        Self { db }
+    }
+
+    /// First and last indexed block heights, zeros when nothing is indexed.
+    pub fn range(&self) -> (u64, u64) {
+        side_range(self.db.as_ref())
+    }
+
+    /// Whether the diamonds already in state were read in by `backfill`.
+    pub fn backfilled(&self) -> bool {
+        self.db.read(b"b").is_some()
+    }
+
+    /// Read every diamond from the state disk, as it is after block
+    /// `height`, so diamonds that no indexed block touched have an owner
+    /// and their inscriptions are found. Snapshots that went stale are
+    /// corrected. Runs once; block `height` itself gets no events.
+    pub fn backfill(&self, height: u64, disk: &dyn DiskDB) -> Rerr {
+        if self.backfilled() {
+            return Ok(());
+        }
+        let mut stos = vec![];
+        disk.for_each(&mut |k, v| {
+            if let Some(name) = diamond_state_key(k)
+                && let Ok(sto) = DiamondSto::build(v)
+            {
+                stos.push((name, sto));
+            }
+            true
+        })?;
+        let mut batch = SideBatch::new(self.db.as_ref());
+        for (name, sto) in &stos {
+            let prev = batch.get(&key2(b"o", name)).map(|v| DiamondSto::must(&v));
+            update_inscribed(&mut batch, name, prev.as_ref(), sto);
+            batch.put(key2(b"o", name), sto.serialize());
+        }
+        batch.put(b"b".to_vec(), vec![1]);
+        let (_, last) = self.range();
+        batch.commit(height.max(last));
+        Ok(())
+    }
+
+    /// Record block `height`, reading the diamonds it touched from the state
+    /// after it with `load`. Blocks at or below the last indexed are skipped.
+    pub fn apply(&self, height: u64, touches: &DiaTouches, load: &dyn Fn(&DiamondName) -> Option<DiamondSto>) {
+        let (_, last) = self.range();
+        if last > 0 && height <= last {
+            return;
+        }
+        let mut batch = SideBatch::new(self.db.as_ref());
+        let hei = BlockHeight::from(height);
+        for (name, tx, acts) in &touches.list {
+            let Some(sto) = load(name) else {
+                continue;
+            };
+            let prev = batch.get(&key2(b"o", name)).map(|v| DiamondSto::must(&v));
+            let from = prev.as_ref().map_or(Address::default(), |p| p.address);
+            batch.push(b"d", name.as_ref(), DiaIndexEvent {
+                height: hei,
+                tx: *tx,
+                acts: Uint2::from(*acts),
+                from,
+                owner: sto.address,
+                inscripts: Uint1::from(sto.inscripts.length() as u8),
+            }.serialize());
+            // ownership
+            if prev.is_none() || from != sto.address {
+                if prev.is_some() {
+                    batch.push(b"a", from.as_ref(), DiaIndexHold {
+                        height: hei,
+                        diamond: *name,
+                        dir: Uint1::from(DIA_HOLD_OUT),
+                    }.serialize());
+                }
+                batch.push(b"a", sto.address.as_ref(), DiaIndexHold {
+                    height: hei,
+                    diamond: *name,
+                    dir: Uint1::from(DIA_HOLD_IN),
+                }.serialize());
+            }
+            update_inscribed(&mut batch, name, prev.as_ref(), &sto);
+            batch.put(key2(b"o", name), sto.serialize());
+        }
+        batch.commit(height);
+    }
+
+    /// Page `page` (from 1) of what a diamond went through, oldest first.
+    pub fn events(&self, name: &DiamondName, page: usize, limit: usize, desc: bool) -> (usize, Vec<DiaIndexEvent>) {
+        side_page(self.db.as_ref(), b"d", name.as_ref(), (page, limit, desc), DiaIndexEvent::must)
+    }
+
+    /// Page `page` (from 1) of the diamonds that came to or left `addr`.
+    pub fn holds(&self, addr: &Address, page: usize, limit: usize, desc: bool) -> (usize, Vec<DiaIndexHold>) {
+        side_page(self.db.as_ref(), b"a", addr.as_ref(), (page, limit, desc), DiaIndexHold::must)
+    }
+
+    /// Diamonds carrying an inscription of exactly `content` now. An error
+    /// until the diamonds in state were read in, as the answer would miss
+    /// those inscribed before the index started.
+    pub fn inscribed(&self, content: &[u8]) -> Ret<Vec<DiamondName>> {
+        if !self.backfilled() {
+            return errf!("diamond index has not read the diamonds in state yet");
+        }
+        let Some(v) = self.db.read(&insc_key(content)) else {
+            return Ok(vec![]);
+        };
+        Ok(DiamondOwnedForm::must(&v)
+            .names
+            .as_ref()
+            .chunks_exact(DiamondName::SIZE)
+            .map(DiamondName::must)
+            .collect())
+    }
+}
+
+fn update_inscribed(batch: &mut SideBatch, name: &DiamondName, prev: Option<&DiamondSto>, sto: &DiamondSto) {
+    let olds = prev.map(|p| insc_contents(&p.inscripts)).unwrap_or_default();
+    let news = insc_contents(&sto.inscripts);
+    for c in olds.difference(&news) {
+        edit_form(batch, c, |f| {
+            let _ = f.drop_one(name);
+        });
+    }
+    for c in news.difference(&olds) {
+        edit_form(batch, c, |f| f.push_one(name));
+    }
+}
+
+fn edit_form(batch: &mut SideBatch, content: &[u8], f: impl FnOnce(&mut DiamondOwnedForm)) {
+    let k = insc_key(content);
+    let mut form = batch.get(&k).map(|v| DiamondOwnedForm::must(&v)).unwrap_or_default();
+    f(&mut form);
+    match form.names.length() {
+        0 => batch.del(k),
+        _ => batch.put(k, form.serialize()),
+    }
+}
+
+fn key2(pre: &[u8], name: &DiamondName) -> Vec<u8> {
+    [pre, name.as_ref()].concat()
+}
+
+fn insc_key(content: &[u8]) -> Vec<u8> {
+    [b"i".as_slice(), &sha3(content)].concat()
+}
+
+fn insc_contents(list: &Inscripts) -> BTreeSet<Vec<u8>> {
+    list.as_list().iter().map(|i| i.content.to_vec()).filter(|c| !c.is_empty()).collect()
+}
@@ -0,0 +1,34 @@
+/*
+    Diamond ownership and inscription index.
+
+    A local, non-consensus side DB fed by `Scaner::roll` with each confirmed
+    block. For every diamond a block changed it keeps what happened and the
+    owner after, for every address the diamonds that came and went, and for
+    every inscription content the diamonds carrying it now. Which diamonds
+    changed comes from the block's state writes, so moves inside AST
+    branches and by contracts are seen; the actions only name the kind and
+    transaction. When first enabled, the diamonds already in state are read
+    in, so owners and inscriptions are right from there on; events and
+    holds start at that block.
+*/
+use std::collections::*;
+use std::sync::*;
+
+use basis::component::*;
+use basis::interface::*;
+use field::*;
+use mint::action::*;
+use protocol::action::*;
+use protocol::state::*;
+use protocol::tex::*;
+use serde_json::{Value, json};
+use sys::*;
+
+use crate::sidedb::*;
+
+include! {"index.rs"}
+include! {"scan.rs"}
+include! {"api.rs"}
+
+#[cfg(test)]
+include! {"tests.rs"}
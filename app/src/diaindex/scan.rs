@@ -0,0 +1,137 @@
+
+/// Diamonds an action mints, transfers or inscribes by its own fields, and
+/// its kind. AST containers and contract calls name none themselves.
+pub fn action_diamonds(act: &dyn Action) -> Option<(u16, Vec<DiamondName>)> {
+    macro_rules! one {
+        ($ty: ty, $kind: expr, $a: ident => $names: expr) => {
+            if let Some($a) = act.as_any().downcast_ref::<$ty>() {
+                return Some(($kind, $names));
+            }
+        };
+    }
+    one!(DiamondMint, DIA_ACT_MINT, a => vec![a.d.diamond]);
+    one!(DiaSingleTrs, DIA_ACT_TRANSFER, a => vec![a.diamond]);
+    one!(DiaFromToTrs, DIA_ACT_TRANSFER, a => a.diamonds.as_list().clone());
+    one!(DiaToTrs, DIA_ACT_TRANSFER, a => a.diamonds.as_list().clone());
+    one!(DiaFromTrs, DIA_ACT_TRANSFER, a => a.diamonds.as_list().clone());
+    one!(DiaInscPush, DIA_ACT_INSC_PUSH, a => a.diamonds.as_list().clone());
+    one!(DiaInscClean, DIA_ACT_INSC_CLEAN, a => a.diamonds.as_list().clone());
+    one!(DiaInscEdit, DIA_ACT_INSC_EDIT, a => vec![a.diamond]);
+    one!(DiaInscMove, DIA_ACT_INSC_MOVE, a => vec![a.from_diamond, a.to_diamond]);
+    one!(DiaInscDrop, DIA_ACT_INSC_DROP, a => vec![a.diamond]);
+    let tex = act.as_any().downcast_ref::<TexCellAct>()?;
+    let mut names = vec![];
+    for cell in tex.cells.as_list() {
+        if cell.kind() as u8 == CellTrsDiaPay::CID {
+            let pay = CellTrsDiaPay::build(&cell.serialize()).ok()?;
+            names.extend(pay.diamonds.as_list().iter().cloned());
+        }
+    }
+    maybe!(names.is_empty(), None, Some((DIA_ACT_TEX, names)))
+}
+
+/// What the actions of a block say about the diamonds they touch, walking
+/// into every AST branch. These are hints only: a branch that did not run
+/// is listed too, and diamonds moved by contracts are missing.
+pub fn block_diamond_touches(txs: &[Box<dyn Transaction>]) -> DiaTouches {
+    fn walk(act: &dyn Action, hx: &Hash, touches: &mut DiaTouches) {
+        if let Some((kind, names)) = action_diamonds(act) {
+            for name in &names {
+                touches.add(name, hx, kind);
+            }
+        }
+        for sub in ast_action_childs(act) {
+            walk(sub, hx, touches);
+        }
+    }
+    let mut touches = DiaTouches::default();
+    for tx in txs {
+        let hx = tx.hash();
+        for act in tx.actions() {
+            walk(act.as_ref(), &hx, &mut touches);
+        }
+    }
+    touches
+}
+
+/// The diamonds a block really changed, from its state writes, each with
+/// the transaction and kinds `hints` has for it. A diamond no action names
+/// was changed by a contract and gets a zero transaction.
+pub fn state_diamond_touches(writes: &MemMap, hints: &DiaTouches) -> DiaTouches {
+    let mut keys: Vec<&Vec<u8>> = writes.keys().collect();
+    keys.sort();
+    let names: Vec<DiamondName> = keys.into_iter().filter_map(|k| diamond_state_key(k)).collect();
+    let mut touches = DiaTouches::default();
+    for (name, tx, acts) in &hints.list {
+        if names.contains(name) {
+            touches.add(name, tx, *acts);
+        }
+    }
+    for name in &names {
+        if !touches.list.iter().any(|(n, ..)| n == name) {
+            touches.add(name, &Hash::default(), DIA_ACT_CONTRACT);
+        }
+    }
+    touches
+}
+
+/// The diamond a `CoreState` key is for, if it is a diamond key.
+pub fn diamond_state_key(k: &[u8]) -> Option<DiamondName> {
+    match k.len() == 1 + DiamondName::SIZE && k[0] == DIAMOND_STATE_PREFIX {
+        true => Some(DiamondName::must(&k[1..])),
+        false => None,
+    }
+}
+
+/// Feeds confirmed blocks to the diamond index, if one is configured, and
+/// adds its routes; everything else goes to the wrapped scaner.
+pub struct DiamondIndexScaner {
+    inner: Box<dyn Scaner>,
+}
+
+impl DiamondIndexScaner {
+    pub fn wrap(inner: Box<dyn Scaner>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Scaner for DiamondIndexScaner {
+    fn init(&mut self, ini: &IniObj) -> Rerr {
+        self.inner.init(ini)
+    }
+
+    fn exit(&self) {
+        self.inner.exit()
+    }
+
+    fn start(&self, worker: Worker) {
+        self.inner.start(worker)
+    }
+
+    fn serve(&self, worker: Worker) {
+        self.inner.serve(worker)
+    }
+
+    fn roll(&self, blk: Arc<dyn Block>, sta: Arc<Box<dyn State>>, disk: Arc<dyn DiskDB>) {
+        if let Ok(index) = diamond_index() {
+            let height = blk.height().uint();
+            if !index.backfilled() {
+                if let Err(e) = index.backfill(height, sta.disk().as_ref()) {
+                    println!("[Diamond Index] reading diamonds at block {} failed: {}", height, e);
+                }
+            } else {
+                let state = CoreStateRead::wrap(sta.as_ref().as_ref());
+                let hints = block_diamond_touches(blk.transactions());
+                let touches = state_diamond_touches(sta.as_mem(), &hints);
+                index.apply(height, &touches, &|name| state.diamond(name));
+            }
+        }
+        self.inner.roll(blk, sta, disk)
+    }
+
+    fn api_services(&self) -> Vec<Arc<dyn ApiService>> {
+        let mut services = self.inner.api_services();
+        services.push(service());
+        services
+    }
+}
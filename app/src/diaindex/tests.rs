@@ -0,0 +1,145 @@
+mod tests {
+    use super::*;
+    use testkit::sim::disk::MemDisk;
+
+    fn dia(s: &str) -> DiamondName {
+        DiamondName::from_readable(s.as_bytes()).unwrap()
+    }
+
+    fn addr(s: &str) -> Address {
+        Address::from_readable(s).unwrap()
+    }
+
+    fn sto(owner: &Address, inscs: &[&str]) -> DiamondSto {
+        let mut sto = DiamondSto { status: DIAMOND_STATUS_NORMAL, address: *owner, ..Default::default() };
+        for i in inscs {
+            sto.inscripts.push(DiamondInscript::create_by(0, BytesW1::from(i.as_bytes().to_vec()).unwrap())).unwrap();
+        }
+        sto
+    }
+
+    fn touches(list: &[(&DiamondName, u16)]) -> DiaTouches {
+        let mut t = DiaTouches::default();
+        for (n, acts) in list {
+            t.add(n, &Hash::default(), *acts);
+        }
+        t
+    }
+
+    #[test]
+    fn index_tracks_owners_holds_and_inscriptions() {
+        let index = DiamondIndex::new(Box::new(MemDisk::default()));
+        let (wty, kkk) = (dia("WTYUIA"), dia("KKKKVA"));
+        let a1 = addr("12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi");
+        let a2 = addr("1LsQLqkd8FQDh3R7ZhxC5fndNf92WfhM19");
+        index.backfill(9, &MemDisk::default()).unwrap();
+
+        index.apply(10, &touches(&[(&wty, DIA_ACT_MINT)]), &|_| Some(sto(&a1, &[])));
+        // transferred and inscribed in one block
+        index.apply(11, &touches(&[(&wty, DIA_ACT_TRANSFER), (&wty, DIA_ACT_INSC_PUSH)]), &|_| {
+            Some(sto(&a2, &["hello"]))
+        });
+        // moved to a diamond the index has not seen before
+        index.apply(12, &touches(&[(&wty, DIA_ACT_INSC_MOVE), (&kkk, DIA_ACT_INSC_MOVE)]), &|n| {
+            Some(maybe!(*n == wty, sto(&a2, &[]), sto(&a2, &["hello"])))
+        });
+        // replays are skipped
+        index.apply(12, &touches(&[(&wty, DIA_ACT_TRANSFER)]), &|_| Some(sto(&a1, &[])));
+
+        assert_eq!(index.range(), (9, 12));
+        let (total, events) = index.events(&wty, 1, 20, false);
+        assert_eq!(total, 3);
+        assert_eq!(events[0].from, Address::default());
+        assert_eq!(dia_act_names(events[1].acts.uint()), vec!["transfer", "insc_push"]);
+        assert_eq!((events[1].from, events[1].owner), (a1, a2));
+        assert_eq!(*events[2].inscripts, 0);
+        let (_, last) = index.events(&wty, 1, 1, true);
+        assert_eq!(last[0].height.uint(), 12);
+
+        let (total, holds) = index.holds(&a1, 1, 20, false);
+        assert_eq!(total, 2);
+        assert_eq!((*holds[0].dir, *holds[1].dir), (DIA_HOLD_IN, DIA_HOLD_OUT));
+        let (total, holds) = index.holds(&a2, 2, 1, false);
+        assert_eq!(total, 2);
+        assert_eq!(holds[0].diamond, kkk);
+
+        assert_eq!(index.inscribed(b"hello").unwrap(), vec![kkk]);
+        assert!(index.inscribed(b"world").unwrap().is_empty());
+    }
+
+    #[test]
+    fn backfill_reads_diamonds_in_state() {
+        let index = DiamondIndex::new(Box::new(MemDisk::default()));
+        let (wty, kkk) = (dia("WTYUIA"), dia("KKKKVA"));
+        let a1 = addr("12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi");
+        let a2 = addr("1LsQLqkd8FQDh3R7ZhxC5fndNf92WfhM19");
+        // indexed before the diamonds in state were read in
+        index.apply(5, &touches(&[(&wty, DIA_ACT_MINT)]), &|_| Some(sto(&a1, &["old"])));
+        assert!(index.inscribed(b"old").is_err());
+
+        // since then a contract moved it and cleaned it, and kkk was inscribed
+        let disk = MemDisk::default();
+        let skey = |n: &DiamondName| [&[DIAMOND_STATE_PREFIX][..], n.as_ref()].concat();
+        disk.save(&skey(&wty), &sto(&a2, &[]).serialize());
+        disk.save(&skey(&kkk), &sto(&a1, &["hello"]).serialize());
+        disk.save(&[11u8, 1, 2], &[0]);
+        index.backfill(8, &disk).unwrap();
+        assert!(index.backfilled());
+        assert_eq!(index.range(), (5, 8));
+        assert_eq!(index.inscribed(b"hello").unwrap(), vec![kkk]);
+        assert!(index.inscribed(b"old").unwrap().is_empty());
+
+        index.apply(9, &touches(&[(&wty, DIA_ACT_CONTRACT)]), &|_| Some(sto(&a1, &[])));
+        let (_, last) = index.events(&wty, 1, 1, true);
+        assert_eq!((last[0].from, last[0].owner), (a2, a1));
+        let (total, _) = index.holds(&a2, 1, 20, false);
+        assert_eq!(total, 1);
+    }
+
+    #[test]
+    fn state_writes_decide_touched_diamonds() {
+        let (wty, hhh) = (dia("WTYUIA"), dia("HHHHHH"));
+        // an AST branch names wty and kkk, only wty ran; a contract moved hhh
+        let mut trs = DiaToTrs::new();
+        trs.diamonds = DiamondNameListMax200::from_readable("WTYUIA,KKKKVA").unwrap();
+        let ast = AstSelect::create_list(vec![Box::new(trs)]);
+        let hints = {
+            let mut t = DiaTouches::default();
+            let hx = Hash::from([1u8; 32]);
+            for sub in ast_action_childs(&ast) {
+                let (kind, names) = action_diamonds(sub).unwrap();
+                names.iter().for_each(|n| t.add(n, &hx, kind));
+            }
+            t
+        };
+        assert_eq!(hints.list.len(), 2);
+        let mut writes = MemMap::new();
+        for n in [&hhh, &wty] {
+            writes.insert([&[DIAMOND_STATE_PREFIX][..], n.as_ref()].concat(), Some(vec![]));
+        }
+        writes.insert(vec![16, 1, 2, 3], None);
+        let touches = state_diamond_touches(&writes, &hints);
+        assert_eq!(touches.list, vec![
+            (wty, Hash::from([1u8; 32]), DIA_ACT_TRANSFER),
+            (hhh, Hash::default(), DIA_ACT_CONTRACT),
+        ]);
+        assert_eq!(diamond_state_key(&[13, 1]), None);
+    }
+
+    #[test]
+    fn actions_name_touched_diamonds() {
+        let mut trs = DiaToTrs::new();
+        trs.diamonds = DiamondNameListMax200::from_readable("WTYUIA,KKKKVA").unwrap();
+        let act: Box<dyn Action> = Box::new(trs);
+        assert_eq!(action_diamonds(act.as_ref()), Some((DIA_ACT_TRANSFER, vec![dia("WTYUIA"), dia("KKKKVA")])));
+
+        let mut mv = DiaInscMove::new();
+        mv.from_diamond = dia("WTYUIA");
+        mv.to_diamond = dia("KKKKVA");
+        let act: Box<dyn Action> = Box::new(mv);
+        assert_eq!(action_diamonds(act.as_ref()).unwrap().0, DIA_ACT_INSC_MOVE);
+
+        let act: Box<dyn Action> = Box::new(HacToTrs::new());
+        assert_eq!(action_diamonds(act.as_ref()), None);
+    }
+}
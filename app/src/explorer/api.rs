@@ -0,0 +1,35 @@
+struct ExplorerExportApiService {}
+
+pub fn service() -> Arc<dyn ApiService> {
+    Arc::new(ExplorerExportApiService {})
+}
+
+impl ApiService for ExplorerExportApiService {
+    fn name(&self) -> &'static str {
+        "explorer"
+    }
+
+    fn routes(&self) -> Vec<ApiRoute> {
+        use ApiType::*;
+        vec![
+            ApiRoute::get("/query/explorer/status", query_explorer_status)
+                .summary("Progress of the explorer export")
+                .returns("cursor", Integer, "last exported block height, null before the first")
+                .returns("queued", Integer, "rolled blocks waiting for export")
+                .returns("incomplete", Integer, "blocks exported without state writes since the node started"),
+        ]
+    }
+}
+
+fn query_explorer_status(_: &ApiExecCtx, _: ApiRequest) -> ApiResponse {
+    let exporter = match explorer_exporter() {
+        Ok(e) => e,
+        Err(e) => return ApiResponse::json(json!({"ret":1,"err":e}).to_string()),
+    };
+    ApiResponse::json(json!({
+        "ret": 0,
+        "cursor": exporter.cursor(),
+        "queued": exporter.queued(),
+        "incomplete": exporter.incomplete(),
+    }).to_string())
+}
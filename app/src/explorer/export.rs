@@ -0,0 +1,249 @@
+
+static EXPLORER_EXPORTER: OnceLock<ExplorerExporter> = OnceLock::new();
+
+/// Open the export sink from `[explorer]`. Without it nothing is exported.
+pub fn configure_explorer_export(cnf: &ExplorerConf) -> Rerr {
+    let Some(sink) = cnf.open_sink()? else {
+        return Ok(());
+    };
+    let _ = EXPLORER_EXPORTER.set(ExplorerExporter::new(sink, cnf.start));
+    Ok(())
+}
+
+pub fn explorer_exporter() -> Ret<&'static ExplorerExporter> {
+    EXPLORER_EXPORTER.get().ok_or_else(|| "explorer export not enabled".to_owned())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Sqlite,
+}
+
+/// `[explorer]` keys: the export format, its directory and the first block
+/// of a new export.
+#[derive(Clone, Debug)]
+pub struct ExplorerConf {
+    pub format: Option<ExportFormat>,
+    pub dir: PathBuf,
+    pub start: u64,
+}
+
+impl ExplorerConf {
+    pub fn new(ini: &IniObj, data_dir: &Path) -> Self {
+        let sec = ini_section(ini, "explorer");
+        let format = match ini_must(sec, "export", "").as_str() {
+            "" => None,
+            "csv" => Some(ExportFormat::Csv),
+            "sqlite" => Some(ExportFormat::Sqlite),
+            s => panic!("{}", exiterr!(1, "explorer export '{}' not supported", s)),
+        };
+        let dir = match ini_must(sec, "dir", "").as_str() {
+            "" => data_dir.join("explorer"),
+            d => PathBuf::from(d),
+        };
+        Self { format, dir, start: ini_must_u64(sec, "start", 1).max(1) }
+    }
+
+    pub fn open_sink(&self) -> Ret<Option<Box<dyn ExportSink>>> {
+        Ok(match self.format {
+            None => None,
+            Some(ExportFormat::Csv) => Some(Box::new(CsvSink::open(&self.dir)?)),
+            #[cfg(feature = "sqlite")]
+            Some(ExportFormat::Sqlite) => Some(Box::new(SqliteSink::open(&self.dir.join("explorer.db"))?)),
+            #[cfg(not(feature = "sqlite"))]
+            Some(ExportFormat::Sqlite) => return errf!("explorer sqlite export needs a build with feature 'sqlite'"),
+        })
+    }
+}
+
+/// Blocks rolled but not exported yet; past it the chain waits for the
+/// export thread before rolling on.
+const EXPORT_QUEUE_MAX: usize = 1024;
+
+struct RolledBlock {
+    blk: Arc<dyn Block>,
+    writes: MemMap,
+}
+
+/*
+    Blocks are exported in height order, each once, from the cursor on, by
+    the export thread alone: `roll` only queues a block with its state
+    writes, and waits for room while the queue is full and the thread runs,
+    so no rolled block loses its writes. The thread takes the next block
+    from the queue, or, for the blocks rolled before it started, from the
+    block store up to the highest root seen; those come without state
+    writes and are exported marked incomplete.
+*/
+pub struct ExplorerExporter {
+    sink: Mutex<Box<dyn ExportSink>>,
+    start: u64,
+    logs: OnceLock<Arc<dyn Logs>>,
+    seen: AtomicU64,
+    running: AtomicBool,
+    incomplete: AtomicU64,
+    queue: Mutex<VecDeque<RolledBlock>>,
+    queued: Condvar,
+    room: Condvar,
+}
+
+impl ExplorerExporter {
+    pub fn new(sink: Box<dyn ExportSink>, start: u64) -> Self {
+        Self {
+            sink: Mutex::new(sink),
+            start,
+            logs: OnceLock::new(),
+            seen: AtomicU64::new(0),
+            running: AtomicBool::new(false),
+            incomplete: AtomicU64::new(0),
+            queue: Mutex::default(),
+            queued: Condvar::new(),
+            room: Condvar::new(),
+        }
+    }
+
+    /// Read the VM logs of exported blocks from `logs`; no block is
+    /// exported before.
+    pub fn bind_logs(&self, logs: Arc<dyn Logs>) {
+        let _ = self.logs.set(logs);
+    }
+
+    /// Last exported block height, `None` before the first.
+    pub fn cursor(&self) -> Option<u64> {
+        self.sink.lock().unwrap().cursor()
+    }
+
+    /// Blocks queued for export.
+    pub fn queued(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    /// Blocks exported incomplete, without state writes, since the node started.
+    pub fn incomplete(&self) -> u64 {
+        self.incomplete.load(Ordering::Relaxed)
+    }
+
+    fn next(&self, sink: &dyn ExportSink) -> u64 {
+        sink.cursor().map_or(self.start, |c| c + 1)
+    }
+
+    fn export(&self, sink: &mut dyn ExportSink, blk: &dyn BlockRead, writes: Option<&MemMap>) -> Rerr {
+        let height = blk.height().uint();
+        let mut logs = vec![];
+        if let Some(lgs) = self.logs.get() {
+            while let Some(v) = lgs.load(height, logs.len()) {
+                logs.push(VmLog::build(&v)?);
+            }
+        }
+        sink.write(&block_export_rows(blk, &logs, writes))
+    }
+
+    /// Queue a block that just became the root, with the balance and
+    /// diamond writes from `export_state_writes`.
+    pub fn roll(&self, blk: Arc<dyn Block>, writes: MemMap) {
+        let height = blk.height().uint();
+        let mut queue = self.queue.lock().unwrap();
+        while queue.len() >= EXPORT_QUEUE_MAX && self.running.load(Ordering::Relaxed) {
+            // rechecked, as the export thread may stop without draining
+            queue = self.room.wait_timeout(queue, Duration::from_secs(1)).unwrap().0;
+        }
+        if queue.len() < EXPORT_QUEUE_MAX {
+            queue.push_back(RolledBlock { blk, writes });
+        }
+        // only now, or the block could be read from the store while waiting
+        self.seen.fetch_max(height, Ordering::Relaxed);
+        self.queued.notify_one();
+    }
+
+    /// Whether the export thread drains the queue; `roll` only waits for
+    /// room while it does.
+    pub fn set_running(&self, running: bool) {
+        self.running.store(running, Ordering::Relaxed);
+        self.room.notify_all();
+    }
+
+    /// Wait up to `dur` for a rolled block to be queued.
+    pub fn wait_rolled(&self, dur: Duration) {
+        let queue = self.queue.lock().unwrap();
+        if queue.is_empty() {
+            let _ = self.queued.wait_timeout(queue, dur);
+        }
+    }
+
+    /// Export the blocks from the cursor up to the confirmed root, queued
+    /// ones with their state writes and the others from the store, until
+    /// caught up or `stop` answers true. Returns how many came from the
+    /// store, incomplete.
+    pub fn pump(&self, store: &dyn Store, stop: &mut dyn FnMut() -> bool) -> Ret<u64> {
+        if self.logs.get().is_none() {
+            return Ok(0);
+        }
+        let mut done = 0;
+        let mut sink = self.sink.lock().unwrap();
+        while !stop() {
+            let next = self.next(sink.as_ref());
+            let rolled = {
+                let mut queue = self.queue.lock().unwrap();
+                while queue.front().is_some_and(|r| r.blk.height().uint() < next) {
+                    queue.pop_front();
+                }
+                let rolled = match queue.front().is_some_and(|r| r.blk.height().uint() == next) {
+                    true => queue.pop_front(),
+                    false => None,
+                };
+                self.room.notify_all();
+                rolled
+            };
+            if let Some(r) = rolled {
+                self.export(sink.as_mut(), r.blk.as_read(), Some(&r.writes))?;
+                continue;
+            }
+            let root = store.status().root_height.uint().max(self.seen.load(Ordering::Relaxed));
+            if next > root {
+                break;
+            }
+            let Some((_, data)) = store.block_data_by_height(&BlockHeight::from(next)) else {
+                return errf!("block {} not found in the block store", next);
+            };
+            let (blk, _) = block_create(&data)?;
+            self.export(sink.as_mut(), blk.as_read(), None)?;
+            self.incomplete.fetch_add(1, Ordering::Relaxed);
+            done += 1;
+        }
+        Ok(done)
+    }
+}
+
+/// Bind the engine to the exporter, if one is configured, and keep the
+/// export caught up with the block store.
+pub fn start_explorer_export(mut worker: Worker, hnoder: Arc<dyn HNoder>) {
+    let Ok(exporter) = explorer_exporter() else {
+        return;
+    };
+    let engine = hnoder.engine();
+    exporter.bind_logs(engine.logs());
+    let start = exporter.cursor().map_or(exporter.start, |c| c + 1);
+    println!("[Explorer Export] start from block {}.", start);
+    exporter.set_running(true);
+    thread::spawn(move || {
+        let mut last_err = None;
+        loop {
+            match exporter.pump(engine.store().as_ref(), &mut || worker.quit()) {
+                Ok(n) if n > 0 => println!("[Explorer Export] backfilled {} blocks, cursor {:?}.", n, exporter.cursor()),
+                Ok(_) => last_err = None,
+                Err(e) => {
+                    // retried every second, reported once
+                    if last_err.as_ref() != Some(&e) {
+                        println!("[Explorer Export] export stopped: {}", e);
+                    }
+                    last_err = Some(e);
+                }
+            }
+            if worker.quit() {
+                break;
+            }
+            exporter.wait_rolled(Duration::from_secs(1));
+        }
+        exporter.set_running(false);
+    });
+}
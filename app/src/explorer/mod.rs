@@ -0,0 +1,43 @@
+/*
+    Block explorer export.
+
+    Writes each confirmed block as normalized rows: blocks, transactions,
+    actions with their JSON, HAC, SAT and asset transfers, diamonds,
+    created assets, contract calls, VM logs and changed balances. Blocks
+    come from `Scaner::roll`, which sees only blocks that became the root
+    and will not be reorganized, with their state writes, and from the
+    block store for those the node already had. A cursor in the sink keeps
+    the last exported height, so an export resumes where it stopped.
+*/
+use std::collections::{HashSet, VecDeque};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::*;
+use std::thread;
+use std::time::Duration;
+
+use basis::component::*;
+use basis::interface::*;
+use field::*;
+use mint::action::*;
+use protocol::action::*;
+use protocol::block::block_create;
+use serde_json::json;
+use sys::*;
+use vm::action::*;
+use vm::rt::CodeConf;
+use vm::{ContractAddress, VmLog};
+
+use crate::assetindex::{AssetTouches, action_asset_moves};
+use crate::diaindex::{action_diamonds, dia_act_names, diamond_state_key};
+
+include! {"rows.rs"}
+include! {"sink.rs"}
+include! {"export.rs"}
+include! {"scan.rs"}
+include! {"api.rs"}
+
+#[cfg(test)]
+include! {"tests.rs"}
@@ -0,0 +1,377 @@
+
+/// One cell of an exported row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExportVal {
+    Int(i64),
+    Text(String),
+    Null,
+}
+
+impl ExportVal {
+    fn addr(addr: Option<Address>) -> Self {
+        match addr {
+            Some(a) if a != ADDRESS_ZERO => Self::Text(a.to_readable()),
+            _ => Self::Null,
+        }
+    }
+}
+
+macro_rules! xint { ($v: expr) => { ExportVal::Int($v as i64) } }
+macro_rules! xtext { ($v: expr) => { ExportVal::Text($v.to_string()) } }
+
+/// An exported table, its columns and their SQL types.
+pub struct ExportTable {
+    pub name: &'static str,
+    pub cols: &'static [(&'static str, &'static str)],
+}
+
+pub const EXP_BLOCKS: usize = 0;
+pub const EXP_TRANSACTIONS: usize = 1;
+pub const EXP_ACTIONS: usize = 2;
+pub const EXP_TRANSFERS: usize = 3;
+pub const EXP_DIAMONDS: usize = 4;
+pub const EXP_ASSETS: usize = 5;
+pub const EXP_CONTRACT_CALLS: usize = 6;
+pub const EXP_VM_LOGS: usize = 7;
+pub const EXP_BALANCES: usize = 8;
+
+const INT: &str = "INTEGER";
+const TEXT: &str = "TEXT";
+
+const BALANCE_STATE_PREFIX: u8 = 11;
+const DIAMOND_STATE_PREFIX: u8 = 13;
+
+pub const EXPORT_TABLES: [ExportTable; 9] = [
+    ExportTable { name: "blocks", cols: &[
+        ("height", INT), ("hash", TEXT), ("prevhash", TEXT), ("mrklroot", TEXT), ("timestamp", INT),
+        ("version", INT), ("difficulty", INT), ("nonce", INT), ("tx_count", INT), ("miner", TEXT),
+        ("incomplete", INT),
+    ] },
+    ExportTable { name: "transactions", cols: &[
+        ("height", INT), ("idx", INT), ("hash", TEXT), ("ty", INT), ("main", TEXT),
+        ("fee", TEXT), ("timestamp", INT), ("action_count", INT),
+    ] },
+    ExportTable { name: "actions", cols: &[
+        ("height", INT), ("tx_idx", INT), ("idx", INT), ("parent", INT), ("kind", INT), ("description", TEXT),
+        ("json", TEXT), ("executed", INT),
+    ] },
+    ExportTable { name: "transfers", cols: &[
+        ("height", INT), ("tx_idx", INT), ("act_idx", INT), ("coin", TEXT), ("serial", INT),
+        ("from_addr", TEXT), ("to_addr", TEXT), ("amount", TEXT),
+    ] },
+    ExportTable { name: "diamonds", cols: &[
+        ("height", INT), ("tx_idx", INT), ("act_idx", INT), ("name", TEXT), ("kind", TEXT),
+        ("from_addr", TEXT), ("to_addr", TEXT),
+    ] },
+    ExportTable { name: "assets", cols: &[
+        ("height", INT), ("tx_idx", INT), ("serial", INT), ("ticket", TEXT), ("name", TEXT),
+        ("decimal", INT), ("issuer", TEXT), ("supply", INT),
+    ] },
+    ExportTable { name: "contract_calls", cols: &[
+        ("height", INT), ("tx_idx", INT), ("act_idx", INT), ("kind", TEXT), ("contract", TEXT), ("data", TEXT),
+    ] },
+    ExportTable { name: "vm_logs", cols: &[
+        ("height", INT), ("idx", INT), ("address", TEXT), ("topic0", TEXT), ("topic1", TEXT),
+        ("topic2", TEXT), ("topic3", TEXT), ("data", TEXT),
+    ] },
+    ExportTable { name: "balances", cols: &[
+        ("height", INT), ("address", TEXT), ("hacash", TEXT), ("satoshi", INT), ("diamond", INT), ("assets", TEXT),
+    ] },
+];
+
+/// The rows of one block, by table.
+#[derive(Clone, Debug)]
+pub struct ExportRows {
+    pub height: u64,
+    pub tables: Vec<Vec<Vec<ExportVal>>>,
+}
+
+impl ExportRows {
+    pub fn new(height: u64) -> Self {
+        Self { height, tables: vec![vec![]; EXPORT_TABLES.len()] }
+    }
+
+    pub fn push(&mut self, table: usize, row: Vec<ExportVal>) {
+        debug_assert_eq!(row.len(), EXPORT_TABLES[table].cols.len());
+        self.tables[table].push(row);
+    }
+
+    pub fn count(&self) -> usize {
+        self.tables.iter().map(|t| t.len()).sum()
+    }
+}
+
+/// The balance and diamond writes of a block, the only state the export reads.
+pub fn export_state_writes(writes: &MemMap) -> MemMap {
+    writes.iter()
+        .filter(|(k, _)| matches!(k.first(), Some(&BALANCE_STATE_PREFIX) | Some(&DIAMOND_STATE_PREFIX)))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Normalize a block, the VM logs it left and, when known, its state
+/// writes from `export_state_writes` into rows. Without the writes the
+/// block row is marked incomplete.
+pub fn block_export_rows(blk: &dyn BlockRead, logs: &[VmLog], writes: Option<&MemMap>) -> ExportRows {
+    let height = blk.height().uint();
+    let mut rows = ExportRows::new(height);
+    let miner = blk.prelude_transaction().map(|t| t.main()).ok();
+    rows.push(EXP_BLOCKS, vec![
+        xint!(height),
+        xtext!(blk.hash().to_hex()),
+        xtext!(blk.prevhash().to_hex()),
+        xtext!(blk.mrklroot().to_hex()),
+        xint!(blk.timestamp().uint()),
+        xint!(blk.version().uint()),
+        xint!(blk.difficulty().uint()),
+        xint!(blk.nonce().uint()),
+        xint!(blk.transaction_count().uint()),
+        ExportVal::addr(miner),
+        xint!(writes.is_none()),
+    ]);
+    let mut walk = ActionWalk { height, ti: 0, writes, next: 0, touched: HashSet::new() };
+    for (ti, tx) in blk.transactions().iter().enumerate() {
+        let acts = tx.actions();
+        rows.push(EXP_TRANSACTIONS, vec![
+            xint!(height),
+            xint!(ti),
+            xtext!(tx.hash().to_hex()),
+            xint!(tx.ty()),
+            xtext!(tx.main().to_readable()),
+            xtext!(tx.fee().to_unit_string("mei")),
+            xint!(tx.timestamp().uint()),
+            xint!(acts.len()),
+        ]);
+        (walk.ti, walk.next) = (ti, 0);
+        for act in acts {
+            walk.action(tx.as_ref(), act.as_ref(), None, &mut rows);
+        }
+    }
+    if let Some(writes) = writes {
+        state_export_rows(height, writes, &walk.touched, &mut rows);
+    }
+    for (i, log) in logs.iter().enumerate() {
+        rows.push(EXP_VM_LOGS, vec![
+            xint!(height),
+            xint!(i),
+            xtext!(log.addr.to_readable()),
+            xtext!(log.topic0.raw().to_hex()),
+            xtext!(log.topic1.raw().to_hex()),
+            xtext!(log.topic2.raw().to_hex()),
+            xtext!(log.topic3.raw().to_hex()),
+            xtext!(log.data.raw().to_hex()),
+        ]);
+    }
+    rows
+}
+
+/*
+    Actions are numbered depth first within their transaction, so AST
+    children follow the AST action that holds them and name it as parent.
+    Which AST branch ran is not in the block: a child counts as executed
+    when every balance and diamond entry it would change was written by
+    the block, as not executed when one of them was not, and as unknown
+    when it changes none we can name or the writes are not at hand.
+*/
+struct ActionWalk<'a> {
+    height: u64,
+    ti: usize,
+    writes: Option<&'a MemMap>,
+    next: usize,
+    // state keys named by actions that may have run
+    touched: HashSet<Vec<u8>>,
+}
+
+impl ActionWalk<'_> {
+    fn action(&mut self, tx: &dyn Transaction, act: &dyn Action, parent: Option<(usize, Option<bool>)>, rows: &mut ExportRows) {
+        let ai = self.next;
+        self.next += 1;
+        let keys = action_export_rows(tx, act, (self.height, self.ti, ai), rows);
+        let executed = match parent {
+            None => Some(true),
+            Some((_, Some(false))) => Some(false),
+            Some(_) if keys.is_empty() => None,
+            Some(_) => self.writes.map(|w| keys.iter().all(|k| w.contains_key(k))),
+        };
+        if executed != Some(false) {
+            self.touched.extend(keys);
+        }
+        rows.push(EXP_ACTIONS, vec![
+            xint!(self.height),
+            xint!(self.ti),
+            xint!(ai),
+            parent.map_or(ExportVal::Null, |(p, _)| xint!(p)),
+            xint!(act.kind()),
+            xtext!(act.to_description()),
+            xtext!(act.to_json()),
+            executed.map_or(ExportVal::Null, |e| xint!(e)),
+        ]);
+        for sub in ast_action_childs(act) {
+            self.action(tx, sub, Some((ai, executed)), rows);
+        }
+    }
+}
+
+/// What only the state writes show: diamonds moved by no action we can
+/// read, such as by a contract, and the balance after the block of every
+/// address it changed, contract moves included.
+fn state_export_rows(height: u64, writes: &MemMap, touched: &HashSet<Vec<u8>>, rows: &mut ExportRows) {
+    let mut keys: Vec<&Vec<u8>> = writes.keys().collect();
+    keys.sort();
+    for k in keys {
+        let v = writes[k].as_deref();
+        if let Some(name) = diamond_state_key(k) {
+            if touched.contains(k) {
+                continue;
+            }
+            let owner = v.and_then(|v| DiamondSto::build(v).ok()).map(|d| d.address);
+            rows.push(EXP_DIAMONDS, vec![
+                xint!(height),
+                ExportVal::Null,
+                ExportVal::Null,
+                xtext!(name.to_readable()),
+                xtext!("contract"),
+                ExportVal::Null,
+                ExportVal::addr(owner),
+            ]);
+        } else if k.len() == 1 + Address::SIZE {
+            let addr = Address::from(<[u8; Address::SIZE]>::try_from(&k[1..]).unwrap());
+            let bls = v.and_then(|v| Balance::build(v).ok()).unwrap_or_default();
+            let assets: Vec<_> = bls.assets.as_list().iter().map(|a| serde_json::json!({
+                "serial": a.serial.uint(),
+                "amount": a.amount.uint(),
+            })).collect();
+            rows.push(EXP_BALANCES, vec![
+                xint!(height),
+                xtext!(addr.to_readable()),
+                xtext!(bls.hacash.to_unit_string("mei")),
+                xint!(bls.satoshi.uint()),
+                xint!(bls.diamond.uint()),
+                xtext!(serde_json::Value::from(assets)),
+            ]);
+        }
+    }
+}
+
+fn balance_state_key(addr: &Address) -> Vec<u8> {
+    [&[BALANCE_STATE_PREFIX][..], &addr.serialize()].concat()
+}
+
+/// Transfers, diamonds, assets and contract calls of one action, without
+/// its AST children. Returns the balance and diamond state keys it changes
+/// when it runs.
+fn action_export_rows(tx: &dyn Transaction, act: &dyn Action, at: (u64, usize, usize), rows: &mut ExportRows) -> Vec<Vec<u8>> {
+    let (height, ti, ai) = at;
+    let main = tx.main();
+    let addrs = tx.addrs();
+    let real = |a: &AddrOrPtr| a.real(&addrs).ok();
+    let any = act.as_any();
+    let mut keys: Vec<Vec<u8>> = vec![];
+    let key_of = |keys: &mut Vec<Vec<u8>>, addr: Option<Address>| {
+        if let Some(a) = addr.filter(|a| *a != ADDRESS_ZERO) {
+            keys.push(balance_state_key(&a));
+        }
+    };
+    let mut trs = |coin: &str, serial: Option<u64>, from: Option<Address>, to: Option<Address>, amount: String| {
+        key_of(&mut keys, from);
+        key_of(&mut keys, to);
+        rows.push(EXP_TRANSFERS, vec![
+            xint!(height),
+            xint!(ti),
+            xint!(ai),
+            xtext!(coin),
+            serial.map_or(ExportVal::Null, |s| xint!(s)),
+            ExportVal::addr(from),
+            ExportVal::addr(to),
+            ExportVal::Text(amount),
+        ]);
+    };
+    let mei = |a: &Amount| a.to_unit_string("mei");
+    if let Some(a) = any.downcast_ref::<HacToTrs>() {
+        trs("hac", None, Some(main), real(&a.to), mei(&a.hacash));
+    } else if let Some(a) = any.downcast_ref::<HacFromTrs>() {
+        trs("hac", None, real(&a.from), Some(main), mei(&a.hacash));
+    } else if let Some(a) = any.downcast_ref::<HacFromToTrs>() {
+        trs("hac", None, real(&a.from), real(&a.to), mei(&a.hacash));
+    } else if let Some(a) = any.downcast_ref::<SatToTrs>() {
+        trs("sat", None, Some(main), real(&a.to), a.satoshi.uint().to_string());
+    } else if let Some(a) = any.downcast_ref::<SatFromTrs>() {
+        trs("sat", None, real(&a.from), Some(main), a.satoshi.uint().to_string());
+    } else if let Some(a) = any.downcast_ref::<SatFromToTrs>() {
+        trs("sat", None, real(&a.from), real(&a.to), a.satoshi.uint().to_string());
+    }
+    // assets
+    let mut assets = AssetTouches::default();
+    action_asset_moves(tx, act, &mut assets);
+    for (_, _, amt, from, to) in &assets.moves {
+        trs("asset", Some(amt.serial.uint()), Some(*from), Some(*to), amt.amount.uint().to_string());
+    }
+    for (_, m) in &assets.creates {
+        rows.push(EXP_ASSETS, vec![
+            xint!(height),
+            xint!(ti),
+            xint!(m.serial.uint()),
+            xtext!(m.ticket.to_readable_or_hex()),
+            xtext!(m.name.to_readable_or_hex()),
+            xint!(m.decimal.uint()),
+            xtext!(m.issuer.to_readable()),
+            xint!(m.supply.uint()),
+        ]);
+    }
+    // diamonds
+    if let Some((kind, names)) = action_diamonds(act) {
+        let (from, to) = if let Some(a) = any.downcast_ref::<DiamondMint>() {
+            (None, Some(a.d.address))
+        } else if let Some(a) = any.downcast_ref::<DiaSingleTrs>() {
+            (Some(main), real(&a.to))
+        } else if let Some(a) = any.downcast_ref::<DiaToTrs>() {
+            (Some(main), real(&a.to))
+        } else if let Some(a) = any.downcast_ref::<DiaFromTrs>() {
+            (real(&a.from), Some(main))
+        } else if let Some(a) = any.downcast_ref::<DiaFromToTrs>() {
+            (real(&a.from), real(&a.to))
+        } else {
+            (None, None)
+        };
+        let kind = dia_act_names(kind).join(",");
+        key_of(&mut keys, from);
+        key_of(&mut keys, to);
+        for name in &names {
+            keys.push([&[DIAMOND_STATE_PREFIX][..], &name.serialize()].concat());
+            rows.push(EXP_DIAMONDS, vec![
+                xint!(height),
+                xint!(ti),
+                xint!(ai),
+                xtext!(name.to_readable()),
+                xtext!(kind),
+                ExportVal::addr(from),
+                ExportVal::addr(to),
+            ]);
+        }
+    }
+    // contract calls
+    let call = if let Some(a) = any.downcast_ref::<ContractDeploy>() {
+        let addr = ContractAddress::calculate(&main, &a.nonce);
+        Some(("deploy", Some(*addr), a.construct_argv.to_hex()))
+    } else if let Some(a) = any.downcast_ref::<ContractUpdate>() {
+        Some(("update", Some(a.address), String::new()))
+    } else if let Some(a) = any.downcast_ref::<P2SHScriptProve>() {
+        let addr = CodeConf::parse(a.codeconf.uint()).ok()
+            .and_then(|cnf| P2SHScriptProve::calc_scriptmh_from_lockbox(&a.adrlibs, cnf, &a.lockbox, &a.merkels).ok())
+            .map(|calc| calc.address);
+        Some(("p2shprove", addr, a.argvkey.to_hex()))
+    } else {
+        any.downcast_ref::<ContractMainCall>().map(|a| ("maincall", None, a.codes.to_hex()))
+    };
+    if let Some((kind, contract, data)) = call {
+        rows.push(EXP_CONTRACT_CALLS, vec![
+            xint!(height),
+            xint!(ti),
+            xint!(ai),
+            xtext!(kind),
+            ExportVal::addr(contract),
+            ExportVal::Text(data),
+        ]);
+    }
+    keys
+}
@@ -0,0 +1,43 @@
+
+/// Feeds confirmed blocks to the explorer export, if one is configured;
+/// everything else goes to the wrapped scaner.
+pub struct ExplorerExportScaner {
+    inner: Box<dyn Scaner>,
+}
+
+impl ExplorerExportScaner {
+    pub fn wrap(inner: Box<dyn Scaner>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Scaner for ExplorerExportScaner {
+    fn init(&mut self, ini: &IniObj) -> Rerr {
+        self.inner.init(ini)
+    }
+
+    fn exit(&self) {
+        self.inner.exit()
+    }
+
+    fn start(&self, worker: Worker) {
+        self.inner.start(worker)
+    }
+
+    fn serve(&self, worker: Worker) {
+        self.inner.serve(worker)
+    }
+
+    fn roll(&self, blk: Arc<dyn Block>, sta: Arc<Box<dyn State>>, disk: Arc<dyn DiskDB>) {
+        if let Ok(exporter) = explorer_exporter() {
+            exporter.roll(blk.clone(), export_state_writes(sta.as_mem()));
+        }
+        self.inner.roll(blk, sta, disk)
+    }
+
+    fn api_services(&self) -> Vec<Arc<dyn ApiService>> {
+        let mut services = self.inner.api_services();
+        services.push(service());
+        services
+    }
+}
@@ -0,0 +1,204 @@
+
+/// Where exported rows go. `write` stores the rows of one block and moves
+/// the cursor to it, all or nothing.
+pub trait ExportSink: Send {
+    /// Last exported block height, `None` for a new export.
+    fn cursor(&self) -> Option<u64>;
+    fn write(&mut self, rows: &ExportRows) -> Rerr;
+}
+
+/*
+    CSV files, one per table with a header line, next to a `cursor` file
+    holding the last exported height and the length of every file after
+    it. Opening truncates the files back to those lengths, dropping rows
+    written past the cursor by an interrupted export.
+*/
+pub struct CsvSink {
+    dir: PathBuf,
+    cursor: Option<u64>,
+    lens: Vec<u64>,
+}
+
+impl CsvSink {
+    pub fn open(dir: &Path) -> Ret<Self> {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let mut sink = Self { dir: dir.to_path_buf(), cursor: None, lens: vec![0; EXPORT_TABLES.len()] };
+        if let Ok(txt) = std::fs::read_to_string(sink.cursor_path()) {
+            sink.load_cursor(&txt)?;
+        }
+        for (i, t) in EXPORT_TABLES.iter().enumerate() {
+            let path = sink.table_path(t);
+            let head = t.cols.iter().map(|c| c.0).collect::<Vec<_>>().join(",") + "\n";
+            if sink.lens[i] == 0 {
+                std::fs::write(&path, &head).map_err(|e| e.to_string())?;
+                sink.lens[i] = head.len() as u64;
+            } else {
+                let mut line = String::new();
+                let file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
+                BufReader::new(file).read_line(&mut line).map_err(|e| e.to_string())?;
+                if line != head {
+                    return errf!("explorer csv {} has other columns, remove {} to export again", path.display(), dir.display());
+                }
+            }
+            let file = OpenOptions::new().write(true).open(&path).map_err(|e| e.to_string())?;
+            file.set_len(sink.lens[i]).map_err(|e| e.to_string())?;
+        }
+        Ok(sink)
+    }
+
+    fn cursor_path(&self) -> PathBuf {
+        self.dir.join("cursor")
+    }
+
+    fn table_path(&self, t: &ExportTable) -> PathBuf {
+        self.dir.join(format!("{}.csv", t.name))
+    }
+
+    fn load_cursor(&mut self, txt: &str) -> Rerr {
+        let mut lines = txt.lines();
+        let height = lines.next().and_then(|l| l.trim().parse().ok());
+        let height = height.ok_or_else(|| "explorer csv cursor format invalid".to_owned())?;
+        for line in lines {
+            let Some((name, len)) = line.split_once(' ') else {
+                continue;
+            };
+            if let Some(i) = EXPORT_TABLES.iter().position(|t| t.name == name) {
+                self.lens[i] = len.trim().parse().map_err(|_| "explorer csv cursor format invalid".to_owned())?;
+            }
+        }
+        self.cursor = Some(height);
+        Ok(())
+    }
+
+    fn save_cursor(&self, height: u64) -> Rerr {
+        let mut txt = format!("{}\n", height);
+        for (t, len) in EXPORT_TABLES.iter().zip(&self.lens) {
+            txt += &format!("{} {}\n", t.name, len);
+        }
+        let tmp = self.dir.join("cursor.tmp");
+        std::fs::write(&tmp, txt).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, self.cursor_path()).map_err(|e| e.to_string())
+    }
+}
+
+fn csv_cell(v: &ExportVal) -> String {
+    match v {
+        ExportVal::Int(n) => n.to_string(),
+        ExportVal::Null => String::new(),
+        ExportVal::Text(s) if s.contains([',', '"', '\n', '\r']) => format!("\"{}\"", s.replace('"', "\"\"")),
+        ExportVal::Text(s) => s.clone(),
+    }
+}
+
+impl ExportSink for CsvSink {
+    fn cursor(&self) -> Option<u64> {
+        self.cursor
+    }
+
+    fn write(&mut self, rows: &ExportRows) -> Rerr {
+        for (i, t) in EXPORT_TABLES.iter().enumerate() {
+            if rows.tables[i].is_empty() {
+                continue;
+            }
+            let mut buf = String::new();
+            for row in &rows.tables[i] {
+                buf += &row.iter().map(csv_cell).collect::<Vec<_>>().join(",");
+                buf.push('\n');
+            }
+            let mut file = OpenOptions::new().append(true).open(self.table_path(t)).map_err(|e| e.to_string())?;
+            file.write_all(buf.as_bytes()).map_err(|e| e.to_string())?;
+            self.lens[i] += buf.len() as u64;
+        }
+        self.save_cursor(rows.height)?;
+        self.cursor = Some(rows.height);
+        Ok(())
+    }
+}
+
+/// An embedded SQLite database, one table per `EXPORT_TABLES` entry and the
+/// cursor in `export_cursor`, each block written in one transaction.
+#[cfg(feature = "sqlite")]
+pub struct SqliteSink {
+    conn: rusqlite::Connection,
+    cursor: Option<u64>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteSink {
+    pub fn open(path: &Path) -> Ret<Self> {
+        use rusqlite::OptionalExtension;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let conn = rusqlite::Connection::open(path).map_err(|e| e.to_string())?;
+        let mut ddl = String::from(
+            "CREATE TABLE IF NOT EXISTS export_cursor (id INTEGER PRIMARY KEY CHECK (id = 0), height INTEGER NOT NULL);\n",
+        );
+        for t in &EXPORT_TABLES {
+            let cols = t.cols.iter().map(|(c, ty)| format!("{} {}", c, ty)).collect::<Vec<_>>().join(", ");
+            ddl += &format!("CREATE TABLE IF NOT EXISTS {} ({});\n", t.name, cols);
+            ddl += &format!("CREATE INDEX IF NOT EXISTS {0}_height ON {0} (height);\n", t.name);
+        }
+        ddl += "CREATE INDEX IF NOT EXISTS transactions_hash ON transactions (hash);\n\
+                CREATE INDEX IF NOT EXISTS transfers_from ON transfers (from_addr);\n\
+                CREATE INDEX IF NOT EXISTS transfers_to ON transfers (to_addr);\n\
+                CREATE INDEX IF NOT EXISTS diamonds_name ON diamonds (name);\n";
+        conn.execute_batch(&ddl).map_err(|e| e.to_string())?;
+        // columns added since the database was created, empty in older rows
+        for t in &EXPORT_TABLES {
+            let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", t.name)).map_err(|e| e.to_string())?;
+            let have = stmt
+                .query_map([], |r| r.get::<_, String>(1))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            for (c, ty) in t.cols.iter().filter(|(c, _)| !have.iter().any(|h| h == c)) {
+                conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {};", t.name, c, ty)).map_err(|e| e.to_string())?;
+            }
+        }
+        let cursor = conn
+            .query_row("SELECT height FROM export_cursor WHERE id = 0", [], |r| r.get::<_, i64>(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .map(|h| h as u64);
+        Ok(Self { conn, cursor })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl ExportSink for SqliteSink {
+    fn cursor(&self) -> Option<u64> {
+        self.cursor
+    }
+
+    fn write(&mut self, rows: &ExportRows) -> Rerr {
+        use rusqlite::types::Value as SqlValue;
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        for (i, t) in EXPORT_TABLES.iter().enumerate() {
+            if rows.tables[i].is_empty() {
+                continue;
+            }
+            let cols = t.cols.iter().map(|c| c.0).collect::<Vec<_>>().join(", ");
+            let marks = vec!["?"; t.cols.len()].join(", ");
+            let mut stmt = tx
+                .prepare_cached(&format!("INSERT INTO {} ({}) VALUES ({})", t.name, cols, marks))
+                .map_err(|e| e.to_string())?;
+            for row in &rows.tables[i] {
+                let vals = row.iter().map(|v| match v {
+                    ExportVal::Int(n) => SqlValue::Integer(*n),
+                    ExportVal::Text(s) => SqlValue::Text(s.clone()),
+                    ExportVal::Null => SqlValue::Null,
+                });
+                stmt.execute(rusqlite::params_from_iter(vals)).map_err(|e| e.to_string())?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO export_cursor (id, height) VALUES (0, ?1) ON CONFLICT(id) DO UPDATE SET height = ?1",
+            [rows.height as i64],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        self.cursor = Some(rows.height);
+        Ok(())
+    }
+}
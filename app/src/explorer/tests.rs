@@ -0,0 +1,261 @@
+mod tests {
+    use super::*;
+    use protocol::block::BlockV1;
+    use protocol::state::{BlockStore, EmptyLogs};
+    use protocol::transaction::{DefaultPreludeTx, TransactionType2};
+    use testkit::sim::disk::MemDisk;
+
+    fn registry() -> protocol::setup::TestSetupScopeGuard {
+        protocol::setup::install_test_scope(protocol::setup::new_standard_protocol_setup(|_, s| calculate_hash(s)))
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hacash_explorer_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn addr(s: &str) -> Address {
+        Address::from_readable(s).unwrap()
+    }
+
+    fn block(height: u64, acts: Vec<Box<dyn Action>>) -> BlockV1 {
+        let mut blk = BlockV1::default();
+        blk.intro.head.version = Uint1::from(BlockV1::VERSION);
+        blk.intro.head.height = BlockHeight::from(height);
+        blk.intro.head.timestamp = Timestamp::from(1730000000 + height);
+        let prelude = DefaultPreludeTx { address: addr("1LsQLqkd8FQDh3R7ZhxC5fndNf92WfhM19"), ..Default::default() };
+        blk.transactions.push(Box::new(prelude)).unwrap();
+        if !acts.is_empty() {
+            let mut tx = TransactionType2::new_by(addr("12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi"), Amount::mei(1), 1730000000);
+            for act in acts {
+                tx.push_action(act).unwrap();
+            }
+            blk.transactions.push(Box::new(tx)).unwrap();
+        }
+        blk.intro.head.transaction_count = Uint4::from(blk.transactions.length() as u32);
+        blk
+    }
+
+    fn text(v: &ExportVal) -> &str {
+        match v {
+            ExportVal::Text(s) => s,
+            _ => panic!("not text: {:?}", v),
+        }
+    }
+
+    #[test]
+    fn block_rows_are_normalized() {
+        let _guard = registry();
+        let to = addr("1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9");
+        let mut hac = HacToTrs::new();
+        hac.to = AddrOrPtr::from_addr(to);
+        hac.hacash = Amount::mei(3);
+        let mut dia = DiaToTrs::new();
+        dia.to = AddrOrPtr::from_addr(to);
+        dia.diamonds = DiamondNameListMax200::from_readable("WTYUIA,KKKKVA").unwrap();
+        let mut ast = AssetToTrs::new();
+        ast.to = AddrOrPtr::from_addr(to);
+        ast.asset = AssetAmt { serial: Fold64::from(5).unwrap(), amount: Fold64::from(20).unwrap() };
+        let call = ContractMainCall::from_bytecode(vec![1, 2]).unwrap();
+        let blk = block(7, vec![Box::new(hac), Box::new(dia), Box::new(ast), Box::new(call)]);
+        let log = VmLog::new(to, vec![vm::value::Value::U8(1), vm::value::Value::U8(2)]).unwrap();
+
+        let rows = block_export_rows(&blk, &[log], None);
+        let counts: Vec<usize> = rows.tables.iter().map(|t| t.len()).collect();
+        assert_eq!(counts, vec![1, 2, 4, 2, 2, 0, 1, 1, 0]);
+        assert_eq!(text(&rows.tables[EXP_BLOCKS][0][9]), "1LsQLqkd8FQDh3R7ZhxC5fndNf92WfhM19");
+        let hac = &rows.tables[EXP_TRANSFERS][0];
+        assert_eq!((text(&hac[3]), text(&hac[6]), text(&hac[7])), ("hac", to.to_readable().as_str(), "3"));
+        let ast = &rows.tables[EXP_TRANSFERS][1];
+        assert_eq!((text(&ast[3]), &ast[4]), ("asset", &ExportVal::Int(5)));
+        assert_eq!(text(&rows.tables[EXP_DIAMONDS][1][3]), "KKKKVA");
+        assert_eq!(text(&rows.tables[EXP_CONTRACT_CALLS][0][3]), "maincall");
+        assert_eq!(rows.tables[EXP_CONTRACT_CALLS][0][4], ExportVal::Null);
+        assert!(text(&rows.tables[EXP_ACTIONS][0][6]).starts_with('{'));
+        assert_eq!((&rows.tables[EXP_ACTIONS][0][3], &rows.tables[EXP_ACTIONS][0][7]), (&ExportVal::Null, &ExportVal::Int(1)));
+    }
+
+    fn balance_write(writes: &mut MemMap, adr: &Address, mei: u64) {
+        let bls = Balance { hacash: Amount::mei(mei), ..Default::default() };
+        writes.insert(balance_state_key(adr), Some(bls.serialize()));
+    }
+
+    #[test]
+    fn state_writes_mark_ast_branches_and_contract_moves() {
+        let _guard = registry();
+        let main = addr("12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi");
+        let (x, y) = (Address::from([7u8; Address::SIZE]), Address::from([8u8; Address::SIZE]));
+        let hac_to = |to: Address| -> Box<dyn Action> {
+            let mut hac = HacToTrs::new();
+            hac.to = AddrOrPtr::from_addr(to);
+            hac.hacash = Amount::mei(1);
+            Box::new(hac)
+        };
+        let inner = AstSelect::create_by(1, 1, vec![hac_to(x)]);
+        let astif = AstIf::create_by(AstSelect::nop(), AstSelect::create_by(1, 1, vec![Box::new(inner)]), AstSelect::create_by(1, 1, vec![hac_to(y)]));
+        let blk = block(8, vec![Box::new(astif), Box::new(P2SHScriptProve::new())]);
+        // the if branch ran, and a contract moved a diamond to y
+        let mut writes = MemMap::new();
+        balance_write(&mut writes, &main, 8);
+        balance_write(&mut writes, &x, 1);
+        let name = DiamondName::from_readable(b"WTYUIA").unwrap();
+        let sto = DiamondSto { address: y, ..Default::default() };
+        writes.insert([&[13u8][..], &name.serialize()].concat(), Some(sto.serialize()));
+
+        let rows = block_export_rows(&blk, &[], Some(&writes));
+        let acts: Vec<(i64, &ExportVal, &ExportVal)> = rows.tables[EXP_ACTIONS].iter()
+            .filter(|r| r[1] == ExportVal::Int(1))
+            .map(|r| (match r[2] { ExportVal::Int(i) => i, _ => -1 }, &r[3], &r[7]))
+            .collect();
+        let (one, zero, null) = (ExportVal::Int(1), ExportVal::Int(0), ExportVal::Null);
+        assert_eq!(acts, vec![
+            (0, &null, &one),                // AstIf
+            (1, &ExportVal::Int(0), &null),  // AstSelect in the if branch
+            (2, &ExportVal::Int(1), &one),   // to x, written
+            (3, &ExportVal::Int(0), &zero),  // to y, not written
+            (4, &null, &one),                // P2SHScriptProve
+        ]);
+        assert_eq!(rows.tables[EXP_TRANSFERS].len(), 2);
+        let dia = &rows.tables[EXP_DIAMONDS];
+        assert_eq!(dia.len(), 1);
+        assert_eq!((text(&dia[0][3]), text(&dia[0][4]), &dia[0][1]), ("WTYUIA", "contract", &ExportVal::Null));
+        assert_eq!(text(&dia[0][6]), y.to_readable());
+        let bls: Vec<(&str, &str)> = rows.tables[EXP_BALANCES].iter().map(|r| (text(&r[1]), text(&r[2]))).collect();
+        assert_eq!(bls.len(), 2);
+        assert!(bls.contains(&(x.to_readable().as_str(), "1")));
+        assert_eq!(text(&rows.tables[EXP_CONTRACT_CALLS][0][3]), "p2shprove");
+    }
+
+    #[test]
+    fn csv_sink_resumes_from_cursor() {
+        let dir = test_dir("csv");
+        let mut sink = CsvSink::open(&dir).unwrap();
+        assert_eq!(sink.cursor(), None);
+        let mut rows = ExportRows::new(1);
+        rows.push(EXP_CONTRACT_CALLS, vec![
+            ExportVal::Int(1), ExportVal::Int(0), ExportVal::Int(0),
+            ExportVal::Text("a,\"b\"".to_owned()), ExportVal::Null, ExportVal::Text(String::new()),
+        ]);
+        sink.write(&rows).unwrap();
+        // rows written past the cursor by an interrupted export
+        let path = dir.join("contract_calls.csv");
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"2,0,0,x,,\n").unwrap();
+
+        let sink = CsvSink::open(&dir).unwrap();
+        assert_eq!(sink.cursor(), Some(1));
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(csv, "height,tx_idx,act_idx,kind,contract,data\n1,0,0,\"a,\"\"b\"\"\",,\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn exporter_takes_queued_rolls_and_backfills_the_rest() {
+        let _guard = registry();
+        let dir = test_dir("follow");
+        let disk = Arc::new(MemDisk::default());
+        let store = BlockStore::wrap(disk.clone());
+        let blocks: Vec<BlockV1> = (1..=4).map(|h| block(h, vec![])).collect();
+        for blk in &blocks[..3] {
+            store.save_block_data(&blk.hash(), &blk.serialize());
+            store.save_block_hash(blk.height(), &blk.hash());
+        }
+        let status = ChainStatus { root_height: BlockHeight::from(3), last_height: BlockHeight::from(3) };
+        disk.save(BlockStore::CSK, &status.serialize());
+
+        let exporter = ExplorerExporter::new(Box::new(CsvSink::open(&dir).unwrap()), 1);
+        let mut writes = MemMap::new();
+        balance_write(&mut writes, &Address::from([7u8; Address::SIZE]), 2);
+        // nothing before the logs are bound
+        exporter.roll(Arc::new(blocks[1].clone()), writes.clone());
+        assert_eq!(exporter.pump(&store, &mut || false).unwrap(), 0);
+        assert_eq!(exporter.cursor(), None);
+
+        // 1 and 3 from the store, 2 from the queue with its writes
+        exporter.bind_logs(Arc::new(EmptyLogs {}));
+        assert_eq!(exporter.pump(&store, &mut || false).unwrap(), 2);
+        assert_eq!(exporter.pump(&store, &mut || false).unwrap(), 0);
+        exporter.roll(Arc::new(blocks[3].clone()), writes);
+        assert_eq!(exporter.pump(&store, &mut || false).unwrap(), 0);
+        assert_eq!(exporter.cursor(), Some(4));
+        let csv = std::fs::read_to_string(dir.join("balances.csv")).unwrap();
+        let heights: Vec<&str> = csv.lines().skip(1).map(|l| l.split(',').next().unwrap()).collect();
+        assert_eq!(heights, vec!["2", "4"]);
+        let csv = std::fs::read_to_string(dir.join("blocks.csv")).unwrap();
+        let heights: Vec<&str> = csv.lines().skip(1).map(|l| l.split(',').next().unwrap()).collect();
+        assert_eq!(heights, vec!["1", "2", "3", "4"]);
+        // the blocks from the store are marked incomplete
+        let marks: Vec<&str> = csv.lines().skip(1).map(|l| l.rsplit(',').next().unwrap()).collect();
+        assert_eq!(marks, vec!["1", "0", "1", "0"]);
+        assert_eq!(exporter.incomplete(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn full_queue_holds_the_roll_until_exported() {
+        let _guard = registry();
+        let dir = test_dir("full");
+        let disk = Arc::new(MemDisk::default());
+        let store = BlockStore::wrap(disk.clone());
+        let exporter = Arc::new(ExplorerExporter::new(Box::new(CsvSink::open(&dir).unwrap()), 1));
+        exporter.set_running(true);
+        for h in 1..=EXPORT_QUEUE_MAX as u64 {
+            exporter.roll(Arc::new(block(h, vec![])), MemMap::new());
+        }
+        let last = Arc::new(block(EXPORT_QUEUE_MAX as u64 + 1, vec![]));
+        let roller = {
+            let exporter = exporter.clone();
+            thread::spawn(move || exporter.roll(last, MemMap::new()))
+        };
+        thread::sleep(Duration::from_millis(200));
+        assert!(!roller.is_finished());
+        assert_eq!(exporter.queued(), EXPORT_QUEUE_MAX);
+
+        exporter.bind_logs(Arc::new(EmptyLogs {}));
+        exporter.pump(&store, &mut || false).unwrap();
+        roller.join().unwrap();
+        exporter.pump(&store, &mut || false).unwrap();
+        assert_eq!(exporter.cursor(), Some(EXPORT_QUEUE_MAX as u64 + 1));
+        assert_eq!(exporter.incomplete(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_sink_keeps_cursor_with_rows() {
+        let dir = test_dir("sqlite");
+        let path = dir.join("explorer.db");
+        let mut sink = SqliteSink::open(&path).unwrap();
+        let mut rows = ExportRows::new(9);
+        rows.push(EXP_VM_LOGS, vec![
+            ExportVal::Int(9), ExportVal::Int(0), ExportVal::Text("addr".to_owned()), ExportVal::Null,
+            ExportVal::Null, ExportVal::Null, ExportVal::Null, ExportVal::Text("01".to_owned()),
+        ]);
+        sink.write(&rows).unwrap();
+        drop(sink);
+
+        let sink = SqliteSink::open(&path).unwrap();
+        assert_eq!(sink.cursor(), Some(9));
+        let n: i64 = sink.conn.query_row("SELECT count(*) FROM vm_logs WHERE height = 9", [], |r| r.get(0)).unwrap();
+        assert_eq!(n, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_sink_adds_new_columns() {
+        let _guard = registry();
+        let dir = test_dir("sqlite-cols");
+        let path = dir.join("explorer.db");
+        std::fs::create_dir_all(&dir).unwrap();
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE blocks (height INTEGER, hash TEXT, prevhash TEXT, mrklroot TEXT, timestamp INTEGER, \
+            version INTEGER, difficulty INTEGER, nonce INTEGER, tx_count INTEGER, miner TEXT);").unwrap();
+        drop(conn);
+        let mut sink = SqliteSink::open(&path).unwrap();
+        sink.write(&block_export_rows(&block(3, vec![]), &[], None)).unwrap();
+        let mark: i64 = sink.conn.query_row("SELECT incomplete FROM blocks WHERE height = 3", [], |r| r.get(0)).unwrap();
+        assert_eq!(mark, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
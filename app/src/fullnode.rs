@@ -27,6 +27,9 @@ pub type ScanerReadyHook = Arc<dyn Fn(&dyn Scaner, &IniObj) -> Rerr + Send + Syn
 pub struct NilScaner {}
 impl Scaner for NilScaner {}
 
+pub struct NilServer {}
+impl Server for NilServer {}
+
 /***************************************/
 
 pub struct FullnodeRuntime {
@@ -39,6 +42,10 @@ pub struct FullnodeRuntime {
 }
 
 impl FullnodeRuntime {
+    pub fn hnoder(&self) -> Arc<dyn HNoder> {
+        self.hnoder.clone()
+    }
+
     pub fn run(self) -> Rerr {
         let exiter = self.exiter.clone();
         let mut tasks: Vec<JoinHandle<()>> = vec![];
@@ -110,7 +117,18 @@ impl FullnodeBuilder {
         Ok(Self::from_ini(cnfini))
     }
 
-    pub fn from_ini(cnfini: IniObj) -> Self {
+    pub fn from_ini(mut cnfini: IniObj) -> Self {
+        if DevConf::new(&cnfini).enable {
+            DevConf::apply(&mut cnfini);
+        }
+        // before any config or genesis reads it
+        let spec = ChainSpec::new(&cnfini);
+        if spec.is_custom() {
+            println!("[Config] chain spec '{}', chain id {}.", spec.name, spec.chain_id.unwrap_or(0));
+        }
+        if let Err(e) = install_chain_spec(spec) {
+            panic!("[Config Error] {}", e)
+        }
         let datdir = get_mainnet_data_dir(&cnfini);
         let engcnf = Arc::new(EngineConf::new(&cnfini));
         let nodcnf = Arc::new(NodeConf::new(&cnfini));
@@ -4,4 +4,11 @@ pub mod diaworker;
 pub mod poworker;
 // pub mod svrapi; // server api
 pub mod diabider;
+pub mod diaindex;
+pub mod assetindex;
+pub mod explorer;
+pub mod devminer;
 pub mod fullnode;
+pub mod lightnode;
+pub mod sidedb;
+pub mod texrelay;
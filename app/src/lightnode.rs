@@ -0,0 +1,33 @@
+use std::sync::*;
+use std::thread::spawn;
+
+use basis::interface::*;
+use sys::*;
+
+/// Run a light node and its api server until ctrl+c.
+pub fn run_lightnode(light: Arc<dyn LightNoder>, server: Arc<dyn Server>) -> Rerr {
+    let exiter = Exiter::new();
+    let ctrlc_exiter = exiter.clone();
+    ctrlc::set_handler(move || {
+        ctrlc_exiter.exit();
+    })
+    .map_err(|e| format!("failed to install ctrlc handler: {}", e))?;
+
+    let worker = exiter.worker();
+    let srvtask = spawn(move || server.start(worker));
+    let worker = exiter.worker();
+    let lightnd = light.clone();
+    let lgttask = spawn(move || lightnd.start(worker));
+
+    if exiter.wait_exit_or_done() {
+        light.exit();
+    }
+    exiter.wait();
+
+    let panic_count = [srvtask.join(), lgttask.join()].iter().filter(|r| r.is_err()).count();
+    if panic_count > 0 {
+        return errf!("{} thread panicked", panic_count);
+    }
+    println!("[Exit] Hacash light node closed.");
+    Ok(())
+}
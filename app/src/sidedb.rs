@@ -0,0 +1,89 @@
+/*
+    Helpers for the local side DBs the index scaners keep next to the chain.
+
+    A side DB records one block per write. Lists are kept as a count under
+    a base key and one entry per `base ++ seq`, so they can be paged without
+    a prefix scan, which `DiskDB` does not have.
+*/
+use basis::component::*;
+use basis::interface::*;
+use field::*;
+
+/// Writes of one block, read back before they reach the disk.
+pub struct SideBatch<'a> {
+    db: &'a dyn DiskDB,
+    pub mem: MemKV,
+}
+
+impl<'a> SideBatch<'a> {
+    pub fn new(db: &'a dyn DiskDB) -> Self {
+        Self { db, mem: MemKV::new() }
+    }
+
+    pub fn get(&self, k: &Vec<u8>) -> Option<Vec<u8>> {
+        match self.mem.get(k) {
+            Some(v) => v.clone(),
+            None => self.db.read(k),
+        }
+    }
+
+    pub fn put(&mut self, k: Vec<u8>, v: Vec<u8>) {
+        self.mem.put(k, v);
+    }
+
+    pub fn del(&mut self, k: Vec<u8>) {
+        self.mem.del(k);
+    }
+
+    /// Append to the list under `pre ++ id`.
+    pub fn push(&mut self, pre: &[u8], id: &[u8], v: Vec<u8>) {
+        let base = [pre, id].concat();
+        let n = self.get(&base).map_or(0, |v| Uint4::must(&v).uint());
+        self.put(seq_key(&base, n as usize), v);
+        self.put(base, Uint4::from(n + 1).serialize());
+    }
+
+    /// Mark block `height` indexed and write the batch.
+    pub fn commit(mut self, height: u64) {
+        let (start, _) = side_range(self.db);
+        let hei = BlockHeight::from(height).serialize();
+        if start == 0 {
+            self.put(b"start".to_vec(), hei.clone());
+        }
+        self.put(b"height".to_vec(), hei);
+        self.db.write(&self.mem);
+    }
+}
+
+/// First and last indexed block heights, zeros when nothing is indexed.
+pub fn side_range(db: &dyn DiskDB) -> (u64, u64) {
+    let get = |k: &[u8]| db.read(k).map_or(0, |v| BlockHeight::must(&v).uint());
+    (get(b"start"), get(b"height"))
+}
+
+/// Total length and page `page` (from 1) of the list under `pre ++ id`.
+pub fn side_page<T>(
+    db: &dyn DiskDB,
+    pre: &[u8],
+    id: &[u8],
+    (page, limit, desc): (usize, usize, bool),
+    f: impl Fn(&[u8]) -> T,
+) -> (usize, Vec<T>) {
+    let base = [pre, id].concat();
+    let total = db.read(&base).map_or(0, |v| Uint4::must(&v).uint() as usize);
+    let skip = page.max(1).saturating_sub(1).saturating_mul(limit);
+    let seqs: Vec<usize> = match desc {
+        true => (0..total).rev().skip(skip).take(limit).collect(),
+        false => (0..total).skip(skip).take(limit).collect(),
+    };
+    let list = seqs
+        .into_iter()
+        .filter_map(|i| db.read(&seq_key(&base, i)))
+        .map(|v| f(&v))
+        .collect();
+    (total, list)
+}
+
+fn seq_key(base: &[u8], i: usize) -> Vec<u8> {
+    [base, &(i as u32).to_be_bytes()].concat()
+}
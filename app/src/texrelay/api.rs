@@ -0,0 +1,86 @@
+
+struct TexRelayApiService {}
+
+pub fn service() -> Arc<dyn ApiService> {
+    Arc::new(TexRelayApiService {})
+}
+
+impl ApiService for TexRelayApiService {
+    fn name(&self) -> &'static str {
+        "texrelay"
+    }
+
+    fn routes(&self) -> Vec<ApiRoute> {
+        use ApiType::*;
+        vec![
+            ApiRoute::post("/submit/tex/offer", submit_tex_offer)
+                .summary("Post a signed TEX offer to the relay")
+                .body("TexCellAct bytes")
+                .returns("hash", String, "")
+                .returns("expire", Integer, ""),
+            ApiRoute::post("/submit/tex/cancel", submit_tex_cancel)
+                .summary("Cancel a TEX offer")
+                .body("TexOfferCancel bytes")
+                .returns("hash", String, "offer hash"),
+            ApiRoute::get("/query/tex/offers", query_tex_offers)
+                .summary("Live TEX offers, soonest expiry first")
+                .param("address", String, "only offers of this address")
+                .param("limit", Integer, "100 by default")
+                .returns("total", Integer, "")
+                .returns("list", Array, ""),
+            ApiRoute::get("/query/tex/matches", query_tex_matches)
+                .summary("Latest TEX match transactions")
+                .returns("list", Array, ""),
+        ]
+    }
+}
+
+fn api_error(errmsg: &str) -> ApiResponse {
+    ApiResponse::json(json!({"ret":1,"err":errmsg}).to_string())
+}
+
+fn api_body(req: &ApiRequest) -> Ret<Vec<u8>> {
+    match req.query("hexbody") {
+        Some("true" | "1") => hex::decode(&req.body).map_err(|_| "hex format invalid".to_owned()),
+        _ => Ok(req.body.clone()),
+    }
+}
+
+/// Body: a signed `TexCellAct` with a `CellCondHeightAtMost` expiry.
+fn submit_tex_offer(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let res = api_body(&req).and_then(|body| accept_offer(ctx.hnoder.as_ref(), body));
+    match res {
+        Ok(offer) => ApiResponse::json(json!({"ret":0,"hash":offer.hash.to_hex(),"expire":offer.expire}).to_string()),
+        Err(e) => api_error(&e),
+    }
+}
+
+/// Body: a `TexOfferCancel` signed by the offer's address.
+fn submit_tex_cancel(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let res = api_body(&req).and_then(|body| accept_cancel(ctx.hnoder.as_ref(), body));
+    match res {
+        Ok(cancel) => ApiResponse::json(json!({"ret":0,"hash":cancel.offer.to_hex()}).to_string()),
+        Err(e) => api_error(&e),
+    }
+}
+
+fn query_tex_offers(_: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let addr = match req.query("address") {
+        None | Some("") => None,
+        Some(a) => match Address::from_readable(a) {
+            Ok(a) => Some(a),
+            Err(_) => return api_error("address format invalid"),
+        },
+    };
+    let limit = req.query_usize("limit", 100).min(1000);
+    let book = TEX_OFFER_BOOK.lock().unwrap();
+    let list: Vec<serde_json::Value> =
+        book.list(addr.as_ref()).into_iter().take(limit).map(|o| o.to_json()).collect();
+    ApiResponse::json(json!({"ret":0,"total":book.len(),"list":list}).to_string())
+}
+
+fn query_tex_matches(_: &ApiExecCtx, _: ApiRequest) -> ApiResponse {
+    let subs = TEX_MATCH_SUBMITTED.lock().unwrap();
+    let list: Vec<&serde_json::Value> = subs.iter().rev().collect();
+    ApiResponse::json(json!({"ret":0,"list":list}).to_string())
+}
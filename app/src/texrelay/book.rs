@@ -0,0 +1,152 @@
+
+pub const TEX_BOOK_MAX: usize = 4096;
+pub const TEX_BOOK_ADDR_MAX: usize = 32;
+
+type ExpireKey = (u64, [u8; Hash::SIZE]);
+
+/// Live offers by hash. Cancelled and matched ones are remembered until they
+/// expire so gossip cannot bring them back. A full book, or a full address, makes
+/// room by dropping the offer expiring soonest.
+#[derive(Default)]
+pub struct OfferBook {
+    offers: HashMap<Hash, TexOffer>,
+    cancelled: HashMap<Hash, u64>,
+    matched: HashMap<Hash, u64>,
+    by_expire: BTreeSet<ExpireKey>,
+    by_addr: HashMap<Address, BTreeSet<ExpireKey>>,
+}
+
+fn expire_key(offer: &TexOffer) -> ExpireKey {
+    (offer.expire, offer.hash.to_array())
+}
+
+impl OfferBook {
+    pub fn len(&self) -> usize {
+        self.offers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offers.is_empty()
+    }
+
+    pub fn get(&self, hx: &Hash) -> Option<&TexOffer> {
+        self.offers.get(hx)
+    }
+
+    /// Add an offer seen at chain height `height`; false if already known.
+    /// When the book or the address is full the offer expiring soonest
+    /// makes room, unless that is the new one.
+    pub fn insert(&mut self, offer: TexOffer, height: u64) -> Ret<bool> {
+        if offer.expire <= height {
+            return errf!("tex offer expired at height {}", offer.expire);
+        }
+        if offer.expire > height + TEX_OFFER_MAX_LIFE {
+            return errf!("tex offer expiry {} is more than {} blocks ahead", offer.expire, TEX_OFFER_MAX_LIFE);
+        }
+        if self.cancelled.contains_key(&offer.hash) {
+            return errf!("tex offer {} was cancelled", offer.hash);
+        }
+        if self.matched.contains_key(&offer.hash) {
+            return errf!("tex offer {} was already matched", offer.hash);
+        }
+        if self.offers.contains_key(&offer.hash) {
+            return Ok(false);
+        }
+        let key = expire_key(&offer);
+        let same = self.by_addr.get(offer.addr());
+        let evict = match same.filter(|s| s.len() >= TEX_BOOK_ADDR_MAX) {
+            Some(s) => Some((s.first().copied(), format!("address {} has too many tex offers", offer.addr()))),
+            None if self.offers.len() >= TEX_BOOK_MAX => {
+                Some((self.by_expire.first().copied(), "tex offer book is full".to_owned()))
+            }
+            None => None,
+        };
+        if let Some((first, err)) = evict {
+            match first {
+                Some(first) if first < key => {
+                    self.take(&Hash::from(first.1));
+                }
+                _ => return Err(err),
+            }
+        }
+        self.by_expire.insert(key);
+        self.by_addr.entry(*offer.addr()).or_default().insert(key);
+        self.offers.insert(offer.hash, offer);
+        Ok(true)
+    }
+
+    fn take(&mut self, hx: &Hash) -> Option<TexOffer> {
+        let offer = self.offers.remove(hx)?;
+        let key = expire_key(&offer);
+        self.by_expire.remove(&key);
+        if let Some(set) = self.by_addr.get_mut(offer.addr()) {
+            set.remove(&key);
+            if set.is_empty() {
+                self.by_addr.remove(offer.addr());
+            }
+        }
+        Some(offer)
+    }
+
+    /// Drop an offer by its signer's cancel; false if already cancelled.
+    pub fn cancel(&mut self, cancel: &TexOfferCancel) -> Ret<bool> {
+        if self.cancelled.contains_key(&cancel.offer) {
+            return Ok(false);
+        }
+        let Some(offer) = self.offers.get(&cancel.offer) else {
+            return errf!("tex offer {} not found", cancel.offer);
+        };
+        cancel.verify(offer.addr())?;
+        let offer = self.take(&cancel.offer).unwrap();
+        self.cancelled.insert(offer.hash, offer.expire);
+        Ok(true)
+    }
+
+    /// Take offers out, such as those no longer funded.
+    pub fn remove(&mut self, hxs: &[Hash]) {
+        for hx in hxs {
+            self.take(hx);
+        }
+    }
+
+    /// Take offers out that were put into a transaction, by this relay or
+    /// another, and refuse them until they expire.
+    pub fn matched(&mut self, offers: &[&TexOffer]) {
+        for offer in offers {
+            self.take(&offer.hash);
+            self.matched.insert(offer.hash, offer.expire);
+        }
+    }
+
+    /// Forget everything that expired by `height`.
+    pub fn prune(&mut self, height: u64) {
+        while let Some(first) = self.by_expire.first().copied() {
+            if first.0 > height {
+                break;
+            }
+            self.take(&Hash::from(first.1));
+        }
+        self.cancelled.retain(|_, e| *e > height);
+        self.matched.retain(|_, e| *e > height);
+    }
+
+    /// Drop the offers `keep` rejects, such as those no longer funded.
+    pub fn retain(&mut self, keep: impl Fn(&TexOffer) -> bool) {
+        let drop: Vec<Hash> = self.offers.values().filter(|o| !keep(o)).map(|o| o.hash).collect();
+        self.remove(&drop);
+    }
+
+    /// Offers sorted by expiry, then hash, optionally of one address.
+    pub fn list(&self, addr: Option<&Address>) -> Vec<&TexOffer> {
+        let keys = match addr {
+            Some(a) => match self.by_addr.get(a) {
+                Some(set) => set.iter().collect(),
+                None => vec![],
+            },
+            None => self.by_expire.iter().collect::<Vec<_>>(),
+        };
+        keys.into_iter().filter_map(|(_, h)| self.offers.get(&Hash::from(*h))).collect()
+    }
+}
+
+static TEX_OFFER_BOOK: LazyLock<Mutex<OfferBook>> = LazyLock::new(Mutex::default);
@@ -0,0 +1,26 @@
+
+#[derive(Clone)]
+pub struct TexRelayConf {
+    pub enable: bool,
+    // pairs offers and pays the fee of the settling tx when set
+    pub matcher: Option<Account>,
+    pub matcher_fee: Amount,
+    pub match_interval: u64, // seconds
+}
+
+impl TexRelayConf {
+    pub fn new(ini: &IniObj) -> Self {
+        let sec = ini_section(ini, "tex");
+        let enable = ini_must_bool(sec, "relay", false);
+        let matcher = match ini_must(sec, "matcher_password", "").as_str() {
+            "" => None,
+            _ => Some(ini_must_account(sec, "matcher_password")),
+        };
+        Self {
+            enable,
+            matcher,
+            matcher_fee: ini_must_amount(sec, "matcher_fee"),
+            match_interval: ini_must_u64(sec, "match_interval", 10).max(1),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+
+/// Pairs of offers from different addresses whose transfers cancel out,
+/// soonest expiring first. Each offer is used at most once and pairs in
+/// `skip`, in either order, are left out.
+pub fn find_matches(offers: &[&TexOffer], skip: &HashSet<(Hash, Hash)>) -> Vec<(Hash, Hash)> {
+    let mut offers = offers.to_vec();
+    offers.sort_by(|a, b| a.cmp_expire(b));
+    let mut by_net: HashMap<&OfferNet, Vec<&TexOffer>> = HashMap::new();
+    for o in &offers {
+        by_net.entry(&o.net).or_default().push(o);
+    }
+    let mut used = HashSet::new();
+    let mut res = vec![];
+    for a in &offers {
+        if used.contains(&a.hash) {
+            continue;
+        }
+        let want = a.net.neg();
+        let Some(cands) = by_net.get(&want) else {
+            continue;
+        };
+        let pick = cands.iter().find(|b| {
+            b.addr() != a.addr()
+                && !used.contains(&b.hash)
+                && !skip.contains(&(a.hash, b.hash))
+                && !skip.contains(&(b.hash, a.hash))
+        });
+        if let Some(b) = pick {
+            used.insert(a.hash);
+            used.insert(b.hash);
+            res.push((a.hash, b.hash));
+        }
+    }
+    res
+}
+
+/// A `TransactionType3` paid by the matcher carrying the offers, which
+/// settle through the TEX ledger at its end.
+pub fn build_match_tx(acc: &Account, fee: &Amount, offers: &[&TexOffer], ts: u64) -> Ret<TransactionType3> {
+    let mut tx = TransactionType3::new_by(Address::from(*acc.address()), fee.clone(), ts);
+    for o in offers {
+        tx.push_action(Box::new(o.act.clone()))?;
+    }
+    tx.fill_sign(acc)?;
+    Ok(tx)
+}
+
+/// Offers carried by `txs`, whoever put them there.
+fn tex_offers_in<'a>(txs: impl Iterator<Item = &'a dyn TransactionRead>) -> Vec<TexOffer> {
+    let mut offers = vec![];
+    for tx in txs {
+        for act in tx.actions() {
+            if let Some(act) = act.as_any().downcast_ref::<TexCellAct>()
+                && let Ok(offer) = TexOffer::new(act.clone())
+            {
+                offers.push(offer);
+            }
+        }
+    }
+    offers
+}
+
+/// Mark offers as matched once a transaction in the tx pool, or in a block
+/// above `from`, carries them, so a second relay does not fill them again.
+/// Answers the height scanned up to.
+fn mark_seen_matches(hnoder: &dyn HNoder, from: u64) -> u64 {
+    let engine = hnoder.engine();
+    let height = latest_height(engine.as_ref());
+    let mut offers = vec![];
+    // offers in older blocks have expired anyway
+    let from = from.max(height.saturating_sub(TEX_OFFER_MAX_LIFE));
+    for hei in from.saturating_add(1)..=height {
+        let Some(blk) = engine
+            .store()
+            .block_data_by_height(&BlockHeight::from(hei))
+            .and_then(|(_, dts)| protocol::block::build_block_package(dts).ok())
+        else {
+            continue;
+        };
+        offers.extend(tex_offers_in(blk.block().transactions().iter().map(|t| t.as_read())));
+    }
+    let _ = hnoder.txpool().iter(&mut |txp| {
+        offers.extend(tex_offers_in(std::iter::once(txp.tx_read())));
+        true
+    });
+    if !offers.is_empty() {
+        TEX_OFFER_BOOK.lock().unwrap().matched(&offers.iter().collect::<Vec<_>>());
+    }
+    height
+}
+
+// pairs whose transaction failed, kept until one side leaves the book
+static TEX_MATCH_FAILED: LazyLock<Mutex<HashSet<(Hash, Hash)>>> = LazyLock::new(Mutex::default);
+
+// recently submitted match transactions, newest last
+static TEX_MATCH_SUBMITTED: LazyLock<Mutex<VecDeque<serde_json::Value>>> = LazyLock::new(Mutex::default);
+
+const TEX_MATCH_HISTORY: usize = 100;
+
+fn run_matcher(acc: &Account, fee: &Amount, hnoder: &dyn HNoder) {
+    let engine = hnoder.engine();
+    let pairs = {
+        let book = TEX_OFFER_BOOK.lock().unwrap();
+        let mut failed = TEX_MATCH_FAILED.lock().unwrap();
+        failed.retain(|(a, b)| book.get(a).is_some() && book.get(b).is_some());
+        let pairs = find_matches(&book.list(None), &failed);
+        pairs
+            .into_iter()
+            .map(|(a, b)| (book.get(&a).unwrap().clone(), book.get(&b).unwrap().clone()))
+            .collect::<Vec<_>>()
+    };
+    for (a, b) in pairs {
+        let res = build_match_tx(acc, fee, &[&a, &b], curtimes()).and_then(|tx| {
+            engine.try_execute_tx(&tx)?;
+            let txp = TxPkg::create(Box::new(tx));
+            hnoder.submit_transaction(&txp, false, false)?;
+            Ok(txp.hash().to_hex())
+        });
+        match res {
+            Ok(txhx) => {
+                TEX_OFFER_BOOK.lock().unwrap().matched(&[&a, &b]);
+                let mut subs = TEX_MATCH_SUBMITTED.lock().unwrap();
+                subs.push_back(json!({"tx": txhx, "offers": [a.hash.to_hex(), b.hash.to_hex()]}));
+                if subs.len() > TEX_MATCH_HISTORY {
+                    subs.pop_front();
+                }
+                println!("[Tex Relay] matched offers {} and {} in tx {}.", a.hash, b.hash, txhx);
+            }
+            Err(e) => {
+                TEX_MATCH_FAILED.lock().unwrap().insert((a.hash, b.hash));
+                println!("[Tex Relay] match of {} and {} failed: {}", a.hash, b.hash, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+/*
+    TEX offer relay.
+
+    A `TexCellAct` signs only its address and cells, so it is a standing
+    offer anyone may put into a transaction. The relay gossips such offers
+    between nodes, keeps the live ones in a local book, and, when a matcher
+    account is configured, pairs offers whose transfers cancel out into a
+    `TransactionType3` that settles both at once.
+*/
+use std::collections::*;
+use std::sync::*;
+use std::thread;
+use std::time::*;
+
+use basis::component::*;
+use basis::interface::*;
+use field::*;
+use protocol::state::*;
+use protocol::tex::*;
+use protocol::transaction::*;
+use serde_json::json;
+use sys::*;
+
+include! {"config.rs"}
+include! {"offer.rs"}
+include! {"book.rs"}
+include! {"matcher.rs"}
+include! {"p2p.rs"}
+include! {"api.rs"}
+
+#[cfg(test)]
+include! {"tests.rs"}
@@ -0,0 +1,181 @@
+
+pub const MSG_TEX_OFFER: u16 = 3100;
+pub const MSG_TEX_CANCEL: u16 = 3101;
+
+/// Offers must expire within this many blocks of the current height.
+pub const TEX_OFFER_MAX_LIFE: u64 = 2016;
+
+/// What an offer moves through the settlement ledger: pays count up, gets
+/// count down, as `TexLedger` does. Offers whose sums are all zero settle.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OfferNet {
+    pub zhu: i128,
+    pub sat: i128,
+    pub dia: i128,
+    pub assets: BTreeMap<u64, i128>,
+}
+
+impl OfferNet {
+    pub fn is_zero(&self) -> bool {
+        self.zhu == 0 && self.sat == 0 && self.dia == 0 && self.assets.is_empty()
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            zhu: -self.zhu,
+            sat: -self.sat,
+            dia: -self.dia,
+            assets: self.assets.iter().map(|(k, v)| (*k, -v)).collect(),
+        }
+    }
+
+    fn asset(&mut self, serial: u64, v: i128) {
+        let n = self.assets.entry(serial).or_insert(0);
+        *n += v;
+        if *n == 0 {
+            self.assets.remove(&serial);
+        }
+    }
+}
+
+/// A verified offer with what the relay reads from its cells.
+#[derive(Clone, Debug)]
+pub struct TexOffer {
+    pub hash: Hash,
+    pub act: TexCellAct,
+    pub net: OfferNet,
+    // lowest `CellCondHeightAtMost` of the offer
+    pub expire: u64,
+}
+
+impl TexOffer {
+    pub fn parse(buf: &[u8]) -> Ret<Self> {
+        let (act, sz) = TexCellAct::create(buf)?;
+        if sz != buf.len() {
+            return errf!("tex offer has trailing bytes");
+        }
+        Self::new(act)
+    }
+
+    pub fn new(act: TexCellAct) -> Ret<Self> {
+        act.verify_sign()?;
+        let mut net = OfferNet::default();
+        let mut expire = None;
+        for cell in act.cells.as_list() {
+            let buf = cell.serialize();
+            let kind = cell.kind() as u8;
+            macro_rules! read {
+                ($ty: ty) => {
+                    <$ty>::create(&buf)?.0
+                };
+            }
+            match kind {
+                CellTrsZhuPay::CID => net.zhu += read!(CellTrsZhuPay).haczhu.uint() as i128,
+                CellTrsZhuGet::CID => net.zhu -= read!(CellTrsZhuGet).haczhu.uint() as i128,
+                CellTrsSatPay::CID => net.sat += read!(CellTrsSatPay).satnum.uint() as i128,
+                CellTrsSatGet::CID => net.sat -= read!(CellTrsSatGet).satnum.uint() as i128,
+                CellTrsDiaPay::CID => net.dia += read!(CellTrsDiaPay).diamonds.length() as i128,
+                CellTrsDiaGet::CID => net.dia -= read!(CellTrsDiaGet).dianum.uint() as i128,
+                CellTrsAssetPay::CID => {
+                    let a = read!(CellTrsAssetPay).asset;
+                    net.asset(a.serial.uint(), a.amount.uint() as i128)
+                }
+                CellTrsAssetGet::CID => {
+                    let a = read!(CellTrsAssetGet).asset;
+                    net.asset(a.serial.uint(), -(a.amount.uint() as i128))
+                }
+                CellCondHeightAtMost::CID => {
+                    let h = read!(CellCondHeightAtMost).height.uint();
+                    expire = Some(expire.map_or(h, |e: u64| e.min(h)));
+                }
+                _ => {}
+            }
+        }
+        let Some(expire) = expire else {
+            return errf!("tex offer needs a height at most condition as its expiry");
+        };
+        if net.is_zero() {
+            return errf!("tex offer moves nothing");
+        }
+        let hash = Hash::from(sha3(act.serialize()));
+        Ok(Self { hash, act, net, expire })
+    }
+
+    /// Whether the maker holds what the offer pays out now: the HAC,
+    /// satoshi and assets it pays more than it gets, and the diamonds it
+    /// hands over.
+    pub fn check_funds(&self, state: &CoreStateRead) -> Rerr {
+        let addr = self.addr();
+        let short = |what: &str| errf!("address {} cannot fund tex offer {}: not enough {}", addr, self.hash, what);
+        let bls = state.balance(addr).unwrap_or_default();
+        let net = &self.net;
+        if net.zhu > 0 && Amount::zhu(u64::try_from(net.zhu).unwrap_or(u64::MAX)) > bls.hacash {
+            return short("HAC");
+        }
+        if net.sat > 0 && net.sat as u128 > bls.satoshi.uint() as u128 {
+            return short("satoshi");
+        }
+        for (serial, n) in &net.assets {
+            let have = bls.asset(Fold64::from(*serial).unwrap_or_default()).map_or(0, |a| a.amount.uint());
+            if *n > 0 && *n as u128 > have as u128 {
+                return short(&format!("asset {}", serial));
+            }
+        }
+        for cell in self.act.cells.as_list() {
+            if cell.kind() as u8 != CellTrsDiaPay::CID {
+                continue;
+            }
+            let pay = CellTrsDiaPay::create(&cell.serialize())?.0;
+            for name in pay.diamonds.as_list() {
+                if state.diamond(name).is_none_or(|d| d.address != *addr) {
+                    return short(&format!("diamond {}", name.to_readable()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Soonest expiry first, ties broken by hash.
+    pub fn cmp_expire(&self, other: &Self) -> std::cmp::Ordering {
+        (self.expire, self.hash.as_ref()).cmp(&(other.expire, other.hash.as_ref()))
+    }
+
+    pub fn addr(&self) -> &Address {
+        &self.act.addr
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let cells: serde_json::Value =
+            serde_json::from_str(&self.act.cells.to_json()).unwrap_or_default();
+        json!({
+            "hash": self.hash.to_hex(),
+            "address": self.addr().to_readable(),
+            "expire": self.expire,
+            "cells": cells,
+            "body": self.act.serialize().to_hex(),
+        })
+    }
+}
+
+// TexOfferCancel
+combi_struct!{ TexOfferCancel,
+    offer : Hash
+    sign  : Sign
+}
+
+impl TexOfferCancel {
+    fn sign_stuff(offer: &Hash) -> Hash {
+        Hash::from(sha3([b"tex offer cancel ".as_slice(), offer.as_ref()].concat()))
+    }
+
+    pub fn create_by(acc: &Account, offer: Hash) -> Self {
+        Self { offer, sign: Sign::create_by(acc, &Self::sign_stuff(&offer)) }
+    }
+
+    pub fn verify(&self, addr: &Address) -> Rerr {
+        if !basis::method::verify_signature(&Self::sign_stuff(&self.offer), addr, &self.sign) {
+            return errf!("cancel of tex offer {} is not signed by {}", self.offer, addr);
+        }
+        Ok(())
+    }
+}
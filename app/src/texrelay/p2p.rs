@@ -0,0 +1,118 @@
+
+struct TexRelayExt {
+    hnoder: Weak<dyn HNoder>,
+}
+
+fn latest_height(engine: &dyn Engine) -> u64 {
+    engine.latest_block().height().uint()
+}
+
+/// Put a gossiped or posted offer in the book and pass it on if it is new.
+fn accept_offer(hnoder: &dyn HNoder, body: Vec<u8>) -> Ret<TexOffer> {
+    let offer = TexOffer::parse(&body)?;
+    let engine = hnoder.engine();
+    let staptr = engine.state();
+    offer.check_funds(&CoreStateRead::wrap(staptr.as_ref().as_ref()))?;
+    let height = latest_height(engine.as_ref());
+    let fresh = TEX_OFFER_BOOK.lock().unwrap().insert(offer.clone(), height)?;
+    if fresh {
+        hnoder.broadcast_p2p_extension_message(offer.hash, MSG_TEX_OFFER, body)?;
+    }
+    Ok(offer)
+}
+
+/// Same as `accept_offer` for cancels.
+fn accept_cancel(hnoder: &dyn HNoder, body: Vec<u8>) -> Ret<TexOfferCancel> {
+    let (cancel, sz) = TexOfferCancel::create(&body)?;
+    if sz != body.len() {
+        return errf!("tex offer cancel has trailing bytes");
+    }
+    let fresh = TEX_OFFER_BOOK.lock().unwrap().cancel(&cancel)?;
+    if fresh {
+        let key = Hash::from(sha3(&body));
+        hnoder.broadcast_p2p_extension_message(key, MSG_TEX_CANCEL, body)?;
+    }
+    Ok(cancel)
+}
+
+impl NodeP2PExtension for TexRelayExt {
+    fn on_connect(&self, peer: Arc<dyn NPeer>, _: Arc<dyn Engine>, _: Arc<dyn TxPool>) -> Rerr {
+        let bodys: Vec<Vec<u8>> = TEX_OFFER_BOOK
+            .lock()
+            .unwrap()
+            .list(None)
+            .iter()
+            .map(|o| o.act.serialize())
+            .collect();
+        if bodys.is_empty() {
+            return Ok(());
+        }
+        thread::spawn(move || {
+            for body in bodys {
+                if peer.send_msg_on_block(MSG_TEX_OFFER, body).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn on_message(
+        &self,
+        _: Arc<dyn NPeer>,
+        _: Arc<dyn Engine>,
+        _: Arc<dyn TxPool>,
+        ty: u16,
+        body: Vec<u8>,
+    ) -> Rerr {
+        let Some(hnoder) = self.hnoder.upgrade() else {
+            return Ok(());
+        };
+        // bad or stale gossip is dropped without blaming the peer
+        let _ = match ty {
+            MSG_TEX_OFFER => accept_offer(hnoder.as_ref(), body).map(|_| ()),
+            MSG_TEX_CANCEL => accept_cancel(hnoder.as_ref(), body).map(|_| ()),
+            _ => Ok(()),
+        };
+        Ok(())
+    }
+}
+
+/// Register the offer gossip and keep the book pruned, running the matcher
+/// each round when it has an account.
+pub fn start_tex_relay(cnf: &TexRelayConf, mut worker: Worker, hnoder: Arc<dyn HNoder>) {
+    if !cnf.enable {
+        return;
+    }
+    let ext = Arc::new(TexRelayExt { hnoder: Arc::downgrade(&hnoder) });
+    if let Err(e) = hnoder.register_p2p_extension(vec![MSG_TEX_OFFER, MSG_TEX_CANCEL], ext) {
+        println!("[Tex Relay] cannot register p2p messages: {}", e);
+        return;
+    }
+    match &cnf.matcher {
+        Some(acc) => println!("[Tex Relay] start with matcher {}.", acc.readable()),
+        None => println!("[Tex Relay] start without matcher."),
+    }
+    let cnf = cnf.clone();
+    thread::spawn(move || {
+        let mut scanned = latest_height(hnoder.engine().as_ref());
+        loop {
+            if worker.sleep_or_quit(Duration::from_secs(cnf.match_interval)) {
+                break;
+            }
+            let engine = hnoder.engine();
+            let height = latest_height(engine.as_ref());
+            let staptr = engine.state();
+            let state = CoreStateRead::wrap(staptr.as_ref().as_ref());
+            {
+                let mut book = TEX_OFFER_BOOK.lock().unwrap();
+                book.prune(height);
+                book.retain(|o| o.check_funds(&state).is_ok());
+            }
+            scanned = mark_seen_matches(hnoder.as_ref(), scanned);
+            if let Some(acc) = &cnf.matcher {
+                run_matcher(acc, &cnf.matcher_fee, hnoder.as_ref());
+            }
+        }
+    });
+}
@@ -0,0 +1,143 @@
+mod tests {
+    use super::*;
+
+    fn offer_act(acc: &Account, cells: Vec<Box<dyn TexCell>>) -> TexCellAct {
+        let mut act = TexCellAct::create_by(Address::from(*acc.address()));
+        for cell in cells {
+            act.add_cell(cell).unwrap();
+        }
+        act.do_sign(acc).unwrap();
+        act
+    }
+
+    // sells `dias` diamonds for `zhu`, or buys them when `buy`
+    fn dia_offer(acc: &Account, buy: bool, zhu: u64, expire: u64) -> TexOffer {
+        let fzhu = Fold64::from(zhu).unwrap();
+        let cells: Vec<Box<dyn TexCell>> = match buy {
+            true => vec![
+                Box::new(CellTrsZhuPay::new(fzhu)),
+                Box::new(CellTrsDiaGet::new(DiamondNumber::from(2))),
+                Box::new(CellCondHeightAtMost::new(expire)),
+            ],
+            false => vec![
+                Box::new(CellTrsZhuGet::new(fzhu)),
+                Box::new(CellTrsDiaPay::new(DiamondNameListMax200::from_readable("KKKKVA,HYXYHY").unwrap())),
+                Box::new(CellCondHeightAtMost::new(expire + 5)),
+                Box::new(CellCondHeightAtMost::new(expire)),
+            ],
+        };
+        TexOffer::parse(&offer_act(acc, cells).serialize()).unwrap()
+    }
+
+    #[test]
+    fn offers_with_opposite_nets_match() {
+        let seller = Account::create_by("texrelay-test-seller").unwrap();
+        let buyer = Account::create_by("texrelay-test-buyer").unwrap();
+        let sell = dia_offer(&seller, false, 500, 90);
+        let buy = dia_offer(&buyer, true, 500, 80);
+        let cheap = dia_offer(&buyer, true, 400, 70);
+        assert_eq!(sell.expire, 90);
+        assert_eq!(sell.net, OfferNet { zhu: -500, sat: 0, dia: 2, assets: BTreeMap::new() });
+        assert_eq!(buy.net, sell.net.neg());
+
+        // the buyer cannot fill its own offers, nor at another price
+        let self_buy = dia_offer(&seller, true, 500, 60);
+        let skip = HashSet::new();
+        let pairs = find_matches(&[&sell, &cheap, &buy, &self_buy], &skip);
+        assert_eq!(pairs, vec![(buy.hash, sell.hash)]);
+        let skip = HashSet::from([(buy.hash, sell.hash)]);
+        assert!(find_matches(&[&sell, &buy], &skip).is_empty());
+
+        let matcher = Account::create_by("texrelay-test-matcher").unwrap();
+        let tx = build_match_tx(&matcher, &Amount::mei(1), &[&buy, &sell], 1_730_000_000).unwrap();
+        tx.verify_signature().unwrap();
+        assert_eq!(tx.actions.length(), 2);
+        // any relay reading the transaction finds both offers in it
+        let seen: Vec<Hash> = tex_offers_in(std::iter::once(tx.as_read())).iter().map(|o| o.hash).collect();
+        assert_eq!(seen, vec![buy.hash, sell.hash]);
+    }
+
+    #[test]
+    fn book_expiry_and_cancel() {
+        let acc = Account::create_by("texrelay-test-seller").unwrap();
+        let other = Account::create_by("texrelay-test-buyer").unwrap();
+        let offer = dia_offer(&acc, false, 500, 90);
+
+        // no expiry, nothing moved, or a foreign signature
+        let noexp = offer_act(&acc, vec![Box::new(CellTrsZhuGet::new(Fold64::from(5).unwrap()))]);
+        assert!(TexOffer::new(noexp).is_err());
+        let empty = offer_act(&acc, vec![Box::new(CellCondHeightAtMost::new(90))]);
+        assert!(TexOffer::new(empty).is_err());
+        let mut forged = offer.act.clone();
+        forged.addr = Address::from(*other.address());
+        assert!(TexOffer::new(forged).is_err());
+
+        let mut book = OfferBook::default();
+        assert!(book.insert(offer.clone(), 90).is_err());
+        assert!(book.insert(dia_offer(&acc, false, 500, 11 + TEX_OFFER_MAX_LIFE), 10).is_err());
+        assert!(book.insert(offer.clone(), 10).unwrap());
+        assert!(!book.insert(offer.clone(), 10).unwrap());
+        assert_eq!(book.list(Some(offer.addr())).len(), 1);
+        assert!(book.list(Some(&Address::from(*other.address()))).is_empty());
+
+        assert!(book.cancel(&TexOfferCancel::create_by(&other, offer.hash)).is_err());
+        let cancel = TexOfferCancel::create_by(&acc, offer.hash);
+        assert!(book.cancel(&cancel).unwrap());
+        assert!(!book.cancel(&cancel).unwrap());
+        assert!(book.is_empty());
+        assert!(book.insert(offer.clone(), 10).is_err());
+
+        // the cancel is forgotten once the offer could no longer run
+        book.prune(90);
+        assert!(book.insert(offer.clone(), 89).unwrap());
+        book.prune(90);
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn full_book_evicts_soonest_expiry() {
+        let acc = Account::create_by("texrelay-test-seller").unwrap();
+        let mut book = OfferBook::default();
+        for i in 0..TEX_BOOK_ADDR_MAX as u64 {
+            assert!(book.insert(dia_offer(&acc, false, 500 + i, 100 + i), 10).unwrap());
+        }
+        // the address is full: an offer expiring sooner than all is refused,
+        // a later one takes the place of the soonest
+        assert!(book.insert(dia_offer(&acc, false, 1, 50), 10).is_err());
+        let late = dia_offer(&acc, false, 2, 1000);
+        assert!(book.insert(late.clone(), 10).unwrap());
+        let list = book.list(Some(late.addr()));
+        assert_eq!(list.len(), TEX_BOOK_ADDR_MAX);
+        assert_eq!((list[0].expire, list.last().unwrap().hash), (101, late.hash));
+
+        // a matched offer stays out when gossiped again, until it expires
+        book.matched(&[&late]);
+        assert!(book.insert(late.clone(), 10).is_err());
+        book.prune(110);
+        assert_eq!(book.len(), TEX_BOOK_ADDR_MAX - 11);
+        assert_eq!(book.list(None)[0].expire, 111);
+        book.retain(|o| o.expire > 120);
+        assert_eq!(book.list(Some(late.addr())).len(), book.len());
+    }
+
+    #[test]
+    fn offers_need_funds() {
+        use testkit::sim::state::FlatMemState;
+        let seller = Account::create_by("texrelay-test-seller").unwrap();
+        let buyer = Account::create_by("texrelay-test-buyer").unwrap();
+        let (sell, buy) = (dia_offer(&seller, false, 500, 90), dia_offer(&buyer, true, 500, 80));
+        let mut sta = FlatMemState::default();
+        assert!(sell.check_funds(&CoreStateRead::wrap(&sta)).is_err());
+        assert!(buy.check_funds(&CoreStateRead::wrap(&sta)).is_err());
+
+        let mut core = CoreState::wrap(&mut sta);
+        core.balance_set(buy.addr(), &Balance::hac(Amount::zhu(500)));
+        let dia = DiamondSto { address: *sell.addr(), ..Default::default() };
+        core.diamond_set(&DiamondName::from_readable(b"KKKKVA").unwrap(), &dia);
+        assert!(buy.check_funds(&CoreStateRead::wrap(&sta)).is_ok());
+        // one of the two diamonds is missing
+        assert!(sell.check_funds(&CoreStateRead::wrap(&sta)).is_err());
+        CoreState::wrap(&mut sta).diamond_set(&DiamondName::from_readable(b"HYXYHY").unwrap(), &dia);
+        assert!(sell.check_funds(&CoreStateRead::wrap(&sta)).is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+/*
+    Local single-node dev mode (`fullnode --dev`, or `[default] dev = true`).
+
+    Runs its own chain (DEV_CHAIN_ID) whose genesis funds DEV_ACCOUNTS accounts
+    derived from DEV_MNEMONIC, accepts blocks without PoW, and mines a block as
+    soon as a transaction enters the pool, or every `[dev] block_interval` secs.
+*/
+
+pub const DEV_CHAIN_ID: u32 = 1337;
+pub const DEV_ACCOUNTS: usize = 10;
+pub const DEV_MNEMONIC: &str = "hacash dev mode test test test test test test test test junk";
+const DEV_GENESIS_TIME: u64 = 1700000000;
+
+/// The pre-funded dev accounts, the first one also takes the block rewards.
+pub fn dev_accounts() -> Vec<Account> {
+    (0..DEV_ACCOUNTS).map(|i| {
+        Account::create_by_password(&format!("{} {}", DEV_MNEMONIC, i)).unwrap()
+    }).collect()
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct DevConf {
+    pub enable: bool,
+    /// Seconds between blocks; 0 mines whenever the pool is not empty.
+    pub block_interval: u64,
+}
+
+impl DevConf {
+    pub fn new(ini: &IniObj) -> DevConf {
+        let sec = ini_section(ini, "dev");
+        DevConf {
+            enable: ini_must_bool(ini_section(ini, "default"), "dev", false),
+            block_interval: ini_must_u64(sec, "block_interval", 0),
+        }
+    }
+
+    /// Turn `ini` into a dev config: own data dir, no peer discovery, the api
+    /// server on, open to all scopes but bound to localhost, and the first dev
+    /// account as miner. Keys already set are kept.
+    pub fn apply(ini: &mut IniObj) {
+        let acc = &dev_accounts()[0];
+        let mut set = |sec: &str, key: &str, val: String| {
+            ini.entry(sec.to_owned()).or_default().entry(key.to_owned()).or_insert(Some(val));
+        };
+        set("default", "dev", "true".to_owned());
+        set("default", "data_dir", "hacash_dev_data".to_owned());
+        set("node", "not_find_nodes", "true".to_owned());
+        set("server", "enable", "true".to_owned());
+        set("server", "bind", "127.0.0.1".to_owned());
+        set("server", "public_scopes", "all".to_owned());
+        set("miner", "enable", "true".to_owned());
+        set("miner", "reward", acc.readable().to_owned());
+        set("miner", "message", "dev".to_owned());
+    }
+}
+
+impl ChainSpec {
+    pub fn dev() -> ChainSpec {
+        let fund = Amount::coin(1, 254); // 1000000 HAC
+        let accs = dev_accounts();
+        ChainSpec {
+            name: "dev".to_owned(),
+            chain_id: Some(DEV_CHAIN_ID),
+            genesis: Some(GenesisSpec {
+                timestamp: DEV_GENESIS_TIME,
+                reward: Address::from(*accs[0].address()),
+                message: "hacash dev".to_owned(),
+                allocations: accs.iter().map(|a| (Address::from(*a.address()), fund.clone())).collect(),
+            }),
+            difficulty: DifficultySpec {
+                skip_pow: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod dev_tests {
+    use super::*;
+
+    #[test]
+    fn apply_keeps_explicit_keys() {
+        let mut ini = IniObj::new();
+        ini.entry("server".to_owned()).or_default().insert("listen".to_owned(), Some("9000".to_owned()));
+        ini.entry("default".to_owned()).or_default().insert("data_dir".to_owned(), Some("mydev".to_owned()));
+        DevConf::apply(&mut ini);
+        let dft = ini_section(&ini, "default");
+        assert_eq!(ini_must(dft, "data_dir", ""), "mydev");
+        assert!(DevConf::new(&ini).enable);
+        assert_eq!(ini_must_u64(ini_section(&ini, "server"), "listen", 0), 9000);
+        assert_eq!(ini_must(ini_section(&ini, "miner"), "reward", ""), dev_accounts()[0].readable());
+        assert_eq!(ChainSpec::new(&ini), ChainSpec::dev());
+        // every scope is open, so only to this machine
+        assert_eq!(ServerConf::new(&ini).bind, IpAddr::from([127, 0, 0, 1]));
+        let mut ini = IniObj::new();
+        ini.entry("server".to_owned()).or_default().insert("bind".to_owned(), Some("0.0.0.0".to_owned()));
+        DevConf::apply(&mut ini);
+        assert_eq!(ServerConf::new(&ini).bind, IpAddr::from([0, 0, 0, 0]));
+    }
+}
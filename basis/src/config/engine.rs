@@ -46,8 +46,16 @@ pub struct EngineConf {
     // VM contract cache (performance-only, consensus-neutral)
     // Unit: MB. `0` disables cache.
     pub contract_cache_size: f64,
+    // local dev mode
+    pub dev: DevConf,
 }
 
+/// `ini_must_account` takes this password when `bid_password` is missing.
+const DMER_BID_PASSWORD_UNSET: &str = "123456";
+
+/// INI sections with settings a running node may change, see `update_runtime`.
+pub const ENGINE_RUNTIME_SECTIONS: [&str; 4] = ["miner", "diamondminer", "txpool", "server"];
+
 
 impl EngineConf {
 
@@ -59,6 +67,13 @@ impl EngineConf {
         self.chain_id == 0
     }
 
+    /// Whether `[diamondminer] bid_password` names a bid account; without it
+    /// the account is one anyone can sign for, which never bids.
+    pub fn dmer_bid_account_set(&self) -> bool {
+        let unset = Account::create_by_password(DMER_BID_PASSWORD_UNSET).unwrap();
+        self.dmer_bid_account.address() != unset.address()
+    }
+
     // Coinbase used by non-block external execution contexts (mempool check/sandbox).
     // If this node has miner enabled and a configured reward address, use that address;
     // otherwise keep zero-address semantics.
@@ -68,8 +83,69 @@ impl EngineConf {
         }
         Address::default()
     }
+
+    /// Take the settings of `sections` from `src`: mining, bidding and tx pool
+    /// sizes, and from `server` only the lowest fee. Everything else stays.
+    pub fn update_runtime(&mut self, src: &EngineConf, sections: &[&str]) -> Rerr {
+        for sec in sections {
+            match *sec {
+                "miner" => {
+                    self.miner_enable = src.miner_enable;
+                    self.miner_reward_address = src.miner_reward_address;
+                    self.miner_message = src.miner_message;
+                }
+                "diamondminer" => {
+                    self.dmer_enable = src.dmer_enable;
+                    self.dmer_reward_address = src.dmer_reward_address;
+                    self.dmer_bid_account = src.dmer_bid_account.clone();
+                    self.dmer_bid_min = src.dmer_bid_min.clone();
+                    self.dmer_bid_max = src.dmer_bid_max.clone();
+                    self.dmer_bid_step = src.dmer_bid_step.clone();
+                }
+                "txpool" => self.txpool_maxs = src.txpool_maxs.clone(),
+                "server" => self.lowest_fee_purity = src.lowest_fee_purity,
+                _ => return errf!("config section [{}] cannot change while running", sec),
+            }
+        }
+        Ok(())
+    }
+
+    /// The checks `new` does by panicking, for settings changed while running.
+    pub fn check_runtime(&self) -> Rerr {
+        if self.miner_enable && self.miner_reward_address == Address::default() {
+            return errf!("miner reward address is not set")
+        }
+        if self.dmer_enable {
+            if !self.dmer_reward_address.is_privakey() {
+                return errf!("diamond miner reward address {} must be PRIVAKEY type",
+                    self.dmer_reward_address.to_readable())
+            }
+            if !self.dmer_bid_account_set() {
+                return errf!("diamond miner bid_password is not set")
+            }
+            if self.dmer_bid_max < self.dmer_bid_min {
+                return errf!("max bid fee {} cannot be less than min fee {}", self.dmer_bid_max, self.dmer_bid_min)
+            }
+            if self.dmer_bid_step < Amount::coin(1, 244) {
+                return errf!("bid step {} cannot be less than 1:244", self.dmer_bid_step)
+            }
+        }
+        if self.txpool_maxs.contains(&0) {
+            return errf!("tx pool group size cannot be 0")
+        }
+        Ok(())
+    }
     
     pub fn new(ini: &IniObj) -> EngineConf {
+        match Self::try_new(ini) {
+            Ok(cnf) => cnf,
+            Err(e) => panic!("[Config Error] {}.", e),
+        }
+    }
+
+    /// Read the engine settings from `ini`, answering an error for values
+    /// `new` would panic on, as a reload on a running node must not.
+    pub fn try_new(ini: &IniObj) -> Ret<EngineConf> {
         
 
         // datadir
@@ -82,6 +158,8 @@ impl EngineConf {
         // fee_purity is now per-byte: 1:244 = 1000000:238, purity = 1000000 / 166 ≈ 6024
         const LOWEST_FEE_PURITY: u64 = 10000_00 / 166; // 6024
 
+        let spec = chain_spec();
+
         let mut cnf = EngineConf{
             max_block_txs: 1000,
             max_block_size: 1024*1024*1, // 1MB
@@ -94,7 +172,7 @@ impl EngineConf {
             block_data_dir: join_path(&data_dir, "block"),
             state_data_dir: join_path(&data_dir, "state"),
             vmlog_data_dir: join_path(&data_dir, "vmlog"),
-            data_dir: data_dir.to_str().unwrap().to_owned(),
+            data_dir: data_dir.to_string_lossy().into_owned(),
             dev_count_switch: 0,
             show_miner_name: false,
             // logs
@@ -106,7 +184,7 @@ impl EngineConf {
             diamond_form: ini_must_bool(sec_server, "diamond_form", true),
             recent_blocks: ini_must_bool(sec_server, "recent_blocks", false),
             average_fee_purity: ini_must_bool(sec_server, "average_fee_purity", false),
-            lowest_fee_purity: LOWEST_FEE_PURITY,
+            lowest_fee_purity: spec.lowest_fee_purity.unwrap_or(LOWEST_FEE_PURITY),
             // HAC miner
             miner_enable: false,
             miner_reward_address: Address::default(),
@@ -114,7 +192,7 @@ impl EngineConf {
             // Diamond miner
             dmer_enable: false,
             dmer_reward_address: Address::default(),
-            dmer_bid_account: Account::create_by_password("123456").unwrap(),
+            dmer_bid_account: Account::create_by_password(DMER_BID_PASSWORD_UNSET).unwrap(),
             dmer_bid_min:  Amount::small_mei(1),
             dmer_bid_max:  Amount::small_mei(31),
             dmer_bid_step: Amount::small(5, 247),
@@ -122,11 +200,12 @@ impl EngineConf {
             txpool_maxs: Vec::default(),
             // vm cache
             contract_cache_size: 0.0,
+            dev: DevConf::new(ini),
         };
         // setup lowest_fee
         if ini_must(sec_server, "lowest_fee", "").len() > 0 {
-            let lfepr = ini_must_amount(sec_server, "lowest_fee").compress(2, AmtCpr::Grow)
-                .unwrap().to_238_u64().unwrap() / 166; //  =6024, simple hac trs size
+            let lfepr = ini_amount(sec_server, "lowest_fee")?.compress(2, AmtCpr::Grow)?
+                .to_238_u64()? / 166; //  =6024, simple hac trs size
             cnf.lowest_fee_purity = lfepr;
             println!("[Config] node accepted lowest fee purity {}.", lfepr);
         }
@@ -135,7 +214,7 @@ impl EngineConf {
         cnf.fast_sync = ini_must_bool(sec, "fast_sync", false);
 
         let sec_mint = &ini_section(ini, "mint");
-        cnf.chain_id = ini_must_u64(sec_mint, "chain_id", 0) as u32;
+        cnf.chain_id = spec.chain_id.unwrap_or(ini_must_u64(sec_mint, "chain_id", 0) as u32);
         cnf.sync_maxh = ini_must_u64(sec_mint, "height_max", 0);
         cnf.dev_count_switch = ini_must_u64(sec_mint, "dev_count_switch", 0) as usize;
         cnf.show_miner_name = ini_must_bool(sec_mint, "show_miner_name", false);
@@ -150,26 +229,32 @@ impl EngineConf {
         let sec_miner = &ini_section(ini, "miner");
         cnf.miner_enable = ini_must_bool(sec_miner, "enable", false);
         if cnf.miner_enable {
-            cnf.miner_reward_address = ini_must_address(sec_miner, "reward");
+            cnf.miner_reward_address = ini_address(sec_miner, "reward")?;
             let msg = ini_must_maxlen(sec_miner, "message", "", 16);
             let msgapp = vec![' ' as u8].repeat(16-msg.len());
             let msg: [u8; 16] = vec![msg.as_bytes().to_vec(), msgapp].concat().try_into().unwrap();
-            cnf.miner_message = Fixed16::from_readable(&msg).unwrap();
+            cnf.miner_message = Fixed16::from_readable(&msg)?;
         }
 
         // Diamond miner
         let sec_dmer = &ini_section(ini, "diamondminer");
         cnf.dmer_enable = ini_must_bool(sec_dmer, "enable", false);
+        // the bid account is taken even when disabled, so the admin api can enable bidding
+        if sec_dmer.contains_key("bid_password") {
+            cnf.dmer_bid_account = ini_account(sec_dmer, "bid_password")?;
+        }
         if cnf.dmer_enable {
-            cnf.dmer_reward_address = ini_must_address(sec_dmer, "reward");
+            cnf.dmer_reward_address = ini_address(sec_dmer, "reward")?;
             if !cnf.dmer_reward_address.is_privakey() {
-                panic!("diamond miner reward address {} must be PRIVAKEY type but got version {}",
+                return errf!("diamond miner reward address {} must be PRIVAKEY type but got version {}",
                     cnf.dmer_reward_address.to_readable(), cnf.dmer_reward_address.version())
             }
-            cnf.dmer_bid_account = ini_must_account(sec_dmer, "bid_password");
-            cnf.dmer_bid_min =  ini_must_amount(sec_dmer, "bid_min").compress(2, AmtCpr::Grow).unwrap();
-            cnf.dmer_bid_max =  ini_must_amount(sec_dmer, "bid_max").compress(2, AmtCpr::Grow).unwrap();
-            cnf.dmer_bid_step = ini_must_amount(sec_dmer, "bid_step").compress(2, AmtCpr::Grow).unwrap();
+            if !cnf.dmer_bid_account_set() {
+                return errf!("diamond miner bid_password must be set")
+            }
+            cnf.dmer_bid_min =  ini_amount(sec_dmer, "bid_min")?.compress(2, AmtCpr::Grow)?;
+            cnf.dmer_bid_max =  ini_amount(sec_dmer, "bid_max")?.compress(2, AmtCpr::Grow)?;
+            cnf.dmer_bid_step = ini_amount(sec_dmer, "bid_step")?.compress(2, AmtCpr::Grow)?;
         }
 
         // tx pool
@@ -186,7 +271,7 @@ impl EngineConf {
         cnf.contract_cache_size = ini_must_f64(sec_vm, "contract_cache_size", 0.0);
 
         // ok
-        cnf
+        Ok(cnf)
     }
     
 }
@@ -219,4 +304,56 @@ mod tests {
             Address::from_readable(&reward).unwrap()
         );
     }
+
+    #[test]
+    fn try_new_answers_errors_for_invalid_values() {
+        let mut ini = IniObj::new();
+        ini.insert(
+            "miner".to_owned(),
+            HashMap::from([
+                ("enable".to_owned(), Some("true".to_owned())),
+                ("reward".to_owned(), Some("not an address".to_owned())),
+            ]),
+        );
+        assert!(EngineConf::try_new(&ini).is_err());
+        ini.insert(
+            "diamondminer".to_owned(),
+            HashMap::from([("enable".to_owned(), Some("true".to_owned()))]),
+        );
+        ini.remove("miner");
+        assert!(EngineConf::try_new(&ini).is_err());
+    }
+
+    #[test]
+    fn update_runtime_takes_only_selected_sections() {
+        let reward = Address::from_readable("1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9").unwrap();
+        let mut cnf = EngineConf::new(&IniObj::new());
+        let mut src = cnf.clone();
+        src.miner_enable = true;
+        src.miner_reward_address = reward;
+        src.lowest_fee_purity = 1;
+        src.unstable_block = 99;
+        cnf.update_runtime(&src, &["miner"]).unwrap();
+        assert!(cnf.miner_enable);
+        assert_eq!(cnf.external_exec_author(), reward);
+        assert_ne!(cnf.lowest_fee_purity, 1);
+        assert_ne!(cnf.unstable_block, 99);
+        assert!(cnf.update_runtime(&src, &["node"]).is_err());
+        cnf.check_runtime().unwrap();
+
+        cnf.miner_reward_address = Address::default();
+        assert!(cnf.check_runtime().is_err());
+        cnf.miner_enable = false;
+        cnf.dmer_enable = true;
+        cnf.dmer_reward_address = reward;
+        assert!(cnf.check_runtime().is_err());
+        cnf.dmer_bid_account = Account::create_by("a bid account for tests").unwrap();
+        cnf.check_runtime().unwrap();
+        cnf.dmer_bid_step = Amount::coin(9, 243);
+        assert!(cnf.check_runtime().is_err());
+        cnf.dmer_bid_step = Amount::coin(1, 244);
+        cnf.check_runtime().unwrap();
+        cnf.dmer_bid_min = Amount::small_mei(40);
+        assert!(cnf.check_runtime().is_err());
+    }
 }
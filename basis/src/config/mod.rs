@@ -11,3 +11,5 @@ use sys::*;
 include! {"engine.rs"}
 include! {"node.rs"}
 include! {"server.rs"}
+include! {"spec.rs"}
+include! {"dev.rs"}
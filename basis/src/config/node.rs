@@ -11,6 +11,8 @@ pub struct NodeConf {
     pub offshoot_peers: usize, // private IP
     pub backbone_peers: usize, // public IP
     pub use_stable_nodes: bool,
+    pub encrypt: bool,      // offer encrypted sessions to peers
+    pub encrypt_only: bool, // refuse plaintext (legacy) peers
     pub data_dir: PathBuf,
     
     pub multi_thread: bool,
@@ -43,6 +45,8 @@ impl NodeConf {
         let find = ini_must_bool(sec, "not_find_nodes", false) == false;
         let accept = ini_must_bool(sec, "not_accept_nodes", false) == false;
         let use_stable_nodes = ini_must_bool(sec, "use_stable_nodes", true);
+        let encrypt_only = ini_must_bool(sec, "encrypt_only", false);
+        let encrypt = encrypt_only || ini_must_bool(sec, "encrypt", true);
 
         // boots
         let boots = ini_must(sec, "boots", "");
@@ -68,6 +72,8 @@ impl NodeConf {
             offshoot_peers: 200,
             backbone_peers: 4,
             use_stable_nodes: use_stable_nodes,
+            encrypt,
+            encrypt_only,
             data_dir: get_mainnet_data_dir(ini),
             multi_thread:  ini_must_bool(sec, "multi_thread", false),
         };
@@ -1,10 +1,118 @@
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct ServerConf {
     pub enable: bool,
+    pub bind: IpAddr,
     pub listen: u16,
     pub multi_thread: bool,
     pub debug_open: bool,
+    pub access: ApiAccessConf,
+}
+
+
+/// Route groups an api key can be allowed, by path prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiScope {
+    Query,   // everything not below
+    Submit,  // /submit/*
+    Operate, // /operate/*
+    Debug,   // /debug/*
+    Admin,   // /admin/*, only with a key naming it, never part of `all`
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 4] = [Self::Query, Self::Submit, Self::Operate, Self::Debug];
+
+    pub fn of_path(path: &str) -> Self {
+        match path.split('/').nth(1) {
+            Some("submit") => Self::Submit,
+            Some("operate") => Self::Operate,
+            Some("debug") => Self::Debug,
+            Some("admin") => Self::Admin,
+            _ => Self::Query,
+        }
+    }
+
+    pub fn parse(s: &str) -> Ret<Self> {
+        Ok(match s {
+            "query" => Self::Query,
+            "submit" => Self::Submit,
+            "operate" => Self::Operate,
+            "debug" => Self::Debug,
+            "admin" => Self::Admin,
+            _ => return errf!("api scope '{}' not supported", s),
+        })
+    }
+
+    // comma separated, `all` for every scope
+    pub fn parse_list(s: &str) -> Ret<Vec<Self>> {
+        let mut res = vec![];
+        for one in s.split(',').map(|a| a.trim()).filter(|a| !a.is_empty()) {
+            let add = match one {
+                "all" => Self::ALL.to_vec(),
+                _ => vec![Self::parse(one)?],
+            };
+            for sc in add {
+                if !res.contains(&sc) {
+                    res.push(sc);
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+
+#[derive(Clone, Debug)]
+pub struct ApiKeyConf {
+    pub name: String,
+    pub token: String,
+    pub scopes: Vec<ApiScope>,
+}
+
+
+/// Who may call the api server and how often.
+#[derive(Clone, Debug, Default)]
+pub struct ApiAccessConf {
+    pub public_scopes: Vec<ApiScope>, // open without a key, query and submit unless set
+    pub keys: Vec<ApiKeyConf>,
+    pub rate_limit: u64, // requests per second per ip or key, 0 is no limit
+    pub rate_burst: u64,
+    pub cors_origins: Vec<String>, // `*` for any
+}
+
+impl ApiAccessConf {
+    pub fn new(ini: &IniObj) -> ApiAccessConf {
+        let sec = ini_section(ini, "server");
+        let public = ini_must(sec, "public_scopes", "query,submit");
+        let public_scopes = ApiScope::parse_list(&public)
+            .unwrap_or_else(|e| panic!("{}", exiterr!(1, "server public_scopes: {}", e)));
+        if public_scopes.contains(&ApiScope::Admin) {
+            panic!("{}", exiterr!(1, "server public_scopes: {}", "admin scope needs an api key"));
+        }
+        // [apikey] name = token:scope,scope
+        let mut keys = vec![];
+        for (name, val) in ini_section(ini, "apikey") {
+            let val = val.clone().unwrap_or_default();
+            let (token, scopes) = val.split_once(':').unwrap_or((&val, "all"));
+            let token = token.trim();
+            if token.len() < 16 {
+                panic!("{}", exiterr!(1, "api key '{}' token must be at least 16 chars", name));
+            }
+            let scopes = ApiScope::parse_list(scopes)
+                .unwrap_or_else(|e| panic!("{}", exiterr!(1, "api key '{}': {}", name, e)));
+            keys.push(ApiKeyConf { name: name.clone(), token: token.to_owned(), scopes });
+        }
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        let rate_limit = ini_must_u64(sec, "rate_limit", 0);
+        let rate_burst = ini_must_u64(sec, "rate_burst", rate_limit * 2).max(rate_limit);
+        let cors_origins = ini_must(sec, "cors_origins", "")
+            .split(',')
+            .map(|a| a.trim().to_owned())
+            .filter(|a| !a.is_empty())
+            .collect();
+        ApiAccessConf { public_scopes, keys, rate_limit, rate_burst, cors_origins }
+    }
 }
 
 #[cfg(test)]
@@ -16,6 +124,7 @@ mod server_conf_tests {
         let ini = IniObj::new();
         let cnf = ServerConf::new(&ini);
         assert!(!cnf.debug_open);
+        assert_eq!(cnf.access.public_scopes, vec![ApiScope::Query, ApiScope::Submit]);
     }
 
     #[test]
@@ -28,6 +137,39 @@ mod server_conf_tests {
         let cnf = ServerConf::new(&ini);
         assert!(cnf.debug_open);
     }
+
+    #[test]
+    fn access_reads_keys_and_scopes() {
+        let mut ini = IniObj::new();
+        let mut server = std::collections::HashMap::new();
+        server.insert("bind".to_owned(), Some("127.0.0.1".to_owned()));
+        server.insert("public_scopes".to_owned(), Some("query".to_owned()));
+        server.insert("rate_limit".to_owned(), Some("5".to_owned()));
+        ini.insert("server".to_owned(), server);
+        let mut keys = std::collections::HashMap::new();
+        keys.insert("wallet".to_owned(), Some("0123456789abcdefXYZ:query, submit".to_owned()));
+        keys.insert("admin".to_owned(), Some("fedcba9876543210xyz".to_owned()));
+        keys.insert("ops".to_owned(), Some("fedcba9876543210ops:admin,query".to_owned()));
+        ini.insert("apikey".to_owned(), keys);
+
+        let cnf = ServerConf::new(&ini);
+        assert_eq!(cnf.bind, IpAddr::from([127, 0, 0, 1]));
+        let access = cnf.access;
+        assert_eq!(access.public_scopes, vec![ApiScope::Query]);
+        assert_eq!((access.rate_limit, access.rate_burst), (5, 10));
+        assert_eq!(access.keys[0].name, "admin");
+        assert_eq!(access.keys[0].scopes, ApiScope::ALL.to_vec());
+        assert_eq!(access.keys[1].scopes, vec![ApiScope::Admin, ApiScope::Query]);
+        assert_eq!(access.keys[2].token, "0123456789abcdefXYZ");
+        assert_eq!(access.keys[2].scopes, vec![ApiScope::Query, ApiScope::Submit]);
+
+        assert_eq!(ApiScope::of_path("/submit/transaction"), ApiScope::Submit);
+        assert_eq!(ApiScope::of_path("/debug/trace/tx"), ApiScope::Debug);
+        assert_eq!(ApiScope::of_path("/query/latest"), ApiScope::Query);
+        assert_eq!(ApiScope::of_path("/admin/peers"), ApiScope::Admin);
+        assert!(!ApiScope::parse_list("all").unwrap().contains(&ApiScope::Admin));
+        assert!(ApiScope::parse_list("query,owner").is_err());
+    }
 }
 
 
@@ -38,9 +180,12 @@ impl  ServerConf {
         let sec = ini_section(ini, "server");
         let cnf = ServerConf{
             enable:       ini_must_bool(&sec, "enable", false),
+            bind: ini_must(&sec, "bind", "0.0.0.0").parse::<IpAddr>()
+                .unwrap_or_else(|_| panic!("{}", exiterr!(1, "server bind address '{}' not supported", ini_must(&sec, "bind", "")))),
             listen:   ini_must_u64(&sec, "listen", 8083) as u16,
             multi_thread: ini_must_bool(&sec, "multi_thread", false),
             debug_open: ini_must_bool(&sec, "debug_open", false),
+            access: ApiAccessConf::new(ini),
         };
 
         cnf
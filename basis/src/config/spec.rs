@@ -0,0 +1,234 @@
+/*
+    Chain spec: the consensus rules of one network in a single INI file, named
+    by `[default] chain_spec` in the node config. Every value is an override;
+    anything left out keeps the built-in mainnet rule (or the legacy `[mint]`
+    key), so a node without a spec file behaves exactly as before.
+
+        [chain]       name, chain_id
+        [genesis]     timestamp, reward, message
+        [allocation]  <label> = <address> <amount>
+        [activation]  dev_open_max_height, online_open_height
+        [difficulty]  adjust_blocks, group_blocks, target_time,
+                      lwma_height, asert_height, asert_half_life,
+                      skip_pow (never on mainnet)
+        [fee]         lowest_fee_purity, vm_lowest_fee_purity
+*/
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenesisSpec {
+    pub timestamp: u64,
+    pub reward: Address,
+    pub message: String,
+    /// Balances set in the initial state, in place of the mainnet ones. Each
+    /// address appears once; the genesis coinbase commits to the list.
+    pub allocations: Vec<(Address, Amount)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActivationSpec {
+    pub dev_open_max_height: u64,
+    pub online_open_height: u64,
+}
+
+impl ActivationSpec {
+    pub const MAINNET: ActivationSpec = ActivationSpec {
+        dev_open_max_height: 65_432,
+        online_open_height: 765_432,
+    };
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DifficultySpec {
+    pub adjust_blocks: Option<u64>,
+    pub group_blocks: Option<u64>,
+    pub target_time: Option<u64>,
+    pub lwma_height: Option<u64>,
+    pub asert_height: Option<u64>,
+    pub asert_half_life: Option<u64>,
+    /// Accept blocks without checking difficulty or PoW hash.
+    pub skip_pow: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChainSpec {
+    pub name: String,
+    pub chain_id: Option<u32>,
+    pub genesis: Option<GenesisSpec>,
+    pub activation: Option<ActivationSpec>,
+    pub difficulty: DifficultySpec,
+    pub lowest_fee_purity: Option<u64>,
+    pub vm_lowest_fee_purity: Option<u64>,
+}
+
+fn spec_u64(sec: &HashMap<String, Option<String>>, key: &str) -> Ret<Option<u64>> {
+    match sec.get(key).and_then(|v| v.as_deref()) {
+        None | Some("") => Ok(None),
+        Some(v) => v.parse::<u64>().map(Some).map_err(|_| format!("{} '{}' is not a number", key, v)),
+    }
+}
+
+impl ChainSpec {
+
+    /// Load the spec file named in the node config, the dev spec in dev mode,
+    /// or the empty spec if neither.
+    pub fn new(ini: &IniObj) -> ChainSpec {
+        if DevConf::new(ini).enable {
+            return ChainSpec::dev()
+        }
+        let path = ini_must(ini_section(ini, "default"), "chain_spec", "");
+        if path.is_empty() {
+            return ChainSpec::default()
+        }
+        let path = get_current_exe_absolute_dir(&path);
+        match load_ini_file(&path).and_then(|f| ChainSpec::parse(&f)) {
+            Ok(spec) => spec,
+            Err(e) => panic!("[Config Error] chain spec {}: {}", path.display(), e),
+        }
+    }
+
+    pub fn parse(file: &IniObj) -> Ret<ChainSpec> {
+        let chain = ini_section(file, "chain");
+        let mut spec = ChainSpec {
+            name: ini_must(chain, "name", "custom"),
+            ..Default::default()
+        };
+        if let Some(id) = spec_u64(chain, "chain_id")? {
+            spec.chain_id = Some(u32::try_from(id).map_err(|_| format!("chain_id {} overflow", id))?);
+        }
+        if file.contains_key("genesis") {
+            spec.genesis = Some(Self::parse_genesis(file)?);
+        }
+        let act = ini_section(file, "activation");
+        let dev = spec_u64(act, "dev_open_max_height")?;
+        let online = spec_u64(act, "online_open_height")?;
+        if dev.is_some() || online.is_some() {
+            spec.activation = Some(ActivationSpec {
+                dev_open_max_height: dev.unwrap_or(0),
+                online_open_height: online.unwrap_or(0),
+            });
+        }
+        let diff = ini_section(file, "difficulty");
+        spec.difficulty = DifficultySpec {
+            adjust_blocks: spec_u64(diff, "adjust_blocks")?,
+            group_blocks: spec_u64(diff, "group_blocks")?,
+            target_time: spec_u64(diff, "target_time")?,
+            lwma_height: spec_u64(diff, "lwma_height")?,
+            asert_height: spec_u64(diff, "asert_height")?,
+            asert_half_life: spec_u64(diff, "asert_half_life")?,
+            skip_pow: ini_must_bool(diff, "skip_pow", false),
+        };
+        if spec.difficulty.skip_pow && spec.chain_id.unwrap_or(0) == 0 {
+            return errf!("skip_pow needs a non-mainnet chain_id")
+        }
+        if spec.difficulty.asert_half_life == Some(0) {
+            return errf!("asert_half_life must be greater than 0")
+        }
+        let fee = ini_section(file, "fee");
+        spec.lowest_fee_purity = spec_u64(fee, "lowest_fee_purity")?;
+        spec.vm_lowest_fee_purity = spec_u64(fee, "vm_lowest_fee_purity")?;
+        Ok(spec)
+    }
+
+    fn parse_genesis(file: &IniObj) -> Ret<GenesisSpec> {
+        let sec = ini_section(file, "genesis");
+        let timestamp = spec_u64(sec, "timestamp")?.ok_or("genesis timestamp missing".to_owned())?;
+        let reward = ini_must(sec, "reward", "");
+        let reward = Address::from_readable(&reward).map_err(|_| format!("genesis reward address '{}' invalid", reward))?;
+        let message = ini_must(sec, "message", "");
+        if message.len() > 16 {
+            return errf!("genesis message '{}' longer than 16 bytes", message)
+        }
+        // sorted by label so every node builds the same state
+        let allocs: BTreeMap<_, _> = ini_section(file, "allocation").iter().collect();
+        let mut allocations = Vec::with_capacity(allocs.len());
+        let mut seen = HashMap::new();
+        for (label, val) in allocs {
+            let val = val.as_deref().unwrap_or("");
+            let mut parts = val.split_whitespace();
+            let (Some(adr), Some(amt), None) = (parts.next(), parts.next(), parts.next()) else {
+                return errf!("allocation {} must be '<address> <amount>' but got '{}'", label, val)
+            };
+            let addr = Address::from_readable(adr).map_err(|_| format!("allocation {} address '{}' invalid", label, adr))?;
+            let amount = Amount::from(amt).map_err(|e| format!("allocation {} amount '{}' invalid: {}", label, amt, e))?;
+            if let Some(first) = seen.insert(addr.to_readable(), label) {
+                return errf!("allocation {} repeats the address of allocation {}", label, first)
+            }
+            allocations.push((addr, amount));
+        }
+        Ok(GenesisSpec { timestamp, reward, message, allocations })
+    }
+
+    pub fn is_custom(&self) -> bool {
+        *self != ChainSpec::default()
+    }
+}
+
+static CHAIN_SPEC: std::sync::OnceLock<ChainSpec> = std::sync::OnceLock::new();
+
+/// Install the spec this process runs under. Must happen before anything reads
+/// `chain_spec()`; installing a different spec afterwards is an error.
+pub fn install_chain_spec(spec: ChainSpec) -> Rerr {
+    let cur = CHAIN_SPEC.get_or_init(|| spec.clone());
+    if *cur != spec {
+        return errf!("chain spec '{}' already installed", cur.name)
+    }
+    Ok(())
+}
+
+/// The installed spec, or the empty spec (plain mainnet rules) if none was.
+pub fn chain_spec() -> &'static ChainSpec {
+    CHAIN_SPEC.get_or_init(ChainSpec::default)
+}
+
+
+#[cfg(test)]
+mod spec_tests {
+    use super::*;
+
+    fn ini(secs: &[(&str, &[(&str, &str)])]) -> IniObj {
+        secs.iter().map(|(name, kvs)| {
+            let sec = kvs.iter().map(|(k, v)| (k.to_string(), Some(v.to_string()))).collect();
+            (name.to_string(), sec)
+        }).collect()
+    }
+
+    #[test]
+    fn parses_full_spec() {
+        let addr = "1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9";
+        let alloc = format!("{} 1:248", addr);
+        let alloc2 = "12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi 2:248";
+        let spec = ChainSpec::parse(&ini(&[
+            ("chain", &[("name", "devnet"), ("chain_id", "7")]),
+            ("genesis", &[("timestamp", "1700000000"), ("reward", addr), ("message", "hello devnet")]),
+            ("allocation", &[("b", alloc2), ("a", &alloc)]),
+            ("activation", &[("online_open_height", "100")]),
+            ("difficulty", &[("adjust_blocks", "20"), ("group_blocks", "4"), ("target_time", "10")]),
+            ("fee", &[("vm_lowest_fee_purity", "1")]),
+        ])).unwrap();
+        assert_eq!(spec.name, "devnet");
+        assert_eq!(spec.chain_id, Some(7));
+        let gns = spec.genesis.as_ref().unwrap();
+        assert_eq!(gns.timestamp, 1700000000);
+        assert_eq!(gns.allocations.len(), 2);
+        assert_eq!(spec.activation, Some(ActivationSpec { dev_open_max_height: 0, online_open_height: 100 }));
+        assert_eq!(spec.difficulty.adjust_blocks, Some(20));
+        assert_eq!(spec.difficulty.asert_height, None);
+        assert_eq!((spec.lowest_fee_purity, spec.vm_lowest_fee_purity), (None, Some(1)));
+        assert!(spec.is_custom());
+        assert!(!ChainSpec::new(&IniObj::new()).is_custom());
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert!(ChainSpec::parse(&ini(&[("chain", &[("chain_id", "x")])])).is_err());
+        assert!(ChainSpec::parse(&ini(&[("chain", &[("chain_id", "4294967296")])])).is_err());
+        assert!(ChainSpec::parse(&ini(&[("genesis", &[("reward", "1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9")])])).is_err());
+        let gns: &[(&str, &str)] = &[("timestamp", "1"), ("reward", "1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9")];
+        assert!(ChainSpec::parse(&ini(&[("genesis", gns), ("allocation", &[("a", "1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9")])])).is_err());
+        let twice = "1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9 1:248";
+        let err = ChainSpec::parse(&ini(&[("genesis", gns), ("allocation", &[("a", twice), ("b", twice)])])).unwrap_err();
+        assert_eq!(err, "allocation b repeats the address of allocation a");
+        assert!(ChainSpec::parse(&ini(&[("difficulty", &[("asert_half_life", "0")])])).is_err());
+        assert!(ChainSpec::parse(&ini(&[("difficulty", &[("skip_pow", "true")])])).is_err());
+    }
+}
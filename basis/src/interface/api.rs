@@ -71,12 +71,80 @@ pub enum ApiHandler {
     Async(ApiHandlerAsyncFn),
 }
 
+/// Value type of a query parameter or response field.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ApiType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Object,
+    Array,
+}
+
+impl ApiType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Object => "object",
+            Self::Array => "array",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiField {
+    pub name: &'static str,
+    pub ty: ApiType,
+    pub required: bool,
+    pub desc: &'static str,
+}
+
+/// What a route takes and returns, for validation and the OpenAPI document.
+/// Responses are always wrapped as `{"ret":0,...}` or `{"ret":1,"err":...}`.
+#[derive(Clone, Debug, Default)]
+pub struct ApiDoc {
+    pub summary: &'static str,
+    pub params: Vec<ApiField>,
+    pub body: Option<&'static str>,
+    pub returns: Vec<ApiField>,
+}
+
+impl ApiDoc {
+    /// Check the declared query parameters are present and well typed.
+    /// Empty values count as absent, as the handlers read them.
+    pub fn check(&self, req: &ApiRequest) -> Result<(), String> {
+        for p in &self.params {
+            let val = req.query(p.name).filter(|v| !v.is_empty());
+            let Some(val) = val else {
+                if p.required {
+                    return Err(format!("parameter '{}' is required", p.name));
+                }
+                continue;
+            };
+            let ok = match p.ty {
+                ApiType::Integer => val.parse::<i64>().is_ok() || val.parse::<u64>().is_ok(),
+                ApiType::Number => val.parse::<f64>().is_ok(),
+                _ => true,
+            };
+            if !ok {
+                return Err(format!("parameter '{}' must be {}", p.name, p.ty.name()));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct ApiRoute {
     pub method: ApiMethod,
     pub path: String,
     pub handler: ApiHandler,
     pub debug: bool,
+    pub doc: ApiDoc,
 }
 
 impl ApiRoute {
@@ -86,6 +154,7 @@ impl ApiRoute {
             path: path.to_owned(),
             handler: ApiHandler::Sync(handler),
             debug: false,
+            doc: ApiDoc::default(),
         }
     }
 
@@ -95,6 +164,7 @@ impl ApiRoute {
             path: path.to_owned(),
             handler: ApiHandler::Sync(handler),
             debug: false,
+            doc: ApiDoc::default(),
         }
     }
 
@@ -104,6 +174,7 @@ impl ApiRoute {
             path: path.to_owned(),
             handler: ApiHandler::Async(handler),
             debug: false,
+            doc: ApiDoc::default(),
         }
     }
 
@@ -113,6 +184,7 @@ impl ApiRoute {
             path: debug_path(path),
             handler: ApiHandler::Sync(handler),
             debug: true,
+            doc: ApiDoc::default(),
         }
     }
 
@@ -122,10 +194,53 @@ impl ApiRoute {
             path: debug_path(path),
             handler: ApiHandler::Sync(handler),
             debug: true,
+            doc: ApiDoc::default(),
         }
     }
 }
 
+impl ApiRoute {
+    pub fn summary(mut self, summary: &'static str) -> Self {
+        self.doc.summary = summary;
+        self
+    }
+
+    pub fn param(self, name: &'static str, ty: ApiType, desc: &'static str) -> Self {
+        self.push_param(name, ty, false, desc)
+    }
+
+    pub fn param_must(self, name: &'static str, ty: ApiType, desc: &'static str) -> Self {
+        self.push_param(name, ty, true, desc)
+    }
+
+    /// The `unit` parameter of routes showing HAC amounts.
+    pub fn param_unit(self) -> Self {
+        self.param("unit", ApiType::String, "HAC amount unit: fin (default), mei, zhu, shuo, ai, miao")
+    }
+
+    /// A raw body, or hex with `hexbody=true`.
+    pub fn body(mut self, desc: &'static str) -> Self {
+        self.doc.body = Some(desc);
+        self.param("hexbody", ApiType::Boolean, "body is hex encoded")
+    }
+
+    /// A JSON body, taken as is.
+    pub fn body_json(mut self, desc: &'static str) -> Self {
+        self.doc.body = Some(desc);
+        self
+    }
+
+    pub fn returns(mut self, name: &'static str, ty: ApiType, desc: &'static str) -> Self {
+        self.doc.returns.push(ApiField { name, ty, required: false, desc });
+        self
+    }
+
+    fn push_param(mut self, name: &'static str, ty: ApiType, required: bool, desc: &'static str) -> Self {
+        self.doc.params.push(ApiField { name, ty, required, desc });
+        self
+    }
+}
+
 fn debug_path(path: &str) -> String {
     let suffix = path.trim_start_matches('/');
     format!("/debug/{}", suffix)
@@ -1,9 +1,12 @@
 
 pub trait EngineRead: Send + Sync {
-    fn config(&self) -> &EngineConf { never!() }
+    /// Current settings; the runtime ones may change between calls.
+    fn config(&self) -> Arc<EngineConf> { never!() }
 
     fn state(&self) -> Arc<Box<dyn State>> { never!() }
     fn fork_sub_state(&self) -> Box<dyn State> { never!() }
+    /// Throw-away chain state right after block `height`; may replay blocks, debug use only.
+    fn historical_state(&self, _: u64) -> Ret<Box<dyn State>> { never!() }
     fn store(&self) -> Arc<dyn Store> { never!() }
     fn logs(&self) -> Arc<dyn Logs> { never!() }
 
@@ -38,7 +41,12 @@ pub trait Engine : EngineRead + Send + Sync {
 
     // for v2
     fn discover(&self, _: BlkPkg) -> Rerr { never!() }
-    fn synchronize(&self, _: Vec<u8>) -> Rerr { never!() }
+    /// Insert blocks laid back to back; the caller may keep a handle on them
+    /// to look into a batch that failed.
+    fn synchronize(&self, _: Arc<Vec<u8>>) -> Rerr { never!() }
+
+    /// Take the runtime settings of `cnf`, see `EngineConf::update_runtime`.
+    fn update_config(&self, _: &EngineConf) -> Rerr { errf!("engine config cannot change while running") }
 
     fn exit(&self) {}
 }
@@ -1,4 +1,29 @@
 
+/// Peer reputation entry reported by the node: a misbehaving peer with its
+/// current score, or a banned one with `banned_until` set (unix seconds).
+#[derive(Clone, Debug, Default)]
+pub struct PeerRepute {
+    pub ip: String,
+    pub key: String,
+    pub name: String,
+    pub score: u64,
+    pub reason: String,
+    pub banned_until: u64,
+}
+
+/// A connected peer: `inbound` when it dialed us, `active` is the unix
+/// second it last sent anything.
+#[derive(Clone, Debug, Default)]
+pub struct PeerInfo {
+    pub key: String,
+    pub name: String,
+    pub addr: String,
+    pub public: bool,
+    pub inbound: bool,
+    pub secure: bool,
+    pub active: u64,
+}
+
 // Hacash node
 pub trait HNoder: Send + Sync {
 
@@ -20,7 +45,22 @@ pub trait HNoder: Send + Sync {
 
     fn all_peer_prints(&self) -> Vec<String> { never!() }
 
+    fn peer_reputes(&self) -> Vec<PeerRepute> { vec![] }
+    // lift bans by IP or hex node key, returns the number removed
+    fn peer_unban(&self, _: &str) -> Ret<usize> { errf!("peer bans not supported") }
+
+    fn peer_details(&self) -> Vec<PeerInfo> { vec![] }
+    // dials in the background, the peer shows up in `peer_details` once connected
+    fn peer_connect(&self, _: std::net::SocketAddr) -> Rerr { errf!("peer connect not supported") }
+    // close peers by IP, IP:port or hex node key, returns the number closed
+    fn peer_disconnect(&self, _: &str) -> Ret<usize> { errf!("peer disconnect not supported") }
+    // drop identity pins by hex identity public key or node key, returns the number removed
+    fn peer_unpin(&self, _: &str) -> Ret<usize> { errf!("peer pins not supported") }
+
+    // stop the whole node as Ctrl+C does
+    fn shutdown(&self) -> Rerr { errf!("shutdown not supported") }
+
     fn exit(&self) {}
-    
+
 }
 
@@ -0,0 +1,36 @@
+
+/// A transaction proven by a merkle branch against a verified block intro.
+#[derive(Clone)]
+pub struct LightTx {
+    pub height: u64,
+    pub block_hash: Hash,
+    pub block_timestamp: u64,
+    pub index: usize,
+    pub tx: TxPkg,
+}
+
+/// Balances a full peer reported at `height`, where `hash` matched the
+/// verified header chain. Balances are not proven, only cross-checked.
+#[derive(Clone, Debug, Default)]
+pub struct LightBalances {
+    pub height: u64,
+    pub hash: Hash,
+    pub list: Vec<Balance>,
+}
+
+// Light (SPV) node: verified block intros only, queries go to full peers
+pub trait LightNoder: Send + Sync {
+
+    fn start(&self, _: Worker) {}
+
+    // verified header tip
+    fn latest(&self) -> (u64, Hash) { never!() }
+
+    fn balances(&self, _: &[Address]) -> Ret<LightBalances> { never!() }
+    fn transaction(&self, _: &Hash) -> Ret<LightTx> { never!() }
+
+    fn all_peer_prints(&self) -> Vec<String> { never!() }
+
+    fn exit(&self) {}
+
+}
@@ -39,6 +39,11 @@ pub trait Minter : Send + Sync {
     fn blk_arrive(&self, _: &dyn BlockRead, _: &Vec<u8>, _: &dyn Store) -> Rerr { Ok(()) }
     // Full pre-exec block check. Runs before generic block verification and block execution.
     fn blk_verify(&self, _: &dyn BlockRead, _prev: &dyn BlockRead, _: &dyn BlockIntroSource) -> Rerr { Ok(()) }
+    // Intro-only PoW and difficulty check. Lets headers-first sync validate intros before bodies arrive.
+    fn blk_intro_verify(&self, _: &dyn BlockRead, _prev: &dyn BlockRead, _: &dyn BlockIntroSource) -> Rerr { Ok(()) }
+    // Whether the header hash misses the target its own difficulty claims. Needs nothing but the block,
+    // so a miss proves the sender wrong whatever our view of the chain.
+    fn blk_pow_fault(&self, _: &dyn BlockRead) -> bool { false }
     // Final gate. Runs after block execution and before forktree insertion.
     fn blk_insert(&self, _: &BlkPkg, _sub: &dyn State, _prev: &dyn State) -> Rerr { Ok(()) }
     // Stable-root callback. Runs after root/head roll is fully committed.
@@ -27,6 +27,7 @@ include! {"minter.rs"}
 include! {"engine.rs"}
 include! {"p2p_ext.rs"}
 include! {"hnoder.rs"}
+include! {"light.rs"}
 include! {"api.rs"}
 include! {"scaner.rs"}
 include! {"server.rs"}
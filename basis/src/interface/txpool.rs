@@ -10,9 +10,15 @@ pub trait TxPool: Send + Sync {
     fn find_at(&self,   _: usize, _: &Hash) -> Option<TxPkg> { None } // from group id
     fn clear_at(&self,  _: usize) -> Rerr { Ok(()) } // by group id
     fn retain_at(&self, _: usize, _: &mut dyn FnMut(&TxPkg)->bool) -> Rerr { Ok(()) }
+    fn capacity_at(&self, _: usize) -> Ret<usize> { Ok(0) } // max tx count of group
+    fn resize_at(&self, _: usize, _: usize) -> Rerr { Ok(()) } // drops the lowest fee txs over size
+    // settings
+    fn lowest_fee_purity(&self) -> u64 { 0 }
+    fn set_lowest_fee_purity(&self, _: u64) {}
     // all
     fn insert_by(&self, _: TxPkg, _: &dyn Fn(&TxPkg)->usize) -> Rerr { Ok(()) }
     fn find(&self,   _: &Hash) -> Option<TxPkg> { None }
+    fn iter(&self,   _: &mut dyn FnMut(&TxPkg)->bool) -> Rerr { Ok(()) } // every group in order
     fn drain(&self,  _: &[Hash]) -> Ret<Vec<TxPkg>> { Ok(vec![]) }
     // 
     fn print(&self) -> String { s!("") }
@@ -5,3 +5,4 @@ pub mod config;
 pub mod difficulty;
 pub mod interface;
 pub mod method;
+pub mod metrics;
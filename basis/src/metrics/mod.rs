@@ -0,0 +1,5 @@
+use std::collections::BTreeMap;
+use std::sync::*;
+use std::time::Instant;
+
+include! {"registry.rs"}
@@ -0,0 +1,195 @@
+/*
+    Process-wide metrics in Prometheus text exposition format.
+
+    Counters and timing summaries are recorded where things happen; values that
+    are cheaper to read than to track (pool sizes, cache stats) come from
+    collectors registered once and run on every scrape.
+*/
+
+#[derive(Clone, Copy, PartialEq)]
+enum MetricKind {
+    Counter,
+    Gauge,
+    Summary,
+}
+
+impl MetricKind {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+            Self::Summary => "summary",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Sample {
+    value: f64,
+    count: u64,
+}
+
+struct Family {
+    kind: MetricKind,
+    series: BTreeMap<String, Sample>,
+}
+
+type Collector = Arc<dyn Fn() + Send + Sync>;
+
+#[derive(Default)]
+pub struct Registry {
+    families: Mutex<BTreeMap<&'static str, Family>>,
+    collectors: Mutex<Vec<Collector>>,
+}
+
+fn label_str(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let items: Vec<String> = labels.iter().map(|(k, v)| {
+        let v = v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+        format!("{}=\"{}\"", k, v)
+    }).collect();
+    format!("{{{}}}", items.join(","))
+}
+
+impl Registry {
+
+    fn update(&self, name: &'static str, kind: MetricKind, labels: &[(&str, &str)], f: impl FnOnce(&mut Sample)) {
+        let mut fams = self.families.lock().unwrap();
+        let fam = fams.entry(name).or_insert_with(|| Family { kind, series: BTreeMap::new() });
+        if fam.kind != kind {
+            return // name already taken by another kind
+        }
+        f(fam.series.entry(label_str(labels)).or_default());
+    }
+
+    pub fn counter_add(&self, name: &'static str, labels: &[(&str, &str)], n: u64) {
+        self.update(name, MetricKind::Counter, labels, |s| s.value += n as f64)
+    }
+
+    /// Set a counter from a cumulative total kept elsewhere.
+    pub fn counter_set(&self, name: &'static str, labels: &[(&str, &str)], n: u64) {
+        self.update(name, MetricKind::Counter, labels, |s| s.value = n as f64)
+    }
+
+    pub fn gauge_set(&self, name: &'static str, labels: &[(&str, &str)], v: f64) {
+        self.update(name, MetricKind::Gauge, labels, |s| s.value = v)
+    }
+
+    /// Add one observation, in seconds, to a `_sum`/`_count` summary.
+    pub fn observe(&self, name: &'static str, labels: &[(&str, &str)], secs: f64) {
+        self.update(name, MetricKind::Summary, labels, |s| {
+            s.value += secs;
+            s.count += 1;
+        })
+    }
+
+    /// Set a summary from a cumulative sum and count kept elsewhere.
+    pub fn summary_set(&self, name: &'static str, labels: &[(&str, &str)], secs: f64, count: u64) {
+        self.update(name, MetricKind::Summary, labels, |s| {
+            s.value = secs;
+            s.count = count;
+        })
+    }
+
+    pub fn register_collector(&self, f: impl Fn() + Send + Sync + 'static) {
+        self.collectors.lock().unwrap().push(Arc::new(f));
+    }
+
+    pub fn render(&self) -> String {
+        let collectors = self.collectors.lock().unwrap().clone();
+        for c in collectors {
+            c();
+        }
+        let fams = self.families.lock().unwrap();
+        let mut out = String::new();
+        for (name, fam) in fams.iter() {
+            out += &format!("# TYPE {} {}\n", name, fam.kind.name());
+            for (labels, s) in &fam.series {
+                match fam.kind {
+                    MetricKind::Summary => {
+                        out += &format!("{}_sum{} {}\n", name, labels, s.value);
+                        out += &format!("{}_count{} {}\n", name, labels, s.count);
+                    }
+                    _ => out += &format!("{}{} {}\n", name, labels, s.value),
+                }
+            }
+        }
+        out
+    }
+}
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+pub fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(Registry::default)
+}
+
+pub fn counter_add(name: &'static str, labels: &[(&str, &str)], n: u64) {
+    registry().counter_add(name, labels, n)
+}
+
+pub fn counter_set(name: &'static str, labels: &[(&str, &str)], n: u64) {
+    registry().counter_set(name, labels, n)
+}
+
+pub fn gauge_set(name: &'static str, labels: &[(&str, &str)], v: f64) {
+    registry().gauge_set(name, labels, v)
+}
+
+pub fn observe(name: &'static str, labels: &[(&str, &str)], secs: f64) {
+    registry().observe(name, labels, secs)
+}
+
+pub fn summary_set(name: &'static str, labels: &[(&str, &str)], secs: f64, count: u64) {
+    registry().summary_set(name, labels, secs, count)
+}
+
+/// Run `f` and record its duration under `name`.
+pub fn timed<T>(name: &'static str, labels: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let res = f();
+    observe(name, labels, start.elapsed().as_secs_f64());
+    res
+}
+
+pub fn register_collector(f: impl Fn() + Send + Sync + 'static) {
+    registry().register_collector(f)
+}
+
+pub fn render() -> String {
+    registry().render()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_prometheus_text() {
+        let reg = Registry::default();
+        reg.counter_add("t_bytes_total", &[("dir", "in"), ("msg", "7")], 10);
+        reg.counter_add("t_bytes_total", &[("dir", "in"), ("msg", "7")], 5);
+        reg.gauge_set("t_height", &[], 42.0);
+        reg.observe("t_insert_seconds", &[], 0.5);
+        reg.observe("t_insert_seconds", &[], 0.25);
+        let out = reg.render();
+        assert!(out.contains("# TYPE t_bytes_total counter\nt_bytes_total{dir=\"in\",msg=\"7\"} 15\n"));
+        assert!(out.contains("t_height 42\n"));
+        assert!(out.contains("t_insert_seconds_sum 0.75\nt_insert_seconds_count 2\n"));
+        reg.summary_set("t_insert_seconds", &[], 2.5, 7);
+        assert!(reg.render().contains("t_insert_seconds_sum 2.5\nt_insert_seconds_count 7\n"));
+    }
+
+    #[test]
+    fn collectors_run_on_render_and_kinds_do_not_mix() {
+        let reg = Arc::new(Registry::default());
+        let r2 = reg.clone();
+        reg.register_collector(move || r2.gauge_set("t_pool", &[("group", "a\"b")], 3.0));
+        assert!(reg.render().contains("t_pool{group=\"a\\\"b\"} 3\n"));
+        reg.counter_add("t_pool", &[], 1); // name is already a gauge
+        assert!(!reg.render().contains("counter"));
+    }
+}
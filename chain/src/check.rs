@@ -6,7 +6,7 @@ fn try_execute_tx_by_author(
     sub_state: &mut Box<dyn State>,
     author: Address,
 ) -> Rerr {
-    let cnf = &this.cnf;
+    let cnf = this.config();
     if protocol::transaction::is_prelude_tx_type(tx.ty()) {
         return errf!("cannot submit author tx");
     }
@@ -27,8 +27,8 @@ fn try_execute_tx_by_author(
     let hash = Hash::from([0u8; 32]);
     let env = Env {
         chain: ChainInfo {
-            id: this.cnf.chain_id,
-            diamond_form: this.cnf.diamond_form,
+            id: this.config().chain_id,
+            diamond_form: this.config().diamond_form,
             fast_sync: false,
         },
         block: BlkInfo {
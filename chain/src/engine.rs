@@ -5,18 +5,20 @@
 pub type FnBuildDB = Arc<dyn Fn(&PathBuf) -> Box<dyn DiskDB> + Send + Sync>;
 
 pub struct ChainEngine {
-    pub(crate) cnf: Arc<EngineConf>,
+    pub(crate) cnf: RwLock<Arc<EngineConf>>,
     pub(crate) minter: Arc<dyn Minter>,
     pub(crate) scaner: Arc<dyn Scaner>,
     pub(crate) store: Arc<BlockStore>,
     pub(crate) logs: Arc<BlockLogs>,
     pub(crate) disk: Arc<dyn DiskDB>,
+    pub(crate) undo: Arc<dyn DiskDB>, // state undo logs by root height
     pub(crate) tree: RwLock<Roller>,
     pub(crate) syncing: Mutex<()>,
     pub(crate) inserting: AtomicUsize,
     // Caches
     pub(crate) recent_blocks: Mutex<VecDeque<Arc<RecentBlockInfo>>>,
     pub(crate) avgfees: Mutex<VecDeque<u64>>,
+    pub(crate) history: Mutex<BTreeMap<u64, Box<dyn State>>>, // replay snapshots by height
 }
 
 
@@ -35,6 +37,10 @@ impl ChainEngine {
             &format!("state_v{}", db_version),
         );
         Arc::make_mut(&mut cnf).state_data_dir = state_dir;
+        let undo_dir = join_path(
+            PathBuf::from(cnf.data_dir.as_str()).as_path(),
+            &format!("undo_v{}", db_version),
+        );
         let blk_dir = &cnf.block_data_dir;
         let sta_dir = &cnf.state_data_dir;
         let log_dir = &cnf.vmlog_data_dir;
@@ -47,6 +53,11 @@ impl ChainEngine {
             println!("[Engine] interrupted rebuild detected, reset state db and rebuild");
             std::fs::remove_dir_all(sta_dir).unwrap();
         }
+        if ! sta_dir.exists() && undo_dir.exists() {
+            // undo logs are rebuilt along with the state
+            std::fs::remove_dir_all(&undo_dir).unwrap();
+        }
+        std::fs::create_dir_all(&undo_dir).unwrap();
         let no_sta_dir = ! sta_dir.exists();
         if no_sta_dir {
             std::fs::create_dir_all(sta_dir).unwrap();
@@ -58,6 +69,7 @@ impl ChainEngine {
         let disk_db  = dbopfn(blk_dir);
         let log_db   = dbopfn(log_dir);
         let state_db = dbopfn(sta_dir);
+        let undo_db  = dbopfn(&undo_dir);
 
         let disk: Arc<dyn DiskDB> = disk_db.into();
         let store = Arc::new(BlockStore::wrap(disk.clone()));
@@ -68,12 +80,13 @@ impl ChainEngine {
         let state = StateInst::build(state_db.clone(), None);
 
         let engine = ChainEngine {
-            cnf: cnf.clone(),
+            cnf: RwLock::new(cnf.clone()),
             minter,
             scaner,
             store,
             logs: blogs.clone(),
             disk,
+            undo: undo_db.into(),
             tree: RwLock::new(Roller::new(
                 rtblk, 
                 Arc::new(Box::new(state)), 
@@ -84,6 +97,7 @@ impl ChainEngine {
             inserting: AtomicUsize::new(0),
             recent_blocks: Mutex::new(VecDeque::new()),
             avgfees: Mutex::new(VecDeque::new()),
+            history: Mutex::default(),
         };
 
         initialize(&engine, state_db, no_sta_dir);
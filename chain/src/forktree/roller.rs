@@ -5,6 +5,8 @@ pub(crate) struct Roller {
     level: u64,
     root: ChunkRef,
     head: ChunkRef,
+    // blocks held, root included, kept as they come and go
+    size: usize,
     // pub tree: HashMap<Hash, ChunkRef>,
 }
 
@@ -20,6 +22,7 @@ impl Roller {
             level,
             head: root.clone(),
             root,
+            size: 1,
         }
     }
 
@@ -40,6 +43,7 @@ impl Roller {
     }
 
     pub(crate) fn reset_root_head(&mut self, node: ChunkRef) {
+        self.size = subtree_size(&node);
         self.root = node.clone();
         self.head = node;
     }
@@ -60,6 +64,11 @@ impl Roller {
         None
     }
 
+    /// Number of blocks held in the tree, root included.
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
     pub(crate) fn has_child_hash(&self, parent: &ChunkRef, hash: &Hash) -> bool {
         parent.children().iter().any(|child| child.hash() == hash)
     }
@@ -96,6 +105,7 @@ impl Roller {
         fast_sync: bool,
     ) -> Ret<(Option<ChunkRef>, Option<ChunkRef>)> {
         parent.append(&child, fast_sync)?;
+        self.size += 1;
         let mut head_change: Option<ChunkRef> = None;
         let mut root_change: Option<ChunkRef> = None;
         if child.height() > self.head.height() {
@@ -106,6 +116,15 @@ impl Roller {
                 let Some(new_root) = Self::trace_parent(self.head.clone(), new_root_height) else {
                     return errf!("root height {} not found when tracing", new_root_height);
                 };
+                // the old root goes, with every fork off the new one
+                let dropped: usize = self
+                    .root
+                    .children()
+                    .iter()
+                    .filter(|c| !c.ptr_eq(&new_root))
+                    .map(subtree_size)
+                    .sum();
+                self.size = self.size.saturating_sub(1 + dropped);
                 self.root = new_root.clone();
                 root_change = Some(new_root);
             }
@@ -131,3 +150,13 @@ impl Roller {
         Some(seek)
     }
 }
+
+fn subtree_size(node: &ChunkRef) -> usize {
+    let mut n = 0;
+    let mut stack = vec![node.clone()];
+    while let Some(node) = stack.pop() {
+        n += 1;
+        stack.extend(node.children());
+    }
+    n
+}
@@ -0,0 +1,224 @@
+/// Backing disk for replayed states: reads nothing and drops writes,
+/// so a replay never touches the node's own state db.
+struct NilDisk {}
+
+impl DiskDB for NilDisk {
+    fn for_each(&self, _: &mut dyn FnMut(&[u8], &[u8]) -> bool) -> Ret<()> {
+        Ok(())
+    }
+}
+
+/// Reads the state db as it was at an older root height: keys written by
+/// later roots answer from their undo logs. Roots that advance while the
+/// state is in use are folded in before the db is trusted.
+struct UndoDisk {
+    disk: Arc<dyn DiskDB>,
+    undo: Arc<dyn DiskDB>,
+    olds: Mutex<(u64, MemKV)>, // highest undo log folded in, values before it
+}
+
+impl UndoDisk {
+    fn fold_newer(&self, olds: &mut (u64, MemKV)) {
+        while let Some(data) = self.undo.read(&(olds.0 + 1).to_be_bytes()) {
+            let Ok(log) = undo_decode(&data) else {
+                break
+            };
+            for (k, v) in log {
+                olds.1.memry.entry(k).or_insert(v);
+            }
+            olds.0 += 1;
+        }
+    }
+}
+
+impl DiskDB for UndoDisk {
+    fn read(&self, k: &[u8]) -> Option<Vec<u8>> {
+        let mut olds = self.olds.lock().unwrap();
+        loop {
+            self.fold_newer(&mut olds);
+            if let Some(v) = olds.1.get(&k.to_vec()) {
+                return v.clone()
+            }
+            let v = self.disk.read(k);
+            // the undo log of a root is saved before its state, so if none
+            // showed up meanwhile the read saw the folded-in height
+            let top = olds.0;
+            self.fold_newer(&mut olds);
+            if olds.0 == top {
+                return v
+            }
+        }
+    }
+
+    fn for_each(&self, _: &mut dyn FnMut(&[u8], &[u8]) -> bool) -> Ret<()> {
+        Ok(())
+    }
+}
+
+/// Blocks one historical state may replay; deeper requests are refused.
+pub const HISTORY_REPLAY_MAX: u64 = 10000;
+/// Heights between the replayed states kept to start later replays from.
+const HISTORY_SNAPSHOT_GAP: u64 = 1000;
+/// Replayed states kept, the lowest dropped first.
+const HISTORY_SNAPSHOTS_MAX: usize = 4;
+
+/*
+    Chain state right after block `height`, as a throw-away state.
+
+    Heights still inside the fork tree reuse the in-memory chunk state directly.
+    Below the root, the state db is read through the undo logs every root
+    saves as it rolls, back down to `height`. They are kept on disk, so any
+    height they cover is reachable after a restart; a node fills them for the
+    whole chain on its next full state rebuild.
+
+    Heights older than the first undo log are rebuilt by replaying stored
+    blocks into a memory-only `StateInst`, from the highest replay snapshot at
+    or below `height`, or from genesis. A replay costs one full block execution
+    per height, so it is refused past `HISTORY_REPLAY_MAX` blocks; asking for
+    lower heights first leaves snapshots to go higher from.
+    This is meant for debug use.
+*/
+fn historical_state(eng: &ChainEngine, height: u64) -> Ret<Box<dyn State>> {
+    let root = {
+        let tree = eng.tree.read().unwrap();
+        if height > tree.head_height() {
+            return errf!("height {} exceeds latest height {}", height, tree.head_height());
+        }
+        if height >= tree.root_height() {
+            let Some(chunk) = Roller::ancestor_at(&tree.head(), height) else {
+                return errf!("block {} not found in fork tree", height);
+            };
+            let state = chunk.state();
+            return Ok(state.fork_sub(Arc::downgrade(&state)));
+        }
+        tree.root_height()
+    };
+    if let Some(state) = undo_state(eng, height, root)? {
+        return Ok(state)
+    }
+    replay_state(eng, height)
+}
+
+/// State at `height` from the undo logs of roots `height+1..=root`,
+/// or none when one of them is missing.
+fn undo_state(eng: &ChainEngine, height: u64, root: u64) -> Ret<Option<Box<dyn State>>> {
+    let mut olds = MemKV::new();
+    for hei in (height + 1..=root).rev() {
+        let Some(data) = eng.undo.read(&hei.to_be_bytes()) else {
+            return Ok(None)
+        };
+        let log = undo_decode(&data).map_err(|e| format!("undo log {} broken: {}", hei, e))?;
+        olds.memry.extend(log);
+    }
+    let disk = UndoDisk {
+        disk: eng.tree.read().unwrap().root().state().disk(),
+        undo: eng.undo.clone(),
+        olds: Mutex::new((root, olds)),
+    };
+    Ok(Some(Box::new(StateInst::build(Arc::new(disk), None))))
+}
+
+fn replay_state(eng: &ChainEngine, height: u64) -> Ret<Box<dyn State>> {
+    let snap = eng.history.lock().unwrap().range(..=height).next_back().map(|(h, s)| (*h, s.clone_state()));
+    let (from, mut state) = match snap {
+        Some(snap) => snap,
+        None => {
+            let mut state: Box<dyn State> = Box::new(StateInst::build(Arc::new(NilDisk {}), None));
+            eng.minter.initialize(state.as_mut())?;
+            (0, state)
+        }
+    };
+    if height - from > HISTORY_REPLAY_MAX {
+        return errf!("state of height {} needs a replay of {} blocks from height {}, more than {}; ask for a lower height first",
+            height, height - from, from, HISTORY_REPLAY_MAX);
+    }
+    let chain_info = ChainInfo {
+        fast_sync: true,
+        diamond_form: eng.config().diamond_form,
+        id: eng.config().chain_id,
+    };
+    for hei in from + 1..=height {
+        let Some((_, _, blk)) = block::load_block_by_height(eng.store.as_ref(), &hei.into()) else {
+            return errf!("cannot load block {} for replay", hei);
+        };
+        let logs = Box::new(eng.logs.next(0));
+        let (next, _) = blk.execute(chain_info.clone(), state, logs)
+            .map_err(|e| format!("replay block {} failed: {}", hei, e))?;
+        state = next;
+        if hei % HISTORY_SNAPSHOT_GAP == 0 {
+            keep_history_snapshot(eng, hei, state.as_ref());
+        }
+    }
+    Ok(state)
+}
+
+fn keep_history_snapshot(eng: &ChainEngine, height: u64, state: &dyn State) {
+    let mut snaps = eng.history.lock().unwrap();
+    if snaps.contains_key(&height) {
+        return;
+    }
+    snaps.insert(height, state.clone_state());
+    while snaps.len() > HISTORY_SNAPSHOTS_MAX {
+        snaps.pop_first();
+    }
+}
+
+/*
+    Save the undo log of a new root before its state is written: the value
+    each key it writes holds on disk now, or none for a new key. A log already
+    saved for the height is kept, since the state may have been written before
+    a crash left the root unrecorded, and reading it again would see the new
+    values.
+*/
+fn keep_undo_log(eng: &ChainEngine, height: u64, state: &dyn State) {
+    let key = height.to_be_bytes();
+    if eng.undo.read(&key).is_some() {
+        return
+    }
+    let disk = state.disk();
+    let mut data = Vec::new();
+    data.extend((state.as_mem().len() as u32).to_be_bytes());
+    for k in state.as_mem().keys() {
+        data.extend((k.len() as u32).to_be_bytes());
+        data.extend(k);
+        match disk.read(k) {
+            Some(v) => {
+                data.push(1);
+                data.extend((v.len() as u32).to_be_bytes());
+                data.extend(v);
+            }
+            None => data.push(0),
+        }
+    }
+    eng.undo.save(&key, &data);
+}
+
+fn undo_decode(data: &[u8]) -> Ret<Vec<(Vec<u8>, Option<Vec<u8>>)>> {
+    fn take<'a>(data: &mut &'a [u8], n: usize) -> Ret<&'a [u8]> {
+        if data.len() < n {
+            return errf!("need {} bytes but only {}", n, data.len())
+        }
+        let (head, rest) = data.split_at(n);
+        *data = rest;
+        Ok(head)
+    }
+    fn take_len(data: &mut &[u8]) -> Ret<usize> {
+        Ok(u32::from_be_bytes(take(data, 4)?.try_into().unwrap()) as usize)
+    }
+    let mut data = data;
+    let num = take_len(&mut data)?;
+    let mut log = Vec::with_capacity(num.min(data.len()));
+    for _ in 0..num {
+        let kl = take_len(&mut data)?;
+        let k = take(&mut data, kl)?.to_vec();
+        let v = match take(&mut data, 1)?[0] {
+            0 => None,
+            _ => {
+                let vl = take_len(&mut data)?;
+                Some(take(&mut data, vl)?.to_vec())
+            }
+        };
+        log.push((k, v));
+    }
+    Ok(log)
+}
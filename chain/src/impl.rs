@@ -10,7 +10,7 @@ impl Engine for ChainEngine {
         let mut tree = self.tree.write().unwrap();
         let rid = insert_by(self, tree.deref_mut(), blk)?;
         let recent_ctx = maybe!(
-            self.cnf.recent_blocks,
+            self.config().recent_blocks,
             Some((rid.block.block_clone(), tree.root_height())),
             None
         );
@@ -31,7 +31,7 @@ impl Engine for ChainEngine {
         if became_head {
             let head = tree.head().block();
             let head = head.as_read();
-            if self.cnf.average_fee_purity {
+            if self.config().average_fee_purity {
                 record_avgfee(self, head);
             }
             
@@ -48,12 +48,21 @@ impl Engine for ChainEngine {
         Ok(())
     }
 
-    fn synchronize(&self, datas: Vec<u8>) -> Rerr {
+    fn synchronize(&self, datas: Arc<Vec<u8>>) -> Rerr {
         let _isrtlock = inserting_lock(self, ISRT_STAT_SYNCING,
             "the blockchain is syncing and must wait"
         )?;
         let _lk = self.syncing.lock().unwrap();
-        do_synchronize(self, datas.into(), BlkOrigin::Sync)
+        do_synchronize(self, datas, BlkOrigin::Sync)
+    }
+
+    fn update_config(&self, cnf: &EngineConf) -> Rerr {
+        cnf.check_runtime()?;
+        let mut cur = self.cnf.write().unwrap();
+        let mut newcnf = cur.as_ref().clone();
+        newcnf.update_runtime(cnf, &ENGINE_RUNTIME_SECTIONS)?;
+        *cur = Arc::new(newcnf);
+        Ok(())
     }
 
     fn exit(&self) { 
@@ -66,7 +75,7 @@ impl Engine for ChainEngine {
 
 impl EngineRead for ChainEngine {
 
-    fn config(&self) -> &EngineConf { &self.cnf }
+    fn config(&self) -> Arc<EngineConf> { self.cnf.read().unwrap().clone() }
 
     fn latest_block(&self) -> Arc<dyn Block> {
         self.tree.read().unwrap().head().block()
@@ -98,7 +107,7 @@ impl EngineRead for ChainEngine {
     }
 
     fn try_execute_tx_by(&self, tx: &dyn TransactionRead, pd_hei: u64, sub_state: &mut Box<dyn State>) -> Rerr {
-        self.try_execute_tx_by_author(tx, pd_hei, sub_state, self.cnf.external_exec_author())
+        self.try_execute_tx_by_author(tx, pd_hei, sub_state, self.config().external_exec_author())
     }
 
     fn try_execute_tx_by_author(
@@ -111,11 +120,15 @@ impl EngineRead for ChainEngine {
         try_execute_tx_by_author(self, tx, pd_hei, sub_state, author)
     }
 
+    fn historical_state(&self, height: u64) -> Ret<Box<dyn State>> {
+        historical_state(self, height)
+    }
+
     fn average_fee_purity(&self) -> u64 {
         let avgfs = self.avgfees.lock().unwrap();
         let al = avgfs.len();
         if al == 0 {
-            return self.cnf.lowest_fee_purity
+            return self.config().lowest_fee_purity
         }
         let ttn: u128 = avgfs.iter().map(|v| *v as u128).sum();
         (ttn / avgfs.len() as u128) as u64
@@ -18,11 +18,11 @@ pub fn initialize(engine: &ChainEngine, state_db: Arc<dyn DiskDB>, no_sta_dir: b
     if is_rebuild_all {
         rebuild_all_blocks(engine);
     }else{        
-        dev_count_switch_print(engine.cnf.dev_count_switch, state_db.as_ref());
+        dev_count_switch_print(engine.config().dev_count_switch, state_db.as_ref());
         rebuild_unstable_blocks(engine);
     }
     if no_sta_dir {
-        let marker = engine.cnf.state_data_dir.join(REBUILD_ALL_MARKER_FILE);
+        let marker = engine.config().state_data_dir.join(REBUILD_ALL_MARKER_FILE);
         let _ = std::fs::remove_file(marker);
     }
 }
@@ -44,7 +44,7 @@ fn rebuild_unstable_blocks(engine: &ChainEngine) {
     let mut roller = engine.tree.write().unwrap();
     let mut next_height = roller.root_height() + 1;
     // rebuild unstable blocks
-    print!("[Engine] Data: {}, rebuild ({})", engine.cnf.data_dir, next_height);
+    print!("[Engine] Data: {}, rebuild ({})", engine.config().data_dir, next_height);
     loop {
         let Some((_hx, blkdata, block)) = block::load_block_by_height(engine.store.as_ref(), &next_height.into()) else {
             break;
@@ -77,7 +77,7 @@ fn rebuild_all_blocks(engine: &ChainEngine) {
     const STUFFCAP: usize = 20*1000*1000; // 20 mb
 
     std::thread::scope(|s| {
-        let chsize = engine.cnf.unstable_block as usize;
+        let chsize = engine.config().unstable_block as usize;
         let (blkdtch, blkdtcv) = std::sync::mpsc::sync_channel(chsize);
         // read block
         s.spawn(move || {
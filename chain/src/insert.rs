@@ -73,9 +73,18 @@ impl BlockIntroSource for InsertBlockIntroSource<'_> {
     }
 }
 
-fn insert_by(eng: &ChainEngine, tree: &mut Roller, mut blk: BlkPkg) -> Ret<InsertResult> {
+fn insert_by(eng: &ChainEngine, tree: &mut Roller, blk: BlkPkg) -> Ret<InsertResult> {
+    let res = metrics::timed("hacash_chain_block_insert_seconds", &[], || do_insert_by(eng, tree, blk));
+    metrics::counter_add("hacash_chain_block_insert_total", &[("result", maybe!(res.is_ok(), "ok", "err"))], 1);
+    metrics::gauge_set("hacash_chain_head_height", &[], tree.head_height() as f64);
+    metrics::gauge_set("hacash_chain_root_height", &[], tree.root_height() as f64);
+    metrics::gauge_set("hacash_chain_forktree_blocks", &[], tree.size() as f64);
+    res
+}
+
+fn do_insert_by(eng: &ChainEngine, tree: &mut Roller, mut blk: BlkPkg) -> Ret<InsertResult> {
     let orgi = blk.origin();
-    let fast_sync = (eng.cnf.fast_sync && orgi == BlkOrigin::Sync) || orgi == BlkOrigin::Rebuild;
+    let fast_sync = (eng.config().fast_sync && orgi == BlkOrigin::Sync) || orgi == BlkOrigin::Rebuild;
 
     let height = blk.hein();
     let hash = blk.hash();
@@ -98,10 +107,12 @@ fn insert_by(eng: &ChainEngine, tree: &mut Roller, mut blk: BlkPkg) -> Ret<Inser
         let parent_block = parent.block();
         let parent_blk = parent_block.as_read();
         let src = InsertBlockIntroSource::new(eng.store.as_ref(), parent.clone(), old_root_height);
-        // Stage 4: minter pre-exec block gate.
-        eng.minter.blk_verify(blk.block_read(), parent_blk, &src)?;
-        // Stage 5: generic structural block gate.
-        block_verify(&eng.cnf, blk.block_read(), blk.data().len(), parent_blk)?;
+        metrics::timed("hacash_chain_block_verify_seconds", &[], || -> Rerr {
+            // Stage 4: minter pre-exec block gate.
+            eng.minter.blk_verify(blk.block_read(), parent_blk, &src)?;
+            // Stage 5: generic structural block gate.
+            block_verify(&eng.config(), blk.block_read(), blk.data().len(), parent_blk)
+        })?;
     }
 
     let prev_state = parent.state();
@@ -109,8 +120,8 @@ fn insert_by(eng: &ChainEngine, tree: &mut Roller, mut blk: BlkPkg) -> Ret<Inser
 
     let chain_info = ChainInfo {
         fast_sync,
-        diamond_form: eng.cnf.diamond_form,
-        id: eng.cnf.chain_id,
+        diamond_form: eng.config().diamond_form,
+        id: eng.config().chain_id,
     };
 
     let logs = Box::new(eng.logs.next(maybe!(is_open_vmlog(eng, height), height, 0)));
@@ -159,6 +170,7 @@ fn roll_by(eng: &ChainEngine, rid: InsertResult) -> Rerr {
     if let Some(new_root) = &root_change {
         // Persist state/logs before store batch commit.
         // If a crash happens before batch durability, restart from old store root can replay and reconcile.
+        keep_undo_log(eng, new_root.height(), new_root.state().as_ref().as_ref());
         new_root.state().write_to_disk();
         if is_open_vmlog(eng, new_root.logs().height()) {
             new_root.logs().write_to_disk();
@@ -203,7 +215,7 @@ fn roll_by(eng: &ChainEngine, rid: InsertResult) -> Rerr {
 }
 
 fn record_recent(eng: &ChainEngine, block: &dyn BlockRead, root_height: u64) {
-    let deln = root_height.saturating_sub(eng.cnf.unstable_block);
+    let deln = root_height.saturating_sub(eng.config().unstable_block);
     let mut rcts = eng.recent_blocks.lock().unwrap();
     rcts.retain(|x| x.height > deln);
     rcts.push_front(Arc::new(create_recent_block_info(block)));
@@ -211,7 +223,7 @@ fn record_recent(eng: &ChainEngine, block: &dyn BlockRead, root_height: u64) {
 
 fn record_avgfee(eng: &ChainEngine, block: &dyn BlockRead) {
     let mut rfees = eng.avgfees.lock().unwrap();
-    let mut avgf = eng.cnf.lowest_fee_purity;
+    let mut avgf = eng.config().lowest_fee_purity;
     let txs = block.transactions();
     let txnum = txs.len();
     if txnum >= 30 {
@@ -230,5 +242,5 @@ fn record_avgfee(eng: &ChainEngine, block: &dyn BlockRead) {
 
 
 fn is_open_vmlog(eng: &ChainEngine, ck_hei: u64) -> bool {
-    eng.cnf.vm_log_enable && ck_hei >= eng.cnf.vm_log_open_height
+    eng.config().vm_log_enable && ck_hei >= eng.config().vm_log_open_height
 }
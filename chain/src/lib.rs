@@ -9,6 +9,7 @@ use std::time::Duration;
 use basis::component::*;
 use basis::config::*;
 use basis::interface::*;
+use basis::metrics;
 use field::*;
 use protocol::block::{self, BlockHeadOnlyHeight};
 use protocol::context as ctx;
@@ -24,6 +25,7 @@ include! {"state.rs"}
 include! {"verify.rs"}
 include! {"init.rs"}
 include! {"check.rs"}
+include! {"history.rs"}
 include! {"insert.rs"}
 include! {"sync.rs"}
 include! {"lock.rs"}
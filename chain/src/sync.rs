@@ -25,7 +25,7 @@ fn do_synchronize(this: &ChainEngine, datas: Arc<Vec<u8>>, ori: BlkOrigin) -> Re
     let errch1 = errch.clone();
     let errch2 = errch.clone();
     // data channel
-    let chsize = this.cnf.unstable_block as usize * 2;
+    let chsize = this.config().unstable_block as usize * 2;
     let (blkch, blkcv) = std::sync::mpsc::sync_channel(chsize);
     let (ridch, ridcv) = std::sync::mpsc::sync_channel(chsize);
 
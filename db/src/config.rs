@@ -1,4 +1,6 @@
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::time::Instant;
 
 fn db_env_enable(name: &str) -> bool {
     std::env::var(name)
@@ -23,3 +25,34 @@ fn db_sled_small_machine_enabled() -> bool {
     *DB_SLED_SMALL_MACHINE.get_or_init(|| 
         db_env_enable("HACASH_DB_SMALL_MACHINE"))
 }
+
+const DB_OP_REMOVE: usize = 0;
+const DB_OP_SAVE: usize = 1;
+const DB_OP_BATCH: usize = 2;
+
+// (op label, nanoseconds, count) of the disk writes of each op
+static DB_WRITE_TOTALS: [(&str, AtomicU64, AtomicU64); 3] = [
+    ("remove", AtomicU64::new(0), AtomicU64::new(0)),
+    ("save", AtomicU64::new(0), AtomicU64::new(0)),
+    ("batch", AtomicU64::new(0), AtomicU64::new(0)),
+];
+
+/// Time one disk write under `hacash_db_write_seconds{op=...}`. Writes only
+/// add to atomics; a collector registered on first use publishes them.
+fn db_timed<T>(op: usize, f: impl FnOnce() -> T) -> T {
+    static COLLECTOR: OnceLock<()> = OnceLock::new();
+    COLLECTOR.get_or_init(|| {
+        basis::metrics::register_collector(|| {
+            for (op, nanos, count) in &DB_WRITE_TOTALS {
+                let secs = nanos.load(Relaxed) as f64 / 1e9;
+                basis::metrics::summary_set("hacash_db_write_seconds", &[("op", op)], secs, count.load(Relaxed));
+            }
+        })
+    });
+    let start = Instant::now();
+    let res = f();
+    let (_, nanos, count) = &DB_WRITE_TOTALS[op];
+    nanos.fetch_add(start.elapsed().as_nanos() as u64, Relaxed);
+    count.fetch_add(1, Relaxed);
+    res
+}
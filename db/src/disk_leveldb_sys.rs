@@ -23,11 +23,11 @@ impl DiskKV {
 impl DiskDB for DiskKV {
 
     fn remove(&self, k: &[u8]) {
-        self.ldb.rm(k)
+        db_timed(DB_OP_REMOVE, || self.ldb.rm(k))
     }
 
     fn save(&self, k: &[u8], v: &[u8]) {
-        self.ldb.put(k, v)
+        db_timed(DB_OP_SAVE, || self.ldb.put(k, v))
     }
 
     fn read(&self, k: &[u8]) -> Option<Vec<u8>> {
@@ -35,8 +35,10 @@ impl DiskDB for DiskKV {
     }
 
     fn write(&self, memkv: &dyn MemDB) {
-        let wb = Membatch::from_memkv(memkv);
-        self.ldb.write(&wb.into_batch()); // must
+        db_timed(DB_OP_BATCH, || {
+            let wb = Membatch::from_memkv(memkv);
+            self.ldb.write(&wb.into_batch()); // must
+        })
     }
 
     /*
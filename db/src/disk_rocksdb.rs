@@ -28,13 +28,17 @@ impl DiskKV {
 impl DiskDB for DiskKV {
 
     fn remove(&self, k: &[u8]) {
-        let opts = Self::write_options();
-        self.rdb.delete_opt(k, &opts).unwrap();
+        db_timed(DB_OP_REMOVE, || {
+            let opts = Self::write_options();
+            self.rdb.delete_opt(k, &opts).unwrap();
+        })
     }
 
     fn save(&self, k: &[u8], v: &[u8]) {
-        let opts = Self::write_options();
-        self.rdb.put_opt(k, v, &opts).unwrap();
+        db_timed(DB_OP_SAVE, || {
+            let opts = Self::write_options();
+            self.rdb.put_opt(k, v, &opts).unwrap();
+        })
     }
 
     fn read(&self, k: &[u8]) -> Option<Vec<u8>> {
@@ -42,9 +46,11 @@ impl DiskDB for DiskKV {
     }
 
     fn write(&self, memkv: &dyn MemDB) {
-        let wb = Membatch::from_memkv(memkv);
-        let opts = Self::write_options();
-        self.rdb.write_opt(wb.into_batch().obj, &opts).unwrap(); // must
+        db_timed(DB_OP_BATCH, || {
+            let wb = Membatch::from_memkv(memkv);
+            let opts = Self::write_options();
+            self.rdb.write_opt(wb.into_batch().obj, &opts).unwrap(); // must
+        })
     }
 
     /*
@@ -23,19 +23,23 @@ impl DiskKV {
 impl DiskDB for DiskKV {
 
     fn remove(&self, k: &[u8]) {
-        let mut ldb =  self.ldb.lock().unwrap();
-        ldb.delete(k).unwrap();
-        if db_sync_enabled() {
-            ldb.flush().unwrap();
-        }
+        db_timed(DB_OP_REMOVE, || {
+            let mut ldb =  self.ldb.lock().unwrap();
+            ldb.delete(k).unwrap();
+            if db_sync_enabled() {
+                ldb.flush().unwrap();
+            }
+        })
     }
 
     fn save(&self, k: &[u8], v: &[u8]) {
-        let mut ldb =  self.ldb.lock().unwrap();
-        ldb.put(k, v).unwrap();
-        if db_sync_enabled() {
-            ldb.flush().unwrap();
-        }
+        db_timed(DB_OP_SAVE, || {
+            let mut ldb =  self.ldb.lock().unwrap();
+            ldb.put(k, v).unwrap();
+            if db_sync_enabled() {
+                ldb.flush().unwrap();
+            }
+        })
     }
 
     fn read(&self, k: &[u8]) -> Option<Vec<u8>> {
@@ -43,13 +47,15 @@ impl DiskDB for DiskKV {
     }
 
     fn write(&self, memkv: &dyn MemDB) {
-        let wb = Membatch::from_memkv(memkv);
-        let sync = db_sync_enabled();
-        let mut ldb =  self.ldb.lock().unwrap();
-        ldb.write(wb.into_batch().obj, sync).unwrap(); // must
-        if sync {
-            ldb.flush().unwrap();
-        }
+        db_timed(DB_OP_BATCH, || {
+            let wb = Membatch::from_memkv(memkv);
+            let sync = db_sync_enabled();
+            let mut ldb =  self.ldb.lock().unwrap();
+            ldb.write(wb.into_batch().obj, sync).unwrap(); // must
+            if sync {
+                ldb.flush().unwrap();
+            }
+        })
     }
 
     /*
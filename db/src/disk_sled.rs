@@ -27,17 +27,21 @@ impl DiskKV {
 impl DiskDB for DiskKV {
 
     fn remove(&self, k: &[u8]) {
-        self.ldb.remove(k).unwrap();
-        if db_sync_enabled() {
-            self.ldb.flush().unwrap();
-        }
+        db_timed(DB_OP_REMOVE, || {
+            self.ldb.remove(k).unwrap();
+            if db_sync_enabled() {
+                self.ldb.flush().unwrap();
+            }
+        })
     }
 
     fn save(&self, k: &[u8], v: &[u8]) {
-        self.ldb.insert(k, v).unwrap();
-        if db_sync_enabled() {
-            self.ldb.flush().unwrap();
-        }
+        db_timed(DB_OP_SAVE, || {
+            self.ldb.insert(k, v).unwrap();
+            if db_sync_enabled() {
+                self.ldb.flush().unwrap();
+            }
+        })
     }
 
     fn read(&self, k: &[u8]) -> Option<Vec<u8>> {
@@ -45,11 +49,13 @@ impl DiskDB for DiskKV {
     }
 
     fn write(&self, memkv: &dyn MemDB) {
-        let wb = Membatch::from_memkv(memkv);
-        self.ldb.apply_batch(wb.into_batch().obj).unwrap(); // must
-        if db_sync_enabled() {
-            self.ldb.flush().unwrap();
-        }
+        db_timed(DB_OP_BATCH, || {
+            let wb = Membatch::from_memkv(memkv);
+            self.ldb.apply_batch(wb.into_batch().obj).unwrap(); // must
+            if db_sync_enabled() {
+                self.ldb.flush().unwrap();
+            }
+        })
     }
 
     /*
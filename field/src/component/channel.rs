@@ -41,6 +41,44 @@ combi_optional!{ ClosedDistributionDataOptional, closed_distribution : ClosedDis
 
 
 
+/******************************* */
+
+
+
+// ReconciliationBill
+combi_struct!{ ReconciliationBill,
+	// Off-chain balance split, both parties sign every new one
+	channel_id                        : ChannelId
+	reuse_version                     : Uint4            // Must match the channel's reuse version
+	bill_auto_number                  : Uint8            // Grows by one per bill, the higher one is newer
+	left_bill                         : HacSat
+	right_bill                        : HacSat
+}
+
+// SignedReconciliationBill
+combi_struct!{ SignedReconciliationBill,
+	bill                              : ReconciliationBill
+	left_sign                         : Sign
+	right_sign                        : Sign
+}
+
+impl ReconciliationBill {
+	pub fn sign_stuff(&self) -> Hash {
+		Hash::from(sha3(self.serialize()))
+	}
+}
+
+impl SignedReconciliationBill {
+	pub fn sign_left(&mut self, acc: &Account) {
+		self.left_sign = Sign::create_by(acc, &self.bill.sign_stuff());
+	}
+	pub fn sign_right(&mut self, acc: &Account) {
+		self.right_sign = Sign::create_by(acc, &self.bill.sign_stuff());
+	}
+}
+
+
+
 
 /*
 * ChannelSto
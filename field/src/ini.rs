@@ -2,20 +2,28 @@
 
 
 pub fn ini_must_address(sec: &HashMap<String, Option<String>>, key: &str) -> Address {
+    match ini_address(sec, key) {
+        Ok(addr) => addr,
+        Err(e) => panic!("[Config Error] {}.", e),
+    }
+}
+
+pub fn ini_address(sec: &HashMap<String, Option<String>>, key: &str) -> Ret<Address> {
     let adr = ini_must(sec, key, "1AVRuFXNFi3rdMrPH4hdqSgFrEBnWisWaS");
-    let Ok(addr) = Address::from_readable(&adr) else {
-        panic!("[Config Error] address {} format invalid.", &adr)
-    };
-    addr
+    Address::from_readable(&adr).map_err(|_| format!("address {} format invalid", &adr))
 }
 
 
 pub fn ini_must_amount(sec: &HashMap<String, Option<String>>, key: &str) -> Amount {
+    match ini_amount(sec, key) {
+        Ok(amount) => amount,
+        Err(e) => panic!("[Config Error] {}.", e),
+    }
+}
+
+pub fn ini_amount(sec: &HashMap<String, Option<String>>, key: &str) -> Ret<Amount> {
     let amt = ini_must(sec, key, "1:248");
-    let Ok(amount) = Amount::from(&amt) else {
-        panic!("[Config Error] amount {} format invalid.", &amt)
-    };
-    amount
+    Amount::from(&amt).map_err(|_| format!("amount {} format invalid", &amt))
 }
 
 
@@ -160,3 +160,262 @@ fn channel_close(this: &ChannelClose, ctx: &mut dyn Context) -> XRet<Vec<u8>> {
     // do close
     Ok(close_channel_default(pending_height, ctx, cid, &chan)?)
 }
+
+/*******************************************/
+
+/*
+* Unilateral close:
+* one party launches with the latest bill both sides signed, or with bill 0,
+* the opening split, which needs no signatures, the other party may answer
+* with a newer bill until `arbitration_lock_block` blocks have passed,
+* after that anyone can settle the channel by the launched bill.
+*/
+
+action_define! { ChannelCloseLaunch, 23,
+    ActScope::TOP, 2, false, [],
+    {
+        assert_left    : Bool
+        bill           : SignedReconciliationBill
+    },
+    (self, format!("Launch unilateral close of channel {} with bill {}",
+        self.bill.bill.channel_id, *self.bill.bill.bill_auto_number)),
+    (self, ctx, _gas {
+        channel_close_launch(self, ctx)
+    })
+}
+
+fn channel_close_launch(this: &ChannelCloseLaunch, ctx: &mut dyn Context) -> XRet<Vec<u8>> {
+    let bill = &this.bill.bill;
+    let cid = &bill.channel_id;
+    check_valid_store_item_key("channel", cid, ChannelId::SIZE)?;
+
+    let pending_height = ctx.env().block.height;
+    let state = MintState::wrap(ctx.state());
+    let Some(mut chan) = state.channel(cid) else {
+        return xerrf!("channel not found");
+    };
+    if chan.status != CHANNEL_STATUS_OPENING {
+        return xerrf!("channel is not open");
+    }
+    match *bill.bill_auto_number {
+        0 => check_opening_bill(&chan, bill)?,
+        _ => {
+            check_reconciliation_bill(&chan, &this.bill)?;
+        }
+    }
+
+    // the launcher signs
+    let is_left = this.assert_left.check();
+    let launcher = maybe!(is_left, &chan.left_bill.address, &chan.right_bill.address);
+    ctx.check_sign(launcher)?;
+
+    // start challenge period
+    chan.status = CHANNEL_STATUS_CHALLENGING;
+    chan.if_challenging = ChallengePeriodDataOptional::must(ChallengePeriodData {
+        is_have_challenge_log: Bool::new(true),
+        challenge_launch_height: BlockHeight::from(pending_height),
+        assert_bill_auto_number: bill.bill_auto_number,
+        assert_address_is_left_or_right: this.assert_left,
+        assert_bill: maybe!(is_left, bill.left_bill.clone(), bill.right_bill.clone()),
+    });
+    let mut state = MintState::wrap(ctx.state());
+    state.channel_set(cid, &chan);
+    Ok(vec![])
+}
+
+/*******************************************/
+
+action_define! { ChannelCloseChallenge, 24,
+    ActScope::TOP, 2, false, [],
+    {
+        bill           : SignedReconciliationBill
+    },
+    (self, format!("Challenge close of channel {} with bill {}",
+        self.bill.bill.channel_id, *self.bill.bill.bill_auto_number)),
+    (self, ctx, _gas {
+        channel_close_challenge(self, ctx)
+    })
+}
+
+fn channel_close_challenge(this: &ChannelCloseChallenge, ctx: &mut dyn Context) -> XRet<Vec<u8>> {
+    let bill = &this.bill.bill;
+    let cid = &bill.channel_id;
+    check_valid_store_item_key("channel", cid, ChannelId::SIZE)?;
+
+    let pending_height = ctx.env().block.height;
+    let state = MintState::wrap(ctx.state());
+    let Some(chan) = state.channel(cid) else {
+        return xerrf!("channel not found");
+    };
+    let Some(challenge) = chan.if_challenging.if_value() else {
+        return xerrf!("channel is not in challenge period");
+    };
+    let end = challenge_period_end(&chan, challenge);
+    if pending_height > end {
+        return xerrf!("challenge period of channel {} ended at height {}", cid, end);
+    }
+    if *bill.bill_auto_number <= *challenge.assert_bill_auto_number {
+        return xerrf!(
+            "bill number {} is not newer than the launched {}",
+            *bill.bill_auto_number,
+            *challenge.assert_bill_auto_number
+        );
+    }
+    let (left_bls, right_bls) = check_reconciliation_bill(&chan, &this.bill)?;
+
+    // the counterparty of the launcher signs
+    let respondent = maybe!(
+        challenge.assert_address_is_left_or_right.check(),
+        &chan.right_bill.address,
+        &chan.left_bill.address
+    );
+    ctx.check_sign(respondent)?;
+
+    // a newer bill settles at once
+    Ok(close_channel_with_distribution(
+        pending_height,
+        ctx,
+        cid,
+        &chan,
+        &left_bls,
+        &right_bls,
+        true,
+    )?)
+}
+
+/*******************************************/
+
+action_define! { ChannelCloseSettle, 27,
+    ActScope::TOP, 2, false, [],
+    {
+        channel_id     : ChannelId
+    },
+    (self, format!("Settle unilateral close of channel {}", self.channel_id)),
+    (self, ctx, _gas {
+        channel_close_settle(self, ctx)
+    })
+}
+
+fn channel_close_settle(this: &ChannelCloseSettle, ctx: &mut dyn Context) -> XRet<Vec<u8>> {
+    let cid = &this.channel_id;
+    check_valid_store_item_key("channel", cid, ChannelId::SIZE)?;
+
+    let pending_height = ctx.env().block.height;
+    let state = MintState::wrap(ctx.state());
+    let Some(chan) = state.channel(cid) else {
+        return xerrf!("channel not found");
+    };
+    let Some(challenge) = chan.if_challenging.if_value() else {
+        return xerrf!("channel is not in challenge period");
+    };
+    let end = challenge_period_end(&chan, challenge);
+    if pending_height <= end {
+        return xerrf!("challenge period of channel {} ends at height {}", cid, end);
+    }
+
+    // the launcher gets the asserted bill, the other side the rest
+    let assert_bls = hacsat_balance(&challenge.assert_bill);
+    let ttamt = chan
+        .left_bill
+        .balance
+        .hacash
+        .add_mode_u64(&chan.right_bill.balance.hacash)?;
+    let ttsat = chan.left_bill.balance.satoshi.uint() + chan.right_bill.balance.satoshi.uint();
+    let Some(other_sat) = ttsat.checked_sub(assert_bls.satoshi.uint()) else {
+        return xerrf!("asserted BTC exceeds lock-in");
+    };
+    let other_bls = Balance {
+        hacash: ttamt.sub_mode_u64(&assert_bls.hacash)?,
+        satoshi: SatoshiAuto::from(other_sat)?,
+        ..Default::default()
+    };
+    let (left_bls, right_bls) = maybe!(
+        challenge.assert_address_is_left_or_right.check(),
+        (assert_bls, other_bls),
+        (other_bls, assert_bls)
+    );
+    Ok(close_channel_with_distribution(
+        pending_height,
+        ctx,
+        cid,
+        &chan,
+        &left_bls,
+        &right_bls,
+        true,
+    )?)
+}
+
+/*******************************************/
+
+fn challenge_period_end(chan: &ChannelSto, challenge: &ChallengePeriodData) -> u64 {
+    *challenge.challenge_launch_height + *chan.arbitration_lock_block as u64
+}
+
+fn hacsat_balance(hs: &HacSat) -> Balance {
+    Balance {
+        hacash: hs.amount.clone(),
+        satoshi: SatoshiAuto::from_satoshi(&hs.satoshi.value()),
+        ..Default::default()
+    }
+}
+
+// bill 0 is the split the channel opened with, so neither side has to sign it
+fn check_opening_bill(chan: &ChannelSto, bill: &ReconciliationBill) -> Rerr {
+    if bill.reuse_version != chan.reuse_version {
+        return errf!(
+            "bill reuse version {} does not match channel {}",
+            *bill.reuse_version,
+            *chan.reuse_version
+        );
+    }
+    for (side, hs, bls) in [
+        ("left", &bill.left_bill, &chan.left_bill.balance),
+        ("right", &bill.right_bill, &chan.right_bill.balance),
+    ] {
+        let opened = hacsat_balance(hs);
+        if opened.hacash.cmp(&bls.hacash).is_ne() || opened.satoshi.uint() != bls.satoshi.uint() {
+            return errf!("bill 0 {} side must be the opening lock-in", side);
+        }
+    }
+    Ok(())
+}
+
+// check a bill belongs to this channel, is signed by both sides and splits exactly the lock-in
+fn check_reconciliation_bill(chan: &ChannelSto, sbill: &SignedReconciliationBill) -> Ret<(Balance, Balance)> {
+    let bill = &sbill.bill;
+    if bill.reuse_version != chan.reuse_version {
+        return errf!(
+            "bill reuse version {} does not match channel {}",
+            *bill.reuse_version,
+            *chan.reuse_version
+        );
+    }
+    let stuff = bill.sign_stuff();
+    let (left_addr, right_addr) = (&chan.left_bill.address, &chan.right_bill.address);
+    if !verify_signature(&stuff, left_addr, &sbill.left_sign) {
+        return errf!("bill signature of left address {} verification failed", left_addr);
+    }
+    if !verify_signature(&stuff, right_addr, &sbill.right_sign) {
+        return errf!("bill signature of right address {} verification failed", right_addr);
+    }
+    let (left_amt, right_amt) = (&bill.left_bill.amount, &bill.right_bill.amount);
+    left_amt.check_6_long().map_err(|_| "left amount bytes too long".to_string())?;
+    right_amt.check_6_long().map_err(|_| "right amount bytes too long".to_string())?;
+    if left_amt.is_negative() || right_amt.is_negative() {
+        return errf!("bill amount cannot be negative");
+    }
+    let (left_bls, right_bls) = (hacsat_balance(&bill.left_bill), hacsat_balance(&bill.right_bill));
+    let ttamt = chan
+        .left_bill
+        .balance
+        .hacash
+        .add_mode_u64(&chan.right_bill.balance.hacash)?;
+    if left_amt.add_mode_u64(right_amt)? != ttamt {
+        return errf!("bill HAC amount must match lock-in");
+    }
+    let ttsat = chan.left_bill.balance.satoshi.uint() + chan.right_bill.balance.satoshi.uint();
+    if left_bls.satoshi.uint().checked_add(right_bls.satoshi.uint()) != Some(ttsat) {
+        return errf!("bill BTC amount must match lock-in");
+    }
+    Ok((left_bls, right_bls))
+}
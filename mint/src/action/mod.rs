@@ -33,6 +33,9 @@ action_register! {
     // channel
     ChannelOpen
     ChannelClose
+    ChannelCloseLaunch
+    ChannelCloseChallenge
+    ChannelCloseSettle
     DiamondMint
 
     // asset
@@ -0,0 +1,226 @@
+
+/// Tx pool groups probed by the admin api, the pool answers an error past its last.
+const ADMIN_TXPOOL_GROUPS: usize = 16;
+
+/// Held over each read-change-write of the engine settings, so concurrent
+/// updates do not overwrite each other.
+static ADMIN_UPDATE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn admin_peer_list(ctx: &ApiExecCtx, _req: ApiRequest) -> ApiResponse {
+    let list = ctx
+        .hnoder
+        .peer_details()
+        .into_iter()
+        .map(|p| {
+            json!({
+                "key": p.key,
+                "name": p.name,
+                "addr": p.addr,
+                "public": p.public,
+                "inbound": p.inbound,
+                "secure": p.secure,
+                "active": p.active,
+            })
+        })
+        .collect();
+    api_data_list(list)
+}
+
+/// Dial `?addr=ip:port`, the peer is listed once the handshake is done.
+fn admin_peer_connect(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let addr = q_string(&req, "addr", "");
+    let Ok(addr) = addr.parse::<std::net::SocketAddr>() else {
+        return api_error("addr must be like 127.0.0.1:3337");
+    };
+    match ctx.hnoder.peer_connect(addr) {
+        Ok(()) => api_ok(vec![("dialing", json!(addr.to_string()))]),
+        Err(e) => api_error(&e),
+    }
+}
+
+/// Close peers by IP, IP:port or hex node key, `?target=`.
+fn admin_peer_disconnect(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let target = q_string(&req, "target", "");
+    if target.is_empty() {
+        return api_error("target must be an IP address, IP:port or node key");
+    }
+    match ctx.hnoder.peer_disconnect(&target) {
+        Ok(n) => api_ok(vec![("closed", json!(n))]),
+        Err(e) => api_error(&e),
+    }
+}
+
+/// Lift a ban by IP address or hex node key, `?target=`.
+fn admin_peer_unban(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let target = q_string(&req, "target", "");
+    if target.is_empty() {
+        return api_error("target must be an IP address or node key");
+    }
+    match ctx.hnoder.peer_unban(&target) {
+        Ok(n) => api_ok(vec![("removed", json!(n))]),
+        Err(e) => api_error(&e),
+    }
+}
+
+/// Drop identity pins by hex identity public key or node key, `?target=`.
+fn admin_peer_unpin(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let target = q_string(&req, "target", "");
+    if target.is_empty() {
+        return api_error("target must be a hex identity public key or node key");
+    }
+    match ctx.hnoder.peer_unpin(&target) {
+        Ok(n) => api_ok(vec![("removed", json!(n))]),
+        Err(e) => api_error(&e),
+    }
+}
+
+fn admin_config(ctx: &ApiExecCtx, _req: ApiRequest) -> ApiResponse {
+    api_data(admin_config_view(ctx))
+}
+
+fn admin_miner(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    admin_update(ctx, |cnf| {
+        cnf.miner_enable = q_bool(&req, "enable", cnf.miner_enable);
+        if let Some(reward) = req.query("reward") {
+            cnf.miner_reward_address = admin_address(reward)?;
+        }
+        Ok(())
+    })
+}
+
+fn admin_diamondminer(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    admin_update(ctx, |cnf| {
+        cnf.dmer_enable = q_bool(&req, "enable", cnf.dmer_enable);
+        if let Some(reward) = req.query("reward") {
+            cnf.dmer_reward_address = admin_address(reward)?;
+        }
+        for (key, amt) in [
+            ("bid_min", &mut cnf.dmer_bid_min),
+            ("bid_max", &mut cnf.dmer_bid_max),
+            ("bid_step", &mut cnf.dmer_bid_step),
+        ] {
+            if let Some(v) = req.query(key) {
+                let Ok(v) = Amount::from(v) else {
+                    return errf!("{} amount {} is invalid", key, v);
+                };
+                *amt = v.compress(2, AmtCpr::Grow)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+fn admin_txpool(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    admin_update(ctx, |cnf| {
+        if let Some(maxs) = req.query("maxs") {
+            let mut sizes = vec![];
+            for one in maxs.split(',').map(|a| a.trim()) {
+                let Ok(n) = one.parse::<usize>() else {
+                    return errf!("tx pool group size {} is invalid", one);
+                };
+                sizes.push(n);
+            }
+            cnf.txpool_maxs = sizes;
+        }
+        if req.query("lowest_fee_purity").is_some() {
+            cnf.lowest_fee_purity = req.query_u64("lowest_fee_purity", cnf.lowest_fee_purity);
+        }
+        Ok(())
+    })
+}
+
+/// Load the config file again and take `?sections=` of it, every runtime
+/// section by default.
+fn admin_reload(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let sections = q_string(&req, "sections", "");
+    let mut sections: Vec<&str> = sections.split(',').map(|a| a.trim()).filter(|a| !a.is_empty()).collect();
+    if sections.is_empty() {
+        sections = ENGINE_RUNTIME_SECTIONS.to_vec();
+    }
+    let Some(path) = loaded_config_path() else {
+        return api_error("node was not started from a config file");
+    };
+    let mut ini = match load_ini_file(&path) {
+        Ok(ini) => ini,
+        Err(e) => return api_error(&format!("load config {} failed: {}", path.display(), e)),
+    };
+    if DevConf::new(&ini).enable {
+        DevConf::apply(&mut ini);
+    }
+    let src = match EngineConf::try_new(&ini) {
+        Ok(src) => src,
+        Err(e) => return api_error(&format!("config {} is invalid: {}", path.display(), e)),
+    };
+    admin_update(ctx, |cnf| cnf.update_runtime(&src, &sections))
+}
+
+fn admin_shutdown(ctx: &ApiExecCtx, _req: ApiRequest) -> ApiResponse {
+    match ctx.hnoder.shutdown() {
+        Ok(()) => api_ok(vec![("shutdown", json!(true))]),
+        Err(e) => api_error(&e),
+    }
+}
+
+/// Change a copy of the engine settings, then hand them to the engine and the tx pool.
+fn admin_update(ctx: &ApiExecCtx, edit: impl FnOnce(&mut EngineConf) -> Rerr) -> ApiResponse {
+    let _lk = ADMIN_UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cnf = ctx.engine.config().as_ref().clone();
+    if let Err(e) = edit(&mut cnf) {
+        return api_error(&e);
+    }
+    if let Err(e) = ctx.engine.update_config(&cnf) {
+        return api_error(&e);
+    }
+    if let Err(e) = admin_apply_txpool(ctx.hnoder.txpool().as_ref(), &ctx.engine.config()) {
+        return api_error(&e);
+    }
+    api_data(admin_config_view(ctx))
+}
+
+fn admin_apply_txpool(txpool: &dyn TxPool, cnf: &EngineConf) -> Rerr {
+    for (gi, sz) in cnf.txpool_maxs.iter().enumerate() {
+        if txpool.capacity_at(gi).is_err() {
+            break;
+        }
+        txpool.resize_at(gi, *sz)?;
+    }
+    txpool.set_lowest_fee_purity(cnf.lowest_fee_purity);
+    Ok(())
+}
+
+fn admin_address(s: &str) -> Ret<Address> {
+    Address::from_readable(s).map_err(|_| format!("address {} is invalid", s))
+}
+
+fn admin_config_view(ctx: &ApiExecCtx) -> serde_json::Map<String, Value> {
+    let cnf = ctx.engine.config();
+    let txpool = ctx.hnoder.txpool();
+    let mut maxs = vec![];
+    let mut counts = vec![];
+    for gi in 0..ADMIN_TXPOOL_GROUPS {
+        let (Ok(max), Ok(n)) = (txpool.capacity_at(gi), txpool.count_at(gi)) else {
+            break;
+        };
+        maxs.push(max);
+        counts.push(n);
+    }
+    serde_json::Map::from_iter([
+        ("miner".to_owned(), json!({
+            "enable": cnf.miner_enable,
+            "reward": cnf.miner_reward_address.to_readable(),
+        })),
+        ("diamondminer".to_owned(), json!({
+            "enable": cnf.dmer_enable,
+            "reward": cnf.dmer_reward_address.to_readable(),
+            "bid_address": cnf.dmer_bid_account.readable(),
+            "bid_min": cnf.dmer_bid_min.to_fin_string(),
+            "bid_max": cnf.dmer_bid_max.to_fin_string(),
+            "bid_step": cnf.dmer_bid_step.to_fin_string(),
+        })),
+        ("txpool".to_owned(), json!({
+            "maxs": maxs,
+            "counts": counts,
+            "lowest_fee_purity": txpool.lowest_fee_purity(),
+        })),
+    ])
+}
@@ -122,6 +122,123 @@ fn debug_transaction_receipt(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
     }
 }
 
+/// Collects logs pushed while re-executing a historic transaction.
+#[derive(Default)]
+struct TraceLogs {
+    entries: Vec<Vec<u8>>,
+}
+
+impl Logs for TraceLogs {
+    fn push(&mut self, stuff: &dyn Serialize) {
+        self.entries.push(stuff.serialize());
+    }
+
+    fn load(&self, _: u64, idx: usize) -> Option<Vec<u8>> {
+        self.entries.get(idx).cloned()
+    }
+
+    fn snapshot_len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.entries.truncate(len);
+    }
+}
+
+/// Re-execute a mined transaction on the state its block started from,
+/// replaying the earlier transactions of that block first.
+fn debug_transaction_trace(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let hash = q_string(&req, "hash", "");
+    let Ok(hx) = hex::decode(&hash) else {
+        return api_error("transaction hash format invalid");
+    };
+    if hx.len() != Hash::SIZE {
+        return api_error("transaction hash format invalid");
+    }
+    let txhx = Hash::must(&hx);
+    let (blkpkg, index, tx) = match find_block_tx(ctx, &txhx) {
+        Ok(v) => v,
+        Err(e) => return api_error(&e),
+    };
+    let blk = blkpkg.block();
+    let height = blk.height().uint();
+    let state = match ctx.engine.historical_state(height - 1) {
+        Ok(s) => s,
+        Err(e) => return api_error(&format!("rebuild state failed: {}", e)),
+    };
+    let Ok(ptx) = blk.prelude_transaction() else {
+        return api_error("block prelude transaction not found");
+    };
+    let engcnf = ctx.engine.config();
+    let env = Env {
+        chain: ChainInfo {
+            id: engcnf.chain_id,
+            diamond_form: engcnf.diamond_form,
+            fast_sync: false,
+        },
+        block: BlkInfo {
+            height,
+            hash: blkpkg.hash(),
+            author: ptx.author().unwrap_or_default(),
+        },
+        tx: TxInfo::default(),
+    };
+
+    // earlier transactions of the same block
+    let mut ctxobj = protocol::context::ContextInst::new(
+        env.clone(), state, Box::new(TraceLogs::default()), ptx,
+    );
+    for prev in blk.transactions().iter().take(index) {
+        ctxobj.reset_for_new_tx(prev.as_read());
+        if let Err(e) = prev.execute(&mut ctxobj) {
+            return api_error(&format!("replay block transaction failed: {}", e));
+        }
+    }
+    let (state, _) = ctxobj.release();
+
+    // the traced transaction on its own sub state, so its writes can be listed
+    let parent: Arc<Box<dyn State>> = Arc::new(state);
+    let sub = parent.fork_sub(Arc::downgrade(&parent));
+    let mut ctxobj = protocol::context::ContextInst::new(
+        env, sub, Box::new(TraceLogs::default()), tx.as_read(),
+    );
+    ctxobj.reset_for_new_tx(tx.as_read());
+    protocol::context::trace_begin();
+    let res = tx.execute(&mut ctxobj);
+    let steps = protocol::context::trace_finish();
+    let (sub, logs) = ctxobj.release();
+
+    let trace: Vec<Value> = steps
+        .iter()
+        .map(|s| json!({"depth": s.depth, "op": s.op, "detail": s.detail}))
+        .collect();
+    let logs: Vec<Value> = (0..logs.snapshot_len())
+        .filter_map(|i| logs.load(height, i))
+        .map(|l| json!(hex::encode(l)))
+        .collect();
+    let mut writes: Vec<(&Vec<u8>, &Option<Vec<u8>>)> = sub.as_mem().iter().collect();
+    writes.sort_by(|a, b| a.0.cmp(b.0));
+    let writes: Vec<Value> = writes
+        .into_iter()
+        .map(|(k, v)| json!({"key": hex::encode(k), "value": v.as_ref().map(hex::encode)}))
+        .collect();
+
+    let mut data = serde_json::Map::new();
+    data.insert("hash".to_owned(), json!(txhx.to_hex()));
+    data.insert("block_hash".to_owned(), json!(blkpkg.hash().to_hex()));
+    data.insert("block_height".to_owned(), json!(height));
+    data.insert("index".to_owned(), json!(index));
+    data.insert("ok".to_owned(), json!(res.is_ok()));
+    if let Err(e) = res {
+        data.insert("error".to_owned(), json!(e));
+    }
+    data.insert("trace".to_owned(), json!(trace));
+    data.insert("logs".to_owned(), json!(logs));
+    data.insert("writes".to_owned(), json!(writes));
+    api_data(data)
+}
+
 fn debug_transaction_simulate(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
     let Ok(bddts) = body_data_may_hex(&req) else {
         return api_error("transaction body invalid");
@@ -0,0 +1,70 @@
+
+/// Most blocks one dev API call may mine.
+const DEV_MINE_MAX: u64 = 10000;
+
+/// Most seconds one dev warp may move the clock, a year.
+const DEV_WARP_TIME_MAX: u64 = 365 * 24 * 3600;
+
+/// Pack the pool into the next block and insert it without PoW. Dev chains only.
+pub fn dev_mine_block(engine: &Arc<dyn Engine>, hnoder: &dyn HNoder) -> Ret<u64> {
+    if !engine.config().dev.enable {
+        return errf!("dev mode not enabled")
+    }
+    // block times must increase, so mining faster than the clock moves it on
+    let prevt = engine.latest_block().timestamp().uint();
+    let now = curtimes();
+    if now <= prevt {
+        warp_time(prevt + 1 - now);
+    }
+    let block = engine.minter().packing_next_block(engine.as_read(), hnoder.txpool().as_ref());
+    let Ok(block) = block.downcast::<BlockV1>() else {
+        return errf!("packed block is not a BlockV1")
+    };
+    let hei = block.height().uint();
+    hnoder.submit_block(&BlkPkg::create(block), false)?;
+    Ok(hei)
+}
+
+fn dev_mine(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let count = req.query_u64("count", 1);
+    if count == 0 || count > DEV_MINE_MAX {
+        return api_error(&format!("count must be between 1 and {}", DEV_MINE_MAX));
+    }
+    let mut heights = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        match dev_mine_block(&ctx.engine, ctx.hnoder.as_ref()) {
+            Ok(hei) => heights.push(hei),
+            Err(e) => return api_error(&format!("mine block failed: {}", e)),
+        }
+    }
+    api_ok(vec![
+        ("heights", json!(heights)),
+    ])
+}
+
+fn dev_warp(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    if !ctx.engine.config().dev.enable {
+        return api_error("dev mode not enabled");
+    }
+    let secs = req.query_u64("time", 0);
+    if secs > DEV_WARP_TIME_MAX {
+        return api_error(&format!("cannot warp more than {} seconds at once", DEV_WARP_TIME_MAX));
+    }
+    let target = req.query_u64("height", 0);
+    let lasthei = ctx.engine.latest_block().height().uint();
+    if target > lasthei.saturating_add(DEV_MINE_MAX) {
+        return api_error(&format!("cannot warp more than {} blocks at once", DEV_MINE_MAX));
+    }
+    // time first, so blocks mined up to `height` carry the new time
+    warp_time(secs);
+    for _ in lasthei..target {
+        if let Err(e) = dev_mine_block(&ctx.engine, ctx.hnoder.as_ref()) {
+            return api_error(&format!("mine block failed: {}", e));
+        }
+    }
+    api_ok(vec![
+        ("height", json!(ctx.engine.latest_block().height().uint())),
+        ("time", json!(curtimes())),
+        ("time_warp", json!(time_warp())),
+    ])
+}
@@ -3,6 +3,7 @@ use std::sync::LazyLock;
 use std::sync::{Arc, Mutex};
 
 use basis::component::*;
+use basis::config::*;
 use basis::difficulty::*;
 use basis::interface::*;
 use field::*;
@@ -21,6 +22,8 @@ include!("core_action.rs");
 include!("submit_transaction.rs");
 include!("submit_block.rs");
 include!("debug.rs");
+include!("peer.rs");
+include!("admin.rs");
 include!("fee.rs");
 include!("routes.rs");
 include!("latest.rs");
@@ -42,3 +45,4 @@ include!("miner_pending.rs");
 include!("miner_success.rs");
 include!("diamondminer_init.rs");
 include!("diamondminer_success.rs");
+include!("dev.rs");
@@ -0,0 +1,22 @@
+fn debug_peer_list(ctx: &ApiExecCtx, _req: ApiRequest) -> ApiResponse {
+    let list: Vec<Value> = ctx
+        .hnoder
+        .peer_reputes()
+        .into_iter()
+        .map(|p| {
+            json!({
+                "ip": p.ip,
+                "key": p.key,
+                "name": p.name,
+                "score": p.score,
+                "reason": p.reason,
+                "banned": p.banned_until > 0,
+                "banned_until": p.banned_until,
+            })
+        })
+        .collect();
+    api_data(serde_json::Map::from_iter([
+        ("connected".to_owned(), json!(ctx.hnoder.all_peer_prints())),
+        ("reputes".to_owned(), json!(list)),
+    ]))
+}
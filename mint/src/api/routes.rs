@@ -1,57 +1,288 @@
 fn routes() -> Vec<ApiRoute> {
     use ApiRoute as R;
+    use ApiType::*;
+    const TX_SHOW: &str = "also show";
     vec![
-        R::get("/", console),
-        R::get("/query/block/intro", block_intro),
-        R::get("/query/block/recents", block_recents),
-        R::get("/query/block/views", block_views),
-        R::get("/query/block/datas", block_datas),
-        R::get("/query/fee/average", fee_average),
-        R::get("/query/transaction", transaction_exist),
-        R::post("/create/transaction", transaction_build),
-        R::post("/submit/transaction", submit_transaction),
-        R::post("/submit/block", submit_block),
-        R::debug_get("block/txs", debug_block_txs),
-        R::debug_get("transaction/receipt", debug_transaction_receipt),
-        R::debug_post("transaction/simulate", debug_transaction_simulate),
-        R::debug_post("submit/transaction", debug_submit_transaction),
-        R::post("/operate/fee/raise", fee_raise),
-        R::post("/util/transaction/check", transaction_check),
-        R::post("/util/transaction/sign", transaction_sign),
-        R::get("/query/hashrate", hashrate),
-        R::get("/query/hashrate/logs", hashrate_logs),
-        R::get("/query/balance", balance),
-        R::get("/query/channel", channel),
-        R::get("/query/diamond", diamond),
-        R::get("/query/diamond/bidding", diamond_bidding),
-        R::get("/query/diamond/views", diamond_views),
-        R::get("/query/diamond/engrave", diamond_engrave),
+        R::get("/", console).summary("Node console page"),
+        R::get("/query/block/intro", block_intro)
+            .summary("Block header and summary")
+            .param("hash", String, "block hash, or use height")
+            .param("height", Integer, "")
+            .param("tx_hash_list", Boolean, "list transaction hashes")
+            .param_unit(),
+        R::get("/query/block/recents", block_recents)
+            .summary("Recently arrived blocks")
+            .param_unit()
+            .returns("list", Array, ""),
+        R::get("/query/block/views", block_views)
+            .summary("Page of block summaries")
+            .param("limit", Integer, "20 by default")
+            .param("page", Integer, "from 1")
+            .param("start", Integer, "start height")
+            .param("desc", Boolean, "newest first")
+            .param_unit()
+            .returns("list", Array, ""),
+        R::get("/query/block/datas", block_datas)
+            .summary("Raw block data from a height")
+            .param("start_height", Integer, "")
+            .param("limit", Integer, "block count")
+            .param("max_size", Integer, "max bytes, 1MB by default")
+            .param("confirm", Boolean, "only confirmed blocks")
+            .param("hexbody", Boolean, "answer hex instead of bytes"),
+        R::get("/query/fee/average", fee_average)
+            .summary("Average fee purity of the tx pool")
+            .param("consumption", Integer, "tx size or gas to price")
+            .param("tx_type", Integer, "2 by default")
+            .param_unit()
+            .returns("purity", Integer, "")
+            .returns("feasible", String, "fee to set for `consumption`"),
+        R::get("/query/transaction", transaction_exist)
+            .summary("Transaction in a block or the tx pool")
+            .param_must("hash", String, "")
+            .param("body", Boolean, TX_SHOW)
+            .param("action", Boolean, TX_SHOW)
+            .param("signature", Boolean, TX_SHOW)
+            .param("description", Boolean, TX_SHOW)
+            .param_unit(),
+        R::post("/create/transaction", transaction_build)
+            .summary("Build a transaction from JSON")
+            .body("transaction JSON")
+            .param("action", Boolean, TX_SHOW)
+            .param("signature", Boolean, TX_SHOW)
+            .param("description", Boolean, TX_SHOW)
+            .param_unit(),
+        R::post("/submit/transaction", submit_transaction)
+            .summary("Submit a signed transaction")
+            .body("transaction bytes")
+            .param("only_insert_txpool", Boolean, "do not broadcast")
+            .returns("hash", String, ""),
+        R::post("/submit/block", submit_block)
+            .summary("Submit a block")
+            .body("block bytes")
+            .returns("ok", Boolean, ""),
+        R::debug_get("block/txs", debug_block_txs)
+            .summary("Transactions of a block")
+            .param("hash", String, "block hash, or use height")
+            .param("height", Integer, "")
+            .param("coinbase", Boolean, "include the coinbase")
+            .param("body", Boolean, TX_SHOW)
+            .param("action", Boolean, TX_SHOW)
+            .param("signature", Boolean, TX_SHOW)
+            .param("description", Boolean, TX_SHOW)
+            .param_unit(),
+        R::debug_get("transaction/receipt", debug_transaction_receipt)
+            .summary("Execution receipt of a transaction")
+            .param_must("hash", String, "")
+            .param("body", Boolean, TX_SHOW)
+            .param("action", Boolean, TX_SHOW)
+            .param("signature", Boolean, TX_SHOW)
+            .param("description", Boolean, TX_SHOW)
+            .param_unit(),
+        R::debug_post("transaction/simulate", debug_transaction_simulate)
+            .summary("Run a transaction on the latest state without keeping it")
+            .body("transaction bytes"),
+        R::debug_get("transaction/trace", debug_transaction_trace)
+            .summary("Execution trace of a transaction")
+            .param_must("hash", String, ""),
+        R::debug_post("submit/transaction", debug_submit_transaction)
+            .summary("Submit a transaction skipping the api checks")
+            .body("transaction bytes")
+            .param("only_insert_txpool", Boolean, "do not broadcast"),
+        R::debug_get("peer/list", debug_peer_list).summary("Connected peers"),
+        R::get("/admin/peers", admin_peer_list)
+            .summary("Connected peers with session details")
+            .returns("list", Array, ""),
+        R::post("/admin/peer/connect", admin_peer_connect)
+            .summary("Dial a peer")
+            .param_must("addr", String, "ip:port"),
+        R::post("/admin/peer/disconnect", admin_peer_disconnect)
+            .summary("Close connected peers")
+            .param_must("target", String, "ip, ip:port or hex node key")
+            .returns("closed", Integer, ""),
+        R::post("/admin/peer/unban", admin_peer_unban)
+            .summary("Lift a peer ban")
+            .param_must("target", String, "peer ip address or hex node key")
+            .returns("removed", Integer, ""),
+        R::post("/admin/peer/unpin", admin_peer_unpin)
+            .summary("Drop a peer identity pin")
+            .param_must("target", String, "hex identity public key or node key")
+            .returns("removed", Integer, ""),
+        R::get("/admin/config", admin_config)
+            .summary("Settings that can change while running")
+            .returns("miner", Object, "")
+            .returns("diamondminer", Object, "")
+            .returns("txpool", Object, ""),
+        R::post("/admin/miner", admin_miner)
+            .summary("Switch HAC mining")
+            .param("enable", Boolean, "")
+            .param("reward", String, "reward address"),
+        R::post("/admin/diamondminer", admin_diamondminer)
+            .summary("Switch diamond mining and set bid limits")
+            .param("enable", Boolean, "")
+            .param("reward", String, "reward address")
+            .param("bid_min", String, "")
+            .param("bid_max", String, "")
+            .param("bid_step", String, ""),
+        R::post("/admin/txpool", admin_txpool)
+            .summary("Resize tx pool groups and set the lowest fee")
+            .param("maxs", String, "comma separated group sizes")
+            .param("lowest_fee_purity", Integer, ""),
+        R::post("/admin/reload", admin_reload)
+            .summary("Load runtime sections of the config file again")
+            .param("sections", String, "miner, diamondminer, txpool, server; all by default"),
+        R::post("/admin/shutdown", admin_shutdown).summary("Stop the node as Ctrl+C does"),
+        R::post("/operate/fee/raise", fee_raise)
+            .summary("Raise the fee of a transaction and resubmit it")
+            .param_must("fee", String, "new fee")
+            .param_must("fee_prikey", String, "private key of the main address")
+            .param("hash", String, "transaction in the pool, or send its body")
+            .body("transaction bytes when no hash")
+            .returns("hash", String, "")
+            .returns("hash_with_fee", String, "")
+            .returns("fee", String, ""),
+        R::post("/util/transaction/check", transaction_check)
+            .summary("Decode and check a transaction")
+            .body("transaction bytes")
+            .param("set_fee", String, "replace the fee first")
+            .param("sign_address", String, "show the hash this address signs")
+            .param("body", Boolean, TX_SHOW)
+            .param("signature", Boolean, TX_SHOW)
+            .param("description", Boolean, TX_SHOW)
+            .param_unit(),
+        R::post("/util/transaction/sign", transaction_sign)
+            .summary("Sign a transaction with a private key, or add a signature")
+            .body("transaction bytes")
+            .param("prikey", String, "")
+            .param("pubkey", String, "with sigdts, a signature made elsewhere")
+            .param("sigdts", String, "")
+            .param("signature", Boolean, TX_SHOW)
+            .param("description", Boolean, TX_SHOW)
+            .param_unit(),
+        R::get("/query/hashrate", hashrate).summary("Current network hashrate"),
+        R::get("/query/hashrate/logs", hashrate_logs)
+            .summary("Daily hashrate history")
+            .param("days", Integer, "200 by default")
+            .param("target", Boolean, "difficulty targets instead")
+            .param("scale", Number, "")
+            .returns("day200", Array, "")
+            .returns("dayall", Array, ""),
+        R::get("/query/balance", balance)
+            .summary("Balances of addresses")
+            .param_must("address", String, "comma separated")
+            .param("coinkind", String, "h, s, d or all (default)")
+            .param("diamonds", Boolean, "list diamond names")
+            .param("assets", Boolean, "list assets")
+            .param("asset", String, "only these asset serials")
+            .param_unit()
+            .returns("list", Array, ""),
+        R::get("/query/channel", channel)
+            .summary("Payment channel state")
+            .param_must("id", String, "channel id, hex")
+            .param_unit()
+            .returns("status", Integer, "0 opening, 1 challenging, 2 and 3 closed")
+            .returns("left", Object, "")
+            .returns("right", Object, ""),
+        R::get("/query/diamond", diamond)
+            .summary("One diamond by name or number")
+            .param("name", String, "")
+            .param("number", Integer, "")
+            .param_unit(),
+        R::get("/query/diamond/bidding", diamond_bidding)
+            .summary("Bids for the next diamond")
+            .param("limit", Integer, "20 by default")
+            .param("number", Integer, "diamond number")
+            .param("since", Boolean, "")
+            .param_unit(),
+        R::get("/query/diamond/views", diamond_views)
+            .summary("Page of diamonds")
+            .param("name", String, "comma separated, instead of paging")
+            .param("limit", Integer, "20 by default")
+            .param("page", Integer, "from 1")
+            .param("start", Integer, "start number")
+            .param("desc", Boolean, "newest first")
+            .param_unit(),
+        R::get("/query/diamond/engrave", diamond_engrave)
+            .summary("Diamond inscriptions made in a block")
+            .param_must("height", Integer, "")
+            .param("tx_hash", Boolean, "show transaction hashes")
+            .param("txposi", Integer, "only this transaction"),
         R::get(
             "/query/diamond/inscription_protocol_cost",
             diamond_inscription_protocol_cost,
-        ),
+        )
+        .summary("Protocol cost of an inscription action")
+        .param("action", String, "append (default), move, edit or drop")
+        .param("name", String, "diamonds for append, edit and drop")
+        .param("from", String, "source diamond for move")
+        .param("to", String, "target diamond for move")
+        .param_unit(),
         R::get(
             "/query/diamond/inscription_protocol_cost/append",
             diamond_inscription_protocol_cost_append,
-        ),
+        )
+        .summary("Protocol cost of appending inscriptions")
+        .param_must("name", String, "comma separated diamonds")
+        .param_unit(),
         R::get(
             "/query/diamond/inscription_protocol_cost/move",
             diamond_inscription_protocol_cost_move,
-        ),
+        )
+        .summary("Protocol cost of moving an inscription")
+        .param_must("to", String, "target diamond")
+        .param("from", String, "source diamond")
+        .param_unit(),
         R::get(
             "/query/diamond/inscription_protocol_cost/edit",
             diamond_inscription_protocol_cost_edit,
-        ),
+        )
+        .summary("Protocol cost of editing an inscription")
+        .param_must("name", String, "")
+        .param_unit(),
         R::get(
             "/query/diamond/inscription_protocol_cost/drop",
             diamond_inscription_protocol_cost_drop,
-        ),
-        R::get("/query/latest", latest),
-        R::get("/query/supply", supply),
-        R::get_async("/query/miner/notice", miner_notice),
-        R::get("/query/miner/pending", miner_pending),
-        R::get("/submit/miner/success", miner_success),
-        R::get("/query/diamondminer/init", diamondminer_init),
-        R::post("/submit/diamondminer/success", diamondminer_success),
+        )
+        .summary("Protocol cost of dropping an inscription")
+        .param_must("name", String, "")
+        .param_unit(),
+        R::get("/query/latest", latest)
+            .summary("Latest block height and diamond number")
+            .returns("height", Integer, "")
+            .returns("diamond", Integer, ""),
+        R::get("/query/supply", supply)
+            .summary("Coin supply and burn statistics")
+            .returns("latest_height", Integer, "")
+            .returns("current_circulation", Number, "HAC"),
+        R::get_async("/query/miner/notice", miner_notice)
+            .summary("Wait until a block above a height arrives")
+            .param("height", Integer, "")
+            .param("wait", Integer, "max seconds, 45 by default"),
+        R::get("/query/miner/pending", miner_pending)
+            .summary("Block template for pool miners")
+            .param("detail", Boolean, "")
+            .param("transaction", Boolean, "")
+            .param("stuff", Boolean, ""),
+        R::get("/submit/miner/success", miner_success)
+            .summary("Report a mined block nonce")
+            .param_must("height", Integer, "")
+            .param("block_nonce", Integer, "")
+            .param("coinbase_nonce", String, "hex")
+            .returns("height", Integer, ""),
+        R::get("/query/diamondminer/init", diamondminer_init)
+            .summary("Addresses of the diamond miner")
+            .returns("bid_address", String, "")
+            .returns("reward_address", String, ""),
+        R::post("/submit/diamondminer/success", diamondminer_success)
+            .summary("Submit a mined diamond")
+            .body("diamond mint action bytes")
+            .returns("tx_hash", String, ""),
+        R::post("/operate/dev/mine", dev_mine)
+            .summary("Dev mode: mine blocks now")
+            .param("count", Integer, "1 by default")
+            .returns("heights", Array, ""),
+        R::post("/operate/dev/warp", dev_warp)
+            .summary("Dev mode: move the clock and mine to a height")
+            .param("time", Integer, "seconds to add")
+            .param("height", Integer, "mine up to"),
     ]
 }
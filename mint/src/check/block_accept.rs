@@ -10,13 +10,25 @@ fn impl_blk_verify(this: &HacashMinter, curblk: &dyn BlockRead, prevblk: &dyn Bl
         return errf!("mainnet prelude tx must be coinbase")
     }
     verify_coinbase(curhei, ptx)?;
-    // check difficulty
-    let blkcln = this.cnf.difficulty_adjust_blocks; // 288
-    if curhei < blkcln*200 && this.cnf.is_mainnet() {
+    if skip_history_difficulty(this, curhei) {
         return Ok(()) // not check, compatible history code
     }
     // coinbase address must be PRIVAKEY type for modern blocks
     verify_coinbase_privakey(ptx)?;
+    impl_blk_intro_verify(this, curblk, prevblk, src)
+}
+
+fn skip_history_difficulty(this: &HacashMinter, curhei: u64) -> bool {
+    let blkcln = this.cnf.difficulty_adjust_blocks; // 288
+    curhei < blkcln*200 && this.cnf.is_mainnet()
+}
+
+// check difficulty and PoW hash, needs only the block intro
+fn impl_blk_intro_verify(this: &HacashMinter, curblk: &dyn BlockRead, prevblk: &dyn BlockRead, src: &dyn BlockIntroSource) -> Rerr {
+    let curhei = curblk.height().uint();
+    if this.cnf.skip_pow || skip_history_difficulty(this, curhei) {
+        return Ok(())
+    }
     let curn = curblk.difficulty().uint(); // u32
     let (tarn, tarhx, _tarbign) = this.next_difficulty(prevblk, curblk.timestamp().uint(), src);
     if tarn != curn {
@@ -29,6 +41,15 @@ fn impl_blk_verify(this: &HacashMinter, curblk: &dyn BlockRead, prevblk: &dyn Bl
     Ok(())
 }
 
+// the compact difficulty drops low bits, one step up bounds the exact target
+fn impl_blk_pow_fault(this: &HacashMinter, curblk: &dyn BlockRead) -> bool {
+    if this.cnf.skip_pow {
+        return false
+    }
+    let limit = u32_to_hash(curblk.difficulty().uint().saturating_add(1));
+    hash_bigger_than(curblk.hash().as_ref(), &limit)
+}
+
 fn impl_blk_insert(this: &HacashMinter, curblk: &BlkPkg, _sta: &dyn State, prev: &dyn State) -> Rerr {
     check_highest_bid_of_block(this, curblk, prev)?;
     Ok(())
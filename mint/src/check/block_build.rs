@@ -365,8 +365,8 @@ mod tests {
         store: Arc<dyn Store>,
     }
     impl EngineRead for TestEngine {
-        fn config(&self) -> &EngineConf {
-            &self.cnf
+        fn config(&self) -> Arc<EngineConf> {
+            Arc::new(self.cnf.clone())
         }
         fn latest_block(&self) -> Arc<dyn Block> {
             self.latest.clone()
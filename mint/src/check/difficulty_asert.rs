@@ -1,7 +1,6 @@
 const ASERT_UPGRADE_HEIGHT: u64 = 738654;
 const ASERT_START_TARGET_NUM: u32 = 0xe9cf_ffff;
 
-const ASERT_HALF_LIFE: i64 = 10800;
 const ASERT_RADIX_BITS: u32 = 16;
 const ASERT_RADIX: i64 = 1i64 << ASERT_RADIX_BITS;
 const ASERT_POLY_1: u128 = 195766423245049;
@@ -34,7 +33,7 @@ impl DifficultyGnr {
         let eval_hei = hei as i128;
         let time_delta = eval_time - anchor_time as i128;
         let height_delta = eval_hei - anchor_hei as i128;
-        let exponent = ((time_delta - self.cnf.each_block_target_time as i128 * height_delta) * ASERT_RADIX as i128) / ASERT_HALF_LIFE as i128;
+        let exponent = ((time_delta - self.cnf.each_block_target_time as i128 * height_delta) * ASERT_RADIX as i128) / self.cnf.difficulty_asert_half_life as i128;
         let num_shifts = exponent >> ASERT_RADIX_BITS;
         let frac = (exponent - (num_shifts << ASERT_RADIX_BITS)) as u128;
         let frac2 = frac * frac;
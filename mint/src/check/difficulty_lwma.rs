@@ -47,7 +47,9 @@ impl DifficultyGnr {
 
 impl DifficultyGnr {
     fn upgrade_height(&self) -> u64 {
-        if self.cnf.is_mainnet() {
+        if let Some(hei) = self.cnf.difficulty_lwma_height {
+            hei
+        } else if self.cnf.is_mainnet() {
             DIFFICULTY_UPGRADE_EPOCH * self.cnf.difficulty_adjust_blocks
         } else {
             1
@@ -55,7 +57,9 @@ impl DifficultyGnr {
     }
 
     fn asert_upgrade_height(&self) -> u64 {
-        if self.cnf.is_mainnet() {
+        if let Some(hei) = self.cnf.difficulty_asert_height {
+            hei
+        } else if self.cnf.is_mainnet() {
             ASERT_UPGRADE_HEIGHT
         } else {
             self.window_blocks() + 2
@@ -1,6 +1,30 @@
 
 fn do_initialize(_this: &HacashMinter, db: &mut dyn State) -> Rerr {
-    
+    let mut state = CoreState::wrap(db);
+    match &chain_spec().genesis {
+        Some(gns) => for (addr, amt) in &gns.allocations {
+            state.balance_set(addr, &Balance::hac(amt.clone()));
+        },
+        None => mainnet_allocations(&mut state),
+    }
+    // just for test develop
+    #[cfg(debug_assertions)] 
+    if _this.cnf.test_coin {
+        let tadr = "1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9";
+        println!("[Debug Mint] {} => 1000000HAC + 5000BTC + 360HACD", tadr);
+	    let addr = Address::from_readable(tadr).unwrap();
+	    let amt = Amount::small(1, 254); // 1000000 HAC
+        let mut bls = Balance::hac(amt); 
+        bls.satoshi = Fold64::from(500000000000)?; // 500 BTC
+        state.balance_set(&addr, &bls);
+        test_add_360_diamonds(&mut state, &addr)?; // 360 HACD
+    }
+
+    // ok
+    Ok(())
+} 
+
+fn mainnet_allocations(state: &mut CoreState) {
 	let addr1 = Address::from_readable("12vi7DEZjh6KrK5PVmmqSgvuJPCsZMmpfi").unwrap();
 	let addr2 = Address::from_readable("1LsQLqkd8FQDh3R7ZhxC5fndNf92WfhM19").unwrap();
 	let addr3 = Address::from_readable("1NUgKsTgM6vQ5nxFHGz1C4METaYTPgiihh").unwrap();
@@ -14,28 +38,12 @@ fn do_initialize(_this: &HacashMinter, db: &mut dyn State) -> Rerr {
     let bls2 = Balance::hac(amt2);
     let bls3 = Balance::hac(amt3);
     let bls4 = Balance::hac(amt4);
-    let mut state = CoreState::wrap(db);
     state.balance_set(&addr1, &bls2);
     state.balance_set(&addr2, &bls1);
     state.balance_set(&addr3, &bls1);
     state.balance_set(&addr4, &bls3);
     state.balance_set(&addr5, &bls4);
-    // just for test develop
-    #[cfg(debug_assertions)] 
-    if _this.cnf.test_coin {
-        let tadr = "1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9";
-        println!("[Debug Mint] {} => 1000000HAC + 5000BTC + 360HACD", tadr);
-	    let addr = Address::from_readable(tadr).unwrap();
-	    let amt = Amount::small(1, 254); // 1000000 HAC
-        let mut bls = Balance::hac(amt); 
-        bls.satoshi = Fold64::from(500000000000)?; // 500 BTC
-        state.balance_set(&addr, &bls);
-        test_add_360_diamonds(&mut state, &addr)?; // 360 HACD
-    }
-
-    // ok
-    Ok(())
-} 
+}
 
 #[allow(unused)]
 fn test_add_360_diamonds(state: &mut CoreState, addr: &Address) -> Rerr {
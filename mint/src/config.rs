@@ -7,6 +7,10 @@ pub struct MintConf {
     pub difficulty_group_blocks: u64, // reuses unstable_block value for PoW grouped sampling span
     pub difficulty_adjust_blocks: u64, // height : 288
     pub each_block_target_time: u64, // secs : 300
+    pub difficulty_lwma_height: Option<u64>, // chain spec override of the LWMA switch height
+    pub difficulty_asert_height: Option<u64>, // chain spec override of the ASERT anchor height
+    pub difficulty_asert_half_life: u64, // secs : 10800
+    pub skip_pow: bool, // chain spec, dev chains only
     pub test_coin: bool
     // pub _test_mul: u64,
 }
@@ -19,10 +23,10 @@ struct DifficultyWindowConf {
 }
 
 impl DifficultyWindowConf {
-    fn parse(sec: &HashMap<String, Option<String>>) -> DifficultyWindowConf {
-        let adjust_blocks = ini_must_u64(sec, "difficulty_adjust_blocks", 288);
-        let group_blocks = ini_must_u64(sec, "difficulty_group_blocks", ini_must_u64(sec, "unstable_block", 4));
-        let target_time = ini_must_u64(sec, "each_block_target_time", 300);
+    fn parse(sec: &HashMap<String, Option<String>>, spec: &DifficultySpec) -> DifficultyWindowConf {
+        let adjust_blocks = spec.adjust_blocks.unwrap_or_else(|| ini_must_u64(sec, "difficulty_adjust_blocks", 288));
+        let group_blocks = spec.group_blocks.unwrap_or_else(|| ini_must_u64(sec, "difficulty_group_blocks", ini_must_u64(sec, "unstable_block", 4)));
+        let target_time = spec.target_time.unwrap_or_else(|| ini_must_u64(sec, "each_block_target_time", 300));
         if group_blocks == 0 {
             panic!("config [mint].difficulty_group_blocks must be greater than 0")
         }
@@ -48,16 +52,21 @@ impl MintConf {
 
     pub fn new(ini: &IniObj) -> MintConf {
 
+        let spec = chain_spec();
         let sec = ini_section(ini, "mint");
-        let diff = DifficultyWindowConf::parse(&sec);
+        let diff = DifficultyWindowConf::parse(&sec, &spec.difficulty);
 
         let cnf = MintConf {
-            chain_id: ini_must_u64(&sec, "chain_id", 0),
+            chain_id: spec.chain_id.map(u64::from).unwrap_or_else(|| ini_must_u64(&sec, "chain_id", 0)),
             sync_maxh: ini_must_u64(&sec, "height_max", 0),
             show_miner_name: ini_must_bool(&sec, "show_miner_name", false),
             difficulty_adjust_blocks: diff.adjust_blocks, // 1 day
             difficulty_group_blocks: diff.group_blocks, // protocol reuses unstable_block numeric value for difficulty grouping
             each_block_target_time: diff.target_time, // 5 mins
+            difficulty_lwma_height: spec.difficulty.lwma_height,
+            difficulty_asert_height: spec.difficulty.asert_height,
+            difficulty_asert_half_life: spec.difficulty.asert_half_life.unwrap_or(10800), // 3 hours
+            skip_pow: spec.difficulty.skip_pow,
             test_coin: ini_must_bool(&sec, "test_coin", false),
             // _test_mul: ini_must_u64(&sec, "_test_mul", 1), // test
         };
@@ -4,9 +4,9 @@ use std::sync::LazyLock;
 static GENESIS_BLOCK_HASH: LazyLock<Hash> = LazyLock::new(|| {
     Hash::from_hex(b"000000077790ba2fcdeaef4a4299d9b667135bac577ce204dee8388f1b97f7e6").unwrap()
 });
-static GENESIS_BLOCK: LazyLock<BlockV1> = LazyLock::new(|| create_genesis_block() );
+static GENESIS_BLOCK: LazyLock<BlockV1> = LazyLock::new(create_genesis_block);
 static GENESIS_BLOCK_PKG: LazyLock<Arc<BlkPkg>>
-    = LazyLock::new(||Arc::new(BlkPkg::create(Box::new(create_genesis_block().clone()))));
+    = LazyLock::new(||Arc::new(BlkPkg::create(Box::new(GENESIS_BLOCK.clone()))));
 
 pub fn genesis_block() -> &'static BlockV1 {
     &GENESIS_BLOCK
@@ -21,13 +21,69 @@ pub fn genesis_block_ptr() -> Arc<BlkPkg>  {
 }
 
 pub fn genesis_block_hash() -> Hash {
-    GENESIS_BLOCK_HASH.clone()
+    GENESIS_BLOCK_PKG.hash()
+}
+
+fn create_genesis_block() -> BlockV1 {
+    match &chain_spec().genesis {
+        Some(gns) => create_spec_genesis_block(gns),
+        None => create_mainnet_genesis_block(),
+    }
+}
+
+/**
+ * hash of the allocation list, so nodes with other balances get another genesis
+ */
+fn allocations_digest(allocs: &[(Address, Amount)]) -> Hash {
+    let stuff: Vec<u8> = allocs.iter().flat_map(|(a, m)| [a.serialize(), m.serialize()].concat()).collect();
+    Hash::from(sys::calculate_hash(stuff))
+}
+
+/**
+ * from chain spec, no fixed hash to check against
+ */
+fn create_spec_genesis_block(gns: &GenesisSpec) -> BlockV1 {
+    let msg = format!("{:<16}", gns.message);
+    let mut trsvec = DynVecTransaction::default();
+    trsvec.push(Box::new(crate::TransactionCoinbase{
+        ty: Uint1::from(0),
+        address: gns.reward.clone(),
+        reward: Amount::small_mei(1),
+        message: Fixed16::from_readable(msg.as_bytes()).unwrap(),
+        extend: match gns.allocations.is_empty() {
+            true => CoinbaseExtend::default(),
+            false => CoinbaseExtend::must(CoinbaseExtendDataV1 {
+                miner_nonce: allocations_digest(&gns.allocations),
+                witness_count: Uint1::from(0),
+            }),
+        },
+    })).unwrap();
+    let mut genesis_block = BlockV1 {
+        intro: BlockHeadMeta {
+            head: BlockHead {
+                version: Uint1::from(1),
+                height: BlockHeight::from(0),
+                timestamp: Timestamp::from(gns.timestamp),
+                prevhash: Hash::default(),
+                mrklroot: Hash::default(),
+                transaction_count: Uint4::from(1)
+            },
+            meta: BlockMeta {
+                nonce: Uint4::from(0),
+                difficulty: Uint4::from(0),
+                witness_stage: Fixed2::default()
+            },
+        },
+        transactions: trsvec
+    };
+    genesis_block.update_mrklroot();
+    genesis_block
 }
 
 /**
  * create
  */ 
-fn create_genesis_block() -> BlockV1 {
+fn create_mainnet_genesis_block() -> BlockV1 {
     let blktime = Timestamp::from(1549250700);
     let blknoncenum = Uint4::from(160117829);
     let reward_addr = Address::from_readable(&"1271438866CSDpJUqrnchoJAiGGBFSQhjd".to_string()).unwrap();
@@ -83,3 +139,26 @@ fn create_genesis_block() -> BlockV1 {
     genesis_block
 }
 
+
+
+#[cfg(test)]
+mod block_tests {
+    use super::*;
+
+    #[test]
+    fn spec_genesis_commits_to_allocations() {
+        let mut setup = protocol::setup::new_standard_protocol_setup(x16rs::block_hash);
+        crate::setup::register_protocol_extensions(&mut setup);
+        let _guard = protocol::setup::install_test_scope(setup);
+        let addr = Address::from_readable("1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9").unwrap();
+        let mut gns = GenesisSpec { timestamp: 1700000000, reward: addr.clone(), message: "devnet".to_owned(), allocations: vec![] };
+        let bare = create_spec_genesis_block(&gns).hash();
+        gns.allocations.push((addr.clone(), Amount::small(1, 248)));
+        let one = create_spec_genesis_block(&gns).hash();
+        gns.allocations[0].1 = Amount::small(2, 248);
+        let other = create_spec_genesis_block(&gns).hash();
+        assert_ne!(bare, one);
+        assert_ne!(one, other);
+        assert_eq!(other, create_spec_genesis_block(&gns).hash());
+    }
+}
@@ -7,6 +7,7 @@ use std::time::{Duration, Instant};
 use num_bigint::*;
 
 use basis::component::*;
+use basis::config::{chain_spec, DifficultySpec, GenesisSpec};
 use basis::difficulty::*;
 use basis::interface::*;
 use basis::*;
@@ -68,6 +68,14 @@ impl Minter for HacashMinter {
         impl_blk_verify(self, curblk, prevblk, src)
     }
 
+    fn blk_intro_verify(&self, curblk: &dyn BlockRead, prevblk: &dyn BlockRead, src: &dyn BlockIntroSource) -> Rerr {
+        impl_blk_intro_verify(self, curblk, prevblk, src)
+    }
+
+    fn blk_pow_fault(&self, curblk: &dyn BlockRead) -> bool {
+        impl_blk_pow_fault(self, curblk)
+    }
+
     fn blk_insert(&self, curblk: &BlkPkg, sta: &dyn State, prev: &dyn State) -> Rerr {
         impl_blk_insert(self, curblk, sta, prev)
     }
@@ -36,7 +36,8 @@ pub fn close_channel_with_distribution(
     let right_sat = &right_bls.satoshi;
 
     // check
-    if paychan.status != CHANNEL_STATUS_OPENING {
+    // a channel in challenge period can still be closed
+    if paychan.status != CHANNEL_STATUS_OPENING && paychan.status != CHANNEL_STATUS_CHALLENGING {
         return errf!("channel is not open");
     }
     let left_addr = &paychan.left_bill.address;
@@ -148,6 +149,7 @@ pub fn close_channel_with_distribution(
         CHANNEL_STATUS_AGREEMENT_CLOSED
     );
     savechan.close_height = Uint5::from(pdhei);
+    savechan.if_challenging = ChallengePeriodDataOptional::default();
     savechan.if_distribution = distribution;
     // save channel and count
     {
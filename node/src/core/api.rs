@@ -7,7 +7,14 @@ pub struct HacashNode {
 impl HacashNode {
     pub fn open(ini: &IniObj, txpool: Arc<dyn TxPool>, engine: Arc<dyn Engine>) -> Self {
         Self {
-            runtime: Arc::new(NodeRuntime::open(ini, txpool, engine)),
+            runtime: Arc::new(NodeRuntime::open(ini, txpool, engine, None)),
+        }
+    }
+
+    /// A node whose p2p listens and dials on an in-memory network.
+    pub fn open_on_memnet(ini: &IniObj, txpool: Arc<dyn TxPool>, engine: Arc<dyn Engine>, net: MemEndpoint) -> Self {
+        Self {
+            runtime: Arc::new(NodeRuntime::open(ini, txpool, engine, Some(net))),
         }
     }
 }
@@ -46,6 +53,34 @@ impl HNoder for HacashNode {
         self.runtime.all_peer_prints()
     }
 
+    fn peer_reputes(&self) -> Vec<PeerRepute> {
+        self.runtime.peer_reputes()
+    }
+
+    fn peer_unban(&self, target: &str) -> Ret<usize> {
+        self.runtime.peer_unban(target)
+    }
+
+    fn peer_details(&self) -> Vec<PeerInfo> {
+        self.runtime.peer_details()
+    }
+
+    fn peer_connect(&self, addr: SocketAddr) -> Rerr {
+        self.runtime.peer_connect(addr)
+    }
+
+    fn peer_disconnect(&self, target: &str) -> Ret<usize> {
+        self.runtime.peer_disconnect(target)
+    }
+
+    fn peer_unpin(&self, target: &str) -> Ret<usize> {
+        self.runtime.peer_unpin(target)
+    }
+
+    fn shutdown(&self) -> Rerr {
+        self.runtime.shutdown()
+    }
+
     fn exit(&self) {
         self.runtime.exit()
     }
@@ -0,0 +1,436 @@
+use super::*;
+use ::protocol::block::{BlockIntro, build_block_package, calculate_mrklroot};
+use ::protocol::transaction::transaction_create;
+use std::collections::{HashMap, VecDeque};
+
+/*
+    Compact block relay.
+
+    Peers that announced MSG_SEND_COMPACT get new blocks as the intro, the
+    coinbase and a short id per remaining transaction instead of the full body.
+    The receiver fills the transactions from its own tx pool, asks the sender for
+    the ones it lacks with MSG_REQ_BLOCK_TXS, and checks the rebuilt body against
+    the merkle root before handing it to the normal block path. A short id
+    collision shows up as a merkle mismatch and is healed by requesting every
+    transaction of the block. Only intros meeting their own PoW target may wait
+    for transactions; when too many wait, the oldest one is fetched in full
+    instead, as is any that waits longer than PARTIAL_TIMEOUT. Peers that never
+    announced support keep receiving MSG_BLOCK_DISCOVER.
+*/
+
+pub(crate) const SHORT_ID_SIZE: usize = 8;
+pub(crate) type ShortId = [u8; SHORT_ID_SIZE];
+/// Relayed blocks whose transactions are kept to answer MSG_REQ_BLOCK_TXS.
+const RECENT_BLOCKS: usize = 8;
+/// Blocks waiting for missing transactions at the same time.
+const PARTIAL_BLOCKS: usize = 8;
+/// Seconds a block may wait for its missing transactions.
+const PARTIAL_TIMEOUT: u64 = 30;
+
+/// Short id of a transaction in the block `blkhx`, salted so ids differ per block.
+pub(crate) fn short_id(blkhx: &Hash, hxfe: &Hash) -> ShortId {
+    let hx = sys::sha3([blkhx.as_bytes(), hxfe.as_bytes()].concat());
+    bufcut!(hx, 0, SHORT_ID_SIZE)
+}
+
+/// Compact message of a block body, plus the serialized transactions to cache.
+pub(crate) fn compact_encode(body: &[u8]) -> Ret<(Hash, Vec<u8>, Vec<Vec<u8>>)> {
+    let blkp = build_block_package(body.to_vec())?;
+    let blk = blkp.block();
+    let blkhx = blk.hash();
+    let intro = BlockIntro::build(body)?;
+    let txs: Vec<Vec<u8>> = blk.transactions().iter().map(|t| t.serialize()).collect();
+    if txs.is_empty() {
+        return errf!("block must have prelude tx")
+    }
+    let mut msg = intro.serialize();
+    msg.extend_from_slice(&txs[0]);
+    for tx in &blk.transactions()[1..] {
+        msg.extend_from_slice(&short_id(&blkhx, &tx.hash_with_fee()));
+    }
+    Ok((blkhx, msg, txs))
+}
+
+/// A block being rebuilt from a compact message.
+pub(crate) struct PartialBlock {
+    peer: PeerKey,
+    height: u64,
+    intro: Vec<u8>,
+    mrklroot: Hash,
+    /// Hash with fee and bytes of each transaction, coinbase first.
+    slots: Vec<Option<(Hash, Vec<u8>)>>,
+    full_requested: bool,
+    since: u64,
+}
+
+impl PartialBlock {
+    /// Decode a compact message, filling every slot matched by a `txpool` transaction.
+    pub(crate) fn decode(peer: PeerKey, buf: &[u8], txpool: &dyn TxPool) -> Ret<(Hash, PartialBlock)> {
+        let mut intro = BlockIntro::default();
+        let mut seek = intro.parse(buf)?;
+        let count = intro.transaction_count().uint() as usize;
+        let (coinbase, size) = transaction_create(&buf[seek..])?;
+        let cbbts = buf[seek..seek + size].to_vec();
+        seek += size;
+        let ids = &buf[seek..];
+        if count == 0 || ids.len() != (count - 1) * SHORT_ID_SIZE {
+            return errf!("compact block short id count mismatch")
+        }
+        let blkhx = intro.hash();
+        let mut want: HashMap<ShortId, usize> = HashMap::with_capacity(count);
+        for (i, id) in ids.chunks(SHORT_ID_SIZE).enumerate() {
+            want.insert(bufcut!(id, 0, SHORT_ID_SIZE), i + 1);
+        }
+        let mut slots = vec![None; count];
+        slots[0] = Some((coinbase.hash_with_fee(), cbbts));
+        let _ = txpool.iter(&mut |txp| {
+            let hxfe = txp.tx().hash_with_fee();
+            if let Some(i) = want.get(&short_id(&blkhx, &hxfe)) {
+                slots[*i] = Some((hxfe, txp.data().to_vec()));
+            }
+            true
+        });
+        let partial = PartialBlock {
+            peer,
+            height: intro.height().uint(),
+            intro: buf[..intro.size()].to_vec(),
+            mrklroot: *intro.mrklroot(),
+            slots,
+            full_requested: false,
+            since: curtimes(),
+        };
+        Ok((blkhx, partial))
+    }
+
+    pub(crate) fn missing(&self) -> Vec<u32> {
+        (0..self.slots.len() as u32).filter(|i| self.slots[*i as usize].is_none()).collect()
+    }
+
+    /// Forget every pooled transaction so all of them get requested again.
+    pub(crate) fn request_all(&mut self) -> Vec<u32> {
+        for slot in self.slots.iter_mut().skip(1) {
+            *slot = None;
+        }
+        self.full_requested = true;
+        self.missing()
+    }
+
+    /// Fill missing slots, in order, from the transactions of a MSG_BLOCK_TXS body.
+    pub(crate) fn fill(&mut self, mut buf: &[u8]) -> Rerr {
+        for i in self.missing() {
+            let (tx, size) = transaction_create(buf)?;
+            self.slots[i as usize] = Some((tx.hash_with_fee(), buf[..size].to_vec()));
+            buf = &buf[size..];
+        }
+        if !buf.is_empty() || !self.missing().is_empty() {
+            return errf!("block txs count mismatch")
+        }
+        Ok(())
+    }
+
+    /// The full block body once every slot is filled and the merkle root matches.
+    pub(crate) fn assemble(&self) -> Ret<Vec<u8>> {
+        let mut hxs = Vec::with_capacity(self.slots.len());
+        let mut body = self.intro.clone();
+        for slot in &self.slots {
+            let Some((hxfe, data)) = slot else {
+                return errf!("block txs not complete")
+            };
+            hxs.push(*hxfe);
+            body.extend_from_slice(data);
+        }
+        if calculate_mrklroot(&hxs) != self.mrklroot {
+            return errf!("rebuilt block mrkl root mismatch")
+        }
+        Ok(body)
+    }
+}
+
+fn req_block_txs_body(blkhx: &Hash, idxs: &[u32]) -> Vec<u8> {
+    let mut buf = blkhx.to_vec();
+    for i in idxs {
+        buf.extend_from_slice(&i.to_be_bytes());
+    }
+    buf
+}
+
+/// Serialized transactions of one block, coinbase first.
+type BlockTxs = Arc<Vec<Vec<u8>>>;
+
+/// A block waiting for its transactions, and the peer asked for them.
+type Waiting<P = Arc<Peer>> = (Hash, PartialBlock, P);
+type Pending<P = Arc<Peer>> = HashMap<Hash, (PartialBlock, P)>;
+
+/// Let a block wait for its transactions, unless it already does. When every
+/// place is taken the oldest waiting block leaves and is returned.
+fn park_partial<P>(pending: &mut Pending<P>, blkhx: Hash, partial: PartialBlock, peer: P) -> Ret<Option<Waiting<P>>> {
+    if pending.contains_key(&blkhx) {
+        return errf!("block already waits for its txs")
+    }
+    let mut evicted = None;
+    if pending.len() >= PARTIAL_BLOCKS {
+        let oldest = pending.iter().min_by_key(|(_, (p, _))| p.since).map(|(hx, _)| *hx);
+        evicted = oldest.and_then(|hx| pending.remove(&hx).map(|(p, pr)| (hx, p, pr)));
+    }
+    pending.insert(blkhx, (partial, peer));
+    Ok(evicted)
+}
+
+/// Take out the blocks that waited for their transactions for too long.
+fn expire_partial<P>(pending: &mut Pending<P>, now: u64) -> Vec<Waiting<P>> {
+    let hxs: Vec<Hash> = pending
+        .iter()
+        .filter(|(_, (p, _))| p.since + PARTIAL_TIMEOUT <= now)
+        .map(|(hx, _)| *hx)
+        .collect();
+    hxs.into_iter().filter_map(|hx| pending.remove(&hx).map(|(p, pr)| (hx, p, pr))).collect()
+}
+
+#[derive(Default)]
+pub struct CompactRelay {
+    recent: StdMutex<VecDeque<(Hash, BlockTxs)>>,
+    partial: StdMutex<Pending>,
+}
+
+impl CompactRelay {
+    fn remember(&self, blkhx: Hash, txs: Vec<Vec<u8>>) {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.iter().any(|(hx, _)| *hx == blkhx) {
+            return;
+        }
+        if recent.len() >= RECENT_BLOCKS {
+            recent.pop_front();
+        }
+        recent.push_back((blkhx, Arc::new(txs)));
+    }
+
+    fn recent_txs(&self, blkhx: &Hash) -> Option<BlockTxs> {
+        let recent = self.recent.lock().unwrap();
+        recent.iter().find(|(hx, _)| hx == blkhx).map(|(_, t)| t.clone())
+    }
+
+    fn park(&self, blkhx: Hash, partial: PartialBlock, peer: Arc<Peer>) -> Ret<Option<Waiting>> {
+        park_partial(&mut self.partial.lock().unwrap(), blkhx, partial, peer)
+    }
+
+    fn expired(&self, now: u64) -> Vec<Waiting> {
+        expire_partial(&mut self.partial.lock().unwrap(), now)
+    }
+}
+
+/// Fetch a block in full from its sender, unless it arrived some other way meanwhile.
+async fn request_full_block(hdl: &MsgHandler, (blkhx, partial, peer): Waiting) {
+    if hdl.knows.check(&blkhx.into_array()) || peer.is_writer_closed() {
+        return;
+    }
+    let next = hdl.engine.latest_block().height().uint() + 1;
+    super::protocol::send_req_block_msg(hdl, peer, partial.height.min(next)).await;
+}
+
+/// Fetch in full the blocks whose missing transactions never came.
+pub(crate) async fn compact_expire(hdl: &MsgHandler) {
+    for waiting in hdl.compact.expired(curtimes()) {
+        request_full_block(hdl, waiting).await;
+    }
+}
+
+/// Broadcast a block to peers that don't know it yet, compact where supported.
+pub(crate) fn relay_block(hdl: &MsgHandler, knowkey: KnowKey, body: Vec<u8>) {
+    let compact = match compact_encode(&body) {
+        Ok((blkhx, compact, txs)) => {
+            hdl.compact.remember(blkhx, txs);
+            compact
+        }
+        Err(_) => vec![],
+    };
+    let p2p = hdl.p2pmng.lock().unwrap();
+    if let Some(p2p) = p2p.as_ref() {
+        p2p.broadcast_block(knowkey, body, compact);
+    }
+}
+
+pub(crate) async fn broadcast_block_unaware(p2p: &P2PManage, key: &KnowKey, full: Vec<u8>, compact: Vec<u8>) {
+    let mut resps = vec![];
+    let peers = vec![p2p.backbones(), p2p.offshoots()].concat();
+    for peer in peers {
+        if !peer.knows.check(key) {
+            peer.knows.add(*key);
+            resps.push(peer);
+        }
+    }
+    let custom = |ty: u16, body: &Vec<u8>| tcp_create_msg(MSG_CUSTOMER, vec![ty.to_be_bytes().to_vec(), body.clone()].concat());
+    let fullbuf = custom(MSG_BLOCK_DISCOVER, &full);
+    let compactbuf = custom(MSG_COMPACT_BLOCK, &compact);
+    for peer in resps {
+        let use_compact = !compact.is_empty() && peer.compact.load(Ordering::Relaxed);
+        let _ = peer.send(if use_compact { &compactbuf } else { &fullbuf }).await;
+    }
+}
+
+pub(crate) async fn receive_compact_block(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    let (blkhx, mut partial) = match PartialBlock::decode(peer.key, &buf, hdl.txpool.as_ref()) {
+        Ok(v) => v,
+        Err(_) => {
+            hdl.penalize(&peer, Misbehave::Malformed);
+            return;
+        }
+    };
+    let Ok(intro) = BlockIntro::build(&partial.intro) else {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        return;
+    };
+    if hdl.engine.minter().blk_pow_fault(&intro) {
+        hdl.penalize(&peer, Misbehave::InvalidBlock);
+        return;
+    }
+    let knowkey: KnowKey = blkhx.into_array();
+    peer.knows.add(knowkey);
+    if hdl.knows.check(&knowkey) {
+        return;
+    }
+    let mut missing = partial.missing();
+    if missing.is_empty() {
+        match partial.assemble() {
+            Ok(body) => return hdl.block_arrive(peer, body).await,
+            Err(_) => missing = partial.request_all(),
+        }
+    }
+    compact_expire(hdl).await;
+    match hdl.compact.park(blkhx, partial, peer.clone()) {
+        Ok(Some(evicted)) => request_full_block(hdl, evicted).await,
+        Ok(None) => {}
+        Err(_) => return,
+    }
+    let _ = peer.send_msg(MSG_REQ_BLOCK_TXS, req_block_txs_body(&blkhx, &missing)).await;
+}
+
+pub(crate) async fn send_block_txs(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    if buf.len() < Hash::SIZE || (buf.len() - Hash::SIZE) % 4 != 0 {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        return;
+    }
+    let blkhx = Hash::must(&buf[..Hash::SIZE]);
+    let txs = match hdl.compact.recent_txs(&blkhx) {
+        Some(txs) => txs,
+        None => {
+            let Some(data) = hdl.engine.store().block_data(&blkhx) else {
+                return;
+            };
+            let Ok(blkp) = build_block_package(data) else {
+                return;
+            };
+            Arc::new(blkp.block().transactions().iter().map(|t| t.serialize()).collect())
+        }
+    };
+    let mut resp = blkhx.to_vec();
+    for i in buf[Hash::SIZE..].chunks(4) {
+        let i = u32::from_be_bytes(bufcut!(i, 0, 4)) as usize;
+        let Some(tx) = txs.get(i) else {
+            hdl.penalize(&peer, Misbehave::Malformed);
+            return;
+        };
+        resp.extend_from_slice(tx);
+    }
+    let _ = peer.send_msg(MSG_BLOCK_TXS, resp).await;
+}
+
+pub(crate) async fn receive_block_txs(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    if buf.len() < Hash::SIZE {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        return;
+    }
+    let blkhx = Hash::must(&buf[..Hash::SIZE]);
+    let Some((mut partial, from)) = hdl.compact.partial.lock().unwrap().remove(&blkhx) else {
+        hdl.penalize(&peer, Misbehave::Unsolicited);
+        return;
+    };
+    if partial.peer != peer.key {
+        hdl.compact.partial.lock().unwrap().insert(blkhx, (partial, from));
+        hdl.penalize(&peer, Misbehave::Unsolicited);
+        return;
+    }
+    if partial.fill(&buf[Hash::SIZE..]).is_err() {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        return;
+    }
+    match partial.assemble() {
+        Ok(body) => hdl.block_arrive(peer, body).await,
+        Err(_) if partial.full_requested => hdl.penalize(&peer, Misbehave::InvalidBlock),
+        Err(_) => {
+            let missing = partial.request_all();
+            hdl.compact.partial.lock().unwrap().insert(blkhx, (partial, from));
+            let _ = peer.send_msg(MSG_REQ_BLOCK_TXS, req_block_txs_body(&blkhx, &missing)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waiting_blocks_make_room_by_age_and_expire() {
+        let mut pending: Pending<u8> = HashMap::new();
+        let now = curtimes();
+        for i in 0..PARTIAL_BLOCKS as u8 {
+            let mut pb = partial(&[], Hash::default());
+            pb.since = now - 10 + i as u64;
+            assert!(park_partial(&mut pending, Hash::must(&[i; 32]), pb, i).unwrap().is_none());
+        }
+        assert!(park_partial(&mut pending, Hash::must(&[1u8; 32]), partial(&[], Hash::default()), 1).is_err());
+        // full: the oldest leaves to be fetched in full
+        let (hx, _, from) = park_partial(&mut pending, Hash::must(&[99u8; 32]), partial(&[], Hash::default()), 99).unwrap().unwrap();
+        assert_eq!((hx, from), (Hash::must(&[0u8; 32]), 0));
+        assert_eq!(pending.len(), PARTIAL_BLOCKS);
+        assert!(expire_partial(&mut pending, now).is_empty());
+        let expired = expire_partial(&mut pending, now + PARTIAL_TIMEOUT - 5);
+        assert_eq!(expired.len(), 5);
+        assert_eq!(pending.len(), PARTIAL_BLOCKS - 5);
+    }
+
+    fn partial(txs: &[(Hash, Vec<u8>)], mrklroot: Hash) -> PartialBlock {
+        PartialBlock {
+            peer: [1u8; crate::peer::PEER_KEY_SIZE],
+            height: 1,
+            intro: vec![9, 9, 9],
+            mrklroot,
+            slots: txs.iter().map(|t| Some(t.clone())).collect(),
+            full_requested: false,
+            since: curtimes(),
+        }
+    }
+
+    #[test]
+    fn short_ids_are_salted_per_block() {
+        let (b1, b2, tx) = (Hash::must(&[1u8; 32]), Hash::must(&[2u8; 32]), Hash::must(&[3u8; 32]));
+        assert_eq!(short_id(&b1, &tx), short_id(&b1, &tx));
+        assert_ne!(short_id(&b1, &tx), short_id(&b2, &tx));
+    }
+
+    #[test]
+    fn rebuild_checks_merkle_and_requests_all_on_mismatch() {
+        let txs: Vec<(Hash, Vec<u8>)> = (1..4u8).map(|i| (Hash::must(&[i; 32]), vec![i; 3])).collect();
+        let root = calculate_mrklroot(&txs.iter().map(|t| t.0).collect());
+        let mut pb = partial(&txs, root);
+        pb.slots[2] = None;
+        assert_eq!(pb.missing(), vec![2]);
+        assert!(pb.assemble().is_err());
+        pb.slots[2] = Some(txs[2].clone());
+        assert_eq!(pb.assemble().unwrap(), [vec![9, 9, 9], vec![1; 3], vec![2; 3], vec![3; 3]].concat());
+
+        // a short id collision picked the wrong pool tx
+        pb.slots[1] = Some((Hash::must(&[7u8; 32]), vec![7; 3]));
+        assert!(pb.assemble().is_err());
+        assert_eq!(pb.request_all(), vec![1, 2]);
+        assert!(pb.full_requested);
+        assert!(pb.slots[0].is_some());
+    }
+
+    #[test]
+    fn req_block_txs_body_layout() {
+        let hx = Hash::must(&[5u8; 32]);
+        let body = req_block_txs_body(&hx, &[1, 258]);
+        assert_eq!(body.len(), 32 + 8);
+        assert_eq!(&body[32..], &[0, 0, 0, 1, 0, 0, 1, 2]);
+    }
+}
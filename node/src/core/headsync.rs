@@ -0,0 +1,838 @@
+use super::*;
+use ::protocol::block::BlockIntro;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/*
+    Headers-first sync.
+
+    When a peer is far ahead, block intros are fetched from one peer and checked
+    (linkage, PoW hash and difficulty through the minter) well before their bodies.
+    Bodies are then pulled in fixed height ranges from every peer known to be high
+    enough, buffered, hash-matched against the verified intros and handed to
+    `Engine::synchronize` strictly in height order.
+*/
+
+/// Remote lead (in blocks) above which headers-first sync replaces the single-peer path.
+pub(crate) const HEADERS_FIRST_MIN_GAP: u64 = 1000;
+/// Intros per header request.
+pub(crate) const HEADER_BATCH: u64 = 2000;
+/// Verified intros kept ahead of the engine head.
+const HEADER_WINDOW: u64 = 20000;
+/// Heights per body request.
+const BODY_RANGE: u64 = 100;
+/// Heights of bodies requested or buffered ahead of the engine head.
+const BODY_WINDOW: u64 = 2000;
+/// Body requests one peer may have outstanding.
+const RANGES_PER_PEER: usize = 2;
+/// Seconds before an unanswered request is handed to another peer.
+const REQUEST_TIMEOUT: u64 = 30;
+/// Seconds late answers to a range request given up on are still ignored.
+const ABORTED_KEEP: u64 = REQUEST_TIMEOUT * 4;
+/// Seconds a peer that timed out is synced through the single-peer path.
+const LEGACY_KEEP: u64 = 600;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RangeTask {
+    end: u64,
+    peer: Option<PeerKey>,
+    sent_at: Option<Instant>,
+}
+
+/// Pure headers-first bookkeeping, keyed by peer only so it can be driven without sockets.
+pub(crate) struct HeadersState {
+    // intros[i] is at height `first + i`; everything below `first` is already in the engine
+    first: u64,
+    intros: VecDeque<(Hash, BlockIntro)>,
+    tip_hash: Hash,
+    target: u64,
+    peers: HashMap<PeerKey, u64>,
+    header_req: Option<(PeerKey, u64, Instant)>,
+    ranges: BTreeMap<u64, RangeTask>,
+    planned: u64,
+    bodies: BTreeMap<u64, Vec<u8>>,
+    flushing: bool,
+}
+
+impl HeadersState {
+    pub(crate) fn new(base: u64, base_hash: Hash, target: u64) -> Self {
+        Self {
+            first: base + 1,
+            intros: VecDeque::new(),
+            tip_hash: base_hash,
+            target,
+            peers: HashMap::new(),
+            header_req: None,
+            ranges: BTreeMap::new(),
+            planned: base,
+            bodies: BTreeMap::new(),
+            flushing: false,
+        }
+    }
+
+    /// Next height the engine expects.
+    pub(crate) fn next_insert(&self) -> u64 {
+        self.first
+    }
+
+    /// Height of the last verified intro.
+    pub(crate) fn header_tip(&self) -> u64 {
+        self.first + self.intros.len() as u64 - 1
+    }
+
+    pub(crate) fn tip_hash(&self) -> Hash {
+        self.tip_hash
+    }
+
+    pub(crate) fn target(&self) -> u64 {
+        self.target
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.first > self.target
+    }
+
+    pub(crate) fn has_peers(&self) -> bool {
+        !self.peers.is_empty()
+    }
+
+    pub(crate) fn note_peer(&mut self, peer: PeerKey, height: u64) {
+        self.peers.insert(peer, height);
+        self.target = self.target.max(height);
+    }
+
+    /// Forget a peer and put its outstanding work back in the queue. Returns
+    /// the starts of the range requests it had outstanding.
+    pub(crate) fn drop_peer(&mut self, peer: &PeerKey) -> Vec<u64> {
+        self.peers.remove(peer);
+        if self.header_req.is_some_and(|(p, ..)| &p == peer) {
+            self.header_req = None;
+        }
+        let mut aborted = vec![];
+        for (start, task) in self.ranges.iter_mut() {
+            if task.peer.as_ref() == Some(peer) {
+                task.peer = None;
+                task.sent_at = None;
+                aborted.push(*start);
+            }
+        }
+        aborted
+    }
+
+    /// Range requests outstanding, as `(peer, start)`.
+    pub(crate) fn outstanding(&self) -> Vec<(PeerKey, u64)> {
+        self.ranges.iter().filter_map(|(start, task)| task.peer.map(|p| (p, *start))).collect()
+    }
+
+    /// Drop peers whose requests went unanswered and free their work.
+    /// Returns them with the starts of their range requests given up on.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<(PeerKey, Vec<u64>)> {
+        let late = |t: &Instant| now.duration_since(*t).as_secs() >= REQUEST_TIMEOUT;
+        let mut stale = vec![];
+        if let Some((p, _, t)) = self.header_req {
+            if late(&t) {
+                stale.push(p);
+            }
+        }
+        for task in self.ranges.values() {
+            if let (Some(p), Some(t)) = (task.peer, task.sent_at) {
+                if late(&t) && !stale.contains(&p) {
+                    stale.push(p);
+                }
+            }
+        }
+        stale.into_iter().map(|p| (p, self.drop_peer(&p))).collect()
+    }
+
+    /// Peer and start height for the next intro request, if one is due.
+    pub(crate) fn next_header_request(&mut self, now: Instant) -> Option<(PeerKey, u64)> {
+        if self.header_req.is_some() {
+            return None;
+        }
+        let start = self.header_tip() + 1;
+        if start > self.target || start > self.first + HEADER_WINDOW {
+            return None;
+        }
+        let (peer, _) = self
+            .peers
+            .iter()
+            .filter(|(_, h)| **h >= start)
+            .max_by_key(|(_, h)| **h)?;
+        let peer = *peer;
+        self.header_req = Some((peer, start, now));
+        Some((peer, start))
+    }
+
+    /// Append intros already checked by `verify_intros`. Anything not answering
+    /// the outstanding request from `peer` is ignored.
+    pub(crate) fn append_intros(&mut self, peer: &PeerKey, intros: Vec<(Hash, BlockIntro)>) -> bool {
+        let Some((p, start, _)) = self.header_req else {
+            return false;
+        };
+        if &p != peer || intros.first().map(|(_, i)| i.height().uint()) != Some(start) {
+            return false;
+        }
+        self.header_req = None;
+        if let Some((hx, _)) = intros.last() {
+            self.tip_hash = *hx;
+        }
+        self.intros.extend(intros);
+        true
+    }
+
+    /// Intro request ended without usable data, let another peer try.
+    pub(crate) fn header_failed(&mut self, peer: &PeerKey) {
+        self.drop_peer(peer);
+    }
+
+    /// Verified intros from `from` up to the header tip, used as the difficulty source.
+    pub(crate) fn intros_from(&self, from: u64) -> Vec<BlockIntro> {
+        let skip = from.saturating_sub(self.first) as usize;
+        self.intros.iter().skip(skip).map(|(_, i)| i.clone()).collect()
+    }
+
+    fn hash_at(&self, height: u64) -> Option<Hash> {
+        let idx = height.checked_sub(self.first)?;
+        self.intros.get(idx as usize).map(|(hx, _)| *hx)
+    }
+
+    /// Split newly verified heights into ranges and hand free ranges to peers
+    /// high enough to serve them. Returns `(peer, start, end)` requests to send.
+    pub(crate) fn assign_ranges(&mut self, now: Instant) -> Vec<(PeerKey, u64, u64)> {
+        let limit = self.header_tip().min(self.first + BODY_WINDOW - 1);
+        while self.planned < limit {
+            let start = self.planned + 1;
+            let end = (start + BODY_RANGE - 1).min(limit);
+            self.ranges.insert(start, RangeTask { end, peer: None, sent_at: None });
+            self.planned = end;
+        }
+        let mut load: HashMap<PeerKey, usize> = self.peers.keys().map(|p| (*p, 0)).collect();
+        for task in self.ranges.values() {
+            if let Some(n) = task.peer.as_ref().and_then(|p| load.get_mut(p)) {
+                *n += 1;
+            }
+        }
+        let mut reqs = vec![];
+        for (start, task) in self.ranges.iter_mut() {
+            if task.peer.is_some() {
+                continue;
+            }
+            let pick = load
+                .iter()
+                .filter(|(p, n)| **n < RANGES_PER_PEER && self.peers[*p] >= task.end)
+                .min_by_key(|(_, n)| **n)
+                .map(|(p, _)| *p);
+            let Some(peer) = pick else { continue };
+            *load.get_mut(&peer).unwrap() += 1;
+            task.peer = Some(peer);
+            task.sent_at = Some(now);
+            reqs.push((peer, *start, task.end));
+        }
+        reqs
+    }
+
+    /// Store bodies answering the range starting at `start`. Each body must hash
+    /// to the verified intro at its height; a short answer leaves the rest queued.
+    pub(crate) fn accept_bodies(&mut self, peer: &PeerKey, start: u64, blocks: Vec<(Hash, Vec<u8>)>) -> Rerr {
+        let Some(task) = self.ranges.get(&start) else {
+            return errf!("no pending range at {}", start);
+        };
+        if task.peer.as_ref() != Some(peer) {
+            return errf!("range {} not requested from this peer", start);
+        }
+        let end = task.end;
+        if blocks.is_empty() || blocks.len() as u64 > end - start + 1 {
+            return errf!("range {}-{} answered with {} blocks", start, end, blocks.len());
+        }
+        for (i, (hx, _)) in blocks.iter().enumerate() {
+            let hei = start + i as u64;
+            if self.hash_at(hei) != Some(*hx) {
+                return errf!("block {} does not match verified intro", hei);
+            }
+        }
+        self.ranges.remove(&start);
+        let got = blocks.len() as u64;
+        if start + got <= end {
+            self.ranges.insert(start + got, RangeTask { end, peer: None, sent_at: None });
+        }
+        for (i, (_, data)) in blocks.into_iter().enumerate() {
+            self.bodies.insert(start + i as u64, data);
+        }
+        Ok(())
+    }
+
+    /// Take the contiguous bodies from the engine head on, unless a batch is
+    /// already being inserted. Returns `(start, end, data)`.
+    pub(crate) fn take_ready(&mut self) -> Option<(u64, u64, Vec<u8>)> {
+        if self.flushing || !self.bodies.contains_key(&self.first) {
+            return None;
+        }
+        let start = self.first;
+        let mut end = start;
+        let mut datas = vec![];
+        while let Some(data) = self.bodies.remove(&end) {
+            datas.push(data);
+            end += 1;
+        }
+        self.flushing = true;
+        Some((start, end - 1, datas.concat()))
+    }
+
+    /// The batch from `take_ready` is in the engine; prune what it covered.
+    pub(crate) fn flushed(&mut self, end: u64) {
+        self.flushing = false;
+        while self.first <= end {
+            self.intros.pop_front();
+            self.first += 1;
+        }
+    }
+}
+
+/// Intro source for difficulty checks: verified intros above the engine head,
+/// the store below it.
+struct SyncIntroSource<'a> {
+    eng: &'a dyn Engine,
+    first: u64,
+    intros: &'a [BlockIntro],
+}
+
+impl BlockIntroSource for SyncIntroSource<'_> {
+    fn cache_height_limit(&self) -> u64 {
+        self.eng.store().status().root_height.uint()
+    }
+
+    fn block_intro(&self, hei: u64) -> Option<Box<dyn BlockRead>> {
+        if hei >= self.first {
+            let intro = self.intros.get((hei - self.first) as usize)?;
+            return Some(Box::new(intro.clone()));
+        }
+        let datas = match hei {
+            0 => self.eng.minter().genesis_block().serialize(),
+            _ => self.eng.store().block_data_by_height(&BlockHeight::from(hei))?.1,
+        };
+        BlockIntro::build(&datas).ok().map(|v| Box::new(v) as Box<dyn BlockRead>)
+    }
+}
+
+/// Check `news` continue the chain ending at `prev_hash` (with `known` verified
+/// intros starting at `first`): sequential heights, linked hashes, and PoW hash
+/// and difficulty accepted by the minter.
+pub(crate) fn verify_intros(
+    eng: &dyn Engine,
+    first: u64,
+    mut known: Vec<BlockIntro>,
+    mut prev_hash: Hash,
+    news: Vec<BlockIntro>,
+) -> Ret<Vec<(Hash, BlockIntro)>> {
+    let minter = eng.minter();
+    let mut res = Vec::with_capacity(news.len());
+    for intro in news {
+        let hei = intro.height().uint();
+        if hei != first + known.len() as u64 {
+            return errf!("intro height {} out of sequence", hei);
+        }
+        if *intro.prevhash() != prev_hash {
+            return errf!("intro {} does not link to previous hash", hei);
+        }
+        let src = SyncIntroSource { eng, first, intros: &known };
+        let Some(prev) = src.block_intro(hei - 1) else {
+            return errf!("cannot load intro {}", hei - 1);
+        };
+        minter.blk_intro_verify(&intro, prev.as_ref(), &src)?;
+        prev_hash = intro.hash();
+        known.push(intro.clone());
+        res.push((prev_hash, intro));
+    }
+    Ok(res)
+}
+
+/// Headers-first sync state shared by the message handler, plus the peer handles
+/// it needs to send requests. Peers that did not answer the headers-first
+/// messages in time (older nodes, or slow ones) are marked legacy and synced
+/// through the single-peer path, until the mark expires or a late answer
+/// shows they do speak headers-first. Range requests given up on are kept
+/// for a while, so late answers to them are dropped quietly.
+pub struct HeadersSync {
+    state: StdMutex<Option<HeadersState>>,
+    peers: StdMutex<HashMap<PeerKey, Arc<Peer>>>,
+    legacy: StdMutex<HashMap<PeerKey, Instant>>,
+    aborted: StdMutex<HashMap<(PeerKey, u64), Instant>>,
+}
+
+impl HeadersSync {
+    pub fn new() -> Self {
+        Self {
+            state: StdMutex::new(None),
+            peers: StdMutex::new(HashMap::new()),
+            legacy: StdMutex::new(HashMap::new()),
+            aborted: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn is_legacy(&self, peer: &Arc<Peer>) -> bool {
+        self.is_legacy_at(&peer.key, Instant::now())
+    }
+
+    fn is_legacy_at(&self, key: &PeerKey, now: Instant) -> bool {
+        let mut legacy = self.legacy.lock().unwrap();
+        legacy.retain(|_, t| now.duration_since(*t).as_secs() < LEGACY_KEEP);
+        legacy.contains_key(key)
+    }
+
+    fn mark_legacy(&self, key: PeerKey, now: Instant) {
+        self.legacy.lock().unwrap().insert(key, now);
+    }
+
+    fn note_aborted(&self, reqs: impl IntoIterator<Item = (PeerKey, u64)>, now: Instant) {
+        let mut aborted = self.aborted.lock().unwrap();
+        aborted.retain(|_, t| now.duration_since(*t).as_secs() < ABORTED_KEEP);
+        aborted.extend(reqs.into_iter().map(|r| (r, now)));
+    }
+
+    /// Whether the range request at `start` to `key` was given up on lately;
+    /// it is forgotten either way.
+    fn take_aborted(&self, key: &PeerKey, start: u64, now: Instant) -> bool {
+        let t = self.aborted.lock().unwrap().remove(&(*key, start));
+        t.is_some_and(|t| now.duration_since(t).as_secs() < ABORTED_KEEP)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.lock().unwrap().is_some()
+    }
+
+    /// Start unless already running. Returns false if it was running.
+    pub(crate) fn begin(&self, base: u64, base_hash: Hash, target: u64) -> bool {
+        let mut st = self.state.lock().unwrap();
+        if st.is_some() {
+            return false;
+        }
+        *st = Some(HeadersState::new(base, base_hash, target));
+        true
+    }
+
+    pub(crate) fn abort(&self) {
+        let st = self.state.lock().unwrap().take();
+        if let Some(st) = st {
+            self.note_aborted(st.outstanding(), Instant::now());
+        }
+    }
+
+    pub(crate) fn with<R>(&self, f: impl FnOnce(&mut HeadersState) -> R) -> Option<R> {
+        self.state.lock().unwrap().as_mut().map(f)
+    }
+
+    pub(crate) fn note_peer(&self, peer: &Arc<Peer>, height: u64) {
+        self.peers.lock().unwrap().insert(peer.key, peer.clone());
+        self.with(|st| st.note_peer(peer.key, height));
+    }
+
+    pub(crate) fn drop_peer(&self, peer: &Arc<Peer>) {
+        self.peers.lock().unwrap().remove(&peer.key);
+        if let Some(starts) = self.with(|st| st.drop_peer(&peer.key)) {
+            self.note_aborted(starts.into_iter().map(|s| (peer.key, s)), Instant::now());
+        }
+    }
+
+    fn peer(&self, key: &PeerKey) -> Option<Arc<Peer>> {
+        self.peers.lock().unwrap().get(key).cloned()
+    }
+}
+
+/// Start headers-first sync toward `peer`, or just add it as a source if already running.
+pub(crate) async fn headers_first_begin(hdl: &MsgHandler, peer: Arc<Peer>, remote_height: u64) {
+    let latest = hdl.engine.latest_block();
+    if hdl
+        .head_sync
+        .begin(latest.height().uint(), latest.hash(), remote_height)
+    {
+        println!(
+            "[Sync] headers-first from height {} to {}.",
+            latest.height().uint() + 1,
+            remote_height
+        );
+    }
+    hdl.head_sync.note_peer(&peer, remote_height);
+    headers_first_drive(hdl).await;
+}
+
+/// Send every request that is due: expired work is reassigned, the next intro
+/// batch is asked for and free body ranges go to idle peers.
+pub(crate) async fn headers_first_drive(hdl: &MsgHandler) {
+    let now = Instant::now();
+    let Some((stale, header, ranges)) = hdl.head_sync.with(|st| {
+        let stale = st.expire(now);
+        let header = st.next_header_request(now);
+        let ranges = st.assign_ranges(now);
+        (stale, header, ranges)
+    }) else {
+        return;
+    };
+    let mut fallback = None;
+    for (key, starts) in stale {
+        hdl.head_sync.mark_legacy(key, now);
+        hdl.head_sync.note_aborted(starts.into_iter().map(|s| (key, s)), now);
+        if let Some(peer) = hdl.head_sync.peer(&key) {
+            println!("[Sync] peer {} timed out, reassigning its requests.", peer.name());
+            hdl.penalize(&peer, Misbehave::Timeout);
+            fallback = Some(peer);
+        }
+    }
+    if let Some((false, target)) = hdl.head_sync.with(|st| (st.has_peers(), st.target())) {
+        // nobody left who speaks headers-first: continue block by block
+        hdl.head_sync.abort();
+        if let Some(peer) = fallback {
+            let lathei = hdl.engine.latest_block().height().uint();
+            super::protocol::get_status_try_sync_blocks(hdl, peer, lathei + 1, target).await;
+        }
+        return;
+    }
+    if let Some((key, start)) = header {
+        match hdl.head_sync.peer(&key) {
+            Some(peer) => send_req_block_intro_msg(peer, HEADER_BATCH as u16, start).await,
+            None => {
+                hdl.head_sync.with(|st| st.header_failed(&key));
+            }
+        }
+    }
+    for (key, start, end) in ranges {
+        let Some(peer) = hdl.head_sync.peer(&key) else {
+            hdl.head_sync.with(|st| st.drop_peer(&key));
+            continue;
+        };
+        // asked again, so an answer is due after all
+        hdl.head_sync.take_aborted(&key, start, now);
+        let buf = [start.to_be_bytes(), end.to_be_bytes()].concat();
+        let _ = peer.send_msg(MSG_REQ_BLOCK_RANGE, buf).await;
+    }
+}
+
+pub(crate) async fn send_req_block_intro_msg(peer: Arc<Peer>, num: u16, starthei: u64) {
+    let buf = [num.to_be_bytes().to_vec(), starthei.to_be_bytes().to_vec()].concat();
+    let _ = peer.send_msg(MSG_REQ_BLOCK_INTRO, buf).await;
+}
+
+pub(crate) async fn send_intros(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    if buf.len() != 2 + 8 {
+        return;
+    }
+    let num = (u16::from_be_bytes(bufcut!(buf, 0, 2)) as u64).min(HEADER_BATCH);
+    let starthei = u64::from_be_bytes(bufcut!(buf, 2, 10));
+    let lathei = hdl.engine.latest_block().height().uint();
+    if num == 0 || starthei == 0 || starthei > lathei {
+        return;
+    }
+    let endhei = lathei.min(starthei + num - 1);
+    let store = hdl.engine.store();
+    let mut intros = vec![starthei.to_be_bytes().to_vec()];
+    for hei in starthei..=endhei {
+        let Some((_, blkdts)) = store.block_data_by_height(&BlockHeight::from(hei)) else {
+            return;
+        };
+        let Ok(intro) = BlockIntro::build(&blkdts) else {
+            return;
+        };
+        intros.push(intro.serialize());
+    }
+    let _ = peer.send_msg(MSG_BLOCK_INTRO, intros.concat()).await;
+}
+
+pub(crate) async fn receive_intros(hdl: &MsgHandler, peer: Arc<Peer>, mut buf: Vec<u8>) {
+    if buf.len() < 8 {
+        return;
+    }
+    let body = buf.split_off(8);
+    let mut news = vec![];
+    let mut seek = 0;
+    while seek < body.len() {
+        let mut intro = BlockIntro::default();
+        match intro.parse(&body[seek..]) {
+            Ok(size) => seek += size,
+            Err(_) => {
+                hdl.penalize(&peer, Misbehave::Malformed);
+                hdl.head_sync.with(|st| st.header_failed(&peer.key));
+                return;
+            }
+        }
+        news.push(intro);
+    }
+    if news.is_empty() {
+        hdl.head_sync.with(|st| st.header_failed(&peer.key));
+        return;
+    }
+    let Some((first, known, prev_hash)) = hdl.head_sync.with(|st| {
+        let first = st.next_insert();
+        (first, st.intros_from(first), st.tip_hash())
+    }) else {
+        return;
+    };
+    let eng = hdl.engine.clone();
+    let (res, pow_faults) = tokio::task::spawn_blocking(move || {
+        let minter = eng.minter();
+        let pow_faults = news.iter().any(|i| minter.blk_pow_fault(i));
+        (verify_intros(eng.as_ref(), first, known, prev_hash, news), pow_faults)
+    })
+    .await
+    .unwrap();
+    match res {
+        Ok(intros) => {
+            let added = intros.len();
+            let ok = hdl.head_sync.with(|st| st.append_intros(&peer.key, intros));
+            if ok == Some(true) {
+                flush!("sync intros {} from {}...", added, peer.name());
+            }
+        }
+        Err(e) => {
+            println!("[Sync] intros from {} rejected: {}", peer.name(), e);
+            if pow_faults {
+                hdl.penalize(&peer, Misbehave::InvalidBlock);
+            }
+            hdl.head_sync.drop_peer(&peer);
+            fallback_if_forked(hdl, peer).await;
+            return;
+        }
+    }
+    headers_first_drive(hdl).await;
+}
+
+/// Intros that do not link to our head mean we are on a fork the remote does not
+/// share; hand over to the hash-matching single-peer path that resolves forks.
+async fn fallback_if_forked(hdl: &MsgHandler, peer: Arc<Peer>) {
+    let no_intros = hdl
+        .head_sync
+        .with(|st| st.header_tip() < st.next_insert())
+        .unwrap_or(false);
+    if !no_intros {
+        headers_first_drive(hdl).await;
+        return;
+    }
+    hdl.head_sync.abort();
+    let lathei = hdl.engine.latest_block().height().uint();
+    let ubh = hdl.engine.config().unstable_block.min(255);
+    send_req_block_hash_msg(peer, ubh as u8, lathei).await;
+}
+
+pub(crate) async fn send_block_range(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    if buf.len() != 8 + 8 {
+        return;
+    }
+    let starthei = u64::from_be_bytes(bufcut!(buf, 0, 8));
+    let reqend = u64::from_be_bytes(bufcut!(buf, 8, 16));
+    let lathei = hdl.engine.latest_block().height().uint();
+    if starthei == 0 || starthei > reqend || starthei > lathei {
+        return;
+    }
+    let maxsendsize = 1024 * 1024 * 20usize;
+    let store = hdl.engine.store();
+    let mut totalsize = 0;
+    let mut endhei = starthei;
+    let mut blkdtsary = vec![];
+    for hei in starthei..=reqend.min(lathei) {
+        let Some((_, blkdts)) = store.block_data_by_height(&BlockHeight::from(hei)) else {
+            return;
+        };
+        totalsize += blkdts.len();
+        endhei = hei;
+        blkdtsary.push(blkdts);
+        if totalsize >= maxsendsize {
+            break;
+        }
+    }
+    let msgbody = vec![
+        starthei.to_be_bytes().to_vec(),
+        endhei.to_be_bytes().to_vec(),
+        blkdtsary.concat(),
+    ]
+    .concat();
+    let _ = peer.send_msg(MSG_BLOCK_RANGE, msgbody).await;
+}
+
+pub(crate) async fn receive_block_range(hdl: &MsgHandler, peer: Arc<Peer>, mut buf: Vec<u8>) {
+    if buf.len() < 2 * 8 {
+        return;
+    }
+    let datas = buf.split_off(2 * 8);
+    let start_hei = u64::from_be_bytes(bufcut!(buf, 0, 8));
+    if hdl.head_sync.take_aborted(&peer.key, start_hei, Instant::now()) {
+        // a late answer: the peer is slow, not an old node
+        hdl.head_sync.legacy.lock().unwrap().remove(&peer.key);
+        return;
+    }
+    let parsed = tokio::task::spawn_blocking(move || -> Ret<Vec<(Hash, Vec<u8>)>> {
+        let mut blocks = vec![];
+        let mut seek = 0;
+        while seek < datas.len() {
+            let (blk, size) = ::protocol::block::block_create(&datas[seek..])?;
+            blocks.push((blk.hash(), datas[seek..seek + size].to_vec()));
+            seek += size;
+        }
+        Ok(blocks)
+    })
+    .await
+    .unwrap();
+    let Ok(blocks) = parsed else {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        hdl.head_sync.drop_peer(&peer);
+        return;
+    };
+    match hdl.head_sync.with(|st| st.accept_bodies(&peer.key, start_hei, blocks)) {
+        None => hdl.penalize(&peer, Misbehave::Unsolicited),
+        Some(Err(e)) => {
+            println!("[Sync] blocks from {} rejected: {}", peer.name(), e);
+            hdl.penalize(&peer, Misbehave::InvalidBlock);
+            hdl.head_sync.drop_peer(&peer);
+        }
+        Some(Ok(())) => {
+            hdl.head_sync.legacy.lock().unwrap().remove(&peer.key);
+        }
+    }
+    headers_first_flush(hdl).await;
+    headers_first_drive(hdl).await;
+}
+
+/// Feed buffered bodies to the engine in height order, one batch at a time.
+async fn headers_first_flush(hdl: &MsgHandler) {
+    while let Some(Some((start, end, blocks))) = hdl.head_sync.with(|st| st.take_ready()) {
+        let target = hdl.head_sync.with(|st| st.target()).unwrap_or(end);
+        let persent = end as f64 / target as f64 * 100.0;
+        let eng = hdl.engine.clone();
+        let inserting = hdl.inserting.clone();
+        let res = tokio::task::spawn_blocking(move || {
+            let _lk = inserting.lock().unwrap();
+            flush!("{}-{}({:.2}%) inserting...", start, end, persent);
+            eng.synchronize(Arc::new(blocks))
+        })
+        .await
+        .unwrap();
+        if let Err(e) = res {
+            println!("{}", e);
+            hdl.head_sync.abort();
+            return;
+        }
+        println!("ok.");
+        let done = hdl.head_sync.with(|st| {
+            st.flushed(end);
+            st.is_done()
+        });
+        if done == Some(true) {
+            hdl.head_sync.abort();
+            println!("all blocks sync finished.");
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intro(hei: u64) -> (Hash, BlockIntro) {
+        let mut i = BlockIntro::default();
+        i.head.height = BlockHeight::from(hei);
+        (Hash::from([hei as u8; 32]), i)
+    }
+
+    fn state_with_intros(base: u64, tip: u64, peers: &[(PeerKey, u64)]) -> HeadersState {
+        let mut st = HeadersState::new(base, Hash::default(), tip);
+        for (p, h) in peers {
+            st.note_peer(*p, *h);
+        }
+        let now = Instant::now();
+        let (p, start) = st.next_header_request(now).unwrap();
+        assert_eq!(start, base + 1);
+        assert!(st.append_intros(&p, (base + 1..=tip).map(intro).collect()));
+        st
+    }
+
+    #[test]
+    fn ranges_spread_over_peers_that_are_high_enough() {
+        let (a, b, c) = ([1u8; 16], [2u8; 16], [3u8; 16]);
+        let mut st = state_with_intros(10, 10 + BODY_RANGE * 5, &[(a, 5000), (b, 5000), (c, 50)]);
+        let reqs = st.assign_ranges(Instant::now());
+        assert_eq!(reqs.len(), 2 * RANGES_PER_PEER);
+        assert!(reqs.iter().all(|(p, ..)| *p != c));
+        assert_eq!(reqs.iter().filter(|(p, ..)| *p == a).count(), RANGES_PER_PEER);
+        assert_eq!(reqs[0].1, 11);
+        assert_eq!(reqs[0].2, 10 + BODY_RANGE);
+        // nothing more until a peer frees up
+        assert!(st.assign_ranges(Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn bodies_are_checked_and_released_in_order() {
+        let a = [1u8; 16];
+        let mut st = state_with_intros(0, 250, &[(a, 1000)]);
+        let reqs = st.assign_ranges(Instant::now());
+        let (_, s1, e1) = reqs[0];
+        let (_, s2, e2) = reqs[1];
+        let body = |h: u64| (intro(h).0, vec![h as u8]);
+        // a wrong hash rejects the whole answer
+        let mut bad: Vec<_> = (s2..=e2).map(body).collect();
+        bad[3].0 = Hash::default();
+        assert!(st.accept_bodies(&a, s2, bad).is_err());
+        // a later range is buffered but not released before the first one
+        st.accept_bodies(&a, s2, (s2..=e2).map(body).collect()).unwrap();
+        assert!(st.take_ready().is_none());
+        // a short answer leaves the rest queued for reassignment
+        st.accept_bodies(&a, s1, (s1..s1 + 10).map(body).collect()).unwrap();
+        let (start, end, data) = st.take_ready().unwrap();
+        assert_eq!((start, end, data.len()), (1, 10, 10));
+        assert!(st.take_ready().is_none(), "one batch in flight at a time");
+        st.flushed(end);
+        assert_eq!(st.next_insert(), 11);
+        let again = st.assign_ranges(Instant::now());
+        assert!(again.contains(&(a, 11, e1)));
+        st.accept_bodies(&a, 11, (11..=e1).map(body).collect()).unwrap();
+        let (start, end, _) = st.take_ready().unwrap();
+        assert_eq!((start, end), (11, e2));
+    }
+
+    #[test]
+    fn dropped_peer_work_goes_back_to_the_queue() {
+        let (a, b) = ([1u8; 16], [2u8; 16]);
+        let mut st = state_with_intros(0, BODY_RANGE * 4, &[(a, 1000)]);
+        let first = st.assign_ranges(Instant::now());
+        assert!(first.iter().all(|(p, ..)| *p == a));
+        st.note_peer(b, 1000);
+        let starts: Vec<_> = first.iter().map(|(_, s, _)| *s).collect();
+        assert_eq!(st.drop_peer(&a), starts);
+        let moved = st.assign_ranges(Instant::now());
+        assert_eq!(moved.len(), RANGES_PER_PEER);
+        assert!(moved.iter().all(|(p, ..)| *p == b));
+        assert_eq!(moved[0].1, first[0].1);
+        // answers from the dropped peer are refused
+        let stale = vec![(intro(first[0].1).0, vec![])];
+        assert!(st.accept_bodies(&a, first[0].1, stale).is_err());
+    }
+
+    #[test]
+    fn legacy_marks_expire_and_late_answers_are_recognized() {
+        let (a, b) = ([1u8; 16], [2u8; 16]);
+        let sync = HeadersSync::new();
+        let now = Instant::now();
+        let later = |secs| now + std::time::Duration::from_secs(secs);
+        *sync.state.lock().unwrap() = Some(state_with_intros(0, BODY_RANGE * 4, &[(a, 1000)]));
+        let reqs = sync.with(|st| st.assign_ranges(now)).unwrap();
+        let stale = sync.with(|st| st.expire(later(REQUEST_TIMEOUT + 1))).unwrap();
+        assert_eq!(stale, vec![(a, reqs.iter().map(|(_, s, _)| *s).collect())]);
+        for (key, starts) in stale {
+            sync.mark_legacy(key, now);
+            sync.note_aborted(starts.into_iter().map(|s| (key, s)), now);
+        }
+        assert!(sync.is_legacy_at(&a, later(1)));
+        assert!(!sync.is_legacy_at(&b, later(1)));
+        assert!(!sync.is_legacy_at(&a, later(LEGACY_KEEP)));
+        // a late answer is known once, and only from the peer that was asked
+        let start = reqs[0].1;
+        assert!(!sync.take_aborted(&b, start, later(1)));
+        assert!(sync.take_aborted(&a, start, later(1)));
+        assert!(!sync.take_aborted(&a, start, later(1)));
+        assert!(!sync.take_aborted(&a, reqs[1].1, later(ABORTED_KEEP)));
+        // aborting the sync keeps its outstanding requests too
+        let reqs = sync.with(|st| {
+            st.note_peer(b, 1000);
+            st.assign_ranges(now)
+        });
+        sync.abort();
+        let (_, start, _) = reqs.unwrap()[0];
+        assert!(sync.take_aborted(&b, start, Instant::now()));
+    }
+}
@@ -7,9 +7,11 @@ pub struct RuntimeMetrics {
 impl RuntimeMetrics {
     pub fn on_start(&mut self) {
         self.start_count += 1;
+        basis::metrics::counter_add("hacash_node_starts_total", &[], 1);
     }
 
     pub fn on_exit(&mut self) {
         self.exit_count += 1;
+        basis::metrics::counter_add("hacash_node_exits_total", &[], 1);
     }
 }
@@ -8,31 +8,45 @@ use basis::interface::*;
 use field::*;
 use sys::*;
 use tokio::io::AsyncWriteExt;
-use tokio::net::TcpStream;
-use tokio::net::tcp::OwnedReadHalf;
 
 use crate::handler::*;
 use crate::p2p::*;
-use crate::peer::{KNOWLEDGE_SIZE, KnowKey, Knowledge, Peer, PeerKey};
+use crate::peer::{KNOWLEDGE_SIZE, KnowKey, Knowledge, Misbehave, PEER_BAN_SCORE, Peer, PeerKey, PeerReader};
 use crate::*;
 
 mod api;
+mod compact;
+mod headsync;
 mod metrics;
 mod network;
+mod proof;
 mod protocol;
 mod runtime;
 mod submit;
 mod sync;
 mod tasks;
 mod transport;
+mod txinv;
 
 pub use api::HacashNode;
+pub use compact::CompactRelay;
+pub use headsync::HeadersSync;
+pub use proof::{BalanceReply, LIGHT_BALANCE_MAX, TxProof, serialize_balance_request};
 pub use sync::SyncTracker;
+pub use txinv::TxRelay;
 
+pub(crate) use compact::{
+    broadcast_block_unaware, compact_expire, receive_block_txs, receive_compact_block, relay_block, send_block_txs,
+};
+pub(crate) use headsync::{
+    HEADERS_FIRST_MIN_GAP, headers_first_begin, headers_first_drive, receive_block_range,
+    receive_intros, send_block_range, send_intros,
+};
 pub(crate) use metrics::RuntimeMetrics;
+pub(crate) use proof::{send_balances, send_tx_proof};
 pub(crate) use protocol::{
     handle_new_block, handle_new_tx, receive_blocks, receive_hashs, receive_status, send_blocks,
-    send_hashs, send_status,
+    send_hashs, send_req_block_hash_msg, send_status,
 };
 pub(crate) use runtime::NodeRuntime;
 pub(crate) use tasks::TaskGroup;
@@ -40,3 +54,7 @@ pub(crate) use transport::{
     broadcast_unaware, connect_boot_nodes, connect_node, connect_stable_nodes,
     connect_stable_then_boot, event_loop, handle_conn, insert_peer,
 };
+pub(crate) use txinv::{
+    TX_INV_FLUSH_SECS, broadcast_tx_unaware, flush_tx_inventory, receive_tx_inv, relay_tx,
+    send_requested_txs,
+};
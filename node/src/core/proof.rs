@@ -0,0 +1,221 @@
+use super::*;
+use ::protocol::block::{block_create, calculate_mrkl_branch};
+use ::protocol::state::CoreStateRead;
+
+/*
+    Light client queries served by a full node.
+
+    MSG_REQ_TX_PROOF asks for one transaction by hash; the answer carries the
+    block height, the transaction's index, its merkle branch (hash-with-fee
+    leaves, as in the block `mrklroot`) and its body, so a light client can
+    check it against a block intro it verified itself. MSG_REQ_BALANCE asks for
+    account balances at the current head. Those cannot be proven without a
+    state commitment, so the answer names the head it was read at and the
+    client cross-checks it against its header chain.
+*/
+
+/// Addresses in one MSG_REQ_BALANCE.
+pub const LIGHT_BALANCE_MAX: usize = 200;
+
+/// A transaction with its merkle branch; `height` 0 means not on chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxProof {
+    pub hash: Hash,
+    pub height: u64,
+    pub index: u32,
+    pub branch: Vec<Hash>,
+    pub body: Vec<u8>,
+}
+
+impl TxProof {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = self.hash.to_vec();
+        buf.extend_from_slice(&self.height.to_be_bytes());
+        if self.height == 0 {
+            return buf;
+        }
+        buf.extend_from_slice(&self.index.to_be_bytes());
+        buf.push(self.branch.len() as u8);
+        for h in &self.branch {
+            buf.extend_from_slice(h.as_ref());
+        }
+        buf.extend_from_slice(&self.body);
+        buf
+    }
+
+    pub fn parse(buf: &[u8]) -> Ret<Self> {
+        if buf.len() < 32 + 8 {
+            return errf!("tx proof too short");
+        }
+        let hash = Hash::must(&buf[0..32]);
+        let height = u64::from_be_bytes(bufcut!(buf, 32, 40));
+        if height == 0 {
+            return Ok(Self { hash, ..Default::default() });
+        }
+        if buf.len() < 40 + 4 + 1 {
+            return errf!("tx proof too short");
+        }
+        let index = u32::from_be_bytes(bufcut!(buf, 40, 44));
+        let num = buf[44] as usize;
+        let mut seek = 45;
+        if buf.len() < seek + num * 32 {
+            return errf!("tx proof branch too short");
+        }
+        let branch = (0..num).map(|i| Hash::must(&buf[seek + i * 32..seek + i * 32 + 32])).collect();
+        seek += num * 32;
+        Ok(Self { hash, height, index, branch, body: buf[seek..].to_vec() })
+    }
+}
+
+/// Balances read at head `height` / `hash`, in request order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BalanceReply {
+    pub height: u64,
+    pub hash: Hash,
+    pub list: Vec<Balance>,
+}
+
+impl BalanceReply {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = self.height.to_be_bytes().to_vec();
+        buf.extend_from_slice(self.hash.as_ref());
+        buf.extend_from_slice(&(self.list.len() as u16).to_be_bytes());
+        for b in &self.list {
+            buf.append(&mut b.serialize());
+        }
+        buf
+    }
+
+    pub fn parse(buf: &[u8]) -> Ret<Self> {
+        if buf.len() < 8 + 32 + 2 {
+            return errf!("balance reply too short");
+        }
+        let height = u64::from_be_bytes(bufcut!(buf, 0, 8));
+        let hash = Hash::must(&buf[8..40]);
+        let num = u16::from_be_bytes(bufcut!(buf, 40, 42)) as usize;
+        if num > LIGHT_BALANCE_MAX {
+            return errf!("balance reply too long");
+        }
+        let mut seek = 42;
+        let mut list = Vec::with_capacity(num);
+        for _ in 0..num {
+            let mut bls = Balance::default();
+            seek += bls.parse(&buf[seek..])?;
+            list.push(bls);
+        }
+        Ok(Self { height, hash, list })
+    }
+}
+
+pub fn serialize_balance_request(addrs: &[Address]) -> Vec<u8> {
+    let mut buf = (addrs.len() as u16).to_be_bytes().to_vec();
+    for a in addrs {
+        buf.extend_from_slice(a.as_ref());
+    }
+    buf
+}
+
+fn parse_balance_request(buf: &[u8]) -> Option<Vec<Address>> {
+    if buf.len() < 2 {
+        return None;
+    }
+    let num = u16::from_be_bytes(bufcut!(buf, 0, 2)) as usize;
+    if num == 0 || num > LIGHT_BALANCE_MAX || buf.len() != 2 + num * Address::SIZE {
+        return None;
+    }
+    let mut addrs = Vec::with_capacity(num);
+    let mut seek = 2;
+    for _ in 0..num {
+        let mut adr = Address::default();
+        adr.parse(&buf[seek..]).ok()?;
+        seek += Address::SIZE;
+        addrs.push(adr);
+    }
+    Some(addrs)
+}
+
+fn build_tx_proof(eng: &dyn Engine, txhx: Hash) -> TxProof {
+    let missing = TxProof { hash: txhx, ..Default::default() };
+    let state = eng.state();
+    let Some(hei) = CoreStateRead::wrap(state.as_ref().as_ref()).tx_exist(&txhx) else {
+        return missing;
+    };
+    let Some((_, blkdts)) = eng.store().block_data_by_height(&hei) else {
+        return missing;
+    };
+    let Ok((blk, _)) = block_create(&blkdts) else {
+        return missing;
+    };
+    let txs = blk.transactions();
+    let Some(index) = txs.iter().position(|t| t.hash() == txhx) else {
+        return missing;
+    };
+    TxProof {
+        hash: txhx,
+        height: hei.uint(),
+        index: index as u32,
+        branch: calculate_mrkl_branch(&blk.transaction_hash_list(true), index),
+        body: txs[index].serialize(),
+    }
+}
+
+pub(crate) async fn send_tx_proof(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    if buf.len() != Hash::SIZE {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        return;
+    }
+    let txhx = Hash::must(&buf);
+    let eng = hdl.engine.clone();
+    let proof = tokio::task::spawn_blocking(move || build_tx_proof(eng.as_ref(), txhx))
+        .await
+        .unwrap();
+    let _ = peer.send_msg(MSG_TX_PROOF, proof.serialize()).await;
+}
+
+pub(crate) async fn send_balances(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    let Some(addrs) = parse_balance_request(&buf) else {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        return;
+    };
+    // read the head before the state, so the state is never older than it claims
+    let latest = hdl.engine.latest_block();
+    let state = hdl.engine.state();
+    let core = CoreStateRead::wrap(state.as_ref().as_ref());
+    let reply = BalanceReply {
+        height: latest.height().uint(),
+        hash: latest.hash(),
+        list: addrs.iter().map(|a| core.balance(a).unwrap_or_default()).collect(),
+    };
+    let _ = peer.send_msg(MSG_BALANCE, reply.serialize()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tx_proof_roundtrip() {
+        let proof = TxProof {
+            hash: Hash::from([1; 32]),
+            height: 9,
+            index: 3,
+            branch: vec![Hash::from([2; 32]), Hash::from([3; 32])],
+            body: vec![7, 8, 9],
+        };
+        assert_eq!(TxProof::parse(&proof.serialize()).unwrap(), proof);
+        let missing = TxProof { hash: Hash::from([1; 32]), ..Default::default() };
+        assert_eq!(TxProof::parse(&missing.serialize()).unwrap(), missing);
+        assert!(TxProof::parse(&proof.serialize()[..50]).is_err());
+    }
+
+    #[test]
+    fn balance_messages_roundtrip() {
+        let addrs = vec![Address::default(); 3];
+        assert_eq!(parse_balance_request(&serialize_balance_request(&addrs)).unwrap(), addrs);
+        assert!(parse_balance_request(&serialize_balance_request(&[])).is_none());
+        let mut bls = Balance::default();
+        bls.hacash = Amount::coin(5, 248);
+        let reply = BalanceReply { height: 4, hash: Hash::from([6; 32]), list: vec![bls, Balance::default()] };
+        assert_eq!(BalanceReply::parse(&reply.serialize()).unwrap(), reply);
+    }
+}
@@ -81,6 +81,9 @@ pub(crate) async fn get_status_try_sync_blocks(
     starthei: u64,
     remote_height: u64,
 ) {
+    if hdl.head_sync.is_active() {
+        return;
+    }
     let prevdo = hdl.doing_sync.load(Ordering::Relaxed);
     if prevdo + 2 > curtimes() {
         if !hdl
@@ -135,17 +138,25 @@ pub(crate) async fn send_status(hdl: &MsgHandler, peer: Arc<Peer>) {
 pub(crate) async fn receive_status(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
     let status = HandshakeStatus::create(&buf);
     if status.is_err() {
+        hdl.penalize(&peer, Misbehave::BadHandshake);
         peer.disconnect();
         return;
     }
     let (status, _) = status.unwrap();
     let my_status = create_status(hdl);
     if status.genesis_hash != my_status.genesis_hash {
+        // a node of another chain, not a fault: just part
+        println!("[Peer] {} runs another chain, disconnect.", peer.name());
         peer.disconnect();
         return;
     }
     let tar_hei = *status.latest_height;
     let my_hei = *my_status.latest_height;
+    let far_ahead = tar_hei > my_hei + HEADERS_FIRST_MIN_GAP;
+    if !hdl.head_sync.is_legacy(&peer) && (far_ahead || hdl.head_sync.is_active()) {
+        headers_first_begin(hdl, peer, tar_hei).await;
+        return;
+    }
     if my_hei == 0 && tar_hei > 0 {
         let start_hei = 1;
         get_status_try_sync_blocks(hdl, peer, start_hei, tar_hei).await;
@@ -203,6 +214,7 @@ pub(crate) async fn receive_hashs(hdl: &MsgHandler, peer: Arc<Peer>, mut buf: Ve
     let end_hei = u64::from_be_bytes(bufcut!(buf, 0, 8));
     let hash_len = hashs.len();
     if hash_len == 0 || hash_len % 32 != 0 {
+        hdl.penalize(&peer, Misbehave::Malformed);
         return;
     }
     let mut hash_num = hash_len as u64 / 32;
@@ -234,6 +246,8 @@ pub(crate) async fn receive_hashs(hdl: &MsgHandler, peer: Arc<Peer>, mut buf: Ve
         }
         hi += 32;
     }
+    // no common block within the unstable span: a fork or an old node, not a fault
+    println!("[Sync] no common block with {} within the last {} blocks.", peer.name(), hash_num);
 }
 
 pub(crate) async fn send_blocks(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
@@ -276,9 +290,10 @@ pub(crate) async fn send_blocks(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>)
 pub(crate) async fn receive_blocks(hdl: &MsgHandler, peer: Arc<Peer>, mut buf: Vec<u8>) {
     if buf.len() < 3 * 8 {
         println!("data check failed");
+        hdl.penalize(&peer, Misbehave::Malformed);
         return;
     }
-    let blocks = buf.split_off(3 * 8);
+    let blocks = Arc::new(buf.split_off(3 * 8));
     let latest_hei = u64::from_be_bytes(bufcut!(buf, 0, 8));
     let _start_hei = u64::from_be_bytes(bufcut!(buf, 8, 16));
     let end_hei = u64::from_be_bytes(bufcut!(buf, 16, 24));
@@ -288,12 +303,17 @@ pub(crate) async fn receive_blocks(hdl: &MsgHandler, peer: Arc<Peer>, mut buf: V
     let res = tokio::task::spawn_blocking(move || {
         let _lk = inserting.lock().unwrap();
         flush!("{}({:.2}%) inserting...", end_hei, persent);
-        eng.synchronize(blocks)
+        // the engine shares the batch, only looked into again if it fails
+        let res = eng.synchronize(blocks.clone());
+        res.map_err(|e| (e, blocks_fault(eng.minter(), &blocks)))
     })
     .await
     .unwrap();
-    if let Err(e) = res {
+    if let Err((e, fault)) = res {
         println!("{}", e);
+        if let Some(what) = fault {
+            hdl.penalize(&peer, what);
+        }
         return;
     }
     println!("ok.");
@@ -317,11 +337,13 @@ pub(crate) async fn handle_new_tx(
     let engcnf = hdl.engine.config();
     let minter = hdl.engine.minter();
     let Ok(txpkg) = protocol::transaction::build_tx_package(body) else {
+        blame(&hdl, &peer, Misbehave::Malformed);
         return errf!("tx parse failed");
     };
     let hxfe = txpkg.tx().hash_with_fee();
     // println!("handle_new_tx: {:?}", hxfe.to_hex());
-    let (already, knowkey) = check_know(&hdl.knows, &hxfe, peer.clone());
+    let (already, knowkey) = check_know(&hdl.knows, &hxfe, peer.as_ref().map(|p| &p.tx_knows));
+    hdl.tx_relay.arrived(&knowkey);
     if already {
         if peer.is_some() || hdl.txpool.find(&txpkg.hash()).is_some() {
             return Ok(());
@@ -339,14 +361,17 @@ pub(crate) async fn handle_new_tx(
     }
     let txdatas = txpkg.data().to_vec();
     let txpr = txpkg.tx_read();
-    hdl.engine.try_execute_tx(txpr)?;
+    if let Err(e) = hdl.engine.try_execute_tx(txpr) {
+        // balances, nonces and known txs depend on our state, a bad signature does not
+        if txpr.verify_signature().is_err() {
+            blame(&hdl, &peer, Misbehave::InvalidTx);
+        }
+        return Err(e);
+    }
     minter.tx_submit(hdl.engine.as_read(), &txpkg)?;
     hdl.txpool
         .insert_by(txpkg, &|tx| minter.tx_pool_group(tx))?;
-    let p2p = hdl.p2pmng.lock().unwrap();
-    if let Some(p2p) = p2p.as_ref() {
-        p2p.broadcast_message(0, knowkey, MSG_TX_SUBMIT, txdatas);
-    }
+    relay_tx(&hdl, knowkey, txdatas);
     Ok(())
 }
 
@@ -358,16 +383,18 @@ pub(crate) async fn handle_new_block(
     let eng = hdl.engine.clone();
     let engcnf = eng.config();
     if body.len() > engcnf.max_block_size + 100 {
+        blame(&hdl, &peer, Misbehave::InvalidBlock);
         return errf!("block size exceeds max_block_size");
     }
     let mut blkhead = protocol::block::BlockIntro::default();
     if let Err(..) = blkhead.parse(&body) {
+        blame(&hdl, &peer, Misbehave::Malformed);
         return errf!("block intro parse failed");
     }
     let blkhei = blkhead.height().uint();
     let blkhx = blkhead.hash();
     let sto = eng.store();
-    let (already, knowkey) = check_know(&hdl.knows, &blkhx, peer.clone());
+    let (already, knowkey) = check_know(&hdl.knows, &blkhx, peer.as_ref().map(|p| &p.knows));
     // println!("knows: {:?}", hdl.knows);
     // println!("handle_new_block {} already: {}", blkhx.to_hex(), already);
     if already {
@@ -386,12 +413,14 @@ pub(crate) async fn handle_new_block(
     let mintckr = eng.minter();
     if let Some(ret) = mintckr.blk_found(&blkhead, &body, sto.as_ref()) {
         match ret {
-            RetBlkFound::Reject => return errf!("block rejected by blk_found"),
-            RetBlkFound::PendingCached => {
-                let p2p = hdl.p2pmng.lock().unwrap();
-                if let Some(p2p) = p2p.as_ref() {
-                    p2p.broadcast_message(0, knowkey, MSG_BLOCK_DISCOVER, body);
+            RetBlkFound::Reject => {
+                if mintckr.blk_pow_fault(&blkhead) {
+                    blame(&hdl, &peer, Misbehave::InvalidBlock);
                 }
+                return errf!("block rejected by blk_found");
+            }
+            RetBlkFound::PendingCached => {
+                relay_block(&hdl, knowkey, body);
                 return Ok(());
             }
             RetBlkFound::Normal => {}
@@ -429,10 +458,19 @@ pub(crate) async fn handle_new_block(
         );
     }
     if let Err(..) = mintckr.blk_arrive(&blkhead, &body, sto.as_ref()) {
+        if mintckr.blk_pow_fault(&blkhead) {
+            blame(&hdl, &peer, Misbehave::InvalidBlock);
+        }
         return errf!("block arrive check failed");
     }
     let blkpkg = protocol::block::build_block_package(body.clone());
-    let mut blkp = blkpkg.map_err(|e| format!("block parse failed: {}", e))?;
+    let mut blkp = match blkpkg {
+        Ok(b) => b,
+        Err(e) => {
+            blame(&hdl, &peer, Misbehave::Malformed);
+            return errf!("block parse failed: {}", e);
+        }
+    };
     blkp.set_origin(BlkOrigin::Discover);
     let hxstrt = blkhx.as_bytes()[4..12].to_vec();
     let hxtail = blkhx.as_bytes()[30..].to_vec();
@@ -467,19 +505,61 @@ pub(crate) async fn handle_new_block(
     .await
     .unwrap();
     if res.is_err() {
+        if let Some(what) = blocks_fault(mintckr, &body) {
+            blame(&hdl, &peer, what);
+        }
         return res;
     }
-    let p2p = hdl.p2pmng.lock().unwrap();
-    if let Some(p2p) = p2p.as_ref() {
-        p2p.broadcast_message(0, knowkey, MSG_BLOCK_DISCOVER, body);
-    }
+    relay_block(&hdl, knowkey, body);
     Ok(())
 }
 
-fn check_know(mine: &Knowledge, hxkey: &Hash, peer: Option<Arc<Peer>>) -> (bool, KnowKey) {
+/*
+    Only faults a block shows by itself are held against the peer that sent
+    it: a hash above its own difficulty target, a broken tx list, a mrkl root
+    mismatch or a bad tx signature. Failures that depend on our view of the
+    chain (unknown parent, known block, moved root, timestamps, execution
+    against our state) also happen to honest peers in a race.
+*/
+pub(crate) fn block_fault(minter: &dyn Minter, blk: &dyn BlockRead) -> bool {
+    if minter.blk_pow_fault(blk) {
+        return true;
+    }
+    let txs = blk.transactions();
+    if txs.is_empty() || txs.len() != blk.transaction_count().uint() as usize {
+        return true;
+    }
+    if protocol::block::calculate_mrklroot(&blk.transaction_hash_list(true)) != *blk.mrklroot() {
+        return true;
+    }
+    txs.iter().skip(1).any(|tx| tx.action_count() != tx.actions().len() || tx.verify_signature().is_err())
+}
+
+/// The penalty earned by `datas`, one or more blocks back to back, if any.
+pub(crate) fn blocks_fault(minter: &dyn Minter, datas: &[u8]) -> Option<Misbehave> {
+    let mut seek = 0;
+    while seek < datas.len() {
+        let Ok((blk, size)) = protocol::block::block_create(&datas[seek..]) else {
+            return Some(Misbehave::Malformed);
+        };
+        if block_fault(minter, blk.as_read()) {
+            return Some(Misbehave::InvalidBlock);
+        }
+        seek += size;
+    }
+    None
+}
+
+fn blame(hdl: &MsgHandler, peer: &Option<Arc<Peer>>, what: Misbehave) {
+    if let Some(pr) = peer {
+        hdl.penalize(pr, what);
+    }
+}
+
+fn check_know(mine: &Knowledge, hxkey: &Hash, seen_by: Option<&Knowledge>) -> (bool, KnowKey) {
     let knowkey: [u8; KNOWLEDGE_SIZE] = hxkey.clone().into_array();
-    if let Some(ref pr) = peer {
-        pr.knows.add(knowkey.clone());
+    if let Some(pk) = seen_by {
+        pk.add(knowkey.clone());
     }
     if mine.check(&knowkey) {
         return (true, knowkey);
@@ -505,3 +585,45 @@ fn may_show_miner_detail(engcnf: &EngineConf, blkp: &BlkPkg) -> String {
         .unwrap_or_else(|| s!(""));
     format!("miner: {}...<{}> ", adrt, message)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::protocol::block::BlockV1;
+    use ::protocol::transaction::DefaultPreludeTx;
+
+    struct PowMinter(bool);
+
+    impl Minter for PowMinter {
+        fn blk_pow_fault(&self, _: &dyn BlockRead) -> bool {
+            self.0
+        }
+    }
+
+    fn block() -> BlockV1 {
+        let mut blk = BlockV1::default();
+        blk.intro.head.version = Uint1::from(BlockV1::VERSION);
+        blk.transactions.push(Box::new(DefaultPreludeTx::default())).unwrap();
+        blk.intro.head.transaction_count = Uint4::from(1);
+        blk.intro.set_mrklroot(protocol::block::calculate_mrklroot(&blk.transaction_hash_list(true)));
+        blk
+    }
+
+    #[test]
+    fn only_self_evident_block_faults_are_blamed() {
+        let _guard = protocol::setup::install_test_scope(protocol::setup::new_standard_protocol_setup(|_, s| calculate_hash(s)));
+        let blk = block();
+        assert!(!block_fault(&PowMinter(false), &blk));
+        assert!(block_fault(&PowMinter(true), &blk));
+        assert_eq!(blocks_fault(&PowMinter(false), &blk.serialize()), None);
+        assert_eq!(blocks_fault(&PowMinter(false), &[1, 2, 3]), Some(Misbehave::Malformed));
+
+        let mut bad = block();
+        bad.intro.set_mrklroot(Hash::default());
+        assert!(block_fault(&PowMinter(false), &bad));
+        assert_eq!(blocks_fault(&PowMinter(false), &[blk.serialize(), bad.serialize()].concat()), Some(Misbehave::InvalidBlock));
+        bad = block();
+        bad.intro.head.transaction_count = Uint4::from(2);
+        assert!(block_fault(&PowMinter(false), &bad));
+    }
+}
@@ -11,16 +11,19 @@ pub struct NodeRuntime {
     pub(super) tasks: Arc<TaskGroup>,
     pub(super) metrics: Arc<StdMutex<RuntimeMetrics>>,
     pub(super) exited: AtomicBool,
+    exiter: StdMutex<Option<Exiter>>,
 }
 
 impl NodeRuntime {
-    pub fn open(ini: &IniObj, txpool: Arc<dyn TxPool>, engine: Arc<dyn Engine>) -> Self {
+    pub fn open(ini: &IniObj, txpool: Arc<dyn TxPool>, engine: Arc<dyn Engine>, memnet: Option<MemEndpoint>) -> Self {
         let cnf = NodeConf::new(ini);
         let msghdl = Arc::new(MsgHandler::new(engine.clone(), txpool.clone()));
-        let p2p = Arc::new(P2PManage::new(&cnf, msghdl.clone()));
+        msghdl.reputation.open(join_path(&cnf.data_dir, "banned.peers"));
+        let p2p = Arc::new(P2PManage::new(&cnf, msghdl.clone(), memnet));
         msghdl.set_p2p_mng(Box::new(PeerMngInst::new(p2p.clone())));
         let protocol = ProtocolAdapter::new(msghdl.clone());
         let transport = TransportAdapter::new(&cnf, p2p.clone());
+        register_txpool_metrics(&txpool);
         Self {
             engine,
             txpool,
@@ -30,10 +33,12 @@ impl NodeRuntime {
             tasks: TaskGroup::new(),
             metrics: Arc::new(StdMutex::new(RuntimeMetrics::default())),
             exited: AtomicBool::new(false),
+            exiter: StdMutex::new(None),
         }
     }
 
     pub fn start(&self, worker: Worker) {
+        *self.exiter.lock().unwrap() = Some(worker.exiter());
         self.start_network(worker)
     }
 
@@ -70,6 +75,82 @@ impl NodeRuntime {
         self.transport.peer_prints()
     }
 
+    pub fn peer_reputes(&self) -> Vec<PeerRepute> {
+        let rep = &self.msghdl.reputation;
+        let scored = rep.scores().into_iter().map(|(key, ip, name, score, last)| PeerRepute {
+            ip: ip.to_string(),
+            key: hex::encode(key),
+            name,
+            score,
+            reason: last.to_owned(),
+            banned_until: 0,
+        });
+        let banned = rep.bans().into_iter().map(|b| PeerRepute {
+            ip: b.ip.to_string(),
+            key: hex::encode(b.key),
+            name: b.name,
+            score: PEER_BAN_SCORE,
+            reason: b.reason,
+            banned_until: b.until,
+        });
+        banned.chain(scored).collect()
+    }
+
+    pub fn peer_unban(&self, target: &str) -> Ret<usize> {
+        match self.msghdl.reputation.unban(target) {
+            0 => errf!("no ban matches '{}'", target),
+            n => Ok(n),
+        }
+    }
+
+    pub fn peer_details(&self) -> Vec<PeerInfo> {
+        self.transport
+            .peers()
+            .into_iter()
+            .map(|p| PeerInfo {
+                key: hex::encode(p.key),
+                name: p.name.clone(),
+                addr: p.addr.to_string(),
+                public: p.is_public,
+                inbound: p.is_cntome,
+                secure: p.secure,
+                active: p
+                    .active
+                    .lock()
+                    .unwrap()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+            })
+            .collect()
+    }
+
+    pub fn peer_connect(&self, addr: SocketAddr) -> Rerr {
+        self.transport.connect(addr)
+    }
+
+    pub fn peer_disconnect(&self, target: &str) -> Ret<usize> {
+        match self.transport.disconnect(target) {
+            0 => errf!("no connected peer matches '{}'", target),
+            n => Ok(n),
+        }
+    }
+
+    pub fn peer_unpin(&self, target: &str) -> Ret<usize> {
+        match self.transport.unpin(target) {
+            0 => errf!("no pin matches '{}'", target),
+            n => Ok(n),
+        }
+    }
+
+    pub fn shutdown(&self) -> Rerr {
+        let Some(exiter) = self.exiter.lock().unwrap().clone() else {
+            return errf!("node is not running");
+        };
+        // the exit broadcast blocks until every worker took it
+        std::thread::spawn(move || exiter.exit());
+        Ok(())
+    }
+
     pub fn running_task_count(&self) -> usize {
         self.tasks.running()
     }
@@ -78,3 +159,22 @@ impl NodeRuntime {
         self.stop_network()
     }
 }
+
+/// Group ids are probed upward until the pool rejects one.
+const TXPOOL_METRIC_GROUPS: usize = 16;
+
+fn register_txpool_metrics(txpool: &Arc<dyn TxPool>) {
+    let txpool = Arc::downgrade(txpool);
+    basis::metrics::register_collector(move || {
+        let Some(txpool) = txpool.upgrade() else {
+            return;
+        };
+        for gi in 0..TXPOOL_METRIC_GROUPS {
+            let Ok(n) = txpool.count_at(gi) else {
+                break;
+            };
+            let group = gi.to_string();
+            basis::metrics::gauge_set("hacash_txpool_txs", &[("group", &group)], n as f64);
+        }
+    });
+}
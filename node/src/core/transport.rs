@@ -29,6 +29,50 @@ impl TransportAdapter {
         self.p2p.all_peer_prints()
     }
 
+    pub(super) fn peers(&self) -> Vec<Arc<Peer>> {
+        [self.p2p.backbones(), self.p2p.offshoots()].concat()
+    }
+
+    pub(super) fn connect(&self, addr: SocketAddr) -> Rerr {
+        let Some(rt) = self.p2p.runtime() else {
+            return errf!("p2p is not running");
+        };
+        if self.p2p.msghandler.reputation.is_banned_ip(&addr.ip()) {
+            return errf!("peer {} is banned", addr.ip());
+        }
+        let p2p = self.p2p.clone();
+        rt.spawn(async move {
+            if let Err(e) = connect_node(&p2p, addr).await {
+                println!("[P2P Error] Connect to {}, {}", &addr, e);
+            }
+        });
+        Ok(())
+    }
+
+    /// Close peers matching an IP, an IP:port or a hex node key.
+    pub(super) fn disconnect(&self, target: &str) -> usize {
+        let sock = target.parse::<SocketAddr>().ok();
+        let ip = target.parse::<std::net::IpAddr>().ok();
+        let key = target.to_lowercase();
+        let mut n = 0;
+        for peer in self.peers() {
+            let hit = match (sock, ip) {
+                (Some(sock), _) => peer.addr == sock,
+                (_, Some(ip)) => peer.addr.ip() == ip,
+                _ => hex::encode(peer.key) == key,
+            };
+            if hit {
+                peer.disconnect();
+                n += 1;
+            }
+        }
+        n
+    }
+
+    pub(super) fn unpin(&self, target: &str) -> usize {
+        self.p2p.pins.clear(target)
+    }
+
     pub(super) fn exit(&self) {
         self.p2p.exit();
     }
@@ -101,30 +145,55 @@ pub(crate) async fn connect_boot_nodes(p2p: &P2PManage) -> Rerr {
 }
 
 pub(crate) async fn connect_node(p2p: &P2PManage, addr: SocketAddr) -> Ret<Arc<Peer>> {
-    let conn = tcp_dial_connect(addr, 6).await?;
+    if p2p.msghandler.reputation.is_banned_ip(&addr.ip()) {
+        return errf!("peer {} is banned", addr.ip());
+    }
+    let conn: Box<dyn PeerConn> = match &p2p.memnet {
+        Some(net) => Box::new(net.dial(addr).map_err(|e| e.to_string())?),
+        None => Box::new(tcp_dial_connect(addr, 6).await?),
+    };
     handle_conn(p2p, conn, true).await
 }
 
 pub(crate) async fn handle_conn(
     p2p: &P2PManage,
-    mut conn: TcpStream,
+    mut conn: Box<dyn PeerConn>,
     report_me: bool,
 ) -> Ret<Arc<Peer>> {
     tcp_check_handshake(&mut conn, 5).await?;
     let mynodeinfo = p2p.pick_my_node_info();
-    let mndf = mynodeinfo.clone();
+    let mut offer = SecureOffer {
+        identity: p2p.identity.clone(),
+        only: p2p.cnf.encrypt_only,
+        sent: None,
+    };
     if report_me {
-        tcp_send_msg(&mut conn, MSG_REPORT_PEER, mndf).await?;
+        let mut report = mynodeinfo.clone();
+        if let Some(idt) = &offer.identity {
+            let hello = SecureHello::new(idt)?;
+            report.extend_from_slice(&hello.bytes);
+            offer.sent = Some((hello, report.clone()));
+        }
+        tcp_send_msg(&mut conn, MSG_REPORT_PEER, report).await?;
     }
-    insert_peer(p2p, conn, mynodeinfo).await
+    insert_peer(p2p, conn, mynodeinfo, offer).await
 }
 
 pub(crate) async fn insert_peer(
     p2p: &P2PManage,
-    conn: TcpStream,
+    conn: Box<dyn PeerConn>,
     mynodeinfo: Vec<u8>,
+    offer: SecureOffer,
 ) -> Ret<Arc<Peer>> {
-    let (peer, conn_read) = try_create_peer(p2p, conn, mynodeinfo).await?;
+    let (peer, conn_read) = try_create_peer(p2p, conn, mynodeinfo, offer).await?;
+    if p2p.msghandler.reputation.is_banned(&peer) {
+        peer.disconnect();
+        return errf!("peer {} is banned", peer.nick());
+    }
+    if let Err(e) = p2p.pins.check(&peer.key, peer.identity.as_ref()) {
+        peer.disconnect();
+        return errf!("peer {} rejected, {}", peer.nick(), e);
+    }
     let dropeds = p2p.insert(peer.clone()).await?;
     p2p.delay_close_peers(dropeds, 15).await;
     handle_peer_message(p2p, peer.clone(), conn_read).await?;
@@ -133,9 +202,10 @@ pub(crate) async fn insert_peer(
 
 pub(crate) async fn try_create_peer(
     p2p: &P2PManage,
-    mut stream: TcpStream,
+    mut stream: Box<dyn PeerConn>,
     mynodeinfo: Vec<u8>,
-) -> Ret<(Arc<Peer>, OwnedReadHalf)> {
+    offer: SecureOffer,
+) -> Ret<(Arc<Peer>, PeerReader)> {
     let conn = &mut stream;
     let (ty, body) = tcp_read_msg(conn, 5).await?;
     if MSG_REMIND_ME_IS_PUBLIC == ty {
@@ -151,13 +221,13 @@ pub(crate) async fn try_create_peer(
         let _ = AsyncWriteExt::write_all(conn, &retbts).await;
         return errf!("ok");
     }
-    Peer::create_with_msg(stream, ty, body, mynodeinfo).await
+    Peer::create_with_msg(stream, ty, body, mynodeinfo, offer).await
 }
 
 pub(crate) async fn handle_peer_message(
     p2p: &P2PManage,
     peer: Arc<Peer>,
-    conn_read: OwnedReadHalf,
+    conn_read: PeerReader,
 ) -> Rerr {
     let peer1 = peer.clone();
     let peer2 = peer.clone();
@@ -183,7 +253,7 @@ pub(crate) async fn do_handle_pmsg(
     peertabletx: PeerTableCmdTx,
     msghdl: Arc<MsgHandler>,
     peer: Arc<Peer>,
-    mut conn_read: OwnedReadHalf,
+    mut conn_read: PeerReader,
 ) {
     {
         let peersnap = peersnaprx.borrow().clone();
@@ -199,7 +269,7 @@ pub(crate) async fn do_handle_pmsg(
             _ = peer.close_notify.notified() => {
                 break
             }
-            rd = tcp_read_msg(&mut conn_read, 0) => rd,
+            rd = conn_read.read_msg(0) => rd,
         };
         if let Err(_) = rdres {
             break;
@@ -236,7 +306,8 @@ pub(crate) async fn event_loop(p2p: Arc<P2PManage>, mut worker: Worker) -> Rerr
     let mut findnodes_tkr = new_ticker(52 * 60 * 4).await;
     let mut checkpeer_tkr = new_ticker(53 * 3).await;
     let mut boostndes_tkr = new_ticker(54 * 5).await;
-    let server_listener = match p2p.server().await {
+    let mut txinv_tkr = new_ticker(TX_INV_FLUSH_SECS).await;
+    let mut server_listener = match p2p.server().await {
         Ok(l) => l,
         Err(ref e) => {
             let e = format!("p2p failed to bind port {}: {}", p2p.cnf.listen, e);
@@ -270,6 +341,11 @@ pub(crate) async fn event_loop(p2p: Arc<P2PManage>, mut worker: Worker) -> Rerr
             _ = checkpeer_tkr.tick() => {
                 p2p.check_active_nodes().await;
                 p2p.ping_nodes().await;
+                headers_first_drive(&p2p.msghandler).await;
+            },
+            _ = txinv_tkr.tick() => {
+                flush_tx_inventory(&p2p).await;
+                compact_expire(&p2p.msghandler).await;
             },
             _ = boostndes_tkr.tick() => {
                 p2p.boost_public().await;
@@ -278,12 +354,16 @@ pub(crate) async fn event_loop(p2p: Arc<P2PManage>, mut worker: Worker) -> Rerr
                 }
             },
             client = server_listener.accept() => {
-                let Ok((client, _)) = terrunbox!( client ) else {
+                let Ok(client) = terrunbox!( client ) else {
                     continue
                 };
                 if !p2p.cnf.accept_nodes {
                     continue
                 }
+                let banned = client.peer_addr().map(|a| p2p.msghandler.reputation.is_banned_ip(&a.ip()));
+                if banned.unwrap_or(false) {
+                    continue
+                }
                 let tobj = p2p.clone();
                 tokio::spawn(async move {
                     let _ = handle_conn(&tobj, client, false).await;
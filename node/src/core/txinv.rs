@@ -0,0 +1,223 @@
+use super::*;
+use std::collections::{HashMap, VecDeque};
+
+/*
+    Inventory-based transaction relay.
+
+    Peers that announced MSG_SEND_TX_INV get new transactions as batches of
+    hash-with-fee inventory (MSG_TX_INV), flushed on a short timer, and fetch the
+    bodies they lack with MSG_REQ_TXS; bodies are answered as ordinary
+    MSG_TX_SUBMIT. Which transactions each peer already has or was told about is
+    tracked in its bounded `tx_knows`, so a body crosses every link at most once.
+    Legacy peers keep receiving full MSG_TX_SUBMIT floods.
+*/
+
+/// Hashes in one MSG_TX_INV or MSG_REQ_TXS.
+pub(crate) const TX_INV_MAX: usize = 1000;
+/// Seconds between inventory flushes.
+pub(crate) const TX_INV_FLUSH_SECS: u64 = 2;
+/// Relayed transaction bodies kept to answer MSG_REQ_TXS.
+const RELAY_TXS: usize = 4096;
+const RELAY_BYTES: usize = 32 * 1024 * 1024;
+/// Seconds before a transaction requested from one peer may be asked of another.
+const TX_REQUEST_TIMEOUT: u64 = 20;
+
+#[derive(Default)]
+struct RelayCache {
+    order: VecDeque<KnowKey>,
+    txs: HashMap<KnowKey, Arc<Vec<u8>>>,
+    bytes: usize,
+}
+
+/// Transactions requested and when, in request order so the expired ones
+/// can be dropped from the front.
+#[derive(Default)]
+struct Requested {
+    order: VecDeque<(u64, KnowKey)>,
+    at: HashMap<KnowKey, u64>,
+}
+
+#[derive(Default)]
+pub struct TxRelay {
+    cache: StdMutex<RelayCache>,
+    requested: StdMutex<Requested>,
+}
+
+impl TxRelay {
+    /// Keep a relayed body, dropping the oldest ones past the count or byte bound.
+    fn keep(&self, key: KnowKey, body: Arc<Vec<u8>>) {
+        let mut c = self.cache.lock().unwrap();
+        if c.txs.contains_key(&key) {
+            return;
+        }
+        c.bytes += body.len();
+        c.txs.insert(key, body);
+        c.order.push_back(key);
+        while c.order.len() > RELAY_TXS || c.bytes > RELAY_BYTES {
+            let Some(old) = c.order.pop_front() else {
+                break;
+            };
+            if let Some(b) = c.txs.remove(&old) {
+                c.bytes -= b.len();
+            }
+        }
+    }
+
+    fn get(&self, key: &KnowKey) -> Option<Arc<Vec<u8>>> {
+        self.cache.lock().unwrap().txs.get(key).cloned()
+    }
+
+    /// Whether `key` should be requested now; marks it in flight if so.
+    fn want(&self, key: KnowKey, now: u64) -> bool {
+        let mut req = self.requested.lock().unwrap();
+        while let Some(&(t, old)) = req.order.front() {
+            if t + TX_REQUEST_TIMEOUT > now {
+                break;
+            }
+            req.order.pop_front();
+            // a newer request or an arrival may have replaced it
+            if req.at.get(&old) == Some(&t) {
+                req.at.remove(&old);
+            }
+        }
+        if req.at.contains_key(&key) {
+            return false;
+        }
+        req.at.insert(key, now);
+        req.order.push_back((now, key));
+        true
+    }
+
+    /// A transaction body arrived, from a request or not.
+    pub(crate) fn arrived(&self, key: &KnowKey) {
+        self.requested.lock().unwrap().at.remove(key);
+    }
+}
+
+/// Announce a transaction accepted into the pool to peers that don't have it.
+pub(crate) fn relay_tx(hdl: &MsgHandler, knowkey: KnowKey, body: Vec<u8>) {
+    hdl.tx_relay.keep(knowkey, Arc::new(body.clone()));
+    let p2p = hdl.p2pmng.lock().unwrap();
+    if let Some(p2p) = p2p.as_ref() {
+        p2p.broadcast_tx(knowkey, body);
+    }
+}
+
+pub(crate) async fn broadcast_tx_unaware(p2p: &P2PManage, key: &KnowKey, body: Vec<u8>) {
+    let msgbuf = tcp_create_msg(MSG_CUSTOMER, [MSG_TX_SUBMIT.to_be_bytes().to_vec(), body].concat());
+    let peers = [p2p.backbones(), p2p.offshoots()].concat();
+    for peer in peers {
+        if peer.tx_knows.check(key) {
+            continue;
+        }
+        peer.tx_knows.add(*key);
+        if peer.tx_inv.load(Ordering::Relaxed) {
+            peer.queue_inv(*key);
+        } else {
+            let _ = peer.send(&msgbuf).await;
+        }
+    }
+}
+
+/// Send every peer the inventory queued for it since the last flush.
+pub(crate) async fn flush_tx_inventory(p2p: &P2PManage) {
+    let peers = [p2p.backbones(), p2p.offshoots()].concat();
+    for peer in peers {
+        for keys in peer.take_inv().chunks(TX_INV_MAX) {
+            let _ = peer.send_msg(MSG_TX_INV, keys.concat()).await;
+        }
+    }
+}
+
+fn parse_tx_inv(buf: &[u8]) -> Option<Vec<KnowKey>> {
+    if buf.is_empty() || buf.len() % KNOWLEDGE_SIZE != 0 || buf.len() / KNOWLEDGE_SIZE > TX_INV_MAX {
+        return None;
+    }
+    Some(buf.chunks(KNOWLEDGE_SIZE).map(|k| bufcut!(k, 0, KNOWLEDGE_SIZE)).collect())
+}
+
+pub(crate) async fn receive_tx_inv(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    let Some(keys) = parse_tx_inv(&buf) else {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        return;
+    };
+    let now = curtimes();
+    let mut wants = vec![];
+    for key in keys {
+        peer.tx_knows.add(key);
+        if !hdl.knows.check(&key) && hdl.tx_relay.want(key, now) {
+            wants.extend_from_slice(&key);
+        }
+    }
+    if !wants.is_empty() {
+        let _ = peer.send_msg(MSG_REQ_TXS, wants).await;
+    }
+}
+
+pub(crate) async fn send_requested_txs(hdl: &MsgHandler, peer: Arc<Peer>, buf: Vec<u8>) {
+    let Some(keys) = parse_tx_inv(&buf) else {
+        hdl.penalize(&peer, Misbehave::Malformed);
+        return;
+    };
+    for key in keys {
+        if let Some(body) = hdl.tx_relay.get(&key) {
+            peer.tx_knows.add(key);
+            let _ = peer.send_msg(MSG_TX_SUBMIT, body.to_vec()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relay_cache_is_bounded() {
+        let relay = TxRelay::default();
+        for i in 0..(RELAY_TXS + 10) as u32 {
+            let mut key = [0u8; KNOWLEDGE_SIZE];
+            key[..4].copy_from_slice(&i.to_be_bytes());
+            relay.keep(key, Arc::new(vec![1; 10]));
+        }
+        assert!(relay.get(&[0u8; KNOWLEDGE_SIZE]).is_none());
+        assert_eq!(relay.cache.lock().unwrap().txs.len(), RELAY_TXS);
+        // one huge body pushes out everything older
+        relay.keep([9u8; KNOWLEDGE_SIZE], Arc::new(vec![0; RELAY_BYTES]));
+        let c = relay.cache.lock().unwrap();
+        assert_eq!((c.txs.len(), c.bytes), (1, RELAY_BYTES));
+    }
+
+    #[test]
+    fn requests_are_not_repeated_until_timeout() {
+        let relay = TxRelay::default();
+        let key = [3u8; KNOWLEDGE_SIZE];
+        assert!(relay.want(key, 100));
+        assert!(!relay.want(key, 101));
+        assert!(relay.want(key, 100 + TX_REQUEST_TIMEOUT));
+        relay.arrived(&key);
+        assert!(relay.want(key, 121));
+    }
+
+    #[test]
+    fn expired_requests_are_dropped() {
+        let relay = TxRelay::default();
+        for i in 0..10u8 {
+            assert!(relay.want([i; KNOWLEDGE_SIZE], 100 + i as u64));
+        }
+        relay.arrived(&[0u8; KNOWLEDGE_SIZE]);
+        // asking anything later lets the timed out ones go
+        assert!(relay.want([99u8; KNOWLEDGE_SIZE], 105 + TX_REQUEST_TIMEOUT));
+        let req = relay.requested.lock().unwrap();
+        assert_eq!((req.at.len(), req.order.len()), (5, 5));
+        assert!(!req.at.contains_key(&[5u8; KNOWLEDGE_SIZE]));
+        assert!(req.at.contains_key(&[6u8; KNOWLEDGE_SIZE]));
+    }
+
+    #[test]
+    fn inventory_size_is_checked() {
+        assert_eq!(parse_tx_inv(&[1u8; 64]).unwrap().len(), 2);
+        assert!(parse_tx_inv(&[1u8; 33]).is_none());
+        assert!(parse_tx_inv(&[]).is_none());
+        assert!(parse_tx_inv(&vec![1u8; (TX_INV_MAX + 1) * KNOWLEDGE_SIZE]).is_none());
+    }
+}
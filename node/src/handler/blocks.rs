@@ -9,4 +9,24 @@ impl MsgHandler {
         crate::core::receive_blocks(self, peer, buf).await;
     }
 
+    async fn send_block_range(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::send_block_range(self, peer, buf).await;
+    }
+
+    async fn receive_block_range(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::receive_block_range(self, peer, buf).await;
+    }
+
+    async fn receive_compact_block(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::receive_compact_block(self, peer, buf).await;
+    }
+
+    async fn send_block_txs(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::send_block_txs(self, peer, buf).await;
+    }
+
+    async fn receive_block_txs(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::receive_block_txs(self, peer, buf).await;
+    }
+
 }
@@ -9,7 +9,11 @@ pub struct MsgHandler {
 
     pub(crate) doing_sync: AtomicU64,
     pub(crate) sync_tracker: SyncTracker,
+    pub(crate) head_sync: HeadersSync,
+    pub(crate) compact: CompactRelay,
+    pub(crate) tx_relay: TxRelay,
     pub(crate) knows: Knowledge,
+    pub(crate) reputation: PeerReputation,
 
     pub(crate) inserting: Arc<StdMutex<bool>>,
     handler_thread: StdMutex<Option<std::thread::ThreadId>>,
@@ -29,7 +33,11 @@ impl MsgHandler {
             blktxch: Some(rx).into(),
             doing_sync: AtomicU64::new(0),
             sync_tracker: SyncTracker::new(),
+            head_sync: HeadersSync::new(),
+            compact: CompactRelay::default(),
+            tx_relay: TxRelay::default(),
             knows: Knowledge::new(2000),
+            reputation: PeerReputation::default(),
             inserting: Arc::new(StdMutex::new(false)),
             handler_thread: StdMutex::new(None),
             exts_by_ty: StdMutex::new(HashMap::new()),
@@ -41,6 +49,14 @@ impl MsgHandler {
         self.p2pmng.lock().unwrap().as_ref().unwrap().switch_peer(p)
     }
 
+    /// Record misbehaviour of `peer`; a peer crossing the ban score is disconnected.
+    pub(crate) fn penalize(&self, peer: &Arc<Peer>, what: Misbehave) {
+        if self.reputation.penalize(peer, what) {
+            println!("[Peer] {} banned for {}.", peer.nick(), what.name());
+            peer.disconnect();
+        }
+    }
+
     pub fn set_p2p_mng(&self, mng: Box<dyn PeerManage>) {
         let mut mymng = self.p2pmng.lock().unwrap();
         *mymng = Some(mng);
@@ -60,6 +76,7 @@ impl MsgHandler {
                     return errf!("message type {} already registered", ty)
                 }
             }
+            crate::p2p::label_ext_msgs(&tys);
             for ty in tys {
                 map.insert(ty, ext.clone());
             }
@@ -95,6 +112,11 @@ impl MsgHandler {
         let _ = self.blktx.send(BlockTxArrive::Tx(None, body, None)).await;
     }
 
+    /// Hand a block body received from `peer` to the block and tx loop.
+    pub(crate) async fn block_arrive(&self, peer: Arc<Peer>, body: Vec<u8>) {
+        let _ = self.blktx.send(BlockTxArrive::Block(Some(peer), body, None)).await;
+    }
+
     pub async fn submit_block(&self, body: Vec<u8>) {
         let _ = self.blktx.send(BlockTxArrive::Block(None, body, None)).await;
     }
@@ -160,6 +182,8 @@ impl MsgHandler {
 
     pub async fn on_connect(&self, peer: Arc<Peer>) {
         let _ = peer.send_msg(MSG_REQ_STATUS, vec![]).await;
+        let _ = peer.send_msg(MSG_SEND_COMPACT, vec![]).await;
+        let _ = peer.send_msg(MSG_SEND_TX_INV, vec![]).await;
         let eng = self.engine.clone();
         let txp = self.txpool.clone();
         if let Err(e) = self.engine.minter().p2p_on_connect(peer.clone(), eng.clone(), txp.clone()) {
@@ -175,6 +199,7 @@ impl MsgHandler {
 
     pub async fn on_disconnect(&self, peer: Arc<Peer>) {
         self.sync_tracker.clear_peer(&peer);
+        self.head_sync.drop_peer(&peer);
         let peer_ext: Arc<dyn NPeer> = peer;
         for ext in self.extensions() {
             ext.on_disconnect(peer_ext.clone());
@@ -184,13 +209,26 @@ impl MsgHandler {
     pub async fn on_message(&self, peer: Arc<Peer>, ty: u16, body: Vec<u8>) {
         match ty {
             MSG_TX_SUBMIT =>      { let _ = self.blktx.send(BlockTxArrive::Tx(Some(peer.clone()), body, None)).await; },
-            MSG_BLOCK_DISCOVER => { let _ = self.blktx.send(BlockTxArrive::Block(Some(peer.clone()), body, None)).await; },
+            MSG_BLOCK_DISCOVER => { self.block_arrive(peer, body).await; },
             MSG_BLOCK_HASH =>     { self.receive_hashs(peer, body).await; },
             MSG_REQ_BLOCK_HASH => { self.send_hashs(peer, body).await; },
             MSG_BLOCK =>          { self.receive_blocks(peer, body).await; },
             MSG_REQ_BLOCK =>      { self.send_blocks(peer, body).await; },
             MSG_REQ_STATUS =>     { self.send_status(peer).await; },
             MSG_STATUS =>         { self.receive_status(peer, body).await; },
+            MSG_BLOCK_INTRO =>    { self.receive_intros(peer, body).await; },
+            MSG_REQ_BLOCK_INTRO =>{ self.send_intros(peer, body).await; },
+            MSG_BLOCK_RANGE =>    { self.receive_block_range(peer, body).await; },
+            MSG_REQ_BLOCK_RANGE =>{ self.send_block_range(peer, body).await; },
+            MSG_SEND_COMPACT =>   { peer.compact.store(true, std::sync::atomic::Ordering::Relaxed); },
+            MSG_COMPACT_BLOCK =>  { self.receive_compact_block(peer, body).await; },
+            MSG_REQ_BLOCK_TXS =>  { self.send_block_txs(peer, body).await; },
+            MSG_BLOCK_TXS =>      { self.receive_block_txs(peer, body).await; },
+            MSG_SEND_TX_INV =>    { peer.tx_inv.store(true, std::sync::atomic::Ordering::Relaxed); },
+            MSG_TX_INV =>         { self.receive_tx_inv(peer, body).await; },
+            MSG_REQ_TXS =>        { self.send_requested_txs(peer, body).await; },
+            MSG_REQ_TX_PROOF =>   { self.send_tx_proof(peer, body).await; },
+            MSG_REQ_BALANCE =>    { self.send_balances(peer, body).await; },
             _ => {
                 let ext = self.extension_for(ty);
                 if let Some(ext) = ext {
@@ -9,4 +9,12 @@ impl MsgHandler {
         crate::core::receive_hashs(self, peer, buf).await;
     }
 
+    async fn send_intros(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::send_intros(self, peer, buf).await;
+    }
+
+    async fn receive_intros(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::receive_intros(self, peer, buf).await;
+    }
+
 }
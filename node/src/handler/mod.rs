@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::sync::atomic::AtomicU64;
 use std::sync::{Arc, Mutex as StdMutex};
 
-use crate::core::SyncTracker;
+use crate::core::{CompactRelay, HeadersSync, SyncTracker, TxRelay};
 
 use tokio::sync::mpsc::{self, Receiver, Sender};
 
@@ -19,3 +19,4 @@ include! {"blocks.rs"}
 include! {"hashs.rs"}
 include! {"start.rs"}
 include! {"txblock.rs"}
+include! {"proof.rs"}
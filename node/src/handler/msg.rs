@@ -11,6 +11,26 @@ pub const MSG_BLOCK:               u16 = 6;
 pub const MSG_TX_SUBMIT:           u16 = basis::P2P_MSG_TX_SUBMIT;
 pub const MSG_BLOCK_DISCOVER:      u16 = 8;
 
+pub const MSG_REQ_BLOCK_INTRO:     u16 = 9;
+pub const MSG_BLOCK_INTRO:         u16 = 10;
+
+pub const MSG_REQ_BLOCK_RANGE:     u16 = 11;
+pub const MSG_BLOCK_RANGE:         u16 = 12;
+
+pub const MSG_SEND_COMPACT:        u16 = 13;
+pub const MSG_COMPACT_BLOCK:       u16 = 14;
+pub const MSG_REQ_BLOCK_TXS:       u16 = 15;
+pub const MSG_BLOCK_TXS:           u16 = 16;
+
+pub const MSG_SEND_TX_INV:         u16 = 17;
+pub const MSG_TX_INV:              u16 = 18;
+pub const MSG_REQ_TXS:             u16 = 19;
+
+pub const MSG_REQ_TX_PROOF:        u16 = 20;
+pub const MSG_TX_PROOF:            u16 = 21;
+pub const MSG_REQ_BALANCE:         u16 = 22;
+pub const MSG_BALANCE:             u16 = 23;
+
 
 pub fn is_inner_msg_ty(ty: u16) -> bool {
     ty < 2048
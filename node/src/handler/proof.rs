@@ -0,0 +1,12 @@
+
+impl MsgHandler {
+
+    async fn send_tx_proof(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::send_tx_proof(self, peer, buf).await;
+    }
+
+    async fn send_balances(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::send_balances(self, peer, buf).await;
+    }
+
+}
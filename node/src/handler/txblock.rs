@@ -1,4 +1,16 @@
 
+impl MsgHandler {
+
+    async fn receive_tx_inv(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::receive_tx_inv(self, peer, buf).await;
+    }
+
+    async fn send_requested_txs(&self, peer: Arc<Peer>, buf: Vec<u8>) {
+        crate::core::send_requested_txs(self, peer, buf).await;
+    }
+
+}
+
 async fn handle_new_tx(this: Arc<MsgHandler>, peer: Option<Arc<Peer>>, body: Vec<u8>) -> Rerr {
     crate::core::handle_new_tx(this, peer, body).await
 }
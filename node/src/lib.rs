@@ -3,6 +3,7 @@ include! {"util.rs"}
 pub mod memtxpool;
 
 pub mod core;
+pub mod light;
 pub mod handler;
 pub mod p2p;
 pub mod peer;
@@ -0,0 +1,367 @@
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use super::*;
+use protocol::block::verify_mrkl_branch;
+use protocol::transaction::build_tx_package;
+
+/// Verified block intros of the best chain a light node knows, from genesis.
+/// With a store file they are kept there too, one fixed-size intro per
+/// height, so a restart does not sync them again.
+pub(crate) struct LightChain {
+    genesis: (Hash, BlockIntro),
+    // intros[i] is at height `i + 1`
+    intros: Vec<(Hash, BlockIntro)>,
+    store: Option<PathBuf>,
+}
+
+/// Intro source for difficulty checks: the chain below a fork point, then the
+/// intros verified so far above it.
+struct LightIntroSource<'a> {
+    genesis: &'a BlockIntro,
+    base: &'a [(Hash, BlockIntro)],
+    extra: &'a [(Hash, BlockIntro)],
+}
+
+impl BlockIntroSource for LightIntroSource<'_> {
+    fn cache_height_limit(&self) -> u64 {
+        // a light chain may still reorg at any height
+        0
+    }
+
+    fn block_intro(&self, hei: u64) -> Option<Box<dyn BlockRead>> {
+        let idx = hei.checked_sub(1);
+        let intro = match idx {
+            None => self.genesis,
+            Some(i) if (i as usize) < self.base.len() => &self.base[i as usize].1,
+            Some(i) => &self.extra.get(i as usize - self.base.len())?.1,
+        };
+        Some(Box::new(intro.clone()))
+    }
+}
+
+impl LightChain {
+    pub(crate) fn new(genesis: BlockIntro) -> Self {
+        Self { genesis: (genesis.hash(), genesis), intros: vec![], store: None }
+    }
+
+    /// Load the intros saved in `path` and keep saving there. They were
+    /// verified before they were written, so only linkage is checked again;
+    /// the file is cut at the first intro that does not link.
+    pub(crate) fn open(genesis: BlockIntro, path: PathBuf) -> Self {
+        let mut chain = Self::new(genesis);
+        let buf = std::fs::read(&path).unwrap_or_default();
+        for one in buf.chunks_exact(BlockIntro::default().size()) {
+            let Ok(intro) = BlockIntro::build(one) else {
+                break
+            };
+            if intro.height().uint() != chain.height() + 1 || *intro.prevhash() != chain.tip_hash() {
+                break
+            }
+            chain.intros.push((intro.hash(), intro));
+        }
+        chain.store = Some(path);
+        if let Err(e) = chain.save(chain.height() + 1) {
+            println!("[Light] cannot save headers: {}", e);
+        }
+        chain
+    }
+
+    /// Cut the store file below height `from` and append our intros from it.
+    fn save(&self, from: u64) -> Rerr {
+        let Some(path) = &self.store else {
+            return Ok(())
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let size = BlockIntro::default().size() as u64;
+        let mut file = OpenOptions::new().create(true).write(true).truncate(false).open(path).map_err(|e| e.to_string())?;
+        file.set_len((from - 1) * size).map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+        let mut buf = Vec::with_capacity((self.height() + 1).saturating_sub(from) as usize * size as usize);
+        for (_, intro) in &self.intros[from as usize - 1..] {
+            intro.serialize_to(&mut buf);
+        }
+        file.write_all(&buf).map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn height(&self) -> u64 {
+        self.intros.len() as u64
+    }
+
+    pub(crate) fn tip_hash(&self) -> Hash {
+        self.intros.last().map_or(self.genesis.0, |(h, _)| *h)
+    }
+
+    pub(crate) fn hash_at(&self, hei: u64) -> Option<Hash> {
+        self.at(hei).map(|(h, _)| *h)
+    }
+
+    pub(crate) fn intro(&self, hei: u64) -> Option<&BlockIntro> {
+        self.at(hei).map(|(_, i)| i)
+    }
+
+    fn at(&self, hei: u64) -> Option<&(Hash, BlockIntro)> {
+        match hei {
+            0 => Some(&self.genesis),
+            _ => self.intros.get(hei as usize - 1),
+        }
+    }
+
+    /// Verify `news`, which start at `start` and must link to our intro below
+    /// it, and take them as the best chain when they reach higher than ours.
+    /// Intros we already have are skipped; returns how many were taken.
+    pub(crate) fn extend(&mut self, minter: &dyn Minter, start: u64, news: Vec<BlockIntro>) -> Ret<usize> {
+        if start == 0 || start > self.height() + 1 {
+            return errf!("intros start at {} above our tip {}", start, self.height());
+        }
+        let Some(first) = news.first() else {
+            return Ok(0);
+        };
+        if first.height().uint() != start {
+            return errf!("intros start at {} instead of {}", first.height().uint(), start);
+        }
+        if Some(*first.prevhash()) != self.hash_at(start - 1) {
+            return errf!("intros do not link at {}", start);
+        }
+        let mut skip = 0;
+        while skip < news.len() && self.hash_at(start + skip as u64) == Some(news[skip].hash()) {
+            skip += 1;
+        }
+        let fork = start + skip as u64;
+        let news: Vec<BlockIntro> = news.into_iter().skip(skip).collect();
+        if news.is_empty() {
+            return Ok(0);
+        }
+        let tip = fork + news.len() as u64 - 1;
+        if tip <= self.height() {
+            return errf!("fork at {} ends at {}, not above our tip {}", fork, tip, self.height());
+        }
+        let base = &self.intros[..fork as usize - 1];
+        let mut extra: Vec<(Hash, BlockIntro)> = Vec::with_capacity(news.len());
+        let mut prev_hash = self.hash_at(fork - 1).unwrap();
+        for intro in news {
+            let hei = intro.height().uint();
+            if hei != fork + extra.len() as u64 {
+                return errf!("intro height {} out of sequence", hei);
+            }
+            if *intro.prevhash() != prev_hash {
+                return errf!("intro {} does not link to previous hash", hei);
+            }
+            let src = LightIntroSource { genesis: &self.genesis.1, base, extra: &extra };
+            let Some(prev) = src.block_intro(hei - 1) else {
+                return errf!("cannot load intro {}", hei - 1);
+            };
+            minter.blk_intro_verify(&intro, prev.as_ref(), &src)?;
+            prev_hash = intro.hash();
+            extra.push((prev_hash, intro));
+        }
+        let added = extra.len();
+        self.intros.truncate(fork as usize - 1);
+        self.intros.append(&mut extra);
+        if let Err(e) = self.save(fork) {
+            println!("[Light] cannot save headers: {}", e);
+        }
+        Ok(added)
+    }
+
+    /// Check a full peer's merkle proof against our intro at its height.
+    pub(crate) fn check_tx_proof(&self, proof: &crate::core::TxProof) -> Ret<LightTx> {
+        let Some(intro) = self.intro(proof.height) else {
+            return errf!("block {} is not in the verified headers", proof.height);
+        };
+        let txnum = intro.transaction_count().uint();
+        if proof.index >= txnum {
+            return errf!("tx index {} out of block tx count {}", proof.index, txnum);
+        }
+        let tx = build_tx_package(proof.body.clone())?;
+        if tx.hash() != proof.hash {
+            return errf!("tx body does not match hash {}", proof.hash);
+        }
+        let leaf = tx.tx().hash_with_fee();
+        if !verify_mrkl_branch(&leaf, proof.index as usize, &proof.branch, intro.mrklroot()) {
+            return errf!("merkle branch of tx {} does not match block {}", proof.hash, proof.height);
+        }
+        Ok(LightTx {
+            height: proof.height,
+            block_hash: self.hash_at(proof.height).unwrap(),
+            block_timestamp: intro.timestamp().uint(),
+            index: proof.index as usize,
+            tx,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Once;
+
+    struct NilMinter {}
+    impl Minter for NilMinter {}
+
+    const POW_DIFFICULTY: u32 = 0xf8ffffff; // 7 leading zero bits
+
+    // checks difficulty and PoW hash the way the chain minter does, against a fixed target
+    struct PowMinter {}
+    impl Minter for PowMinter {
+        fn blk_intro_verify(&self, cur: &dyn BlockRead, prev: &dyn BlockRead, src: &dyn BlockIntroSource) -> Rerr {
+            let prehei = prev.height().uint();
+            if prev.hash() != *cur.prevhash() || src.block_intro(prehei).map(|i| i.hash()) != Some(prev.hash()) {
+                return errf!("intro {} gets a wrong previous intro", cur.height().uint())
+            }
+            if cur.difficulty().uint() != POW_DIFFICULTY {
+                return errf!("intro {} difficulty check failed", cur.height().uint())
+            }
+            if basis::difficulty::hash_bigger_than(cur.hash().as_ref(), &basis::difficulty::u32_to_hash(POW_DIFFICULTY)) {
+                return errf!("intro {} PoW hash check failed", cur.height().uint())
+            }
+            Ok(())
+        }
+    }
+
+    // intro hashes need a block hasher; sha3 stands in for x16rs
+    fn install_setup() {
+        static ONCE: Once = Once::new();
+        ONCE.call_once(|| {
+            let setup = protocol::setup::new_standard_protocol_setup(|_, stuff| sys::sha3(stuff));
+            protocol::setup::install_once(setup);
+        });
+    }
+
+    fn intro(hei: u64, prev: Hash, nonce: u32) -> BlockIntro {
+        let mut i = BlockIntro::default();
+        i.head.height = BlockHeight::from(hei);
+        i.head.prevhash = prev;
+        i.head.transaction_count = Uint4::from(1);
+        i.meta.nonce = Uint4::from(nonce);
+        i
+    }
+
+    fn branch(from: u64, prev: Hash, len: u64, nonce: u32) -> Vec<BlockIntro> {
+        let mut res = vec![];
+        let mut prev = prev;
+        for hei in from..from + len {
+            let i = intro(hei, prev, nonce);
+            prev = i.hash();
+            res.push(i);
+        }
+        res
+    }
+
+    fn mined(hei: u64, prev: Hash) -> BlockIntro {
+        let mut i = intro(hei, prev, 0);
+        i.meta.difficulty = Uint4::from(POW_DIFFICULTY);
+        let target = basis::difficulty::u32_to_hash(POW_DIFFICULTY);
+        while basis::difficulty::hash_bigger_than(i.hash().as_ref(), &target) {
+            i.meta.nonce = Uint4::from(i.meta.nonce.uint() + 1);
+        }
+        i
+    }
+
+    fn mined_branch(from: u64, prev: Hash, len: u64) -> Vec<BlockIntro> {
+        let mut res: Vec<BlockIntro> = vec![];
+        for hei in from..from + len {
+            let prev = res.last().map_or(prev, |i| i.hash());
+            res.push(mined(hei, prev));
+        }
+        res
+    }
+
+    fn chain_of(len: u64) -> LightChain {
+        install_setup();
+        let mut chain = LightChain::new(intro(0, Hash::default(), 0));
+        let news = branch(1, chain.tip_hash(), len, 0);
+        assert_eq!(chain.extend(&NilMinter {}, 1, news).unwrap(), len as usize);
+        chain
+    }
+
+    #[test]
+    fn extends_and_skips_known_intros() {
+        let mut chain = chain_of(5);
+        assert_eq!(chain.height(), 5);
+        let news = branch(4, chain.hash_at(3).unwrap(), 4, 0);
+        assert_eq!(chain.extend(&NilMinter {}, 4, news).unwrap(), 2);
+        assert_eq!(chain.height(), 7);
+        assert!(chain.extend(&NilMinter {}, 9, vec![]).is_err());
+    }
+
+    #[test]
+    fn longer_fork_replaces_the_tail() {
+        let mut chain = chain_of(6);
+        let old4 = chain.hash_at(4).unwrap();
+        // same length: keep ours
+        let fork = branch(4, chain.hash_at(3).unwrap(), 3, 1);
+        assert!(chain.extend(&NilMinter {}, 4, fork).is_err());
+        assert_eq!(chain.hash_at(4), Some(old4));
+        let fork = branch(4, chain.hash_at(3).unwrap(), 4, 1);
+        let tip = fork.last().unwrap().hash();
+        assert_eq!(chain.extend(&NilMinter {}, 4, fork).unwrap(), 4);
+        assert_eq!((chain.height(), chain.tip_hash()), (7, tip));
+        assert_ne!(chain.hash_at(4), Some(old4));
+    }
+
+    #[test]
+    fn rejects_unlinked_intros() {
+        let mut chain = chain_of(3);
+        let news = branch(4, Hash::from([9; 32]), 2, 0);
+        assert!(chain.extend(&NilMinter {}, 4, news).is_err());
+        let mut news = branch(4, chain.tip_hash(), 3, 0);
+        news.remove(1);
+        assert!(chain.extend(&NilMinter {}, 4, news).is_err());
+        assert_eq!(chain.height(), 3);
+    }
+
+    #[test]
+    fn pow_checks_reject_unmined_intros() {
+        install_setup();
+        let mut chain = LightChain::new(intro(0, Hash::default(), 0));
+        let news = mined_branch(1, chain.tip_hash(), 3);
+        assert_eq!(chain.extend(&PowMinter {}, 1, news).unwrap(), 3);
+        // an unmined intro in the middle fails the whole batch
+        let mut news = mined_branch(4, chain.tip_hash(), 3);
+        news[1].meta.nonce = Uint4::from(news[1].meta.nonce.uint() + 1);
+        let target = basis::difficulty::u32_to_hash(POW_DIFFICULTY);
+        while !basis::difficulty::hash_bigger_than(news[1].hash().as_ref(), &target) {
+            news[1].meta.nonce = Uint4::from(news[1].meta.nonce.uint() + 1);
+        }
+        news[2].head.prevhash = news[1].hash();
+        assert!(chain.extend(&PowMinter {}, 4, news).is_err());
+        assert_eq!(chain.height(), 3);
+        // a mined intro that claims another difficulty
+        let mut bad = intro(4, chain.tip_hash(), 0);
+        bad.meta.difficulty = Uint4::from(0xffffffff);
+        assert!(chain.extend(&PowMinter {}, 4, vec![bad]).is_err());
+        // the same chain passes without PoW checks only
+        let news = branch(4, chain.tip_hash(), 2, 0);
+        assert!(chain.extend(&PowMinter {}, 4, news.clone()).is_err());
+        assert_eq!(chain.extend(&NilMinter {}, 4, news).unwrap(), 2);
+    }
+
+    #[test]
+    fn store_file_keeps_intros_over_reorgs() {
+        install_setup();
+        let path = std::env::temp_dir().join(format!("hacash_light_headers_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let genesis = intro(0, Hash::default(), 0);
+        let mut chain = LightChain::open(genesis.clone(), path.clone());
+        assert_eq!(chain.height(), 0);
+        chain.extend(&NilMinter {}, 1, branch(1, chain.tip_hash(), 6, 0)).unwrap();
+        let fork = branch(4, chain.hash_at(3).unwrap(), 5, 1);
+        chain.extend(&NilMinter {}, 4, fork).unwrap();
+        let reopen = LightChain::open(genesis.clone(), path.clone());
+        assert_eq!((reopen.height(), reopen.tip_hash()), (8, chain.tip_hash()));
+        assert_eq!(reopen.hash_at(4), chain.hash_at(4));
+        // a torn or unlinked tail is cut off
+        let size = BlockIntro::default().size();
+        let mut buf = std::fs::read(&path).unwrap();
+        buf[6 * size + 20] ^= 1; // prevhash of height 7
+        std::fs::write(&path, &buf).unwrap();
+        let reopen = LightChain::open(genesis, path.clone());
+        assert_eq!(reopen.height(), 6);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 6 * size as u64);
+        let _ = std::fs::remove_file(&path);
+    }
+}
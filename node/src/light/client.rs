@@ -0,0 +1,447 @@
+use super::chain::LightChain;
+use super::*;
+use crate::core::{BalanceReply, LIGHT_BALANCE_MAX, TxProof, serialize_balance_request};
+
+/*
+    Light (SPV) node.
+
+    It keeps only block intros, which it fetches with MSG_REQ_BLOCK_INTRO and
+    checks like headers-first sync does: linkage, PoW hash and difficulty
+    through the minter. The longest verified chain wins, and is saved to
+    `light.headers` in the data dir. Everything else is
+    asked of full peers on demand: a transaction comes with a merkle branch
+    that must match the `mrklroot` of a verified intro, and balances come with
+    the head they were read at, which must be on the verified chain and agree
+    between peers that answer at the same height.
+
+    Peers are dialed from `[node] boots` and never dialed back (we report no
+    listen port); sessions are plaintext.
+*/
+
+/// Intros per request, as full nodes cap them.
+const INTRO_BATCH: u16 = 2000;
+/// Seconds a peer has to answer one request.
+const REQUEST_TIMEOUT: u64 = 15;
+/// Seconds between status polls and reconnects.
+const POLL_SECS: u64 = 3;
+/// Blocks searched back for a fork point before a peer is given up on.
+const REORG_SEARCH_MAX: u64 = 1024;
+/// Full peers asked for the same balances.
+const BALANCE_CROSS_CHECK: usize = 2;
+
+struct LightPeer {
+    peer: Arc<Peer>,
+    addr: SocketAddr,
+    height: AtomicU64,
+    waits: StdMutex<HashMap<u16, oneshot::Sender<Vec<u8>>>>,
+    busy: tokio::sync::Mutex<()>,
+}
+
+impl LightPeer {
+    /// One request at a time per peer, answered by the `reply` message.
+    async fn request(&self, ty: u16, body: Vec<u8>, reply: u16) -> Ret<Vec<u8>> {
+        let _busy = self.busy.lock().await;
+        let (tx, rx) = oneshot::channel();
+        self.waits.lock().unwrap().insert(reply, tx);
+        self.peer.send_msg(ty, body).await?;
+        match tokio::time::timeout(Duration::from_secs(REQUEST_TIMEOUT), rx).await {
+            Ok(Ok(buf)) => Ok(buf),
+            _ => {
+                self.waits.lock().unwrap().remove(&reply);
+                errf!("peer {} did not answer message {}", self.peer.name(), ty)
+            }
+        }
+    }
+
+    fn deliver(&self, ty: u16, body: Vec<u8>) {
+        if let Some(tx) = self.waits.lock().unwrap().remove(&ty) {
+            let _ = tx.send(body);
+        }
+    }
+}
+
+struct LightRuntime {
+    cnf: NodeConf,
+    minter: Arc<dyn Minter>,
+    genesis_hash: Hash,
+    chain: StdMutex<LightChain>,
+    peers: StdMutex<Vec<Arc<LightPeer>>>,
+    memnet: Option<MemEndpoint>,
+    handle: StdMutex<Option<tokio::runtime::Handle>>,
+    syncing: tokio::sync::Mutex<()>,
+    sync_notify: Notify,
+    exited: AtomicBool,
+}
+
+pub struct LightNode {
+    runtime: Arc<LightRuntime>,
+}
+
+impl LightNode {
+    pub fn open(ini: &IniObj, minter: Arc<dyn Minter>) -> Ret<Self> {
+        Self::open_with(ini, minter, None)
+    }
+
+    /// A light node that dials its peers on an in-memory network.
+    pub fn open_on_memnet(ini: &IniObj, minter: Arc<dyn Minter>, net: MemEndpoint) -> Ret<Self> {
+        Self::open_with(ini, minter, Some(net))
+    }
+
+    fn open_with(ini: &IniObj, minter: Arc<dyn Minter>, memnet: Option<MemEndpoint>) -> Ret<Self> {
+        let cnf = NodeConf::new(ini);
+        if cnf.boot_nodes.is_empty() {
+            return errf!("light node needs full peers in [node] boots");
+        }
+        let genesis = BlockIntro::build(&minter.genesis_block().serialize())?;
+        let store = join_path(&cnf.data_dir, "light.headers");
+        let runtime = LightRuntime {
+            genesis_hash: genesis.hash(),
+            chain: StdMutex::new(LightChain::open(genesis, store)),
+            cnf,
+            minter,
+            peers: StdMutex::default(),
+            memnet,
+            handle: StdMutex::default(),
+            syncing: tokio::sync::Mutex::new(()),
+            sync_notify: Notify::new(),
+            exited: AtomicBool::new(false),
+        };
+        Ok(Self { runtime: Arc::new(runtime) })
+    }
+}
+
+impl LightNoder for LightNode {
+    fn start(&self, worker: Worker) {
+        let rt = new_tokio_rt(true);
+        *self.runtime.handle.lock().unwrap() = Some(rt.handle().clone());
+        let this = self.runtime.clone();
+        println!("[Light] start, full peers {:?}", this.cnf.boot_nodes);
+        rt.block_on(async move { this.run(worker).await });
+    }
+
+    fn latest(&self) -> (u64, Hash) {
+        let chain = self.runtime.chain.lock().unwrap();
+        (chain.height(), chain.tip_hash())
+    }
+
+    fn balances(&self, addrs: &[Address]) -> Ret<LightBalances> {
+        let this = self.runtime.clone();
+        let addrs = addrs.to_vec();
+        self.runtime.block_on(async move { this.query_balances(addrs).await })
+    }
+
+    fn transaction(&self, hash: &Hash) -> Ret<LightTx> {
+        let this = self.runtime.clone();
+        let hash = *hash;
+        self.runtime.block_on(async move { this.query_transaction(hash).await })
+    }
+
+    fn all_peer_prints(&self) -> Vec<String> {
+        self.runtime.peers.lock().unwrap().iter().map(|p| p.peer.nick()).collect()
+    }
+
+    fn exit(&self) {
+        self.runtime.exited.store(true, Ordering::Relaxed);
+        for p in self.runtime.peers.lock().unwrap().iter() {
+            p.peer.disconnect();
+        }
+    }
+}
+
+impl LightRuntime {
+    /// Run a query on the node's runtime and wait for it, from any thread.
+    fn block_on<T: Send + 'static>(&self, fut: impl Future<Output = Ret<T>> + Send + 'static) -> Ret<T> {
+        let Some(handle) = self.handle.lock().unwrap().clone() else {
+            return errf!("light node not started");
+        };
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        handle.spawn(async move {
+            let _ = tx.send(fut.await);
+        });
+        rx.recv().map_err(|e| format!("light node query failed: {}", e))?
+    }
+
+    async fn run(self: Arc<Self>, mut worker: Worker) {
+        loop {
+            if self.exited.load(Ordering::Relaxed) {
+                break;
+            }
+            self.connect_missing().await;
+            self.poll_status().await;
+            self.sync_headers().await;
+            tokio::select! {
+                _ = worker.wait() => break,
+                _ = self.sync_notify.notified() => {},
+                _ = tokio::time::sleep(Duration::from_secs(POLL_SECS)) => {},
+            }
+        }
+        for p in self.peers.lock().unwrap().drain(..) {
+            p.peer.disconnect();
+        }
+        println!("[Light] exit.");
+    }
+
+    fn peer_list(&self) -> Vec<Arc<LightPeer>> {
+        let mut peers = self.peers.lock().unwrap().clone();
+        peers.sort_by_key(|p| std::cmp::Reverse(p.height.load(Ordering::Relaxed)));
+        peers
+    }
+
+    async fn connect_missing(self: &Arc<Self>) {
+        let connected: Vec<SocketAddr> = self.peers.lock().unwrap().iter().map(|p| p.addr).collect();
+        for addr in self.cnf.boot_nodes.clone() {
+            if connected.contains(&addr) {
+                continue;
+            }
+            if let Err(e) = self.connect(addr).await {
+                println!("[Light] connect {} failed: {}", addr, e);
+            }
+        }
+    }
+
+    async fn connect(self: &Arc<Self>, addr: SocketAddr) -> Rerr {
+        let mut conn: Box<dyn PeerConn> = match &self.memnet {
+            Some(net) => Box::new(net.dial(addr).map_err(|e| e.to_string())?),
+            None => Box::new(tcp_dial_connect(addr, 6).await?),
+        };
+        tcp_check_handshake(&mut conn, 5).await?;
+        let mynodeinfo = self.node_info();
+        tcp_send_msg(&mut conn, MSG_REPORT_PEER, mynodeinfo.clone()).await?;
+        let (ty, body) = tcp_read_msg(&mut conn, 5).await?;
+        let offer = SecureOffer { identity: None, only: false, sent: None };
+        let (peer, reader) = Peer::create_with_msg(conn, ty, body, mynodeinfo, offer).await?;
+        let lp = Arc::new(LightPeer {
+            peer,
+            addr,
+            height: AtomicU64::new(0),
+            waits: StdMutex::default(),
+            busy: tokio::sync::Mutex::new(()),
+        });
+        self.peers.lock().unwrap().push(lp.clone());
+        println!("[Light] full peer {} connected.", lp.peer.nick());
+        let this = self.clone();
+        tokio::spawn(async move { this.read_loop(lp, reader).await });
+        Ok(())
+    }
+
+    // same layout as a full node's, with listen port 0 so peers never dial back
+    fn node_info(&self) -> Vec<u8> {
+        let mut nodeinfo = vec![0u8; 2 + 2 + 16 * 2];
+        nodeinfo.splice(4..20, self.cnf.node_key);
+        let mut namebt = self.cnf.node_name.clone();
+        namebt += "                ";
+        namebt.truncate(16);
+        nodeinfo.splice(20..36, namebt.into_bytes());
+        nodeinfo
+    }
+
+    fn status(&self) -> HandshakeStatus {
+        // no blocks to serve, so peers never try to sync from us
+        HandshakeStatus {
+            genesis_hash: self.genesis_hash,
+            block_version: Uint1::from(1),
+            transaction_type: Uint1::from(2),
+            action_kind: Uint2::from(12),
+            repair_serial: Uint2::from(1),
+            __mark: Uint3::from(0),
+            latest_height: BlockHeight::from(0),
+            latest_hash: self.genesis_hash,
+        }
+    }
+
+    async fn read_loop(self: Arc<Self>, lp: Arc<LightPeer>, mut reader: PeerReader) {
+        loop {
+            let rdres = tokio::select! {
+                _ = lp.peer.close_notify.notified() => break,
+                rd = reader.read_msg(0) => rd,
+            };
+            let Ok((ty, msg)) = rdres else {
+                break;
+            };
+            lp.peer.update_active();
+            match ty {
+                MSG_CUSTOMER if msg.len() >= 2 => {
+                    let ty = u16::from_be_bytes(bufcut!(msg, 0, 2));
+                    if !self.on_message(&lp, ty, msg[2..].to_vec()).await {
+                        break;
+                    }
+                }
+                MSG_PING => {
+                    let _ = lp.peer.send_p2p_msg(MSG_PONG, vec![]).await;
+                }
+                MSG_CLOSE => break,
+                _ => {}
+            }
+        }
+        lp.peer.disconnect();
+        self.peers.lock().unwrap().retain(|p| !Arc::ptr_eq(p, &lp));
+        println!("[Light] full peer {} disconnected.", lp.peer.nick());
+    }
+
+    /// Returns false when the peer must be dropped.
+    async fn on_message(&self, lp: &LightPeer, ty: u16, body: Vec<u8>) -> bool {
+        match ty {
+            MSG_REQ_STATUS => {
+                let _ = lp.peer.send_msg(MSG_STATUS, self.status().serialize()).await;
+            }
+            MSG_STATUS => {
+                let Ok((status, _)) = HandshakeStatus::create(&body) else {
+                    return false;
+                };
+                if status.genesis_hash != self.genesis_hash {
+                    println!("[Light] peer {} is on another chain.", lp.peer.name());
+                    return false;
+                }
+                self.note_height(lp, status.latest_height.uint());
+            }
+            MSG_BLOCK_DISCOVER => {
+                if let Ok(intro) = BlockIntro::build(&body) {
+                    self.note_height(lp, intro.height().uint());
+                }
+            }
+            MSG_BLOCK_INTRO | MSG_TX_PROOF | MSG_BALANCE => lp.deliver(ty, body),
+            _ => {}
+        }
+        true
+    }
+
+    fn note_height(&self, lp: &LightPeer, hei: u64) {
+        let old = lp.height.fetch_max(hei, Ordering::Relaxed);
+        if hei > old && hei > self.chain.lock().unwrap().height() {
+            self.sync_notify.notify_one();
+        }
+    }
+
+    async fn poll_status(&self) {
+        for p in self.peer_list() {
+            let _ = p.peer.send_msg(MSG_REQ_STATUS, vec![]).await;
+        }
+    }
+
+    /// Fetch intros from the highest peers until none is ahead of us.
+    async fn sync_headers(self: &Arc<Self>) {
+        let Ok(_syncing) = self.syncing.try_lock() else {
+            return;
+        };
+        let mut back = 0;
+        loop {
+            let tip = self.chain.lock().unwrap().height();
+            let Some(lp) = self.peer_list().into_iter().find(|p| p.height.load(Ordering::Relaxed) > tip) else {
+                return;
+            };
+            let start = tip + 1 - back.min(tip);
+            let res = match self.fetch_intros(&lp, start).await {
+                Ok(news) => {
+                    let this = self.clone();
+                    tokio::task::spawn_blocking(move || {
+                        this.chain.lock().unwrap().extend(this.minter.as_ref(), start, news)
+                    })
+                    .await
+                    .unwrap()
+                }
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(n) if n > 0 => {
+                    back = 0;
+                    flush!("[Light] headers {} ... ", self.chain.lock().unwrap().height());
+                }
+                Err(e) if e.contains("do not link") && back < REORG_SEARCH_MAX.min(tip) => {
+                    back = (back * 2).max(8);
+                }
+                res => {
+                    // nothing usable: stop trusting its height until it reports again
+                    if let Err(e) = res {
+                        println!("[Light] intros from {} rejected: {}", lp.peer.name(), e);
+                    }
+                    lp.height.store(tip, Ordering::Relaxed);
+                    back = 0;
+                }
+            }
+        }
+    }
+
+    async fn fetch_intros(&self, lp: &LightPeer, start: u64) -> Ret<Vec<BlockIntro>> {
+        let req = [INTRO_BATCH.to_be_bytes().to_vec(), start.to_be_bytes().to_vec()].concat();
+        let buf = lp.request(MSG_REQ_BLOCK_INTRO, req, MSG_BLOCK_INTRO).await?;
+        if buf.len() < 8 || u64::from_be_bytes(bufcut!(buf, 0, 8)) != start {
+            return errf!("intros answer malformed");
+        }
+        let mut news = vec![];
+        let mut seek = 8;
+        while seek < buf.len() {
+            let mut intro = BlockIntro::default();
+            seek += intro.parse(&buf[seek..])?;
+            news.push(intro);
+        }
+        Ok(news)
+    }
+
+    /// Catch up first when a peer answered from above our verified tip.
+    async fn ensure_height(self: &Arc<Self>, hei: u64) -> bool {
+        if self.chain.lock().unwrap().height() < hei {
+            self.sync_headers().await;
+        }
+        self.chain.lock().unwrap().height() >= hei
+    }
+
+    async fn query_balances(self: Arc<Self>, addrs: Vec<Address>) -> Ret<LightBalances> {
+        if addrs.is_empty() || addrs.len() > LIGHT_BALANCE_MAX {
+            return errf!("address count must be between 1 and {}", LIGHT_BALANCE_MAX);
+        }
+        let req = serialize_balance_request(&addrs);
+        let mut answers: Vec<BalanceReply> = vec![];
+        for lp in self.peer_list() {
+            if answers.len() >= BALANCE_CROSS_CHECK {
+                break;
+            }
+            let Ok(buf) = lp.request(MSG_REQ_BALANCE, req.clone(), MSG_BALANCE).await else {
+                continue;
+            };
+            let Ok(reply) = BalanceReply::parse(&buf) else {
+                continue;
+            };
+            if reply.list.len() != addrs.len() || !self.ensure_height(reply.height).await {
+                continue;
+            }
+            if self.chain.lock().unwrap().hash_at(reply.height) != Some(reply.hash) {
+                continue;
+            }
+            answers.push(reply);
+        }
+        for (i, a) in answers.iter().enumerate() {
+            for b in &answers[i + 1..] {
+                if a.height == b.height && a.list != b.list {
+                    return errf!("full peers disagree on balances at height {}", a.height);
+                }
+            }
+        }
+        let Some(best) = answers.into_iter().max_by_key(|a| a.height) else {
+            return errf!("no full peer answered on the verified chain");
+        };
+        Ok(LightBalances { height: best.height, hash: best.hash, list: best.list })
+    }
+
+    async fn query_transaction(self: Arc<Self>, hash: Hash) -> Ret<LightTx> {
+        let mut err = format!("transaction {} not found", hash);
+        for lp in self.peer_list() {
+            let Ok(buf) = lp.request(MSG_REQ_TX_PROOF, hash.to_vec(), MSG_TX_PROOF).await else {
+                continue;
+            };
+            let Ok(proof) = TxProof::parse(&buf) else {
+                continue;
+            };
+            if proof.hash != hash || proof.height == 0 {
+                continue;
+            }
+            if !self.ensure_height(proof.height).await {
+                continue;
+            }
+            match self.chain.lock().unwrap().check_tx_proof(&proof) {
+                Ok(tx) => return Ok(tx),
+                Err(e) => err = format!("proof from {} rejected: {}", lp.peer.name(), e),
+            }
+        }
+        Err(err)
+    }
+}
@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use tokio::sync::{Notify, oneshot};
+
+use basis::config::NodeConf;
+use basis::interface::*;
+use field::*;
+use protocol::block::BlockIntro;
+use sys::*;
+
+use crate::handler::*;
+use crate::p2p::*;
+use crate::peer::{Peer, PeerReader};
+use crate::*;
+
+mod chain;
+mod client;
+
+pub use client::LightNode;
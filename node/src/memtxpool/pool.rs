@@ -1,7 +1,5 @@
-#[allow(dead_code)]
 pub struct MemTxPool {
-    lowest_fepr: u64,
-    group_size: Vec<usize>,
+    lowest_fepr: atomic::AtomicU64,
     groups: Vec<Mutex<TxGroup>>,
 }
 
@@ -17,8 +15,7 @@ impl MemTxPool {
             grps.push(Mutex::new(TxGroup::new(sz, fpmds[i])));
         }
         Self {
-            lowest_fepr: lfepr,
-            group_size: gs,
+            lowest_fepr: lfepr.into(),
             groups: grps,
         }
     }
@@ -56,7 +53,7 @@ impl TxPool for MemTxPool {
     }
 
     fn insert_at(&self, gi: usize, txp: TxPkg) -> Rerr {
-        if txp.fpur() < self.lowest_fepr {
+        if txp.fpur() < self.lowest_fee_purity() {
             return errf!("tx fee purity {} too low to add txpool", txp.fpur());
         }
         self.check_group_id(gi)?;
@@ -96,6 +93,30 @@ impl TxPool for MemTxPool {
         Ok(())
     }
 
+    fn capacity_at(&self, gi: usize) -> Ret<usize> {
+        self.check_group_id(gi)?;
+        Ok(self.groups[gi].lock().unwrap().maxsz)
+    }
+
+    fn resize_at(&self, gi: usize, sz: usize) -> Rerr {
+        self.check_group_id(gi)?;
+        if sz == 0 {
+            return errf!("tx pool group size cannot be 0");
+        }
+        let mut grp = self.groups[gi].lock().unwrap();
+        grp.maxsz = sz;
+        grp.txpkgs.truncate(sz);
+        Ok(())
+    }
+
+    fn lowest_fee_purity(&self) -> u64 {
+        self.lowest_fepr.load(atomic::Ordering::Relaxed)
+    }
+
+    fn set_lowest_fee_purity(&self, fepr: u64) {
+        self.lowest_fepr.store(fepr, atomic::Ordering::Relaxed)
+    }
+
     fn find(&self, hx: &Hash) -> Option<TxPkg> {
         for gi in 0..self.groups.len() {
             if let Some(tx) = self.find_at(gi, hx) {
@@ -105,6 +126,18 @@ impl TxPool for MemTxPool {
         None
     }
 
+    fn iter(&self, scan: &mut dyn FnMut(&TxPkg) -> bool) -> Rerr {
+        for gi in 0..self.groups.len() {
+            let grp = self.groups[gi].lock().unwrap();
+            for txp in &grp.txpkgs {
+                if false == scan(txp) {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn insert_by(&self, txp: TxPkg, check_group: &dyn Fn(&TxPkg) -> usize) -> Rerr {
         let group_id = check_group(&txp);
         self.insert_at(group_id, txp)
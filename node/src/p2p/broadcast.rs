@@ -5,4 +5,12 @@ impl P2PManage {
         crate::core::broadcast_unaware(self, key, ty, body).await;
     }
 
+    pub async fn broadcast_block_unaware(&self, key: &KnowKey, full: Vec<u8>, compact: Vec<u8>) {
+        crate::core::broadcast_block_unaware(self, key, full, compact).await;
+    }
+
+    pub async fn broadcast_tx_unaware(&self, key: &KnowKey, body: Vec<u8>) {
+        crate::core::broadcast_tx_unaware(self, key, body).await;
+    }
+
 }
@@ -0,0 +1,35 @@
+
+pub type ConnReadHalf = Box<dyn AsyncRead + Send + Unpin>;
+pub type ConnWriteHalf = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// The byte stream a peer session runs over: a TCP socket, or a `MemNet`
+/// link when several nodes share one process.
+pub trait PeerConn: AsyncRead + AsyncWrite + Send + Unpin {
+    fn peer_addr(&self) -> std::io::Result<SocketAddr>;
+    fn into_halves(self: Box<Self>) -> (ConnReadHalf, ConnWriteHalf);
+}
+
+impl PeerConn for TcpStream {
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        TcpStream::peer_addr(self)
+    }
+
+    fn into_halves(self: Box<Self>) -> (ConnReadHalf, ConnWriteHalf) {
+        let (rd, wt) = self.into_split();
+        (Box::new(rd), Box::new(wt))
+    }
+}
+
+pub(crate) enum P2PListener {
+    Tcp(TcpListener),
+    Mem(MemListener),
+}
+
+impl P2PListener {
+    pub(crate) async fn accept(&mut self) -> std::io::Result<Box<dyn PeerConn>> {
+        match self {
+            Self::Tcp(l) => l.accept().await.map(|(conn, _)| Box::new(conn) as Box<dyn PeerConn>),
+            Self::Mem(l) => l.accept().await.map(|conn| Box::new(conn) as Box<dyn PeerConn>),
+        }
+    }
+}
@@ -16,12 +16,12 @@ impl P2PManage {
         crate::core::connect_node(self, addr).await
     }
 
-    pub async fn handle_conn(&self, conn: TcpStream, report_me: bool) -> Ret<Arc<Peer>> {
+    pub async fn handle_conn(&self, conn: Box<dyn PeerConn>, report_me: bool) -> Ret<Arc<Peer>> {
         crate::core::handle_conn(self, conn, report_me).await
     }
 
-    pub async fn insert_peer(&self, conn: TcpStream, mynodeinfo: Vec<u8>) -> Ret<Arc<Peer>> {
-        crate::core::insert_peer(self, conn, mynodeinfo).await
+    pub async fn insert_peer(&self, conn: Box<dyn PeerConn>, mynodeinfo: Vec<u8>, offer: SecureOffer) -> Ret<Arc<Peer>> {
+        crate::core::insert_peer(self, conn, mynodeinfo, offer).await
     }
 
     pub(crate) fn pick_my_node_info(&self) -> Vec<u8> {
@@ -21,6 +21,20 @@ impl PeerManage for PeerMngInst {
     fn broadcast_message(&self, delay: u64, key: KnowKey, ty: u16, body: Vec<u8>) {
         P2PManage::broadcast_message(self.p2p.clone(), delay, key, ty, body)
     }
+
+    fn broadcast_block(&self, key: KnowKey, full: Vec<u8>, compact: Vec<u8>) {
+        let p2p = self.p2p.clone();
+        tokio::spawn(async move {
+            p2p.broadcast_block_unaware(&key, full, compact).await;
+        });
+    }
+
+    fn broadcast_tx(&self, key: KnowKey, body: Vec<u8>) {
+        let p2p = self.p2p.clone();
+        tokio::spawn(async move {
+            p2p.broadcast_tx_unaware(&key, body).await;
+        });
+    }
 }
 
 
@@ -40,11 +54,17 @@ impl P2PManage {
     }
 
     fn broadcast_message(p2p: Arc<P2PManage>, delay: u64, key: KnowKey, ty: u16, body: Vec<u8>) {
-        tokio::spawn(async move {
+        let rt = p2p.runtime();
+        let task = async move {
             if delay > 0 {
                 asleep(delay as f32).await;
             }
             p2p.broadcast_unaware(&key, ty, body).await;
-        });
+        };
+        // callers may be app or api threads outside of the p2p runtime
+        match rt {
+            Some(rt) => drop(rt.spawn(task)),
+            None => drop(tokio::spawn(task)),
+        }
     }
 }
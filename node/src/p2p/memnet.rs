@@ -0,0 +1,323 @@
+
+/*
+    In-memory network, for running several nodes inside one process.
+
+    Every node gets a `MemEndpoint` (a made-up socket address) instead of a TCP
+    socket; dialing another endpoint yields a pair of in-process byte streams.
+    Each write is one wire frame, so the network can delay frames per link,
+    silently lose them across a partition, and drop a seeded share of
+    MSG_CUSTOMER frames. Handshake and other p2p frames are never dropped, and
+    sealed (encrypted) sessions should not be used with drops.
+*/
+
+/// How frames travel from one endpoint to another.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinkConf {
+    /// One-way delay of every frame.
+    pub latency: Duration,
+    /// Share of MSG_CUSTOMER frames lost, in per mille.
+    pub drop_permille: u16,
+}
+
+type MemFrame = (Instant, Vec<u8>);
+
+#[derive(Default)]
+struct MemNetState {
+    listeners: HashMap<SocketAddr, UnboundedSender<MemConn>>,
+    default_link: LinkConf,
+    links: HashMap<(SocketAddr, SocketAddr), LinkConf>,
+    groups: HashMap<SocketAddr, usize>,
+    rng: u64,
+    dropped: u64,
+}
+
+impl MemNetState {
+    fn reachable(&self, a: &SocketAddr, b: &SocketAddr) -> bool {
+        self.groups.get(a).unwrap_or(&0) == self.groups.get(b).unwrap_or(&0)
+    }
+
+    // xorshift64*, so drops repeat for the same seed and write order
+    fn next_rand(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+}
+
+pub struct MemNet {
+    state: StdMutex<MemNetState>,
+}
+
+impl MemNet {
+    pub fn new(seed: u64) -> Arc<MemNet> {
+        let state = MemNetState {
+            rng: seed | 1,
+            ..Default::default()
+        };
+        Arc::new(MemNet { state: state.into() })
+    }
+
+    pub fn endpoint(self: &Arc<Self>, addr: SocketAddr) -> MemEndpoint {
+        MemEndpoint { net: self.clone(), addr }
+    }
+
+    /// Link used between endpoints without their own `set_link`.
+    pub fn set_default_link(&self, link: LinkConf) {
+        self.state.lock().unwrap().default_link = link;
+    }
+
+    /// Link between `a` and `b`, both directions.
+    pub fn set_link(&self, a: SocketAddr, b: SocketAddr, link: LinkConf) {
+        let mut st = self.state.lock().unwrap();
+        st.links.insert((a, b), link);
+        st.links.insert((b, a), link);
+    }
+
+    /// Split the network: endpoints in different groups cannot dial each
+    /// other and frames between them are lost. Unlisted endpoints join the
+    /// first group.
+    pub fn partition(&self, groups: &[Vec<SocketAddr>]) {
+        let mut st = self.state.lock().unwrap();
+        st.groups.clear();
+        for (i, group) in groups.iter().enumerate() {
+            for addr in group {
+                st.groups.insert(*addr, i);
+            }
+        }
+    }
+
+    pub fn heal(&self) {
+        self.state.lock().unwrap().groups.clear();
+    }
+
+    /// Frames lost to partitions or drops so far.
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    /// Delivery delay of one frame, or None if it is lost.
+    fn route(&self, from: &SocketAddr, to: &SocketAddr, frame: &[u8]) -> Option<Duration> {
+        let mut st = self.state.lock().unwrap();
+        let link = *st.links.get(&(*from, *to)).unwrap_or(&st.default_link);
+        let droppable = frame.len() > 4 && frame[4] == MSG_CUSTOMER
+            && u32::from_be_bytes(bufcut!(frame, 0, 4)) as usize == frame.len() - 4;
+        let lost = !st.reachable(from, to)
+            || (droppable && link.drop_permille > 0 && st.next_rand() % 1000 < link.drop_permille as u64);
+        if lost {
+            st.dropped += 1;
+            return None
+        }
+        Some(link.latency)
+    }
+}
+
+#[derive(Clone)]
+pub struct MemEndpoint {
+    net: Arc<MemNet>,
+    addr: SocketAddr,
+}
+
+impl MemEndpoint {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub(crate) fn listen(&self) -> std::io::Result<MemListener> {
+        let mut st = self.net.state.lock().unwrap();
+        if st.listeners.get(&self.addr).is_some_and(|l| !l.is_closed()) {
+            return Err(std::io::ErrorKind::AddrInUse.into())
+        }
+        let (tx, rx) = unbounded_channel();
+        st.listeners.insert(self.addr, tx);
+        Ok(MemListener { rx })
+    }
+
+    pub(crate) fn dial(&self, to: SocketAddr) -> std::io::Result<MemConn> {
+        let st = self.net.state.lock().unwrap();
+        let refused = || std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        if !st.reachable(&self.addr, &to) {
+            return Err(refused())
+        }
+        let listener = st.listeners.get(&to).ok_or_else(refused)?;
+        let (ours, theirs) = MemConn::pair(&self.net, self.addr, to);
+        listener.send(theirs).map_err(|_| refused())?;
+        Ok(ours)
+    }
+}
+
+pub(crate) struct MemListener {
+    rx: UnboundedReceiver<MemConn>,
+}
+
+impl MemListener {
+    pub(crate) async fn accept(&mut self) -> std::io::Result<MemConn> {
+        self.rx.recv().await.ok_or(std::io::ErrorKind::NotConnected.into())
+    }
+}
+
+pub struct MemReader {
+    rx: UnboundedReceiver<MemFrame>,
+    frame: Option<MemFrame>,
+    offset: usize,
+    delay: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+pub struct MemWriter {
+    net: Arc<MemNet>,
+    from: SocketAddr,
+    to: SocketAddr,
+    tx: Option<UnboundedSender<MemFrame>>,
+}
+
+pub struct MemConn {
+    remote: SocketAddr,
+    reader: MemReader,
+    writer: MemWriter,
+}
+
+impl MemConn {
+    fn pair(net: &Arc<MemNet>, a: SocketAddr, b: SocketAddr) -> (MemConn, MemConn) {
+        let (atx, arx) = unbounded_channel();
+        let (btx, brx) = unbounded_channel();
+        let side = |from, to, tx, rx| MemConn {
+            remote: to,
+            reader: MemReader { rx, frame: None, offset: 0, delay: None },
+            writer: MemWriter { net: net.clone(), from, to, tx: Some(tx) },
+        };
+        (side(a, b, atx, brx), side(b, a, btx, arx))
+    }
+}
+
+impl AsyncRead for MemReader {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        if this.frame.is_none() {
+            match this.rx.poll_recv(cx) {
+                Poll::Ready(Some(frame)) => {
+                    this.frame = Some(frame);
+                    this.offset = 0;
+                }
+                Poll::Ready(None) => return Poll::Ready(Ok(())), // eof
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let (at, data) = this.frame.as_ref().unwrap();
+        if *at > Instant::now() {
+            let delay = this.delay.get_or_insert_with(|| Box::pin(tokio::time::sleep_until(*at)));
+            if delay.as_mut().poll(cx).is_pending() {
+                return Poll::Pending
+            }
+        }
+        this.delay = None;
+        let n = buf.remaining().min(data.len() - this.offset);
+        buf.put_slice(&data[this.offset..this.offset + n]);
+        this.offset += n;
+        if this.offset == data.len() {
+            this.frame = None;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for MemWriter {
+    fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        let Some(tx) = &self.tx else {
+            return Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into()))
+        };
+        if let Some(delay) = self.net.route(&self.from, &self.to, buf) {
+            if tx.send((Instant::now() + delay, buf.to_vec())).is_err() {
+                return Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into()))
+            }
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.tx = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for MemConn {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.reader).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for MemConn {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.writer).poll_shutdown(cx)
+    }
+}
+
+impl PeerConn for MemConn {
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        Ok(self.remote)
+    }
+
+    fn into_halves(self: Box<Self>) -> (ConnReadHalf, ConnWriteHalf) {
+        (Box::new(self.reader), Box::new(self.writer))
+    }
+}
+
+
+#[cfg(test)]
+mod memnet_tests {
+    use super::*;
+
+    fn addr(i: u8) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, i], 3337))
+    }
+
+    #[tokio::test]
+    async fn frames_flow_until_partitioned() {
+        let net = MemNet::new(7);
+        let (a, b) = (net.endpoint(addr(1)), net.endpoint(addr(2)));
+        let mut lb = b.listen().unwrap();
+        assert!(b.listen().is_err());
+        let mut ca = a.dial(b.addr()).unwrap();
+        let mut cb = lb.accept().await.unwrap();
+        assert_eq!(cb.peer_addr().unwrap(), a.addr());
+        tcp_send_msg(&mut ca, MSG_CUSTOMER, vec![1, 2]).await.unwrap();
+        assert_eq!(tcp_read_msg(&mut cb, 1).await.unwrap(), (MSG_CUSTOMER, vec![1, 2]));
+        net.partition(&[vec![a.addr()], vec![b.addr()]]);
+        assert!(a.dial(b.addr()).is_err());
+        tcp_send_msg(&mut ca, MSG_CUSTOMER, vec![3]).await.unwrap();
+        net.heal();
+        tcp_send_msg(&mut ca, MSG_CUSTOMER, vec![4]).await.unwrap();
+        assert_eq!(tcp_read_msg(&mut cb, 1).await.unwrap(), (MSG_CUSTOMER, vec![4]));
+        assert_eq!(net.dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn latency_and_drops_apply_per_link() {
+        let net = MemNet::new(7);
+        let (a, b) = (net.endpoint(addr(1)), net.endpoint(addr(2)));
+        let mut lb = b.listen().unwrap();
+        net.set_link(a.addr(), b.addr(), LinkConf { latency: Duration::from_millis(50), drop_permille: 0 });
+        let mut ca = a.dial(b.addr()).unwrap();
+        let mut cb = lb.accept().await.unwrap();
+        let start = Instant::now();
+        tcp_send_msg(&mut cb, MSG_PING, vec![]).await.unwrap();
+        tcp_read_msg(&mut ca, 1).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        // every custom frame lost, p2p frames still pass
+        net.set_link(a.addr(), b.addr(), LinkConf { latency: Duration::ZERO, drop_permille: 1000 });
+        tcp_send_msg(&mut ca, MSG_CUSTOMER, vec![1]).await.unwrap();
+        tcp_send_msg(&mut ca, MSG_PONG, vec![]).await.unwrap();
+        assert_eq!(tcp_read_msg(&mut cb, 1).await.unwrap(), (MSG_PONG, vec![]));
+    }
+}
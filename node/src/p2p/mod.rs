@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 use std::marker::Unpin;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex as StdMutex};
@@ -7,6 +10,8 @@ use std::time::SystemTime;
 
 use tokio::io::*;
 use tokio::net::*;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::time::Instant;
 
 use basis::config::NodeConf;
 use sys::{self, *};
@@ -18,6 +23,9 @@ use super::*;
 include! {"msg.rs"}
 include! {"util.rs"}
 include! {"dial.rs"}
+include! {"conn.rs"}
+include! {"memnet.rs"}
+include! {"secure.rs"}
 include! {"dht.rs"}
 include! {"find.rs"}
 include! {"ping.rs"}
@@ -5,8 +5,96 @@ pub const MSG_REPORT_PEER: u8 = 1;
 pub const MSG_ANSWER_PEER: u8 = 2;
 pub const MSG_PING: u8 = 3;
 pub const MSG_PONG: u8 = 4;
+pub const MSG_SECURE_AUTH: u8 = 5;
 pub const MSG_REQUEST_NODE_KEY_FOR_PUBLIC_CHECK: u8 = 201;
 pub const MSG_REQUEST_NEAREST_PUBLIC_NODES: u8 = 202;
 pub const MSG_REMIND_ME_IS_PUBLIC: u8 = 151;
 pub const MSG_CLOSE: u8 = 254;
 pub const MSG_CUSTOMER: u8 = 255;
+
+/// Label of a node message carried in MSG_CUSTOMER, by its u16 type.
+fn inner_msg_name(ty: u16) -> Option<&'static str> {
+    Some(match ty {
+        MSG_REQ_STATUS => "req_status",
+        MSG_STATUS => "status",
+        MSG_REQ_BLOCK_HASH => "req_block_hash",
+        MSG_BLOCK_HASH => "block_hash",
+        MSG_REQ_BLOCK => "req_block",
+        MSG_BLOCK => "block",
+        MSG_TX_SUBMIT => "tx_submit",
+        MSG_BLOCK_DISCOVER => "block_discover",
+        MSG_REQ_BLOCK_INTRO => "req_block_intro",
+        MSG_BLOCK_INTRO => "block_intro",
+        MSG_REQ_BLOCK_RANGE => "req_block_range",
+        MSG_BLOCK_RANGE => "block_range",
+        MSG_SEND_COMPACT => "send_compact",
+        MSG_COMPACT_BLOCK => "compact_block",
+        MSG_REQ_BLOCK_TXS => "req_block_txs",
+        MSG_BLOCK_TXS => "block_txs",
+        MSG_SEND_TX_INV => "send_tx_inv",
+        MSG_TX_INV => "tx_inv",
+        MSG_REQ_TXS => "req_txs",
+        MSG_REQ_TX_PROOF => "req_tx_proof",
+        MSG_TX_PROOF => "tx_proof",
+        MSG_REQ_BALANCE => "req_balance",
+        MSG_BALANCE => "balance",
+        _ => return None,
+    })
+}
+
+/// Extension types registered by apps, labelled `ext_<ty>`.
+fn ext_msg_names() -> &'static StdMutex<HashMap<u16, String>> {
+    static NAMES: std::sync::OnceLock<StdMutex<HashMap<u16, String>>> = std::sync::OnceLock::new();
+    NAMES.get_or_init(|| StdMutex::new(HashMap::new()))
+}
+
+/// Give registered extension types their own byte counter label.
+pub(crate) fn label_ext_msgs(tys: &[u16]) {
+    let mut names = ext_msg_names().lock().unwrap();
+    for ty in tys {
+        names.insert(*ty, format!("ext_{}", ty));
+    }
+}
+
+/// Count a plain frame's bytes under `hacash_p2p_bytes_total`, labelled by
+/// direction and `p2p_<ty>`. Extension messages are labelled by their u16
+/// type when it is a node or registered one, else `other`, as the type comes
+/// from the peer and must not mint new series.
+pub(crate) fn count_msg_bytes(dir: &str, ty: u8, body: &[u8]) {
+    static NAMES: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+    let names = NAMES.get_or_init(|| (0..=u8::MAX).map(|t| format!("p2p_{}", t)).collect());
+    let size = 4 + 1 + body.len() as u64;
+    let count = |msg: &str| basis::metrics::counter_add("hacash_p2p_bytes_total", &[("dir", dir), ("msg", msg)], size);
+    if ty != MSG_CUSTOMER {
+        return count(names[ty as usize].as_str());
+    }
+    let Some(ext) = body.get(0..2).map(|b| u16::from_be_bytes([b[0], b[1]])) else {
+        return count("other");
+    };
+    if let Some(msg) = inner_msg_name(ext) {
+        return count(msg);
+    }
+    let exts = ext_msg_names().lock().unwrap();
+    count(exts.get(&ext).map(|s| s.as_str()).unwrap_or("other"))
+}
+
+#[cfg(test)]
+mod msg_tests {
+    use super::*;
+
+    #[test]
+    fn extension_types_are_labelled_when_known() {
+        label_ext_msgs(&[3100]);
+        count_msg_bytes("in", MSG_CUSTOMER, &[0xfe, 0xdc, 1]);
+        count_msg_bytes("in", MSG_CUSTOMER, &[0, MSG_BLOCK_RANGE as u8, 1]);
+        count_msg_bytes("in", MSG_CUSTOMER, &3100u16.to_be_bytes());
+        count_msg_bytes("in", MSG_CUSTOMER, &[9]);
+        count_msg_bytes("in", MSG_PING, &[]);
+        let out = basis::metrics::render();
+        assert!(out.contains("hacash_p2p_bytes_total{dir=\"in\",msg=\"other\"}"));
+        assert!(out.contains("hacash_p2p_bytes_total{dir=\"in\",msg=\"block_range\"}"));
+        assert!(out.contains("hacash_p2p_bytes_total{dir=\"in\",msg=\"ext_3100\"}"));
+        assert!(out.contains("hacash_p2p_bytes_total{dir=\"in\",msg=\"p2p_3\"}"));
+        assert!(!out.contains("65244"));
+    }
+}
@@ -29,13 +29,27 @@ pub struct P2PManage {
     peersnaptx: StdMutex<Option<PeerSnapTx>>,
     pub(crate) peersnaprx: PeerSnapRx,
     pub(crate) shutdown: Arc<tokio::sync::Notify>,
+    pub(crate) identity: Option<Account>,
+    pub(crate) pins: PeerPins,
+    /// Listen and dial here instead of on TCP.
+    pub(crate) memnet: Option<MemEndpoint>,
+    /// The runtime p2p runs on, for broadcasts from outside of it.
+    rt: StdMutex<Option<tokio::runtime::Handle>>,
 }
 
 impl P2PManage {
-    pub fn new(cnf: &NodeConf, msghl: Arc<MsgHandler>) -> P2PManage {
+    pub fn new(cnf: &NodeConf, msghl: Arc<MsgHandler>, memnet: Option<MemEndpoint>) -> P2PManage {
         let (peertabletx, peertablerx) = tokio::sync::mpsc::channel(1024);
         let peersnap = Arc::new(PeerTableSnap::new(vec![], vec![]));
         let (peersnaptx, peersnaprx) = tokio::sync::watch::channel(peersnap);
+        let identity = match cnf.encrypt {
+            true => load_node_identity(&cnf.data_dir)
+                .inspect_err(|e| println!("[P2P Error] Node identity unavailable, sessions stay plaintext: {}", e))
+                .ok(),
+            false => None,
+        };
+        let pins = PeerPins::default();
+        pins.open(join_path(&cnf.data_dir, "peer.pins"));
         P2PManage {
             cnf: cnf.clone(),
             msghandler: msghl,
@@ -44,6 +58,10 @@ impl P2PManage {
             peersnaptx: Some(peersnaptx).into(),
             peersnaprx,
             shutdown: Arc::new(tokio::sync::Notify::new()),
+            identity,
+            pins,
+            memnet,
+            rt: None.into(),
         }
     }
 
@@ -78,6 +96,8 @@ impl P2PManage {
                     }
                 };
                 if changed {
+                    basis::metrics::gauge_set("hacash_p2p_peers", &[("table", "backbones")], backbones.len() as f64);
+                    basis::metrics::gauge_set("hacash_p2p_peers", &[("table", "offshoots")], offshoots.len() as f64);
                     let peersnap = Arc::new(PeerTableSnap::new(backbones.clone(), offshoots.clone()));
                     let _ = peersnaptx.send(peersnap);
                 }
@@ -162,6 +182,10 @@ impl P2PManage {
             l1, l2, mykp, l1names.join(", "))
     }
 
+    pub(crate) fn runtime(&self) -> Option<tokio::runtime::Handle> {
+        self.rt.lock().unwrap().clone()
+    }
+
     pub fn exit(&self) {
         self.shutdown.notify_waiters();
     }
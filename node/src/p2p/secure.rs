@@ -0,0 +1,490 @@
+/*
+    Encrypted and authenticated peer sessions.
+
+    Negotiation rides on the existing peer report: a node that wants a secure
+    session appends a hello (ephemeral X25519 key and static secp256k1 identity
+    key) after its node info in MSG_REPORT_PEER / MSG_ANSWER_PEER. Legacy nodes
+    ignore the trailing bytes and answer without a hello, so the connection stays
+    plaintext. When both sides sent a hello, frame keys for each direction are
+    derived with HKDF from the X25519 secret salted by the transcript hash of both
+    report bodies, every later frame is sealed with ChaCha20-Poly1305, and the
+    first sealed frame each way is MSG_SECURE_AUTH carrying a signature of the
+    transcript by the static identity key.
+*/
+
+pub const SECURE_HELLO_MAGIC: [u8; 4] = *b"HSEC";
+pub const SECURE_HELLO_VERSION: u8 = 1;
+pub const SECURE_HELLO_SIZE: usize = 4 + 1 + 32 + 33;
+const SECURE_TAG_SIZE: usize = 16;
+const SECURE_AUTH_DOMAIN: &[u8] = b"hacash p2p auth";
+
+/// Our half of a secure handshake, kept until the remote hello arrives.
+pub struct SecureHello {
+    eph: ring::agreement::EphemeralPrivateKey,
+    pub bytes: Vec<u8>,
+}
+
+impl SecureHello {
+    pub fn new(identity: &Account) -> Ret<SecureHello> {
+        let rng = ring::rand::SystemRandom::new();
+        let eph = ring::agreement::EphemeralPrivateKey::generate(&ring::agreement::X25519, &rng)
+            .map_err(|_| "cannot generate ephemeral key".to_owned())?;
+        let ephpub = eph
+            .compute_public_key()
+            .map_err(|_| "cannot compute ephemeral key".to_owned())?;
+        let bytes = [
+            &SECURE_HELLO_MAGIC[..],
+            &[SECURE_HELLO_VERSION],
+            ephpub.as_ref(),
+            &identity.public_key().serialize_compressed(),
+        ]
+        .concat();
+        Ok(SecureHello { eph, bytes })
+    }
+}
+
+/// Ephemeral and static public keys from a remote hello at the start of `buf`.
+pub fn parse_secure_hello(buf: &[u8]) -> Option<([u8; 32], [u8; 33])> {
+    if buf.len() < SECURE_HELLO_SIZE || buf[0..4] != SECURE_HELLO_MAGIC || buf[4] != SECURE_HELLO_VERSION {
+        return None
+    }
+    let eph = buf[5..37].try_into().ok()?;
+    let stc = buf[37..70].try_into().ok()?;
+    Some((eph, stc))
+}
+
+fn secure_nonce(counter: &mut u64) -> ring::aead::Nonce {
+    let mut n = [0u8; 12];
+    n[4..].copy_from_slice(&counter.to_be_bytes());
+    *counter += 1;
+    ring::aead::Nonce::assume_unique_for_key(n)
+}
+
+/// Seals outgoing frames of one direction.
+pub struct FrameSealer {
+    key: ring::aead::LessSafeKey,
+    counter: u64,
+}
+
+impl FrameSealer {
+    /// `frame` is a plain frame from `tcp_create_msg`; returns the wire bytes.
+    pub fn seal(&mut self, frame: &[u8]) -> Vec<u8> {
+        let mut ct = frame.to_vec();
+        let nonce = secure_nonce(&mut self.counter);
+        self.key
+            .seal_in_place_append_tag(nonce, ring::aead::Aad::empty(), &mut ct)
+            .expect("chacha20 seal never fails for frame sizes");
+        [(ct.len() as u32).to_be_bytes().to_vec(), ct].concat()
+    }
+}
+
+/// Opens incoming frames of one direction.
+pub struct FrameOpener {
+    key: ring::aead::LessSafeKey,
+    counter: u64,
+}
+
+impl FrameOpener {
+    /// Returns the plain frame, or an error if it was forged, reordered or replayed.
+    pub fn open(&mut self, mut ct: Vec<u8>) -> Ret<Vec<u8>> {
+        let nonce = secure_nonce(&mut self.counter);
+        let plain = self
+            .key
+            .open_in_place(nonce, ring::aead::Aad::empty(), &mut ct)
+            .map_err(|_| "secure frame authentication failed".to_owned())?;
+        Ok(plain.to_vec())
+    }
+}
+
+pub struct SecureSession {
+    pub sealer: FrameSealer,
+    pub opener: FrameOpener,
+    pub remote_static: [u8; 33],
+    transcript: [u8; 32],
+    dialer: bool,
+}
+
+fn secure_frame_key(prk: &ring::hkdf::Prk, info: &[u8]) -> Ret<ring::aead::LessSafeKey> {
+    let info = [info];
+    let okm = prk
+        .expand(&info, &ring::aead::CHACHA20_POLY1305)
+        .map_err(|_| "secure key expand failed".to_owned())?;
+    Ok(ring::aead::LessSafeKey::new(ring::aead::UnboundKey::from(okm)))
+}
+
+/// Finish the key exchange. `report` and `answer` are the full bodies of the
+/// MSG_REPORT_PEER and MSG_ANSWER_PEER messages, hellos included.
+pub fn secure_session(local: SecureHello, remote_eph: [u8; 32], remote_static: [u8; 33], report: &[u8], answer: &[u8], dialer: bool) -> Ret<SecureSession> {
+    let transcript = sys::sha3([report, answer].concat());
+    let peerpub = ring::agreement::UnparsedPublicKey::new(&ring::agreement::X25519, remote_eph);
+    let prk = ring::agreement::agree_ephemeral(local.eph, &peerpub, |shared| {
+        ring::hkdf::Salt::new(ring::hkdf::HKDF_SHA256, &transcript).extract(shared)
+    }).map_err(|_| "secure key agreement failed".to_owned())?;
+    let d2a = secure_frame_key(&prk, b"hacash p2p dialer to acceptor")?;
+    let a2d = secure_frame_key(&prk, b"hacash p2p acceptor to dialer")?;
+    let (send, recv) = match dialer {
+        true => (d2a, a2d),
+        false => (a2d, d2a),
+    };
+    Ok(SecureSession {
+        sealer: FrameSealer { key: send, counter: 0 },
+        opener: FrameOpener { key: recv, counter: 0 },
+        remote_static,
+        transcript,
+        dialer,
+    })
+}
+
+impl SecureSession {
+    fn auth_digest(&self, dialer: bool) -> [u8; 32] {
+        sys::sha3([SECURE_AUTH_DOMAIN, &[dialer as u8], &self.transcript].concat())
+    }
+
+    /// Our signature proving we hold the identity key announced in our hello.
+    pub fn auth_proof(&self, identity: &Account) -> Vec<u8> {
+        identity.do_sign(&self.auth_digest(self.dialer)).to_vec()
+    }
+
+    pub fn check_proof(&self, sign: &[u8]) -> Rerr {
+        let Ok(sign) = <[u8; 64]>::try_from(sign) else {
+            return errf!("secure auth proof size error")
+        };
+        if !Account::verify_signature(&self.auth_digest(!self.dialer), &self.remote_static, &sign) {
+            return errf!("secure auth proof invalid")
+        }
+        Ok(())
+    }
+}
+
+/// Static identity key of this node, kept next to `node.id` and created on first use.
+pub fn load_node_identity(data_dir: &std::path::Path) -> Ret<Account> {
+    let path = join_path(data_dir, "node.identity");
+    if let Ok(hx) = std::fs::read_to_string(&path) {
+        if let Ok(Ok(key)) = hex::decode(hx.trim()).map(<[u8; 32]>::try_from) {
+            return Account::create_by_secret_key_value(key)
+        }
+    }
+    let acc = Account::create_randomly(&|buf: &mut [u8]| {
+        getrandom::fill(buf).map_err(|e| e.to_string())
+    })?;
+    let _ = std::fs::create_dir_all(data_dir);
+    std::fs::write(&path, hex::encode(acc.secret_key().serialize())).map_err(|e| e.to_string())?;
+    Ok(acc)
+}
+
+/// Secure session settings of one connection, built by `handle_conn`.
+pub struct SecureOffer {
+    pub identity: Option<Account>,
+    pub only: bool,
+    /// Dialer side: our hello and the full MSG_REPORT_PEER body it was sent in.
+    pub sent: Option<(SecureHello, Vec<u8>)>,
+}
+
+/*
+    Identity keys pinned on first secure contact, kept in `peer.pins` as
+    `pubkey key seen` lines: the identity public key, the node key it proved
+    last and the unix second it was last seen. While the pin is live, that node
+    key is only accepted over a secure session proving the same identity, so it
+    can no longer be claimed by another node or downgraded to plaintext.
+    Plaintext contact never writes a pin, an identity that shows up with a new
+    node key moves its pin there, and a pin unseen for PEER_PIN_EXPIRE seconds
+    is dropped. `clear` lifts pins by hand.
+*/
+pub const PEER_PIN_EXPIRE: u64 = 60 * 60 * 24 * 30;
+const PEER_PIN_SEEN_STEP: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Copy)]
+struct PeerPin {
+    key: PeerKey,
+    seen: u64,
+}
+
+#[derive(Debug, Default)]
+struct PinTable {
+    ids: HashMap<[u8; 33], PeerPin>,
+    keys: HashMap<PeerKey, [u8; 33]>,
+}
+
+impl PinTable {
+    fn insert(&mut self, idt: [u8; 33], pin: PeerPin) {
+        if let Some(old) = self.ids.insert(idt, pin) {
+            self.keys.remove(&old.key);
+        }
+        self.keys.insert(pin.key, idt);
+    }
+
+    fn remove(&mut self, idt: &[u8; 33]) -> bool {
+        let Some(pin) = self.ids.remove(idt) else {
+            return false
+        };
+        self.keys.remove(&pin.key);
+        true
+    }
+
+    // the identity holding a live pin on `key`
+    fn owner(&mut self, key: &PeerKey, now: u64) -> Option<[u8; 33]> {
+        let idt = *self.keys.get(key)?;
+        if self.ids[&idt].seen + PEER_PIN_EXPIRE < now {
+            self.remove(&idt);
+            return None
+        }
+        Some(idt)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PeerPins {
+    pins: StdMutex<PinTable>,
+    path: StdMutex<Option<PathBuf>>,
+}
+
+impl PeerPins {
+
+    pub fn open(&self, path: PathBuf) {
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let now = curtimes();
+        let mut pins = self.pins.lock().unwrap();
+        *pins = PinTable::default();
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let idt = parts.next().and_then(|k| hex::decode(k).ok()?.try_into().ok());
+            let key = parts.next().and_then(|k| hex::decode(k).ok()?.try_into().ok());
+            let seen = parts.next().and_then(|s| s.parse::<u64>().ok());
+            if let (Some(idt), Some(key), Some(seen)) = (idt, key, seen)
+                && seen + PEER_PIN_EXPIRE >= now
+            {
+                pins.insert(idt, PeerPin { key, seen });
+            }
+        }
+        *self.path.lock().unwrap() = Some(path);
+    }
+
+    /// Check the node `key` of a peer against the pins, pinning or refreshing
+    /// `identity` if the session proved one.
+    pub fn check(&self, key: &PeerKey, identity: Option<&[u8; 33]>) -> Rerr {
+        self.check_at(key, identity, curtimes())
+    }
+
+    fn check_at(&self, key: &PeerKey, identity: Option<&[u8; 33]>, now: u64) -> Rerr {
+        let mut pins = self.pins.lock().unwrap();
+        match (pins.owner(key, now), identity) {
+            (Some(pin), Some(idt)) if pin == *idt => {
+                let seen = &mut pins.ids.get_mut(idt).unwrap().seen;
+                let stale = *seen + PEER_PIN_SEEN_STEP < now;
+                *seen = now;
+                if stale {
+                    self.persist(&pins);
+                }
+                Ok(())
+            }
+            (Some(_), Some(_)) => errf!("node key is pinned to another identity"),
+            (Some(_), None) => errf!("pinned node connected without encryption"),
+            (None, None) => Ok(()),
+            (None, Some(idt)) => {
+                pins.insert(*idt, PeerPin { key: *key, seen: now });
+                self.persist(&pins);
+                Ok(())
+            }
+        }
+    }
+
+    /// Drop the pins matching a hex identity public key or node key,
+    /// returning how many were removed.
+    pub fn clear(&self, target: &str) -> usize {
+        let Ok(bts) = hex::decode(target.trim()) else {
+            return 0
+        };
+        let mut pins = self.pins.lock().unwrap();
+        let idt = match bts.len() {
+            33 => <[u8; 33]>::try_from(bts).ok(),
+            PEER_KEY_SIZE => <PeerKey>::try_from(bts).ok().and_then(|k| pins.keys.get(&k).copied()),
+            _ => None,
+        };
+        let removed = idt.is_some_and(|idt| pins.remove(&idt));
+        if removed {
+            self.persist(&pins);
+        }
+        removed as usize
+    }
+
+    fn persist(&self, pins: &PinTable) {
+        let Some(path) = self.path.lock().unwrap().clone() else {
+            return
+        };
+        let out: String = pins
+            .ids
+            .iter()
+            .map(|(i, p)| format!("{} {} {}\n", hex::encode(i), hex::encode(p.key), p.seen))
+            .collect();
+        let _ = std::fs::write(&path, out);
+    }
+}
+
+/// Read one sealed frame and return its message type and body.
+pub async fn tcp_read_secure_msg(conn: &mut (impl AsyncRead + Unpin), opener: &mut FrameOpener, outsec: u64) -> Ret<(u8, Vec<u8>)> {
+    let size = tcp_read(conn, 4, outsec).await?;
+    let size = u32::from_be_bytes( bufcut!(size, 0, 4) );
+    if (size as usize) < 4 + 1 + SECURE_TAG_SIZE || size > P2P_MSG_DATA_MAX_SIZE + 4 + SECURE_TAG_SIZE as u32 {
+        return errf!("tcp msg size error")
+    }
+    let ct = tcp_read(conn, size as usize, outsec).await?;
+    let frame = opener.open(ct)?;
+    let inner = u32::from_be_bytes( bufcut!(frame, 0, 4) ) as usize;
+    if inner < 1 || inner + 4 != frame.len() {
+        return errf!("secure frame size error")
+    }
+    Ok((frame[4], frame[5..].to_vec()))
+}
+
+#[cfg(test)]
+mod secure_tests {
+    use super::*;
+
+    fn identity(n: u8) -> Account {
+        Account::create_by_secret_key_value([n; 32]).unwrap()
+    }
+
+    fn pair() -> (SecureSession, SecureSession, Account, Account) {
+        let (ida, idb) = (identity(3), identity(4));
+        let (ha, hb) = (SecureHello::new(&ida).unwrap(), SecureHello::new(&idb).unwrap());
+        let report = [b"dialer-info".to_vec(), ha.bytes.clone()].concat();
+        let answer = [b"acceptor-info".to_vec(), hb.bytes.clone()].concat();
+        let (ea, sa) = parse_secure_hello(&report[11..]).unwrap();
+        let (eb, sb) = parse_secure_hello(&answer[13..]).unwrap();
+        let dialer = secure_session(ha, eb, sb, &report, &answer, true).unwrap();
+        let acceptor = secure_session(hb, ea, sa, &report, &answer, false).unwrap();
+        (dialer, acceptor, ida, idb)
+    }
+
+    #[test]
+    fn frames_roundtrip_both_ways_and_reject_tampering() {
+        let (mut d, mut a, ..) = pair();
+        for i in 0..3u8 {
+            let frame = tcp_create_msg(MSG_CUSTOMER, vec![i; 40]);
+            let wire = d.sealer.seal(&frame);
+            assert_eq!(a.opener.open(wire[4..].to_vec()).unwrap(), frame);
+            let back = a.sealer.seal(&frame);
+            assert_eq!(d.opener.open(back[4..].to_vec()).unwrap(), frame);
+        }
+        let mut wire = d.sealer.seal(&tcp_create_msg(MSG_PING, vec![]));
+        wire[6] ^= 1;
+        assert!(a.opener.open(wire[4..].to_vec()).is_err());
+    }
+
+    #[test]
+    fn auth_proof_binds_identity_and_role() {
+        let (d, a, ida, idb) = pair();
+        a.check_proof(&d.auth_proof(&ida)).unwrap();
+        d.check_proof(&a.auth_proof(&idb)).unwrap();
+        // a proof made with another key, or reflected back, is refused
+        assert!(a.check_proof(&d.auth_proof(&identity(9))).is_err());
+        assert!(d.check_proof(&d.auth_proof(&ida)).is_err());
+    }
+
+    #[tokio::test]
+    async fn sealed_frame_reads_back_as_message() {
+        let (mut d, mut a, ..) = pair();
+        let wire = d.sealer.seal(&tcp_create_msg(MSG_CUSTOMER, vec![1, 2, 3]));
+        let mut rd = &wire[..];
+        let (ty, body) = tcp_read_secure_msg(&mut rd, &mut a.opener, 1).await.unwrap();
+        assert_eq!((ty, body), (MSG_CUSTOMER, vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn pins_refuse_other_identity_and_downgrade() {
+        let pins = PeerPins::default();
+        let (key, other) = ([1u8; PEER_KEY_SIZE], [2u8; PEER_KEY_SIZE]);
+        let (ida, idb) = ([3u8; 33], [4u8; 33]);
+        let now = 1_700_000_000;
+        // plaintext contact pins nothing, so it cannot lock out a later identity
+        pins.check_at(&key, None, now).unwrap();
+        pins.check_at(&key, Some(&ida), now).unwrap();
+        pins.check_at(&key, Some(&ida), now).unwrap();
+        assert!(pins.check_at(&key, Some(&idb), now).is_err());
+        assert!(pins.check_at(&key, None, now).is_err());
+        // the identity moves to a new node key, the old one is free again
+        pins.check_at(&other, Some(&ida), now).unwrap();
+        pins.check_at(&key, None, now).unwrap();
+        assert!(pins.check_at(&other, None, now).is_err());
+        // unseen pins expire
+        let later = now + PEER_PIN_EXPIRE + 1;
+        pins.check_at(&other, None, later).unwrap();
+        pins.check_at(&other, Some(&idb), later).unwrap();
+    }
+
+    #[test]
+    fn pins_clear_by_identity_or_node_key_and_persist() {
+        let path = std::env::temp_dir().join(format!("peer.pins.{}", std::process::id()));
+        let pins = PeerPins::default();
+        pins.open(path.clone());
+        let (key, ida, idb) = ([1u8; PEER_KEY_SIZE], [3u8; 33], [4u8; 33]);
+        pins.check(&key, Some(&ida)).unwrap();
+        pins.check(&[2u8; PEER_KEY_SIZE], Some(&idb)).unwrap();
+        let reopened = PeerPins::default();
+        reopened.open(path.clone());
+        assert!(reopened.check(&key, None).is_err());
+        assert_eq!(reopened.clear(&hex::encode(key)), 1);
+        assert_eq!(reopened.clear(&hex::encode(idb)), 1);
+        assert_eq!(reopened.clear(&hex::encode(idb)), 0);
+        reopened.check(&key, None).unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    fn nodeinfo(n: u8) -> Vec<u8> {
+        [vec![0u8; 4], vec![n; PEER_KEY_SIZE], b"node            ".to_vec()].concat()
+    }
+
+    // Run the peer report exchange over loopback, returning both ends.
+    async fn connect(dial: Option<Account>, accept: Option<Account>, only: bool) -> (Ret<(Arc<Peer>, PeerReader)>, Ret<(Arc<Peer>, PeerReader)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let acceptor = tokio::spawn(async move {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            let (ty, body) = tcp_read_msg(&mut conn, 5).await?;
+            let offer = SecureOffer { identity: accept, only, sent: None };
+            Peer::create_with_msg(Box::new(conn), ty, body, nodeinfo(2), offer).await
+        });
+        let mut conn = TcpStream::connect(addr).await.unwrap();
+        let mut report = nodeinfo(1);
+        let mut offer = SecureOffer { identity: dial, only: false, sent: None };
+        if let Some(idt) = &offer.identity {
+            let hello = SecureHello::new(idt).unwrap();
+            report.extend_from_slice(&hello.bytes);
+            offer.sent = Some((hello, report.clone()));
+        }
+        tcp_send_msg(&mut conn, MSG_REPORT_PEER, report).await.unwrap();
+        let dialed = async {
+            let (ty, body) = tcp_read_msg(&mut conn, 5).await?;
+            Peer::create_with_msg(Box::new(conn), ty, body, nodeinfo(1), offer).await
+        }.await;
+        (dialed, acceptor.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn handshake_encrypts_or_falls_back_to_plaintext() {
+        let (d, a) = connect(Some(identity(3)), Some(identity(4)), false).await;
+        let ((dp, mut dr), (ap, mut ar)) = (d.unwrap(), a.unwrap());
+        assert!(dp.secure && ap.secure);
+        assert_eq!(dp.identity, Some(identity(4).public_key().serialize_compressed()));
+        assert_eq!(ap.identity, Some(identity(3).public_key().serialize_compressed()));
+        dp.send_p2p_msg(MSG_PING, vec![7]).await.unwrap();
+        assert_eq!(ar.read_msg(5).await.unwrap(), (MSG_PING, vec![7]));
+        ap.send_msg(9, vec![1, 2]).await.unwrap();
+        assert_eq!(dr.read_msg(5).await.unwrap(), (MSG_CUSTOMER, vec![0, 9, 1, 2]));
+
+        // a legacy dialer without hello stays plaintext, unless plaintext is refused
+        let (d, a) = connect(None, Some(identity(4)), false).await;
+        let ((dp, _), (ap, mut ar)) = (d.unwrap(), a.unwrap());
+        assert!(!dp.secure && !ap.secure && ap.identity.is_none());
+        dp.send_p2p_msg(MSG_PONG, vec![]).await.unwrap();
+        assert_eq!(ar.read_msg(5).await.unwrap(), (MSG_PONG, vec![]));
+        let (_, a) = connect(None, Some(identity(4)), true).await;
+        assert!(a.is_err());
+    }
+
+    #[test]
+    fn legacy_report_has_no_hello() {
+        assert!(parse_secure_hello(&[0u8; 32]).is_none());
+        assert!(parse_secure_hello(&[]).is_none());
+    }
+}
@@ -1,9 +1,12 @@
 
 impl P2PManage {
 
-    pub(crate) async fn server(&self) -> std::io::Result<TcpListener> {
+    pub(crate) async fn server(&self) -> std::io::Result<P2PListener> {
+        if let Some(net) = &self.memnet {
+            return net.listen().map(P2PListener::Mem)
+        }
         let port = self.cnf.listen;
-        TcpListener::bind(format!("0.0.0.0:{}", port)).await
+        TcpListener::bind(format!("0.0.0.0:{}", port)).await.map(P2PListener::Tcp)
     }
 
 }
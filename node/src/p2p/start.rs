@@ -2,6 +2,7 @@
 impl P2PManage {
 
     pub async fn start(this: Arc<P2PManage>, worker: Worker) -> Rerr {
+        *this.rt.lock().unwrap() = Some(tokio::runtime::Handle::current());
         this.start_peer_table_loop();
 
         let p2p = this.clone();
@@ -1,11 +1,9 @@
 use std::collections::VecDeque;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
 use std::time::SystemTime;
 
-use tokio::net::tcp::*;
-use tokio::net::*;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 
 use field::*;
@@ -20,3 +18,4 @@ include! {"trait.rs"}
 include! {"know.rs"}
 include! {"peer.rs"}
 include! {"send.rs"}
+include! {"score.rs"}
@@ -2,6 +2,10 @@ use tokio::sync::Notify;
 
 static PEER_AUTO_ID_INCREASE: AtomicU64 = AtomicU64::new(0);
 
+/// Tx hashes queued for one peer between inventory flushes; past it the
+/// oldest are dropped, as a peer that cannot keep up only needs recent ones.
+const INV_QUEUE_MAX: usize = 4096;
+
 #[derive(Debug)]
 enum PeerWriterCmd {
     Send(Vec<u8>),
@@ -21,6 +25,33 @@ pub struct Peer {
     pub writer_closed: StdMutex<bool>,
     pub close_notify: Arc<Notify>,
     pub knows: Knowledge,
+    /// Peer announced it takes compact blocks.
+    pub compact: AtomicBool,
+    /// Peer announced it takes tx inventory instead of full tx floods.
+    pub tx_inv: AtomicBool,
+    /// Transactions the peer has or was announced, by hash with fee.
+    pub tx_knows: Knowledge,
+    inv_queue: StdMutex<VecDeque<KnowKey>>,
+    /// Frames to this peer are encrypted and its identity key was proven.
+    pub secure: bool,
+    pub identity: Option<[u8; 33]>,
+}
+
+/// Read side of a peer connection, opening sealed frames on secure sessions.
+pub struct PeerReader {
+    half: ConnReadHalf,
+    opener: Option<FrameOpener>,
+}
+
+impl PeerReader {
+    pub async fn read_msg(&mut self, outsec: u64) -> Ret<(u8, Vec<u8>)> {
+        let (ty, msg) = match &mut self.opener {
+            Some(opener) => tcp_read_secure_msg(&mut self.half, opener, outsec).await,
+            None => tcp_read_msg(&mut self.half, outsec).await,
+        }?;
+        count_msg_bytes("in", ty, &msg);
+        Ok((ty, msg))
+    }
 }
 
 impl Peer {
@@ -49,13 +80,26 @@ impl Peer {
         *self.writer_closed.lock().unwrap() = true;
     }
 
+    /// Queue a tx hash for the next inventory flush.
+    pub fn queue_inv(&self, key: KnowKey) {
+        let mut queue = self.inv_queue.lock().unwrap();
+        if queue.len() >= INV_QUEUE_MAX {
+            queue.pop_front();
+        }
+        queue.push_back(key);
+    }
+
+    pub fn take_inv(&self) -> Vec<KnowKey> {
+        std::mem::take(&mut *self.inv_queue.lock().unwrap()).into()
+    }
+
     pub fn disconnect(&self) {
         self.mark_writer_closed();
         let _ = self.writer_tx.try_send(PeerWriterCmd::Close);
         self.close_notify.notify_waiters();
     }
 
-    pub async fn create_with_msg(mut stream: TcpStream, ty: u8, msg: Vec<u8>, mynodeinfo: Vec<u8>) -> Ret<(Arc<Peer>, OwnedReadHalf)> {
+    pub async fn create_with_msg(mut stream: Box<dyn PeerConn>, ty: u8, msg: Vec<u8>, mynodeinfo: Vec<u8>, offer: SecureOffer) -> Ret<(Arc<Peer>, PeerReader)> {
         let mut mykeyname = mynodeinfo;
         if mykeyname.len() > PEER_KEY_SIZE*2 {
             mykeyname = mykeyname[4..].to_vec();
@@ -87,13 +131,39 @@ impl Peer {
         if peerkey == mykeyname[0..PEER_KEY_SIZE] {
             return  errf!("cannot connect to self")
         }
+        let SecureOffer { identity, only, sent } = offer;
+        let mut session = None;
+        if MSG_REPORT_PEER == ty {
+            let mut answer = mykeyname.clone();
+            if let (Some(idt), Some((eph, stc))) = (&identity, parse_secure_hello(&idnamebts[32..])) {
+                let hello = SecureHello::new(idt)?;
+                answer.extend_from_slice(&hello.bytes);
+                session = Some(secure_session(hello, eph, stc, &msg, &answer, false)?);
+            } else if only {
+                return errf!("plaintext peer refused")
+            }
+            tcp_send_msg(conn, MSG_ANSWER_PEER, answer).await?;
+        } else if let (Some((hello, report)), Some((eph, stc))) = (sent, parse_secure_hello(&idnamebts[32..])) {
+            session = Some(secure_session(hello, eph, stc, &report, &msg, true)?);
+        } else if only {
+            return errf!("plaintext peer refused")
+        }
+        if let (Some(ss), Some(idt)) = (&mut session, &identity) {
+            let proof = tcp_create_msg(MSG_SECURE_AUTH, ss.auth_proof(idt));
+            tcp_send(conn, &ss.sealer.seal(&proof)).await?;
+            let (aty, sign) = tcp_read_secure_msg(conn, &mut ss.opener, 5).await?;
+            if aty != MSG_SECURE_AUTH {
+                return errf!("secure auth proof missing")
+            }
+            ss.check_proof(&sign)?;
+        }
         if MSG_REPORT_PEER == ty {
-            tcp_send_msg(conn, MSG_ANSWER_PEER, mykeyname.clone()).await?;
-            if oginport > 0 {
+            // a loopback peer is never public, so don't dial it back
+            if oginport > 0 && !addr.ip().is_loopback() {
                 let mut pubaddr = addr.clone();
                 pubaddr.set_port(oginport);
                 if let Ok(pb) = tcp_dial_to_check_is_public_id(pubaddr, &peerkey, 3).await {
-                    if pb && !addr.ip().is_loopback() {
+                    if pb {
                         is_public = true;
                         addr.set_port(oginport);
                     }
@@ -101,7 +171,11 @@ impl Peer {
             }
         }
 
-        let (read_half, write_half) = stream.into_split();
+        let (sealer, opener, identity) = match session {
+            Some(ss) => (Some(ss.sealer), Some(ss.opener), Some(ss.remote_static)),
+            None => (None, None, None),
+        };
+        let (read_half, write_half) = stream.into_halves();
         let (writer_tx, writer_rx) = mpsc::channel(128);
 
         let atid = PEER_AUTO_ID_INCREASE.fetch_add(1, Ordering::Relaxed) + 1;
@@ -118,25 +192,40 @@ impl Peer {
             writer_closed: false.into(),
             close_notify: Arc::new(Notify::new()),
             knows: Knowledge::new(500),
+            compact: AtomicBool::new(false),
+            tx_inv: AtomicBool::new(false),
+            tx_knows: Knowledge::new(2000),
+            inv_queue: StdMutex::new(VecDeque::new()),
+            secure: identity.is_some(),
+            identity,
         };
         let pptr = Arc::new(peer);
-        Peer::spawn_writer(pptr.clone(), write_half, writer_rx);
+        Peer::spawn_writer(pptr.clone(), write_half, writer_rx, sealer);
 
-        Ok((pptr, read_half))
+        Ok((pptr, PeerReader { half: read_half, opener }))
     }
 
-    fn spawn_writer(peer: Arc<Peer>, mut write_half: OwnedWriteHalf, mut writer_rx: Receiver<PeerWriterCmd>) {
+    fn spawn_writer(peer: Arc<Peer>, mut write_half: ConnWriteHalf, mut writer_rx: Receiver<PeerWriterCmd>, mut sealer: Option<FrameSealer>) {
         tokio::spawn(async move {
             while let Some(cmd) = writer_rx.recv().await {
                 match cmd {
-                    PeerWriterCmd::Send(buf) => {
+                    PeerWriterCmd::Send(mut buf) => {
+                        if buf.len() > 4 {
+                            count_msg_bytes("out", buf[4], &buf[5..]);
+                        }
+                        if let Some(sl) = &mut sealer {
+                            buf = sl.seal(&buf);
+                        }
                         if tcp_send(&mut write_half, &buf).await.is_err() {
                             peer.mark_writer_closed();
                             break;
                         }
                     }
                     PeerWriterCmd::Close => {
-                        let close_msg = tcp_create_msg(MSG_CLOSE, vec![]);
+                        let mut close_msg = tcp_create_msg(MSG_CLOSE, vec![]);
+                        if let Some(sl) = &mut sealer {
+                            close_msg = sl.seal(&close_msg);
+                        }
                         let _ = tcp_send(&mut write_half, &close_msg).await;
                         break;
                     }
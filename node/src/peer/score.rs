@@ -0,0 +1,280 @@
+/// Kinds of peer misbehaviour reported by the message handlers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Misbehave {
+    BadHandshake,
+    InvalidBlock,
+    InvalidTx,
+    Malformed,
+    Unsolicited,
+    Timeout,
+}
+
+impl Misbehave {
+    pub fn penalty(&self) -> u64 {
+        match self {
+            Misbehave::BadHandshake => PEER_BAN_SCORE,
+            Misbehave::InvalidBlock => 25,
+            Misbehave::Malformed => 20,
+            Misbehave::Timeout => 10,
+            Misbehave::InvalidTx => 5,
+            Misbehave::Unsolicited => 5,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Misbehave::BadHandshake => "bad_handshake",
+            Misbehave::InvalidBlock => "invalid_block",
+            Misbehave::InvalidTx => "invalid_tx",
+            Misbehave::Malformed => "malformed",
+            Misbehave::Unsolicited => "unsolicited",
+            Misbehave::Timeout => "timeout",
+        }
+    }
+}
+
+/// Score at which a peer gets banned.
+pub const PEER_BAN_SCORE: u64 = 100;
+/// One point of misbehaviour score is forgiven per this many seconds.
+const SCORE_DECAY_SECS: u64 = 60;
+/// How long a ban lasts.
+const PEER_BAN_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerBan {
+    pub ip: std::net::IpAddr,
+    pub key: PeerKey,
+    pub name: String,
+    pub reason: String,
+    pub until: u64,
+}
+
+#[derive(Debug, Clone)]
+struct PeerScore {
+    ip: std::net::IpAddr,
+    name: String,
+    score: u64,
+    last: &'static str,
+    updated: u64,
+}
+
+/*
+    Misbehaviour scores of connected peers and the ban list.
+
+    Scores decay over time so occasional noise from honest peers is forgiven.
+    A peer reaching `PEER_BAN_SCORE` is banned by both IP and node key until the
+    ban expires. Bans are kept in a plain text file under the data dir, one per
+    line: `until ip key name reason`.
+*/
+#[derive(Debug, Default)]
+pub struct PeerReputation {
+    scores: StdMutex<std::collections::HashMap<PeerKey, PeerScore>>,
+    bans: StdMutex<Vec<PeerBan>>,
+    path: StdMutex<Option<std::path::PathBuf>>,
+}
+
+impl PeerReputation {
+
+    /// Load bans still in force from `path` and persist future changes there.
+    pub fn open(&self, path: std::path::PathBuf) {
+        let now = curtimes();
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut bans = self.bans.lock().unwrap();
+        bans.clear();
+        bans.extend(content.lines().filter_map(parse_ban_line).filter(|b| b.until > now));
+        *self.path.lock().unwrap() = Some(path);
+    }
+
+    /// Add the penalty for `what` to the peer's score. Returns true when this
+    /// pushed the peer over the ban threshold.
+    pub fn penalize(&self, peer: &Peer, what: Misbehave) -> bool {
+        self.penalize_at(peer.key, peer.addr.ip(), &peer.name, what, curtimes())
+    }
+
+    fn penalize_at(&self, key: PeerKey, ip: std::net::IpAddr, name: &str, what: Misbehave, now: u64) -> bool {
+        let banned = {
+            let mut scores = self.scores.lock().unwrap();
+            let sc = scores.entry(key).or_insert_with(|| PeerScore {
+                ip,
+                name: name.to_owned(),
+                score: 0,
+                last: what.name(),
+                updated: now,
+            });
+            let forgiven = now.saturating_sub(sc.updated) / SCORE_DECAY_SECS;
+            sc.score = sc.score.saturating_sub(forgiven) + what.penalty();
+            sc.updated = now;
+            sc.last = what.name();
+            if sc.score < PEER_BAN_SCORE {
+                return false;
+            }
+            scores.remove(&key);
+            PeerBan {
+                ip,
+                key,
+                name: name.to_owned(),
+                reason: what.name().to_owned(),
+                until: now + PEER_BAN_SECS,
+            }
+        };
+        let mut bans = self.bans.lock().unwrap();
+        bans.retain(|b| b.key != key && b.ip != ip);
+        bans.push(banned);
+        self.persist(&bans);
+        true
+    }
+
+    pub fn is_banned_ip(&self, ip: &std::net::IpAddr) -> bool {
+        let now = curtimes();
+        self.bans.lock().unwrap().iter().any(|b| &b.ip == ip && b.until > now)
+    }
+
+    pub fn is_banned(&self, peer: &Peer) -> bool {
+        let (now, ip) = (curtimes(), peer.addr.ip());
+        self.bans
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|b| (b.ip == ip || b.key == peer.key) && b.until > now)
+    }
+
+    /// Bans in force, soonest expiry first.
+    pub fn bans(&self) -> Vec<PeerBan> {
+        let now = curtimes();
+        let mut bans: Vec<_> = self
+            .bans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|b| b.until > now)
+            .cloned()
+            .collect();
+        bans.sort_by_key(|b| b.until);
+        bans
+    }
+
+    /// Current non-zero scores as `(key, ip, name, score, last misbehaviour)`.
+    pub fn scores(&self) -> Vec<(PeerKey, std::net::IpAddr, String, u64, &'static str)> {
+        let now = curtimes();
+        self.scores
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, s)| {
+                let score = s.score.saturating_sub(now.saturating_sub(s.updated) / SCORE_DECAY_SECS);
+                (*k, s.ip, s.name.clone(), score, s.last)
+            })
+            .filter(|s| s.3 > 0)
+            .collect()
+    }
+
+    /// Lift bans matching an IP or a hex node key. Returns how many were removed.
+    pub fn unban(&self, target: &str) -> usize {
+        let target = target.trim();
+        let ip = target.parse::<std::net::IpAddr>().ok();
+        let key = hex::decode(target).ok();
+        let mut bans = self.bans.lock().unwrap();
+        let before = bans.len();
+        bans.retain(|b| Some(b.ip) != ip && key.as_deref() != Some(&b.key[..]));
+        let removed = before - bans.len();
+        if removed > 0 {
+            self.persist(&bans);
+        }
+        removed
+    }
+
+    fn persist(&self, bans: &[PeerBan]) {
+        let Some(path) = self.path.lock().unwrap().clone() else {
+            return;
+        };
+        let mut out = String::new();
+        for b in bans {
+            out.push_str(&format!(
+                "{} {} {} {} {}\n",
+                b.until,
+                b.ip,
+                hex::encode(b.key),
+                b.name.replace(' ', "_"),
+                b.reason
+            ));
+        }
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(&path, out);
+    }
+}
+
+fn parse_ban_line(line: &str) -> Option<PeerBan> {
+    let mut parts = line.split_whitespace();
+    let until = parts.next()?.parse().ok()?;
+    let ip = parts.next()?.parse().ok()?;
+    let key = hex::decode(parts.next()?).ok()?.try_into().ok()?;
+    let name = parts.next()?.to_owned();
+    let reason = parts.collect::<Vec<_>>().join(" ");
+    Some(PeerBan { ip, key, name, reason, until })
+}
+
+#[cfg(test)]
+mod score_tests {
+    use super::*;
+
+    #[test]
+    fn scores_decay_and_ban_persists() {
+        let rep = PeerReputation::default();
+        let path = std::env::temp_dir().join(format!("hacash_peer_bans_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        rep.open(path.clone());
+        let (key, ip) = ([7u8; PEER_KEY_SIZE], "10.0.0.7".parse().unwrap());
+        let now = curtimes();
+        for i in 0..3 {
+            assert!(!rep.penalize_at(key, ip, "p7", Misbehave::InvalidBlock, now + i));
+        }
+        // an hour later most of the score is forgiven
+        let later = now + 3600;
+        assert!(!rep.penalize_at(key, ip, "p7", Misbehave::InvalidBlock, later));
+        for i in 1..4 {
+            let banned = rep.penalize_at(key, ip, "p7", Misbehave::InvalidBlock, later + i);
+            assert_eq!(banned, i == 3);
+        }
+        assert!(rep.is_banned_ip(&ip));
+        let bans = rep.bans();
+        assert_eq!(bans.len(), 1);
+        assert_eq!(bans[0].reason, "invalid_block");
+
+        let reload = PeerReputation::default();
+        reload.open(path.clone());
+        assert_eq!(reload.bans(), bans);
+        assert_eq!(reload.unban(&hex::encode(key)), 1);
+        assert!(!reload.is_banned_ip(&ip));
+        let again = PeerReputation::default();
+        again.open(path.clone());
+        assert!(again.bans().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn bad_handshake_bans_at_once() {
+        let rep = PeerReputation::default();
+        let ip = "10.0.0.8".parse().unwrap();
+        assert!(rep.penalize_at([8u8; PEER_KEY_SIZE], ip, "p8", Misbehave::BadHandshake, curtimes()));
+        assert_eq!(rep.unban("10.0.0.8"), 1);
+        assert!(rep.bans().is_empty());
+    }
+
+    #[test]
+    fn only_repeated_timeouts_ban() {
+        let rep = PeerReputation::default();
+        let (key, ip) = ([9u8; PEER_KEY_SIZE], "10.0.0.9".parse().unwrap());
+        let now = curtimes();
+        // one timeout every ten minutes is forgiven before the next
+        for i in 0..20 {
+            assert!(!rep.penalize_at(key, ip, "p9", Misbehave::Timeout, now + i * 600));
+        }
+        let later = now + 20 * 600;
+        for i in 0..10 {
+            assert_eq!(rep.penalize_at(key, ip, "p9", Misbehave::Timeout, later + i), i == 9);
+        }
+        assert_eq!(rep.bans()[0].reason, "timeout");
+    }
+}
@@ -1,4 +1,6 @@
 pub trait PeerManage: Send + Sync {
     fn switch_peer(&self, _: Arc<Peer>) -> Arc<Peer>;
     fn broadcast_message(&self, delay: u64, key: KnowKey, ty: u16, body: Vec<u8>);
+    fn broadcast_block(&self, key: KnowKey, full: Vec<u8>, compact: Vec<u8>);
+    fn broadcast_tx(&self, key: KnowKey, body: Vec<u8>);
 }
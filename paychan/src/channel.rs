@@ -0,0 +1,275 @@
+
+/// One party's view of an open payment channel.
+pub struct PayChannel {
+    acc: Account,
+    sto: PayChannelSto,
+}
+
+impl PayChannel {
+    /// Start tracking a channel with its opening balances; `acc` must be one of
+    /// the two addresses. Bill 0 stands for the opening balances and is never signed.
+    pub fn open(acc: Account, channel_id: ChannelId, reuse_version: u32, left: &AddrHac, right: &AddrHac) -> Ret<Self> {
+        Self::build(acc, channel_id, reuse_version, (&left.address, &left.amount, 0), (&right.address, &right.amount, 0))
+    }
+
+    /// Start tracking a channel as the chain stores it, satoshi included.
+    pub fn open_by_sto(acc: Account, channel_id: ChannelId, chan: &ChannelSto) -> Ret<Self> {
+        if chan.status != CHANNEL_STATUS_OPENING {
+            return errf!("channel {} is not open", channel_id);
+        }
+        let (l, r) = (&chan.left_bill, &chan.right_bill);
+        Self::build(
+            acc,
+            channel_id,
+            *chan.reuse_version,
+            (&l.address, &l.balance.hacash, l.balance.satoshi.uint()),
+            (&r.address, &r.balance.hacash, r.balance.satoshi.uint()),
+        )
+    }
+
+    fn build(acc: Account, channel_id: ChannelId, reuse_version: u32, left: (&Address, &Amount, u64), right: (&Address, &Amount, u64)) -> Ret<Self> {
+        let addr = Address::from(*acc.address());
+        if left.0 == right.0 {
+            return errf!("left address cannot be equal to right address");
+        }
+        if addr != *left.0 && addr != *right.0 {
+            return errf!("account {} is not a party of channel {}", addr, channel_id);
+        }
+        if left.1.is_negative() || right.1.is_negative() {
+            return errf!("channel amount cannot be negative");
+        }
+        if left.2.checked_add(right.2).is_none() {
+            return errf!("channel satoshi overflow");
+        }
+        let latest = SignedReconciliationBill {
+            bill: ReconciliationBill {
+                channel_id,
+                reuse_version: Uint4::from(reuse_version),
+                bill_auto_number: Uint8::from(0),
+                left_bill: hacsat(left.1, left.2),
+                right_bill: hacsat(right.1, right.2),
+            },
+            ..Default::default()
+        };
+        let sto = PayChannelSto {
+            channel_id,
+            reuse_version: Uint4::from(reuse_version),
+            left_address: *left.0,
+            right_address: *right.0,
+            left_open: left.1.clone(),
+            right_open: right.1.clone(),
+            left_open_sat: Satoshi::from(left.2),
+            right_open_sat: Satoshi::from(right.2),
+            is_left: Bool::new(addr == *left.0),
+            latest,
+            signed_top: Uint8::from(0),
+            pending: SignedReconciliationBillOptional::default(),
+        };
+        Ok(Self { acc, sto })
+    }
+
+    pub fn channel_id(&self) -> &ChannelId {
+        &self.sto.channel_id
+    }
+
+    pub fn is_left(&self) -> bool {
+        self.sto.is_left.check()
+    }
+
+    pub fn our_address(&self) -> &Address {
+        maybe!(self.is_left(), &self.sto.left_address, &self.sto.right_address)
+    }
+
+    pub fn their_address(&self) -> &Address {
+        maybe!(self.is_left(), &self.sto.right_address, &self.sto.left_address)
+    }
+
+    /// The latest bill both parties signed, or bill 0 before any payment.
+    pub fn latest(&self) -> &SignedReconciliationBill {
+        &self.sto.latest
+    }
+
+    pub fn latest_number(&self) -> u64 {
+        *self.sto.latest.bill.bill_auto_number
+    }
+
+    /// A payment we signed and the other side has not countersigned yet.
+    pub fn pending(&self) -> Option<&SignedReconciliationBill> {
+        self.sto.pending.if_value()
+    }
+
+    /// Left and right balances of the latest bill.
+    pub fn balances(&self) -> (Amount, Amount) {
+        let bill = &self.sto.latest.bill;
+        (bill.left_bill.amount.clone(), bill.right_bill.amount.clone())
+    }
+
+    /// Left and right satoshi of the latest bill.
+    pub fn satoshi(&self) -> (u64, u64) {
+        let bill = &self.sto.latest.bill;
+        (bill.left_bill.satoshi.value().uint(), bill.right_bill.satoshi.value().uint())
+    }
+
+    pub fn our_balance(&self) -> Amount {
+        let (l, r) = self.balances();
+        maybe!(self.is_left(), l, r)
+    }
+
+    pub fn their_balance(&self) -> Amount {
+        let (l, r) = self.balances();
+        maybe!(self.is_left(), r, l)
+    }
+
+    pub fn our_satoshi(&self) -> u64 {
+        let (l, r) = self.satoshi();
+        maybe!(self.is_left(), l, r)
+    }
+
+    pub fn their_satoshi(&self) -> u64 {
+        let (l, r) = self.satoshi();
+        maybe!(self.is_left(), r, l)
+    }
+
+    /// Sign a bill paying `amt` to the other side. It becomes the latest once
+    /// `confirm` gets it back countersigned.
+    pub fn pay(&mut self, amt: &Amount) -> Ret<SignedReconciliationBill> {
+        self.pay_hacsat(amt, 0)
+    }
+
+    /// Like `pay`, moving `sat` satoshi along with the HAC amount.
+    pub fn pay_hacsat(&mut self, amt: &Amount, sat: u64) -> Ret<SignedReconciliationBill> {
+        if let Some(pending) = self.pending() {
+            return errf!("payment bill {} is still waiting for countersign", *pending.bill.bill_auto_number);
+        }
+        if amt.is_negative() || (!amt.is_positive() && sat == 0) {
+            return errf!("payment amount must be positive");
+        }
+        let ours = self.our_balance();
+        if *amt > ours {
+            return errf!("channel balance {} is not enough to pay {}", ours, amt);
+        }
+        let our_sat = self.our_satoshi();
+        if sat > our_sat {
+            return errf!("channel satoshi {} is not enough to pay {}", our_sat, sat);
+        }
+        let ours = ours.sub_mode_u64(amt)?;
+        let theirs = self.their_balance().add_mode_u64(amt)?;
+        ours.check_6_long()?;
+        theirs.check_6_long()?;
+        // the sum is the lock-in, which cannot overflow
+        let ours = hacsat(&ours, our_sat - sat);
+        let theirs = hacsat(&theirs, self.their_satoshi() + sat);
+        let (left, right) = maybe!(self.is_left(), (ours, theirs), (theirs, ours));
+        let num = self.next_number();
+        let mut sbill = SignedReconciliationBill { bill: self.sto.latest.bill.clone(), ..Default::default() };
+        sbill.bill.bill_auto_number = Uint8::from(num);
+        sbill.bill.left_bill = left;
+        sbill.bill.right_bill = right;
+        self.sign_ours(&mut sbill);
+        self.sto.signed_top = Uint8::from(num);
+        self.sto.pending = SignedReconciliationBillOptional::must(sbill.clone());
+        Ok(sbill)
+    }
+
+    /// Countersign a payment the other side signed, taking it as the latest bill.
+    /// Only bills that pay us, never ones that take from us, are accepted.
+    pub fn receive(&mut self, sbill: &SignedReconciliationBill) -> Ret<SignedReconciliationBill> {
+        if self.sto.pending.is_exist() {
+            return errf!("cannot receive while our payment bill is pending");
+        }
+        self.check_next(&sbill.bill)?;
+        let (their_sign, ours) = maybe!(
+            self.is_left(),
+            (&sbill.right_sign, &sbill.bill.left_bill),
+            (&sbill.left_sign, &sbill.bill.right_bill)
+        );
+        let (our_amt, our_sat) = (self.our_balance(), self.our_satoshi());
+        let sat = ours.satoshi.value().uint();
+        if ours.amount < our_amt || sat < our_sat || (ours.amount == our_amt && sat == our_sat) {
+            return errf!("bill {} does not pay us", *sbill.bill.bill_auto_number);
+        }
+        if !verify_signature(&sbill.bill.sign_stuff(), self.their_address(), their_sign) {
+            return errf!("bill signature of {} verification failed", self.their_address());
+        }
+        let mut sbill = sbill.clone();
+        self.sign_ours(&mut sbill);
+        self.sto.signed_top = sbill.bill.bill_auto_number;
+        self.sto.latest = sbill.clone();
+        Ok(sbill)
+    }
+
+    /// Take back our pending payment countersigned by the other side.
+    pub fn confirm(&mut self, sbill: &SignedReconciliationBill) -> Rerr {
+        let Some(pending) = self.sto.pending.if_value() else {
+            return errf!("no payment bill is pending");
+        };
+        if pending.bill != sbill.bill {
+            return errf!("bill {} is not our pending payment", *sbill.bill.bill_auto_number);
+        }
+        let stuff = sbill.bill.sign_stuff();
+        if !verify_signature(&stuff, &self.sto.left_address, &sbill.left_sign)
+            || !verify_signature(&stuff, &self.sto.right_address, &sbill.right_sign)
+        {
+            return errf!("bill {} is not signed by both sides", *sbill.bill.bill_auto_number);
+        }
+        self.sto.latest = sbill.clone();
+        self.sto.pending = SignedReconciliationBillOptional::default();
+        Ok(())
+    }
+
+    /// Drop our pending payment. The other side may still hold it signed by
+    /// us, so only do this when it is known to have been refused. Its number
+    /// stays used: the next bill either side signs skips past it.
+    pub fn cancel_pending(&mut self) {
+        self.sto.pending = SignedReconciliationBillOptional::default();
+    }
+
+    /// Number for the next bill we sign: above the latest and above every
+    /// number we ever signed, so no two bills we signed share one.
+    pub fn next_number(&self) -> u64 {
+        self.latest_number().max(*self.sto.signed_top) + 1
+    }
+
+    fn check_next(&self, bill: &ReconciliationBill) -> Rerr {
+        let latest = &self.sto.latest.bill;
+        if bill.channel_id != latest.channel_id || bill.reuse_version != latest.reuse_version {
+            return errf!("bill is not for channel {} version {}", latest.channel_id, *latest.reuse_version);
+        }
+        let num = *bill.bill_auto_number;
+        if num < self.next_number() {
+            return errf!("bill number {} is used, the next one is {}", num, self.next_number());
+        }
+        let (ls, rs) = (bill.left_bill.satoshi.value().uint(), bill.right_bill.satoshi.value().uint());
+        let total_sat = self.sto.left_open_sat.uint() + self.sto.right_open_sat.uint();
+        if ls.checked_add(rs) != Some(total_sat) {
+            return errf!("bill satoshi must match lock-in {}", total_sat);
+        }
+        let (l, r) = (&bill.left_bill.amount, &bill.right_bill.amount);
+        if l.is_negative() || r.is_negative() {
+            return errf!("bill amount cannot be negative");
+        }
+        l.check_6_long()?;
+        r.check_6_long()?;
+        let total = self.sto.left_open.add_mode_u64(&self.sto.right_open)?;
+        if l.add_mode_u64(r)? != total {
+            return errf!("bill amount must match lock-in {}", total);
+        }
+        Ok(())
+    }
+
+    fn sign_ours(&self, sbill: &mut SignedReconciliationBill) {
+        match self.is_left() {
+            true => sbill.sign_left(&self.acc),
+            false => sbill.sign_right(&self.acc),
+        }
+    }
+}
+
+// a bill side, leaving the satoshi out when there is none
+fn hacsat(amount: &Amount, sat: u64) -> HacSat {
+    let satoshi = match sat {
+        0 => SatoshiOptional::default(),
+        _ => SatoshiOptional::must(Satoshi::from(sat)),
+    };
+    HacSat { amount: amount.clone(), satoshi }
+}
@@ -0,0 +1,65 @@
+
+/*
+    Transactions taking a channel to the chain, all paid and signed by our
+    address. `open_tx` and `close_tx` also need the other side's signature,
+    which it adds with `fill_sign` before submitting.
+*/
+
+impl PayChannel {
+    /// `ChannelOpen` for the opening balances.
+    pub fn open_tx(&self, fee: Amount, timestamp: u64) -> Ret<TransactionType2> {
+        let mut act = ChannelOpen::new();
+        act.channel_id = self.sto.channel_id;
+        act.left_bill = AddrHac { address: self.sto.left_address, amount: self.sto.left_open.clone() };
+        act.right_bill = AddrHac { address: self.sto.right_address, amount: self.sto.right_open.clone() };
+        self.signed_tx(Box::new(act), fee, timestamp)
+    }
+
+    /// Cooperative `ChannelClose`. It pays out the opening balances, so it is
+    /// only offered while the latest bill still matches them.
+    pub fn close_tx(&self, fee: Amount, timestamp: u64) -> Ret<TransactionType2> {
+        let (l, r) = self.balances();
+        let opened_sat = (self.sto.left_open_sat.uint(), self.sto.right_open_sat.uint());
+        if l != self.sto.left_open || r != self.sto.right_open || self.satoshi() != opened_sat {
+            return errf!("balances moved since opening, close with the latest bill instead");
+        }
+        let mut act = ChannelClose::new();
+        act.channel_id = self.sto.channel_id;
+        self.signed_tx(Box::new(act), fee, timestamp)
+    }
+
+    /// `ChannelCloseLaunch` with the latest bill, asserting our side of it.
+    /// Before any payment that is bill 0, the opening split, which the chain
+    /// takes without the other side's signature.
+    pub fn launch_close_tx(&self, fee: Amount, timestamp: u64) -> Ret<TransactionType2> {
+        let mut act = ChannelCloseLaunch::new();
+        act.assert_left = self.sto.is_left;
+        act.bill = self.sto.latest.clone();
+        self.signed_tx(Box::new(act), fee, timestamp)
+    }
+
+    /// `ChannelCloseChallenge` answering a close the other side launched with
+    /// bill `launched`; needs a newer bill.
+    pub fn challenge_tx(&self, launched: u64, fee: Amount, timestamp: u64) -> Ret<TransactionType2> {
+        if self.latest_number() <= launched {
+            return errf!("latest bill {} is not newer than the launched {}", self.latest_number(), launched);
+        }
+        let mut act = ChannelCloseChallenge::new();
+        act.bill = self.sto.latest.clone();
+        self.signed_tx(Box::new(act), fee, timestamp)
+    }
+
+    /// `ChannelCloseSettle` once the challenge period is over.
+    pub fn settle_tx(&self, fee: Amount, timestamp: u64) -> Ret<TransactionType2> {
+        let mut act = ChannelCloseSettle::new();
+        act.channel_id = self.sto.channel_id;
+        self.signed_tx(Box::new(act), fee, timestamp)
+    }
+
+    fn signed_tx(&self, act: Box<dyn Action>, fee: Amount, timestamp: u64) -> Ret<TransactionType2> {
+        let mut tx = TransactionType2::new_by(*self.our_address(), fee, timestamp);
+        tx.push_action(act)?;
+        tx.fill_sign(&self.acc)?;
+        Ok(tx)
+    }
+}
@@ -0,0 +1,24 @@
+/*
+    Off-chain side of a payment channel.
+
+    Both parties keep a `PayChannel` for the channel they opened on chain.
+    Every payment is a new `ReconciliationBill` the payer signs and the payee
+    countersigns; the latest bill both signed is what either side can take
+    to the chain with `ChannelCloseLaunch` / `ChannelCloseChallenge`.
+*/
+
+use std::path::Path;
+
+use basis::interface::*;
+use basis::method::verify_signature;
+use field::*;
+use mint::action::*;
+use protocol::transaction::*;
+use sys::*;
+
+include! {"store.rs"}
+include! {"channel.rs"}
+include! {"close.rs"}
+
+#[cfg(test)]
+include! {"tests.rs"}
@@ -0,0 +1,45 @@
+combi_optional!{ SignedReconciliationBillOptional,
+    bill: SignedReconciliationBill
+}
+
+// What one party keeps of a channel; bill number 0 holds the opening balances
+// and `signed_top` the highest bill number we signed
+combi_struct!{ PayChannelSto,
+    channel_id     : ChannelId
+    reuse_version  : Uint4
+    left_address   : Address
+    right_address  : Address
+    left_open      : Amount
+    right_open     : Amount
+    left_open_sat  : Satoshi
+    right_open_sat : Satoshi
+    is_left        : Bool
+    latest         : SignedReconciliationBill
+    signed_top     : Uint8
+    pending        : SignedReconciliationBillOptional
+}
+
+impl PayChannel {
+    /// Write the channel state to `path`, replacing it at once.
+    pub fn save(&self, path: &Path) -> Rerr {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, self.sto.serialize()).map_err(|e| format!("write {}: {}", tmp.display(), e))?;
+        std::fs::rename(&tmp, path).map_err(|e| format!("rename {}: {}", path.display(), e))?;
+        Ok(())
+    }
+
+    /// Read a channel saved by `save`, for the party holding `acc`.
+    pub fn load(acc: Account, path: &Path) -> Ret<Self> {
+        let buf = std::fs::read(path).map_err(|e| format!("read {}: {}", path.display(), e))?;
+        let mut sto = PayChannelSto::default();
+        let sz = sto.parse(&buf)?;
+        if sz != buf.len() {
+            return errf!("channel file {} has {} trailing bytes", path.display(), buf.len() - sz);
+        }
+        let chan = Self { acc, sto };
+        if Address::from(*chan.acc.address()) != *chan.our_address() {
+            return errf!("channel file {} belongs to another account", path.display());
+        }
+        Ok(chan)
+    }
+}
@@ -0,0 +1,189 @@
+
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn pair(left_mei: u64, right_mei: u64) -> (PayChannel, PayChannel) {
+        let lacc = Account::create_by("paychan-test-left").unwrap();
+        let racc = Account::create_by("paychan-test-right").unwrap();
+        let left = AddrHac { address: Address::from(*lacc.address()), amount: Amount::mei(left_mei) };
+        let right = AddrHac { address: Address::from(*racc.address()), amount: Amount::mei(right_mei) };
+        let cid = ChannelId::from([3u8; 16]);
+        (
+            PayChannel::open(lacc, cid, 1, &left, &right).unwrap(),
+            PayChannel::open(racc, cid, 1, &left, &right).unwrap(),
+        )
+    }
+
+    // a channel as the chain keeps it, with satoshi on both sides
+    fn pair_sat(mei: (u64, u64), sat: (u64, u64)) -> (PayChannel, PayChannel) {
+        let lacc = Account::create_by("paychan-test-left").unwrap();
+        let racc = Account::create_by("paychan-test-right").unwrap();
+        let side = |acc: &Account, mei: u64, sat: u64| AddrBalance {
+            address: Address::from(*acc.address()),
+            balance: Balance {
+                hacash: Amount::mei(mei),
+                satoshi: SatoshiAuto::from(sat).unwrap(),
+                ..Default::default()
+            },
+        };
+        let chan = ChannelSto {
+            status: CHANNEL_STATUS_OPENING,
+            reuse_version: Uint4::from(1),
+            left_bill: side(&lacc, mei.0, sat.0),
+            right_bill: side(&racc, mei.1, sat.1),
+            ..Default::default()
+        };
+        let cid = ChannelId::from([4u8; 16]);
+        (
+            PayChannel::open_by_sto(lacc, cid, &chan).unwrap(),
+            PayChannel::open_by_sto(racc, cid, &chan).unwrap(),
+        )
+    }
+
+    // one full payment round, or the payer's error
+    fn transfer(payer: &mut PayChannel, payee: &mut PayChannel, mei: u64) -> Rerr {
+        transfer_sat(payer, payee, mei, 0)
+    }
+
+    fn transfer_sat(payer: &mut PayChannel, payee: &mut PayChannel, mei: u64, sat: u64) -> Rerr {
+        let half = payer.pay_hacsat(&Amount::mei(mei), sat)?;
+        let full = payee.receive(&half)?;
+        payer.confirm(&full)
+    }
+
+    #[test]
+    fn payment_round_and_rejections() {
+        let (mut l, mut r) = pair(10, 5);
+        assert!(l.close_tx(Amount::mei(1), 1).is_ok());
+        assert!(l.launch_close_tx(Amount::mei(1), 1).is_ok());
+
+        transfer(&mut l, &mut r, 4).unwrap();
+        assert_eq!(l.balances(), (Amount::mei(6), Amount::mei(9)));
+        assert_eq!(r.latest(), l.latest());
+        assert!(l.close_tx(Amount::mei(1), 1).is_err());
+
+        // replay and overdraft
+        assert!(r.receive(l.latest()).is_err());
+        assert!(l.pay(&Amount::mei(7)).is_err());
+
+        // a bill taking from the receiver
+        let half = r.pay(&Amount::mei(2)).unwrap();
+        assert!(r.receive(&half).is_err());
+        assert!(l.confirm(&half).is_err());
+        let full = l.receive(&half).unwrap();
+        assert!(r.pay(&Amount::mei(1)).is_err());
+        r.confirm(&full).unwrap();
+        assert_eq!(r.latest_number(), 2);
+
+        // a tampered bill
+        let mut half = l.pay(&Amount::mei(1)).unwrap();
+        half.bill.right_bill.amount = Amount::mei(10);
+        half.bill.left_bill.amount = Amount::mei(5);
+        assert!(r.receive(&half).is_err());
+        l.cancel_pending();
+
+        let tx = l.launch_close_tx(Amount::mei(1), 1).unwrap();
+        tx.verify_signature().unwrap();
+        assert!(r.challenge_tx(2, Amount::mei(1), 1).is_err());
+        assert!(r.challenge_tx(1, Amount::mei(1), 1).is_ok());
+    }
+
+    #[test]
+    fn cancelled_bill_number_is_never_signed_again() {
+        let (mut l, mut r) = pair(10, 5);
+        transfer(&mut l, &mut r, 1).unwrap();
+        let refused = l.pay(&Amount::mei(1)).unwrap();
+        assert_eq!(*refused.bill.bill_auto_number, 2);
+        l.cancel_pending();
+        assert_eq!(l.next_number(), 3);
+
+        // a bill of the other side reusing number 2 is refused too
+        let theirs = r.pay(&Amount::mei(1)).unwrap();
+        assert_eq!(*theirs.bill.bill_auto_number, 2);
+        assert!(l.receive(&theirs).is_err());
+        r.cancel_pending();
+
+        let half = l.pay(&Amount::mei(2)).unwrap();
+        assert_eq!(*half.bill.bill_auto_number, 3);
+        let full = r.receive(&half).unwrap();
+        l.confirm(&full).unwrap();
+        assert_eq!((l.latest_number(), r.next_number()), (3, 4));
+        transfer(&mut r, &mut l, 1).unwrap();
+        assert_eq!(l.latest_number(), 4);
+    }
+
+    #[test]
+    fn satoshi_moves_with_bills() {
+        let (mut l, mut r) = pair_sat((5, 5), (700, 300));
+        assert_eq!(l.latest().bill.left_bill.satoshi.value().uint(), 700);
+        assert!(l.close_tx(Amount::mei(1), 1).is_ok());
+
+        transfer_sat(&mut l, &mut r, 0, 200).unwrap();
+        assert_eq!(l.satoshi(), (500, 500));
+        assert_eq!(l.balances(), (Amount::mei(5), Amount::mei(5)));
+        assert!(l.close_tx(Amount::mei(1), 1).is_err());
+        transfer_sat(&mut r, &mut l, 1, 500).unwrap();
+        assert_eq!(r.satoshi(), (1000, 0));
+        assert!(r.pay_hacsat(&Amount::mei(1), 1).is_err());
+        assert!(r.pay_hacsat(&Amount::zero(), 0).is_err());
+
+        // satoshi that do not sum to the lock-in
+        let mut half = l.pay_hacsat(&Amount::mei(1), 10).unwrap();
+        half.bill.right_bill.satoshi = SatoshiOptional::must(Satoshi::from(11));
+        assert!(r.receive(&half).is_err());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let (mut l, mut r) = pair(3, 3);
+        transfer(&mut l, &mut r, 2).unwrap();
+        l.pay(&Amount::mei(1)).unwrap();
+        let path = std::env::temp_dir().join(format!("paychan-test-{}.dat", std::process::id()));
+        l.save(&path).unwrap();
+        let acc = Account::create_by("paychan-test-left").unwrap();
+        let back = PayChannel::load(acc, &path).unwrap();
+        assert_eq!(back.sto, l.sto);
+        let other = Account::create_by("paychan-test-right").unwrap();
+        assert!(PayChannel::load(other, &path).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(24))]
+
+        #[test]
+        fn funds_are_conserved(
+            open in (0u64..50, 0u64..50),
+            open_sat in (0u64..5000, 0u64..5000),
+            steps in prop::collection::vec((any::<bool>(), 0u64..40, 0u64..3000), 0..30),
+        ) {
+            let (mut l, mut r) = pair_sat(open, open_sat);
+            let total = Amount::mei(open.0 + open.1);
+            let mut done = 0;
+            for (left_pays, mei, sat) in steps {
+                let (payer, payee) = maybe!(left_pays, (&mut l, &mut r), (&mut r, &mut l));
+                let (before, before_sat) = (payer.our_balance(), payer.our_satoshi());
+                match transfer_sat(payer, payee, mei, sat) {
+                    Ok(()) => done += 1,
+                    Err(_) => {
+                        prop_assert!((mei == 0 && sat == 0) || Amount::mei(mei) > before || sat > before_sat);
+                        payer.cancel_pending();
+                    }
+                }
+                prop_assert_eq!(l.latest(), r.latest());
+                let (a, b) = l.balances();
+                prop_assert_eq!(a.add_mode_u64(&b).unwrap(), total.clone());
+                let (a, b) = l.satoshi();
+                prop_assert_eq!(a + b, open_sat.0 + open_sat.1);
+            }
+            prop_assert_eq!(l.latest_number(), done);
+            if done > 0 {
+                let sbill = l.latest();
+                let stuff = sbill.bill.sign_stuff();
+                prop_assert!(verify_signature(&stuff, &l.sto.left_address, &sbill.left_sign));
+                prop_assert!(verify_signature(&stuff, &l.sto.right_address, &sbill.right_sign));
+            }
+        }
+    }
+}
@@ -45,6 +45,12 @@ pub(crate) fn is_ast_container_action(act: &dyn Action) -> bool {
         || act.as_any().downcast_ref::<AstIf>().is_some()
 }
 
+/// Child actions of an AST container, every branch included, or none for
+/// any other action. Which branch ran is only known from execution.
+pub fn ast_action_childs(act: &dyn Action) -> Vec<&dyn Action> {
+    get_action_level_inc_and_childs(act).map(|(_, childs)| childs).unwrap_or_default()
+}
+
 pub(crate) fn get_action_level_inc_and_childs<'a>(
     act: &'a dyn Action,
 ) -> Option<(usize, Vec<&'a dyn Action>)> {
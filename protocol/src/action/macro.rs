@@ -117,22 +117,37 @@ macro_rules! action_define {
         impl ActExec for $class {
             fn execute(&$pself, $pctx: &mut dyn Context) -> XRet<(u32, Vec<u8>)> {
                 use std::any::Any;
-                $crate::upgrade::check_gated_action(
-                    $pctx.env().chain.id,
-                    $pctx.env().block.height,
-                    $pself.kind()
-                )?;
-                if $pctx.env().chain.fast_sync {
-                    $crate::action::precheck_runtime_action_fast_sync($pctx.env().tx.ty, $pself)?;
-                } else {
-                    $crate::action::precheck_runtime_action($pctx.env().tx.ty, $pself, $pctx.exec_from())?;
+                let trace_at = $crate::context::trace_enabled().then(|| {
+                    $crate::context::trace_enter(
+                        "action",
+                        format!("{} {}", $pself.kind(), $pself.to_description()),
+                    )
+                });
+                let done = (|| -> XRet<(u32, Vec<u8>)> {
+                    $crate::upgrade::check_gated_action(
+                        $pctx.env().chain.id,
+                        $pctx.env().block.height,
+                        $pself.kind()
+                    )?;
+                    if $pctx.env().chain.fast_sync {
+                        $crate::action::precheck_runtime_action_fast_sync($pctx.env().tx.ty, $pself)?;
+                    } else {
+                        $crate::action::precheck_runtime_action($pctx.env().tx.ty, $pself, $pctx.exec_from())?;
+                    }
+                    #[allow(unused_mut)]
+                    let mut $pgas: u32 = $pself.size() as u32;
+                    let res: XRet<Vec<u8>> = (|| -> XRet<Vec<u8>> { $exec })();
+                    let res = res?;
+                    do_action_hook($pself.kind(), $pself as &dyn Any, $pctx)?;
+                    Ok(($pgas, res))
+                })();
+                if let Some(at) = trace_at {
+                    match &done {
+                        Ok((gas, _)) => $crate::context::trace_leave_at(at, "action.ok", format!("gas {}", gas)),
+                        Err(e) => $crate::context::trace_leave_at(at, "action.err", e.to_string()),
+                    }
                 }
-                #[allow(unused_mut)]
-                let mut $pgas: u32 = $pself.size() as u32;
-                let res: XRet<Vec<u8>> = (|| -> XRet<Vec<u8>> { $exec })();
-                let res = res?;
-                do_action_hook($pself.kind(), $pself as &dyn Any, $pctx)?;
-                Ok(($pgas, res))
+                done
             }
         }
 
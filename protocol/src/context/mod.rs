@@ -14,3 +14,4 @@ include! {"context.rs"}
 include! {"gas.rs"}
 include! {"actcall.rs"}
 include! {"sub.rs"}
+include! {"trace.rs"}
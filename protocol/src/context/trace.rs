@@ -0,0 +1,93 @@
+/// One step recorded by the execution tracer.
+/// `depth` is the nesting level at which the step happened (0 = transaction top level).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    pub depth: usize,
+    pub op: &'static str,
+    pub detail: String,
+}
+
+#[derive(Default)]
+struct ExecTracer {
+    depth: usize,
+    steps: Vec<TraceStep>,
+}
+
+thread_local! {
+    static EXEC_TRACER: std::cell::RefCell<Option<ExecTracer>> = const { std::cell::RefCell::new(None) };
+}
+
+// Threads with a tracer on. While it is 0, the per-action check is one atomic
+// load and never touches the thread-local; a thread that exits while tracing
+// only keeps the check on the slower path.
+static TRACERS_ON: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// Start recording trace steps on the current thread, dropping any unfinished trace.
+/// Only debug tooling turns this on; normal block execution never pays for it.
+pub fn trace_begin() {
+    EXEC_TRACER.with(|t| {
+        if t.borrow_mut().replace(ExecTracer::default()).is_none() {
+            TRACERS_ON.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    });
+}
+
+/// Stop recording and return all steps since `trace_begin`.
+pub fn trace_finish() -> Vec<TraceStep> {
+    let tracer = EXEC_TRACER.with(|t| t.borrow_mut().take());
+    if tracer.is_some() {
+        TRACERS_ON.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+    }
+    tracer.map(|t| t.steps).unwrap_or_default()
+}
+
+#[inline(always)]
+pub fn trace_enabled() -> bool {
+    TRACERS_ON.load(std::sync::atomic::Ordering::Relaxed) > 0 && EXEC_TRACER.with(|t| t.borrow().is_some())
+}
+
+fn trace_with(f: impl FnOnce(&mut ExecTracer)) {
+    EXEC_TRACER.with(|t| {
+        if let Some(tr) = t.borrow_mut().as_mut() {
+            f(tr)
+        }
+    })
+}
+
+/// Record a step at the current depth without changing it.
+pub fn trace_push(op: &'static str, detail: String) {
+    trace_with(|tr| {
+        let depth = tr.depth;
+        tr.steps.push(TraceStep { depth, op, detail });
+    })
+}
+
+/// Record a step and nest the following ones one level deeper.
+/// Returns the depth to hand back to `trace_leave_at`.
+pub fn trace_enter(op: &'static str, detail: String) -> usize {
+    let mut entered = 0;
+    trace_with(|tr| {
+        entered = tr.depth;
+        tr.steps.push(TraceStep { depth: entered, op, detail });
+        tr.depth += 1;
+    });
+    entered
+}
+
+/// Leave one nesting level and record the step there.
+pub fn trace_leave(op: &'static str, detail: String) {
+    trace_with(|tr| {
+        tr.depth = tr.depth.saturating_sub(1);
+        let depth = tr.depth;
+        tr.steps.push(TraceStep { depth, op, detail });
+    })
+}
+
+/// Leave back to the depth returned by `trace_enter`, so levels skipped by
+/// an error unwinding through `?` do not shift the steps that follow.
+pub fn trace_leave_at(depth: usize, op: &'static str, detail: String) {
+    trace_with(|tr| {
+        tr.depth = depth;
+        tr.steps.push(TraceStep { depth, op, detail });
+    })
+}
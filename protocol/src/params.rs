@@ -14,6 +14,7 @@ pub const CONTRACT_STORE_PERM_PERIODS: u64 = 10_000;
 /*
     Minimum VM fee purity floor, in unit-238 per tx byte.
     50000:238 == 100:244 == 0.000005 HAC per byte.
+    A chain spec `[fee] vm_lowest_fee_purity` replaces it on custom networks.
 */
 pub const VM_LOWEST_FEE_PURITY: u64 = 50_000;
 
@@ -25,7 +26,8 @@ pub const VM_LOWEST_FEE_PURITY_REDUCTIONS: &[(u64, u64)] = &[];
 
 #[inline]
 pub fn vm_lowest_fee_purity(height: u64) -> u64 {
-    let mut purity = VM_LOWEST_FEE_PURITY;
+    let spec = basis::config::chain_spec();
+    let mut purity = spec.vm_lowest_fee_purity.unwrap_or(VM_LOWEST_FEE_PURITY);
     for (activation_height, next_purity) in VM_LOWEST_FEE_PURITY_REDUCTIONS {
         if height >= *activation_height && *next_purity < purity {
             purity = *next_purity;
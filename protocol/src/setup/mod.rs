@@ -84,7 +84,8 @@ pub struct TestSetupScopeGuard {
 impl Drop for TestSetupScopeGuard {
     fn drop(&mut self) {
         let old = self.old.take();
-        SCOPED_SETUP_REGISTRY.with(|cell| {
+        // may run during thread-local teardown, after the registry slot is gone
+        let _ = SCOPED_SETUP_REGISTRY.try_with(|cell| {
             *cell.borrow_mut() = old;
         });
     }
@@ -140,54 +140,7 @@ impl BlockLogs {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
-
-    #[derive(Default)]
-    struct MemDisk {
-        kv: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
-    }
-
-    impl DiskDB for MemDisk {
-        fn read(&self, key: &[u8]) -> Option<Vec<u8>> {
-            self.kv.lock().unwrap().get(key).cloned()
-        }
-
-        fn write(&self, batch: &dyn MemDB) {
-            let mut kv = self.kv.lock().unwrap();
-            batch.for_each(&mut |key, val| match val {
-                Some(v) => {
-                    kv.insert(key.to_vec(), v.to_vec());
-                }
-                None => {
-                    kv.remove(key);
-                }
-            });
-        }
-
-        fn save(&self, key: &[u8], val: &[u8]) {
-            self.kv.lock().unwrap().insert(key.to_vec(), val.to_vec());
-        }
-
-        fn remove(&self, key: &[u8]) {
-            self.kv.lock().unwrap().remove(key);
-        }
-
-        fn for_each(&self, each: &mut dyn FnMut(&[u8], &[u8]) -> bool) -> Result<(), String> {
-            let rows: Vec<(Vec<u8>, Vec<u8>)> = self
-                .kv
-                .lock()
-                .unwrap()
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect();
-            for (k, v) in rows {
-                if !each(&k, &v) {
-                    break;
-                }
-            }
-            Ok(())
-        }
-    }
+    use testkit::sim::disk::MemDisk;
 
     #[test]
     fn remove_clears_items_and_length_key() {
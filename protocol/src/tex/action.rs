@@ -13,12 +13,7 @@ action_define! { TexCellAct, 22,
         if ctx.exec_from() != ExecFrom::Top {
             return xerrf!("TexCellAct can only run in TOP context, got {}", ctx.exec_from())
         }
-        self.addr.must_privakey()?;
-        // Check signature on the standalone TEX bundle.
-        let thx = self.get_sign_stuff();
-        if ! verify_signature(&thx, &self.addr, &self.sign) {
-            return xerrf!("address {} signature verification failed in tex cell action", self.addr)
-        }
+        self.verify_sign()?;
         // Condition/business failures are intentionally surfaced as plain text errors, which map to Fault here.
         self.cells.execute(ctx, &self.addr).map(|_| vec![]).map_err(XError::from)
     })
@@ -34,6 +29,17 @@ impl TexCellAct {
         Hash::from(sha3(&stf))
     }
 
+    /// Check the bundle is signed by `addr`, as done on execution.
+    pub fn verify_sign(&self) -> Rerr {
+        self.addr.must_privakey()?;
+        // Check signature on the standalone TEX bundle.
+        let thx = self.get_sign_stuff();
+        if ! verify_signature(&thx, &self.addr, &self.sign) {
+            return errf!("address {} signature verification failed in tex cell action", self.addr)
+        }
+        Ok(())
+    }
+
     pub fn create_by(addr: Address) -> Self {
         Self {
             addr,
@@ -1,13 +1,24 @@
+use basis::config::{ActivationSpec, chain_spec};
 use field::*;
 use sys::*;
 
 // Local development is allowed from genesis to this height.
-pub const DEV_OPEN_MAX_HEIGHT: u64 = 65_432;
+pub const DEV_OPEN_MAX_HEIGHT: u64 = ActivationSpec::MAINNET.dev_open_max_height;
 
 // Set the real mainnet activation height before rollout.
-pub const ONLINE_OPEN_HEIGHT: u64 = 765_432;
+pub const ONLINE_OPEN_HEIGHT: u64 = ActivationSpec::MAINNET.online_open_height;
 pub const MAINNET_CHAIN_ID: u32 = 0;
 
+// Activation schedule gating `chain_id`: the chain spec's own schedule for its
+// chain, the built-in one for mainnet, none (everything open) otherwise.
+fn activation_of(chain_id: u32) -> Option<ActivationSpec> {
+    let spec = chain_spec();
+    if spec.chain_id.unwrap_or(MAINNET_CHAIN_ID) == chain_id && spec.activation.is_some() {
+        return spec.activation;
+    }
+    (chain_id == MAINNET_CHAIN_ID).then_some(ActivationSpec::MAINNET)
+}
+
 // One-time pre-upgrade allowlist.
 // In the middle closed interval only legacy tx/action kinds below are allowed.
 // Remove this whole file after the activation height has passed and the gate is no longer needed.
@@ -35,45 +46,39 @@ pub fn is_online_upgrade_open(height: u64) -> bool {
 }
 
 #[inline]
-fn is_dev_upgrade_open(height: u64) -> bool {
-    height <= DEV_OPEN_MAX_HEIGHT
+fn is_open_by(act: &ActivationSpec, height: u64) -> bool {
+    height >= act.online_open_height || height <= act.dev_open_max_height
 }
 
 #[inline]
 pub fn check_gated_tx(chain_id: u32, height: u64, tx_type: u8) -> Rerr {
-    if chain_id != MAINNET_CHAIN_ID {
+    let Some(act) = activation_of(chain_id) else {
         return Ok(());
-    }
-    if is_online_upgrade_open(height)
-        || is_dev_upgrade_open(height)
-        || is_pre_upgrade_allowed_tx_type(tx_type)
-    {
+    };
+    if is_open_by(&act, height) || is_pre_upgrade_allowed_tx_type(tx_type) {
         return Ok(());
     }
     errf!(
         "tx type {} not enabled at height {}, allowed when height >= {}",
         tx_type,
         height,
-        ONLINE_OPEN_HEIGHT
+        act.online_open_height
     )
 }
 
 #[inline]
 pub fn check_gated_action(chain_id: u32, height: u64, kind: u16) -> Rerr {
-    if chain_id != MAINNET_CHAIN_ID {
+    let Some(act) = activation_of(chain_id) else {
         return Ok(());
-    }
-    if is_online_upgrade_open(height)
-        || is_dev_upgrade_open(height)
-        || is_pre_upgrade_allowed_action(kind)
-    {
+    };
+    if is_open_by(&act, height) || is_pre_upgrade_allowed_action(kind) {
         return Ok(());
     }
     errf!(
         "action kind {} not enabled at height {}, allowed when height >= {}",
         kind,
         height,
-        ONLINE_OPEN_HEIGHT
+        act.online_open_height
     )
 }
 
@@ -84,22 +89,22 @@ pub fn check_transfer_addr_online_open(
     from: &Address,
     to: &Address,
 ) -> Rerr {
-    if chain_id != MAINNET_CHAIN_ID {
+    let Some(act) = activation_of(chain_id) else {
         return Ok(());
-    }
-    if is_online_upgrade_open(height) {
+    };
+    if height >= act.online_open_height {
         return Ok(());
     }
     if from.is_scriptmh() {
         return errf!(
             "transfer from scriptmh address is not enabled before height {}",
-            ONLINE_OPEN_HEIGHT
+            act.online_open_height
         );
     }
     if from.is_contract() || to.is_contract() {
         return errf!(
             "contract transfer in/out is not enabled before height {}",
-            ONLINE_OPEN_HEIGHT
+            act.online_open_height
         );
     }
     Ok(())
@@ -112,7 +117,7 @@ mod tests {
     #[test]
     fn dev_marker_height_is_not_online_open() {
         let mid = DEV_OPEN_MAX_HEIGHT.saturating_add(1);
-        assert!(is_dev_upgrade_open(0));
+        assert!(is_open_by(&ActivationSpec::MAINNET, 0));
         assert!(!is_online_upgrade_open(0));
         assert!(check_gated_tx(MAINNET_CHAIN_ID, mid, 3).is_err());
         assert!(check_gated_action(MAINNET_CHAIN_ID, mid, 25).is_err());
@@ -180,4 +185,15 @@ mod tests {
         assert!(check_gated_tx(sidechain_id, height, 3).is_ok());
         assert!(check_gated_action(sidechain_id, height, 25).is_ok());
     }
+
+    #[test]
+    fn spec_schedule_replaces_mainnet_heights() {
+        let act = ActivationSpec { dev_open_max_height: 10, online_open_height: 100 };
+        assert!(is_open_by(&act, 10));
+        assert!(!is_open_by(&act, 11));
+        assert!(is_open_by(&act, 100));
+        // no spec installed in tests: only mainnet is gated
+        assert_eq!(activation_of(MAINNET_CHAIN_ID), Some(ActivationSpec::MAINNET));
+        assert_eq!(activation_of(7), None);
+    }
 }
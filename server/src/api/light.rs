@@ -0,0 +1,163 @@
+
+
+/*
+    Query api of a light node: the paths and fields of a full node's
+    latest, balance and transaction queries, backed by proofs fetched from
+    full peers. Not served, as full peers send no proof for them: the
+    diamond names an address owns (`diamonds=true` is refused) and
+    transactions still in a tx pool (only confirmed ones are found).
+*/
+pub fn light_routes() -> Router<LightCtx> {
+    Router::new()
+        .route(&query("latest"), get(light_latest))
+        .route(&query("balance"), get(light_balance))
+        .route(&query("transaction"), get(light_transaction))
+}
+
+
+api_querys_define!{ Q6120,
+    __nnn_, Option<bool>, None,
+}
+
+async fn light_latest(State(ctx): State<LightCtx>, _q: Query<Q6120>) -> impl IntoResponse {
+    let (height, hash) = ctx.light.latest();
+    let data = jsondata!{
+        "height", height,
+        "hash", hash.to_hex(),
+    };
+    api_data(data)
+}
+
+
+api_querys_define!{ Q6121,
+    address, String, s!(""),
+    diamonds, Option<bool>, None,
+    assets, Option<bool>, None,
+    asset, Option<String>, None,
+}
+
+async fn light_balance(State(ctx): State<LightCtx>, q: Query<Q6121>) -> impl IntoResponse {
+    q_unit!(q, unit);
+    q_coinkind!(q, coinkind);
+    if q.diamonds == Some(true) && coinkind.diamond {
+        return api_error("a light node cannot list owned diamonds, ask a full node")
+    }
+    let ads = q.address.replace([' ', '\n'], "");
+    let mut addrs = vec![];
+    for a in ads.split(',').filter(|a| !a.is_empty()) {
+        let Ok(adr) = Address::from_readable(a) else {
+            return api_error(&format!("address {} format invalid", a))
+        };
+        addrs.push(adr);
+    }
+    if addrs.is_empty() {
+        return api_error("address format invalid")
+    }
+    let light = ctx.light.clone();
+    let res = tokio::task::spawn_blocking(move || light.balances(&addrs)).await;
+    let bls = match res {
+        Ok(Ok(v)) => v,
+        Ok(Err(e)) => return api_error(&e),
+        Err(e) => return api_error(&e.to_string()),
+    };
+    let mut resbls = Vec::with_capacity(bls.list.len());
+    for b in bls.list {
+        let mut one = serde_json::Map::new();
+        if coinkind.hacash {
+            one.insert("hacash".to_owned(), json!(b.hacash.to_unit_string(&unit)));
+        }
+        if coinkind.diamond {
+            one.insert("diamond".to_owned(), json!(*b.diamond));
+        }
+        if coinkind.satoshi {
+            one.insert("satoshi".to_owned(), json!(*b.satoshi));
+        }
+        if q.assets == Some(true) || q.asset.is_some() {
+            // one serial with `asset=<serial>`, else all of them
+            let serial = match q.assets {
+                Some(true) => None,
+                _ => q.asset.as_ref().and_then(|a| a.parse::<u64>().ok()),
+            };
+            let arr: Vec<_> = b.assets.as_list().iter()
+                .filter(|it| serial.is_none_or(|s| *it.serial == s))
+                .map(|it| json!({
+                    "serial": *it.serial,
+                    "amount": *it.amount,
+                })).collect();
+            one.insert("assets".to_owned(), json!(arr));
+        }
+        resbls.push(serde_json::Value::Object(one));
+    }
+    api_data_list(resbls)
+}
+
+
+api_querys_define!{ Q6122,
+    hash, String, s!(""),
+    body, Option<bool>, None,
+    action, Option<bool>, None,
+    signature, Option<bool>, None,
+    description, Option<bool>, None,
+}
+
+async fn light_transaction(State(ctx): State<LightCtx>, q: Query<Q6122>) -> impl IntoResponse {
+    q_unit!(q, unit);
+    let Ok(hx) = hex::decode(&q.hash) else {
+        return api_error("transaction hash format invalid")
+    };
+    if hx.len() != Hash::SIZE {
+        return api_error("transaction hash format invalid")
+    }
+    let txhx = Hash::must(&hx);
+    let light = ctx.light.clone();
+    let res = tokio::task::spawn_blocking(move || light.transaction(&txhx)).await;
+    let ltx = match res {
+        Ok(Ok(v)) => v,
+        Ok(Err(e)) => return api_error(&e),
+        Err(e) => return api_error(&e.to_string()),
+    };
+    let (lasthei, _) = ctx.light.latest();
+    let tx = ltx.tx.tx_read();
+    let fee_str = tx.fee().to_unit_string(&unit);
+    let main_addr = tx.main().to_readable();
+    let mut data = jsondata!{
+        "hash", tx.hash().to_hex(),
+        "hash_with_fee", tx.hash_with_fee().to_hex(),
+        "type", tx.ty(),
+        "timestamp", tx.timestamp().uint(),
+        "fee", fee_str.clone(),
+        "fee_got", tx.fee_got().to_unit_string(&unit),
+        "main_address", main_addr.clone(),
+        "action", tx.action_count(),
+        "block", json!({
+            "height": ltx.height,
+            "hash": ltx.block_hash.to_hex(),
+            "timestamp": ltx.block_timestamp,
+        }),
+        "confirm", lasthei.saturating_sub(ltx.height),
+    };
+    if let Some(gas_max) = tx.gas_max_byte() {
+        data.insert("gas_max", json!(gas_max));
+    }
+    if q.body == Some(true) {
+        data.insert("body", json!(tx.serialize().to_hex()));
+    }
+    let description = q.description == Some(true);
+    if q.signature == Some(true) {
+        if let Ok(sigstats) = check_tx_signature(tx) {
+            let sigchs: Vec<_> = sigstats.into_iter().map(|(adr, sg)| json!({
+                "address": adr.to_readable(),
+                "complete": sg,
+            })).collect();
+            data.insert("signatures", json!(sigchs));
+        }
+    }
+    if description {
+        data.insert("description", json!(format!("Main account {} pay {} HAC tx fee", main_addr, fee_str)));
+    }
+    if q.action == Some(true) {
+        let acts: Vec<_> = tx.actions().iter().map(|act| action_to_json_desc(tx, act, &unit, description)).collect();
+        data.insert("actions", json!(acts));
+    }
+    api_data(data)
+}
@@ -0,0 +1,12 @@
+
+
+/// Prometheus text exposition of everything recorded in `basis::metrics`.
+async fn metrics(State(ctx): State<ApiCtx>) -> impl IntoResponse {
+    // known even before the first block insert of this run
+    let lasthei = ctx.engine.latest_block().height().uint();
+    basis::metrics::gauge_set("hacash_chain_head_height", &[], lasthei as f64);
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        basis::metrics::render(),
+    )
+}
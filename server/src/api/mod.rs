@@ -17,4 +17,6 @@ include! {"scan_transfer.rs"}
 include! {"latest.rs"}
 include! {"create_account.rs"}
 include! {"create_transfer.rs"}
+include! {"metrics.rs"}
+include! {"light.rs"}
 include! {"routes.rs"}
@@ -4,4 +4,53 @@ pub fn routes() -> Router<ApiCtx> {
         .route(&query("block/height/latest"), get(latest))
         .route(&create("account"), get(account))
         .route(&create("coin/transfer"), get(create_coin_transfer))
+        .route("/metrics", get(metrics))
+}
+
+// the routes above are served by axum directly, this handler only carries their docs
+fn axum_routed(_: &ApiExecCtx, _: ApiRequest) -> ApiResponse {
+    never!()
+}
+
+/// Docs of `routes` for the OpenAPI document.
+pub fn route_docs() -> Vec<ApiRoute> {
+    use ApiRoute as R;
+    use ApiType::*;
+    vec![
+        R::get(&query("coin/transfer"), axum_routed)
+            .summary("Coin transfers of one transaction in a block")
+            .param("height", Integer, "block height, 1 by default")
+            .param_must("txposi", Integer, "transaction index in the block, coinbase excluded")
+            .param("from", String, "only transfers from this address")
+            .param("to", String, "only transfers to this address")
+            .param("coinkind", String, "coin kinds to show: h, s, d, a, hsda by default")
+            .param_unit()
+            .returns("tx_hash", String, "")
+            .returns("block_hash", String, "")
+            .returns("main_address", String, "")
+            .returns("transfers", Array, ""),
+        R::get(&query("block/height/latest"), axum_routed)
+            .summary("Latest block height")
+            .returns("height", Integer, ""),
+        R::get(&create("account"), axum_routed)
+            .summary("Create random accounts")
+            .param("quantity", Integer, "1 to 200, 1 by default")
+            .returns("list", Array, "addresses with their private and public keys"),
+        R::get(&create("coin/transfer"), axum_routed)
+            .summary("Build and sign a coin transfer transaction")
+            .param_must("fee", String, "fee amount")
+            .param_must("main_prikey", String, "private key of the main address, hex")
+            .param_must("to_address", String, "")
+            .param("timestamp", Integer, "")
+            .param("from_prikey", String, "pay from another address, hex private key")
+            .param("hacash", String, "HAC amount")
+            .param("satoshi", Integer, "")
+            .param("diamonds", String, "comma separated diamond names")
+            .returns("hash", String, "")
+            .returns("hash_with_fee", String, "")
+            .returns("timestamp", Integer, "")
+            .returns("body", String, "transaction hex"),
+        R::get("/metrics", axum_routed)
+            .summary("Prometheus metrics, as text"),
+    ]
 }
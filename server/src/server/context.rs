@@ -28,4 +28,10 @@ impl ApiCtx {
             blocks_max: 4,
         }
     }
-}
\ No newline at end of file
+}
+
+/// State of the light node api.
+#[derive(Clone)]
+pub struct LightCtx {
+    pub light: Arc<dyn LightNoder>,
+}
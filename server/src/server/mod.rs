@@ -1,6 +1,6 @@
 use std::sync::*;
 use std::collections::*;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 
 use tokio::net::*;
 use axum::routing::*;
@@ -23,6 +23,8 @@ include!{"context.rs"}
 include!{"param.rs"}
 include!{"render.rs"}
 include!{"registry.rs"}
+include!{"openapi.rs"}
+include!{"access.rs"}
 include!{"route.rs"}
 include!{"load.rs"}
 include!{"server.rs"}
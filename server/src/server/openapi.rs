@@ -0,0 +1,124 @@
+
+/*
+    OpenAPI 3 document of the api routes, from their `ApiDoc`.
+*/
+
+fn openapi_field_schema(f: &ApiField) -> Value {
+    let mut schema = json!({"type": f.ty.name()});
+    if !f.desc.is_empty() {
+        schema["description"] = json!(f.desc);
+    }
+    schema
+}
+
+fn openapi_operation(route: &ApiRoute) -> Value {
+    let doc = &route.doc;
+    let tag = route.path.split('/').nth(1).filter(|s| !s.is_empty()).unwrap_or("root");
+    let params: Vec<Value> = doc.params.iter().map(|p| json!({
+        "name": p.name,
+        "in": "query",
+        "required": p.required,
+        "description": p.desc,
+        "schema": {"type": p.ty.name()},
+    })).collect();
+    let mut props = serde_json::Map::new();
+    props.insert("ret".to_owned(), json!({"type": "integer", "enum": [0]}));
+    for f in &doc.returns {
+        props.insert(f.name.to_owned(), openapi_field_schema(f));
+    }
+    let ok = json!({"type": "object", "properties": props, "required": ["ret"]});
+    let mut op = json!({
+        "tags": [tag],
+        "summary": doc.summary,
+        "parameters": params,
+        "responses": {
+            "200": {
+                "description": "`ret` 0 on success, else 1 with `err`",
+                "content": {"application/json": {"schema": {"oneOf": [
+                    ok,
+                    {"$ref": "#/components/schemas/ApiError"},
+                ]}}},
+            },
+            "400": {
+                "description": "invalid parameters",
+                "content": {"application/json": {"schema": {"$ref": "#/components/schemas/ApiError"}}},
+            },
+        },
+    });
+    if let Some(body) = doc.body {
+        op["requestBody"] = json!({
+            "description": body,
+            "content": {"application/octet-stream": {"schema": {"type": "string", "format": "binary"}}},
+        });
+    }
+    op
+}
+
+/// The document for `routes`, with path order kept.
+pub fn openapi_doc(routes: &[ApiRoute]) -> Value {
+    let mut paths = serde_json::Map::new();
+    for route in routes {
+        let method = match route.method {
+            ApiMethod::Get => "get",
+            ApiMethod::Post => "post",
+        };
+        let item = paths.entry(route.path.clone()).or_insert_with(|| json!({}));
+        item[method] = openapi_operation(route);
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": {"title": "Hacash node api", "version": env!("CARGO_PKG_VERSION")},
+        "paths": paths,
+        "components": {
+            "schemas": {
+                "ApiError": {
+                    "type": "object",
+                    "properties": {
+                        "ret": {"type": "integer", "enum": [1]},
+                        "err": {"type": "string"},
+                    },
+                    "required": ["ret", "err"],
+                },
+            },
+            "securitySchemes": {
+                "bearer": {"type": "http", "scheme": "bearer"},
+                "apiKey": {"type": "apiKey", "in": "header", "name": "X-Api-Key"},
+            },
+        },
+        "security": [{}, {"bearer": []}, {"apiKey": []}],
+    })
+}
+
+#[cfg(test)]
+mod openapi_tests {
+    use super::*;
+
+    fn handler(_: &ApiExecCtx, _: ApiRequest) -> ApiResponse {
+        ApiResponse::json("{}".to_owned())
+    }
+
+    #[test]
+    fn routes_become_operations() {
+        let routes = vec![
+            ApiRoute::get("/query/balance", handler)
+                .summary("Balances")
+                .param_must("address", ApiType::String, "")
+                .param("limit", ApiType::Integer, "")
+                .returns("list", ApiType::Array, ""),
+            ApiRoute::post("/submit/transaction", handler).body("transaction bytes"),
+            ApiRoute::get("/submit/transaction", handler),
+        ];
+        let doc = openapi_doc(&routes);
+        let op = &doc["paths"]["/query/balance"]["get"];
+        assert_eq!(op["summary"], "Balances");
+        assert_eq!(op["tags"][0], "query");
+        assert_eq!(op["parameters"][0]["required"], true);
+        assert_eq!(op["parameters"][1]["schema"]["type"], "integer");
+        let ok = &op["responses"]["200"]["content"]["application/json"]["schema"]["oneOf"][0];
+        assert_eq!(ok["properties"]["list"]["type"], "array");
+        let submit = &doc["paths"]["/submit/transaction"];
+        assert!(submit["post"]["requestBody"].is_object());
+        assert_eq!(submit["post"]["parameters"][0]["name"], "hexbody");
+        assert!(submit["get"].is_object());
+    }
+}
@@ -59,44 +59,61 @@ fn route_request(headers: HeaderMap, query: HashMap<String, String>, body: Vec<u
     }
 }
 
+// bad parameters are answered alike for every route, before its handler runs
+fn route_param_error(doc: &ApiDoc, req: &ApiRequest) -> Option<Response> {
+    let err = doc.check(req).err()?;
+    let mut resp = ApiResponse::json(json!({"ret":1,"err":err}).to_string());
+    resp.status = 400;
+    Some(api_resp_to_axum(resp))
+}
+
 fn route_handler_exec(
     handler: ApiHandlerFn,
+    doc: &ApiDoc,
     ctx: &ApiCtx,
     headers: HeaderMap,
     query: HashMap<String, String>,
     body: Vec<u8>,
 ) -> Response {
     let req = route_request(headers, query, body);
+    if let Some(resp) = route_param_error(doc, &req) {
+        return resp;
+    }
     let exec = api_exec_ctx(ctx);
     api_resp_to_axum(handler(&exec, req))
 }
 
 async fn route_handler_exec_async(
     handler: ApiHandlerAsyncFn,
+    doc: &ApiDoc,
     ctx: ApiCtx,
     headers: HeaderMap,
     query: HashMap<String, String>,
     body: Vec<u8>,
 ) -> Response {
     let req = route_request(headers, query, body);
+    if let Some(resp) = route_param_error(doc, &req) {
+        return resp;
+    }
     let exec = api_exec_ctx(&ctx);
     api_resp_to_axum(handler(exec, req).await)
 }
 
 fn build_method_router(route: ApiRoute) -> MethodRouter<ApiCtx> {
+    let doc = Arc::new(route.doc);
     match (route.method, route.handler) {
         (ApiMethod::Get, ApiHandler::Sync(handler)) => get(
             move |State(ctx): State<ApiCtx>,
                   headers: HeaderMap,
                   Query(query): Query<HashMap<String, String>>| async move {
-                route_handler_exec(handler, &ctx, headers, query, vec![])
+                route_handler_exec(handler, &doc, &ctx, headers, query, vec![])
             },
         ),
         (ApiMethod::Get, ApiHandler::Async(handler)) => get(
             move |State(ctx): State<ApiCtx>,
                   headers: HeaderMap,
                   Query(query): Query<HashMap<String, String>>| async move {
-                route_handler_exec_async(handler, ctx, headers, query, vec![]).await
+                route_handler_exec_async(handler, &doc, ctx, headers, query, vec![]).await
             },
         ),
         (ApiMethod::Post, ApiHandler::Sync(handler)) => post(
@@ -104,7 +121,7 @@ fn build_method_router(route: ApiRoute) -> MethodRouter<ApiCtx> {
                   headers: HeaderMap,
                   Query(query): Query<HashMap<String, String>>,
                   body: Bytes| async move {
-                route_handler_exec(handler, &ctx, headers, query, body.to_vec())
+                route_handler_exec(handler, &doc, &ctx, headers, query, body.to_vec())
             },
         ),
         (ApiMethod::Post, ApiHandler::Async(handler)) => post(
@@ -112,7 +129,7 @@ fn build_method_router(route: ApiRoute) -> MethodRouter<ApiCtx> {
                   headers: HeaderMap,
                   Query(query): Query<HashMap<String, String>>,
                   body: Bytes| async move {
-                route_handler_exec_async(handler, ctx, headers, query, body.to_vec()).await
+                route_handler_exec_async(handler, &doc, ctx, headers, query, body.to_vec()).await
             },
         ),
     }
@@ -122,21 +139,29 @@ fn route_is_enabled(route: &ApiRoute, debug_open: bool) -> bool {
     !route.debug || debug_open
 }
 
+/// Enabled routes of the global services followed by `services`.
+pub fn registered_routes(services: Vec<Arc<dyn ApiService>>, debug_open: bool) -> Vec<ApiRoute> {
+    let mut all_services = global_api_services();
+    all_services.extend(services);
+    all_services
+        .iter()
+        .flat_map(|svc| svc.routes())
+        .filter(|route| route_is_enabled(route, debug_open))
+        .collect()
+}
+
 pub fn merge_registered_services(
-    mut rtr: Router<ApiCtx>,
+    rtr: Router<ApiCtx>,
     services: Vec<Arc<dyn ApiService>>,
     debug_open: bool,
 ) -> Router<ApiCtx> {
-    let mut all_services = global_api_services();
-    all_services.extend(services);
-    for svc in all_services {
-        for route in svc.routes() {
-            if !route_is_enabled(&route, debug_open) {
-                continue;
-            }
-            let mr = build_method_router(route.clone());
-            rtr = rtr.route(route.path.as_str(), mr);
-        }
+    merge_routes(rtr, registered_routes(services, debug_open))
+}
+
+fn merge_routes(mut rtr: Router<ApiCtx>, routes: Vec<ApiRoute>) -> Router<ApiCtx> {
+    for route in routes {
+        let path = route.path.clone();
+        rtr = rtr.route(path.as_str(), build_method_router(route));
     }
     rtr
 }
@@ -215,8 +240,10 @@ mod tests {
 
         rt.block_on(async {
             let ctx = test_ctx();
+            let doc = ApiDoc::default();
             let sync_resp = route_handler_exec(
                 sync_handler,
+                &doc,
                 &ctx,
                 HeaderMap::new(),
                 HashMap::new(),
@@ -224,6 +251,7 @@ mod tests {
             );
             let async_resp = route_handler_exec_async(
                 async_handler,
+                &doc,
                 ctx,
                 HeaderMap::new(),
                 HashMap::new(),
@@ -235,4 +263,23 @@ mod tests {
             assert_eq!(async_resp.status(), StatusCode::OK);
         });
     }
+
+    #[test]
+    fn declared_params_are_checked_before_the_handler() {
+        let route = ApiRoute::get("/query/x", sync_handler)
+            .param_must("address", ApiType::String, "")
+            .param("limit", ApiType::Integer, "")
+            .param("scale", ApiType::Number, "");
+        let query = |kvs: &[(&str, &str)]| -> HashMap<String, String> {
+            kvs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        let ctx = test_ctx();
+        let run = |q| route_handler_exec(sync_handler, &route.doc, &ctx, HeaderMap::new(), q, vec![]).status();
+        assert_eq!(run(query(&[])), StatusCode::BAD_REQUEST);
+        assert_eq!(run(query(&[("address", "")])), StatusCode::BAD_REQUEST);
+        assert_eq!(run(query(&[("address", "a"), ("limit", "ten")])), StatusCode::BAD_REQUEST);
+        assert_eq!(run(query(&[("address", "a"), ("scale", "x")])), StatusCode::BAD_REQUEST);
+        assert_eq!(run(query(&[("address", "a"), ("limit", "-3"), ("scale", "0.5")])), StatusCode::OK);
+        assert_eq!(run(query(&[("address", "a"), ("limit", "")])), StatusCode::OK);
+    }
 }
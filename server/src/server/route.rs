@@ -29,18 +29,33 @@ pub fn router(
     hnoder: Arc<dyn HNoder>,
     mut rts: Vec<Router<ApiCtx>>,
     services: Vec<Arc<dyn ApiService>>,
-    debug_open: bool,
+    cnf: &ServerConf,
 ) -> Router {
 
     let ctx = ApiCtx::new(hnoder.engine(), hnoder.clone());
     let nrt = crate::api::routes();
+    let routes = registered_routes(services, cnf.debug_open);
+    let mut docs = crate::api::route_docs();
+    docs.extend(routes.iter().cloned());
+    let openapi = openapi_doc(&docs).to_string();
     let mut rtr = Router::new()
         .route("/_server_", get("Hacash Api Server"))
+        .route("/openapi.json", get(|| async move {
+            ([(header::CONTENT_TYPE, "application/json")], openapi)
+        }))
         .merge(nrt);
-    rtr = merge_registered_services(rtr, services, debug_open);
+    rtr = merge_routes(rtr, routes);
     while let Some(r) = rts.pop() {
         rtr = rtr.merge(r);
     }
-    rtr.with_state(ctx)
+    guard_router(rtr.with_state(ctx), &cnf.access)
 }
     
+
+
+pub fn light_router(light: Arc<dyn LightNoder>) -> Router {
+    Router::new()
+        .route("/_server_", get("Hacash Light Api Server"))
+        .merge(crate::api::light_routes())
+        .with_state(LightCtx { light })
+}
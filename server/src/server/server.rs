@@ -45,22 +45,21 @@ impl HttpServer {
 
 
 async fn server_listen(ser: &HttpServer, worker: Worker) {
-    let port = ser.cnf.listen;
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let rtapp = ser.router.lock().unwrap().take().unwrap();
+    serve_router(SocketAddr::new(ser.cnf.bind, ser.cnf.listen), rtapp, worker).await
+}
+
+
+async fn serve_router(addr: SocketAddr, rtapp: Router, worker: Worker) {
     let listener = TcpListener::bind(addr).await;
     if let Err(ref e) = listener {
-        println!("\n[Error] api server failed to bind port {}: {}\n", port, e);
+        println!("\n[Error] api server failed to bind {}: {}\n", addr, e);
         return
     }
     let listener = listener.unwrap();
     println!("[Api Server] listening on http://{addr}");
-    /* let app = route(ApiCtx::new(
-        ser.engine.clone(),
-        ser.hcshnd.clone(),
-    )); */
-    let rtapp = ser.router.lock().unwrap().take().unwrap();
     let mut wkr = worker.clone();
-    if let Err(e) = axum::serve(listener, rtapp)
+    if let Err(e) = axum::serve(listener, rtapp.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(async move {
             let _ = wkr.wait().await;
         })
@@ -70,3 +69,34 @@ async fn server_listen(ser: &HttpServer, worker: Worker) {
     println!("[Server] serve exit.");
 }
 
+
+/// Api server of a light node, see `light_router`.
+#[derive(Clone)]
+pub struct LightServer {
+    cnf: ServerConf,
+    router: Arc<Mutex<Option<Router>>>,
+}
+
+
+impl Server for LightServer {
+    fn start(&self, worker: Worker) {
+        if !self.cnf.enable {
+            return // disable
+        }
+        let rtapp = self.router.lock().unwrap().take().unwrap();
+        let rt = new_tokio_rt(self.cnf.multi_thread);
+        rt.block_on(serve_router(SocketAddr::new(self.cnf.bind, self.cnf.listen), rtapp, worker));
+    }
+}
+
+
+impl LightServer {
+    pub fn open(iniobj: &IniObj, light: Arc<dyn LightNoder>) -> Self {
+        let cnf = ServerConf::new(iniobj);
+        let router = guard_router(light_router(light), &cnf.access);
+        Self {
+            cnf,
+            router: Mutex::new(Some(router)).into(),
+        }
+    }
+}
@@ -77,12 +77,105 @@ fn estimate_protocol_cost_auto_with_periods(
     cur
 }
 
+/// `fitshc test <file.fitsh> [test_file]`: the test file defaults to `<stem>_test.fitsh`.
+#[cfg(not(feature = "fitsh-test"))]
+fn run_tests(_: &[String]) {
+    println!("fitshc test needs the test chain: rebuild with `cargo build --features fitsh-test`");
+    std::process::exit(1);
+}
+
+/// `fitshc test <file.fitsh> [test_file]`: the test file defaults to `<stem>_test.fitsh`.
+#[cfg(feature = "fitsh-test")]
+fn run_tests(args: &[String]) {
+    let Some(file_path) = args.first() else {
+        println!("Usage: fitshc test <file.fitsh> [test_file]");
+        return;
+    };
+    let path = Path::new(file_path);
+    let test_path = match args.get(1) {
+        Some(p) => Path::new(p).to_path_buf(),
+        None => {
+            let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+            path.with_file_name(format!("{}_test.fitsh", stem))
+        }
+    };
+    let read = |p: &Path| {
+        fs::read_to_string(p).unwrap_or_else(|e| {
+            println!("Error reading file {}: {}", p.display(), e);
+            std::process::exit(1);
+        })
+    };
+    let (source, test_source) = (read(path), read(&test_path));
+    let reports = match testkit::sim::fitsh::run_fitsh_tests(&source, &test_source) {
+        Ok(r) => r,
+        Err(e) => {
+            println!("Test compile error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut failed = 0;
+    for r in &reports {
+        if r.passed {
+            println!("test {} ... ok (gas {})", r.name, r.gas);
+        } else {
+            failed += 1;
+            println!("test {} ... FAILED: {}", r.name, r.message);
+        }
+    }
+    println!(
+        "test result: {}. {} passed; {} failed",
+        if failed == 0 { "ok" } else { "FAILED" },
+        reports.len() - failed,
+        failed
+    );
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// `fitshc lint <file.fitsh> [--deny]`: `--deny` exits non-zero on any warning, for CI.
+fn run_lint(args: &[String]) {
+    let deny = args.iter().any(|a| a == "--deny");
+    let Some(file_path) = args.iter().find(|a| !a.starts_with("--")) else {
+        println!("Usage: fitshc lint <file.fitsh> [--deny]");
+        return;
+    };
+    let source = match fs::read_to_string(file_path) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("Error reading file: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let warnings = match vm::fitshc::lint(&source) {
+        Ok(w) => w,
+        Err(e) => {
+            println!("Compile error: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+    for w in &warnings {
+        println!("warning{}", w);
+    }
+    println!("{} lint warning(s)", warnings.len());
+    if deny && !warnings.is_empty() {
+        std::process::exit(1);
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
         println!("Usage: fitshc <file.fitsh> [fee] [nonce]");
+        println!("       fitshc test <file.fitsh> [test_file]");
+        println!("       fitshc lint <file.fitsh> [--deny]");
         return;
     }
+    match args[1].as_str() {
+        "test" => return run_tests(&args[2..]),
+        "lint" => return run_lint(&args[2..]),
+        _ => {}
+    }
     let file_path = &args[1];
     let source = match fs::read_to_string(file_path) {
         Ok(s) => s,
@@ -5,7 +5,7 @@ use basis::config::*;
 use basis::interface::*;
 use chain::*;
 use mint::HacashMinter;
-use node::{core::HacashNode, memtxpool::*};
+use node::{core::HacashNode, light::LightNode, memtxpool::*};
 use server::*;
 use sys::*;
 
@@ -27,6 +27,19 @@ fn main() {
 }
 
 pub fn run() -> Rerr {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).is_some_and(|a| a == "--dev") {
+        return run_dev(args.get(2).map(|s| s.as_str()));
+    }
+    if args.get(1).is_some_and(|a| a == "--light") {
+        return run_light(args.get(2).map_or("./hacash.config.ini", |s| s.as_str()));
+    }
+    if args.get(1).is_some_and(|a| a == "--bid-sim") {
+        let Some(history) = args.get(2) else {
+            return errf!("usage: fullnode --bid-sim <history> [config]");
+        };
+        return run_bid_sim(history, args.get(3).map_or("./hacash.config.ini", |s| s.as_str()));
+    }
     run_with_scaner("./hacash.config.ini", Box::new(NilScaner {}))
 }
 
@@ -42,13 +55,74 @@ pub fn install_standard_fullnode_stack() -> Rerr {
     Ok(())
 }
 
+/// `fullnode --dev [config]`: single-node dev chain, the optional config
+/// only overrides the dev defaults.
+pub fn run_dev(cnfpath: Option<&str>) -> Rerr {
+    let mut ini = match cnfpath {
+        Some(p) => sys::load_ini_file(std::path::Path::new(p))?,
+        None => IniObj::new(),
+    };
+    DevConf::apply(&mut ini);
+    println!("[Dev] chain id {}, mnemonic \"{}\", funded accounts:", DEV_CHAIN_ID, DEV_MNEMONIC);
+    for (i, acc) in dev_accounts().iter().enumerate() {
+        println!("  ({}) {} prikey {}", i, acc.readable(), hex::encode(acc.secret_key().serialize()));
+    }
+    install_standard_fullnode_stack()?;
+    run_builder(FullnodeBuilder::from_ini(ini), Box::new(NilScaner {}))
+}
+
+/// `fullnode --light [config]`: keeps only verified block intros and asks
+/// the full peers in `[node] boots` for balances and transactions.
+pub fn run_light(cnfpath: &str) -> Rerr {
+    let ini = load_config(cnfpath.to_owned());
+    if ini.is_empty() {
+        return errf!("config '{}' is empty or failed to load", cnfpath);
+    }
+    install_chain_spec(ChainSpec::new(&ini))?;
+    install_standard_fullnode_stack()?;
+    let minter: std::sync::Arc<dyn Minter> = std::sync::Arc::new(HacashMinter::create(&ini));
+    let light: std::sync::Arc<dyn LightNoder> = std::sync::Arc::new(LightNode::open(&ini, minter)?);
+    let server = std::sync::Arc::new(LightServer::open(&ini, light.clone()));
+    lightnode::run_lightnode(light, server)
+}
+
+/// `fullnode --bid-sim <history> [config]`: replays recorded
+/// `/query/diamond/bidding` answers against the `[diamondminer]` bid
+/// strategy and prints which diamonds it would have won. Sends nothing.
+pub fn run_bid_sim(history: &str, cnfpath: &str) -> Rerr {
+    let ini = sys::load_ini_file(std::path::Path::new(cnfpath)).unwrap_or_default();
+    let text = std::fs::read_to_string(history).map_err(|e| format!("read {} failed: {}", history, e))?;
+    let history = diabider::BidSnapshot::parse_history(&text)?;
+    let bidcnf = diabider::BidStrategyConf::new(&ini);
+    let limits = diabider::BidLimits::new(&ini);
+    // our own recorded bids are left out, the simulator places them instead
+    let password = ini_must(ini_section(&ini, "diamondminer"), "bid_password", "");
+    let skip = match password.is_empty() {
+        true => String::new(),
+        false => Account::create_by_password(&password)?.readable().to_owned(),
+    };
+    let mut strategy = bidcnf.build();
+    let report = diabider::simulate_bidding(strategy.as_mut(), &limits, &history, &skip);
+    println!("{}", serde_json::to_string_pretty(&report.to_json()).unwrap());
+    Ok(())
+}
+
 pub fn run_with_scaner(cnfpath: &str, scan: Box<dyn Scaner>) -> Rerr {
     install_standard_fullnode_stack()?;
+    let builder = FullnodeBuilder::from_config_path(cnfpath)?;
+    run_builder(builder, scan)
+}
+
+fn run_builder(mut builder: FullnodeBuilder, scan: Box<dyn Scaner>) -> Rerr {
+    // diamond, asset and storage key index side DBs and the explorer export, fed by the scaner
+    let scan = Box::new(diaindex::DiamondIndexScaner::wrap(scan));
+    let scan = Box::new(assetindex::AssetIndexScaner::wrap(scan));
+    let scan = Box::new(explorer::ExplorerExportScaner::wrap(scan));
+    let scan = Box::new(vm::StorageKeyIndexScaner::wrap(scan));
 
     // scan api
     server::setup::api_servicer(scan.api_services());
 
-    let mut builder = FullnodeBuilder::from_config_path(cnfpath)?;
     builder.install_ctrlc(true).scaner(scan);
 
     // Configure global VM contract cache pool (performance-only).
@@ -64,6 +138,29 @@ pub fn run_with_scaner(cnfpath: &str, scan: Box<dyn Scaner>) -> Rerr {
         ..Default::default()
     });
 
+    // Local side DBs served by the vm api: verified contract sources and storage keys.
+    let srcdir = std::path::PathBuf::from(&engcnf.data_dir).join("contract_source");
+    std::fs::create_dir_all(&srcdir).map_err(|e| e.to_string())?;
+    vm::api::configure_contract_source_db(Box::new(db::DiskKV::open(&srcdir)));
+    let idxdir = std::path::PathBuf::from(&engcnf.data_dir).join("storage_index");
+    std::fs::create_dir_all(&idxdir).map_err(|e| e.to_string())?;
+    vm::configure_storage_key_index(Box::new(db::DiskKV::open(&idxdir)));
+    if ini_must_bool(ini_section(builder.ini(), "diamondindex"), "enable", false) {
+        let diadir = std::path::PathBuf::from(&engcnf.data_dir).join("diamond_index");
+        std::fs::create_dir_all(&diadir).map_err(|e| e.to_string())?;
+        diaindex::configure_diamond_index(Box::new(db::DiskKV::open(&diadir)));
+    }
+    if ini_must_bool(ini_section(builder.ini(), "assetindex"), "enable", false) {
+        let astdir = std::path::PathBuf::from(&engcnf.data_dir).join("asset_index");
+        std::fs::create_dir_all(&astdir).map_err(|e| e.to_string())?;
+        assetindex::configure_asset_index(Box::new(db::DiskKV::open(&astdir)))?;
+    }
+    explorer::configure_explorer_export(&explorer::ExplorerConf::new(builder.ini(), std::path::Path::new(&engcnf.data_dir)))?;
+
+    let texcnf = texrelay::TexRelayConf::new(builder.ini());
+    let texrelay_on = texcnf.enable;
+    let bidcnf = diabider::BidStrategyConf::new(builder.ini());
+
     builder
         .diskdb(|dir| Box::new(db::DiskKV::open(dir)))
         .txpool(build_txpool)
@@ -72,18 +169,24 @@ pub fn run_with_scaner(cnfpath: &str, scan: Box<dyn Scaner>) -> Rerr {
             Ok(Box::new(ChainEngine::open(dbfn, cnf, minter, scaner, DB_VERSION)))
         })
         .hnoder(|ini, txpool, engine| Ok(Box::new(HacashNode::open(ini, txpool, engine))))
-        .server(|ini, hnoder| {
+        .server(move |ini, hnoder| {
             #[allow(unused_mut)]
             let mut services: Vec<std::sync::Arc<dyn ApiService>> = vec![mint::api::service()];
             services.push(vm::api::service());
-            let debug_open = ServerConf::new(ini).debug_open;
+            if texrelay_on {
+                services.push(texrelay::service());
+            }
+            let cnf = ServerConf::new(ini);
             Ok(Box::new(HttpServer::open(
                 ini,
                 hnoder.clone(),
-                server::router(hnoder, vec![], services, debug_open),
+                server::router(hnoder, vec![], services, &cnf),
             )))
         })
-        .app(diabider::start_diamond_auto_bidding);
+        .app(move |w, h| diabider::start_diamond_auto_bidding(&bidcnf, w, h))
+        .app(devminer::start_dev_block_producer)
+        .app(move |w, h| texrelay::start_tex_relay(&texcnf, w, h))
+        .app(explorer::start_explorer_export);
 
     // start run
     builder.run()
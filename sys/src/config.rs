@@ -50,6 +50,7 @@ pub fn load_config(mut cnfilestr: String) -> IniObj {
     }
 
     cnfilestr = cnf_file.canonicalize().unwrap().to_str().unwrap().to_string();
+    let _ = LOADED_CONFIG_PATH.set(PathBuf::from(&cnfilestr));
     // println!("{:?} {:?}", args, exedir);
     println!("[Config] load: {} {}.", cnfilestr, ctshow());
     
@@ -57,3 +58,22 @@ pub fn load_config(mut cnfilestr: String) -> IniObj {
     ini::ini!(&cnfilestr)
 
 }
+
+
+static LOADED_CONFIG_PATH: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+/*
+* the file `load_config` read, to load it again while running
+*/
+pub fn loaded_config_path() -> Option<PathBuf> {
+    LOADED_CONFIG_PATH.get().cloned()
+}
+
+
+/*
+* load an ini file by path, without command line override
+*/
+pub fn load_ini_file(path: &std::path::Path) -> Ret<IniObj> {
+    let path = path.to_str().ok_or("path is not utf-8".to_owned())?;
+    ini::ini!(safe path)
+}
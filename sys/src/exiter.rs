@@ -95,6 +95,16 @@ impl Worker {
         }
     }
 
+    /// A handle that can ask every worker to quit, as Ctrl+C does.
+    pub fn exiter(&self) -> Exiter {
+        Exiter {
+            state: self.state.clone(),
+            exiting: self.exiting.clone(),
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+        }
+    }
+
     pub fn wait(&mut self) -> Recv<'_, ()> {
         self.refresh_exit_signal();
         self.receiver.recv_direct()
@@ -65,9 +65,13 @@ pub fn ini_must_bool(sec: &HashMap<String, Option<String>>, key: &str, dv: bool)
 
 
 pub fn ini_must_account(sec: &HashMap<String, Option<String>>, key: &str) -> Account {
+    match ini_account(sec, key) {
+        Ok(acc) => acc,
+        Err(e) => panic!("[Config Error] {}.", e),
+    }
+}
+
+pub fn ini_account(sec: &HashMap<String, Option<String>>, key: &str) -> Ret<Account> {
     let pass = ini_must(sec, key, "123456");
-    let Ok(acc) = Account::create_by(&pass) else {
-        panic!("[Config Error] account password for key '{}' is invalid.", key)
-    };
-    acc
+    Account::create_by(&pass).map_err(|_| format!("account password for key '{}' is invalid", key))
 }
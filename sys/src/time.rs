@@ -2,11 +2,26 @@ use chrono::{Local, TimeZone};
 
 pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
+static TIME_WARP: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
 pub fn curtimes() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_secs())
         .unwrap_or(0)
+        .saturating_add(time_warp())
+}
+
+/// Move this process's clock forward by `secs`, saturating instead of
+/// wrapping. Only local dev chains use it.
+pub fn warp_time(secs: u64) {
+    use std::sync::atomic::Ordering::Relaxed;
+    let _ = TIME_WARP.fetch_update(Relaxed, Relaxed, |w| Some(w.saturating_add(secs)));
+}
+
+/// Seconds the clock has been moved forward by `warp_time`.
+pub fn time_warp() -> u64 {
+    TIME_WARP.load(std::sync::atomic::Ordering::Relaxed)
 }
 
 
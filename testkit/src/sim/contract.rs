@@ -0,0 +1,206 @@
+use basis::component::Env;
+use basis::interface::{ActExec, Context, State, TransactionRead};
+use field::{Address, Amount, BytesW2, Field, Serialize, Uint4};
+use protocol::context::{ContextInst, TX_GAS_BUDGET_CAP_BYTE, decode_gas_budget};
+use protocol::state::CoreState;
+use protocol::transaction::create_tx_info;
+use sys::{Rerr, Ret, XRet};
+use vm::action::{ContractDeploy, contract_protocol_cost_min};
+use vm::machine::run_main_entry;
+use vm::rt::{CodeType, GasExtra, SpaceCap};
+use vm::value::Value;
+use vm::{ContractAddress, ContractSto, StorageEntry, VMStateRead, VmLog};
+
+use crate::sim::integration::{enable_default_vm_setup, vm_main_addr};
+use crate::sim::logs::MemLogs;
+use crate::sim::state::FlatMemState;
+use crate::sim::tx::{StubTx, StubTxBuilder};
+
+/// In-memory chain state for exercising deployed contracts.
+/// Every call runs as one type-3 transaction; a failed call leaves the state untouched.
+pub struct ContractBench {
+    state: Box<dyn State>,
+    logs: Vec<VmLog>,
+    height: u64,
+    caller: Address,
+    gas_max: u8,
+}
+
+impl Default for ContractBench {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContractBench {
+    pub fn new() -> Self {
+        enable_default_vm_setup();
+        Self {
+            state: Box::new(FlatMemState::default()),
+            logs: Vec::new(),
+            height: 1,
+            caller: vm_main_addr(),
+            gas_max: TX_GAS_BUDGET_CAP_BYTE,
+        }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    pub fn set_height(&mut self, height: u64) {
+        self.height = height;
+    }
+
+    pub fn caller(&self) -> Address {
+        self.caller
+    }
+
+    pub fn set_caller(&mut self, caller: Address) {
+        self.caller = caller;
+    }
+
+    pub fn set_gas_max(&mut self, gas_max: u8) {
+        self.gas_max = gas_max;
+    }
+
+    pub fn balance(&mut self, addr: &Address) -> Amount {
+        CoreState::wrap(self.state.as_mut())
+            .balance(addr)
+            .map(|b| b.hacash)
+            .unwrap_or_default()
+    }
+
+    pub fn set_balance(&mut self, addr: &Address, amt: &Amount) {
+        let mut state = CoreState::wrap(self.state.as_mut());
+        let mut bls = state.balance(addr).unwrap_or_default();
+        bls.hacash = amt.clone();
+        state.balance_set(addr, &bls);
+    }
+
+    /// Logs pushed by all successful calls so far, oldest first.
+    pub fn logs(&self) -> &[VmLog] {
+        &self.logs
+    }
+
+    pub fn clear_logs(&mut self) {
+        self.logs.clear();
+    }
+
+    /// Persistent storage value of `key` in contract `addr` at the current height.
+    pub fn storage(&self, addr: &Address, key: &Value) -> Ret<Option<Value>> {
+        let gst = GasExtra::new(self.height);
+        let cap = SpaceCap::new(self.height);
+        let got = VMStateRead::wrap(self.state.as_ref())
+            .debug_storage_get(&gst, &cap, self.height, addr, key)
+            .map_err(|e| e.to_string())?;
+        Ok(got.map(|(v, ..)| v))
+    }
+
+    /// Feed the bench state to the storage key index, as a confirmed block would.
+    pub fn index_storage(&self) -> Rerr {
+        vm::storage_key_index()?.apply(self.height, self.state.as_ref())
+    }
+
+    /// Indexed persistent storage entries of contract `addr` at the current height.
+    pub fn storage_entries(&self, addr: &Address) -> Ret<Vec<StorageEntry>> {
+        let gst = GasExtra::new(self.height);
+        let cap = SpaceCap::new(self.height);
+        let state = VMStateRead::wrap(self.state.as_ref());
+        let (mut list, mut next) = (vec![], Some(0));
+        while let Some(cursor) = next {
+            let (page, more) = state.storage_entries(&gst, &cap, self.height, addr, cursor, 100)?;
+            list.extend(page);
+            next = more;
+        }
+        Ok(list)
+    }
+
+    /// Deploy as the current caller, paying the minimum protocol cost on its behalf.
+    pub fn deploy(&mut self, nonce: u32, sto: ContractSto, argv: Vec<u8>) -> Ret<ContractAddress> {
+        let caller = self.caller;
+        let caddr = ContractAddress::calculate(&caller, &Uint4::from(nonce));
+        let mut act = ContractDeploy::new();
+        act.nonce = Uint4::from(nonce);
+        act.construct_argv = BytesW2::from(argv)?;
+        act.contract = sto;
+        self.transact(vec![caller], |ctx| {
+            act.protocol_cost = contract_protocol_cost_min(
+                ctx,
+                act.contract.size(),
+                protocol::params::CONTRACT_STORE_PERM_PERIODS,
+            )?;
+            if act.protocol_cost.is_positive() {
+                protocol::operate::hac_add(ctx, &caller, &act.protocol_cost)?;
+            }
+            act.execute(ctx)?;
+            Ok(())
+        })?;
+        Ok(caddr)
+    }
+
+    /// Run bytecode main codes with `addrs` as the tx address list (index 0 is the caller).
+    /// Returns total gas used and the return value.
+    pub fn main_call(&mut self, addrs: Vec<Address>, codes: Vec<u8>) -> Ret<(i64, Value)> {
+        self.transact(addrs, |ctx| {
+            let (gas, rv) = run_main_entry(ctx, CodeType::Bytecode as u8, codes)?;
+            ctx.run_deferred_phase()?;
+            Ok((gas.total(), rv))
+        })
+    }
+
+    fn make_tx(&self, addrs: Vec<Address>) -> StubTx {
+        StubTxBuilder::new()
+            .ty(3)
+            .main(self.caller)
+            .addrs(addrs)
+            .fee(Amount::unit238(1_000_000))
+            .gas_max(self.gas_max)
+            .fee_purity(1)
+            .build()
+    }
+
+    /// Start gas metering without billing the caller: the budget is prepaid from a
+    /// temporary credit and the balance restored, and no refund is settled afterwards.
+    fn prepay_gas(ctx: &mut dyn Context, gas_max: u8) -> Rerr {
+        let main = ctx.env().tx.main;
+        let mut state = CoreState::wrap(ctx.state());
+        let origin = state.balance(&main).unwrap_or_default();
+        let mut credit = origin.clone();
+        credit.hacash = Amount::unit238(10_000_000_000_000);
+        state.balance_set(&main, &credit);
+        ctx.gas_initialize(decode_gas_budget(gas_max))?;
+        CoreState::wrap(ctx.state()).balance_set(&main, &origin);
+        Ok(())
+    }
+
+    fn transact<R>(
+        &mut self,
+        mut addrs: Vec<Address>,
+        f: impl FnOnce(&mut dyn Context) -> XRet<R>,
+    ) -> Ret<R> {
+        if addrs.first() != Some(&self.caller) {
+            addrs.insert(0, self.caller);
+        }
+        let tx = self.make_tx(addrs);
+        let mut env = Env::default();
+        env.block.height = self.height;
+        env.chain.id = 1; // non-mainnet: bypasses online-upgrade height gating
+        env.tx = create_tx_info(&tx);
+        let origin = self.state.clone_state();
+        let state = std::mem::replace(&mut self.state, origin);
+        let mut ctx = ContextInst::new(env, state, Box::new(MemLogs::new()), &tx);
+        let res = Self::prepay_gas(&mut ctx, tx.gas_max_byte().unwrap_or(0))
+            .map_err(Into::into)
+            .and_then(|_| f(&mut ctx));
+        let (state, logs) = ctx.release();
+        let ret = res.map_err(|e| e.to_string())?;
+        for idx in 0..logs.snapshot_len() {
+            if let Some(buf) = logs.load(self.height, idx) {
+                self.logs.push(VmLog::build(&buf)?);
+            }
+        }
+        self.state = state;
+        Ok(ret)
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use basis::interface::{DiskDB, MemDB};
+use sys::Rerr;
+
+/// In-memory `DiskDB` for tests. Clones share one store, so a test can keep
+/// a handle to what it gave away; `for_each` walks keys in order.
+#[derive(Default, Clone)]
+pub struct MemDisk {
+    kv: Arc<Mutex<BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl MemDisk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.kv.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kv.lock().unwrap().is_empty()
+    }
+}
+
+impl DiskDB for MemDisk {
+    fn read(&self, k: &[u8]) -> Option<Vec<u8>> {
+        self.kv.lock().unwrap().get(k).cloned()
+    }
+
+    fn save(&self, k: &[u8], v: &[u8]) {
+        self.kv.lock().unwrap().insert(k.to_vec(), v.to_vec());
+    }
+
+    fn remove(&self, k: &[u8]) {
+        self.kv.lock().unwrap().remove(k);
+    }
+
+    fn write(&self, batch: &dyn MemDB) {
+        let mut kv = self.kv.lock().unwrap();
+        batch.for_each(&mut |k, v| match v {
+            Some(v) => {
+                kv.insert(k.to_vec(), v.to_vec());
+            }
+            None => {
+                kv.remove(k);
+            }
+        });
+    }
+
+    fn for_each(&self, each: &mut dyn FnMut(&[u8], &[u8]) -> bool) -> Rerr {
+        // copied out, so the callback may use the store too
+        let rows: Vec<_> = self.kv.lock().unwrap().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        for (k, v) in rows {
+            if !each(&k, &v) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
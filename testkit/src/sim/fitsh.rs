@@ -0,0 +1,134 @@
+use field::Address;
+use sys::Ret;
+use vm::fitshc::{FitshTestCase, FitshTestTarget, compile, compile_tests};
+use vm::value::Value;
+use vm::{ContractAddress, ContractSto};
+
+use crate::sim::contract::ContractBench;
+use crate::sim::integration::vm_main_addr;
+
+#[derive(Debug, Clone)]
+pub struct FitshTestReport {
+    pub name: String,
+    pub passed: bool,
+    pub gas: i64,
+    pub message: String,
+}
+
+/// Compiled contract plus its deploy parameters, shared by every test case.
+struct FitshTestFixture {
+    sto: ContractSto,
+    nonce: u32,
+    argv: Vec<u8>,
+    deployer: Address,
+    target: FitshTestTarget,
+}
+
+/// Compile `contract_src` and the `*_test.fitsh` source `test_src`, then run every
+/// test case against a fresh in-memory state holding the freshly deployed contract.
+pub fn run_fitsh_tests(contract_src: &str, test_src: &str) -> Ret<Vec<FitshTestReport>> {
+    let (contract, deploy, _, name) = compile(contract_src)?;
+    let deploy = deploy.unwrap_or_default();
+    let nonce = deploy.nonce.map(|n| n.uint()).unwrap_or(1);
+    let deployer = vm_main_addr();
+    let caddr = ContractAddress::calculate(&deployer, &field::Uint4::from(nonce));
+    let fixture = FitshTestFixture {
+        sto: contract.into_sto(),
+        nonce,
+        argv: deploy.construct_argv.map(|a| a.to_vec()).unwrap_or_default(),
+        deployer,
+        target: FitshTestTarget {
+            name,
+            addr: caddr.into_addr(),
+        },
+    };
+    let cases = compile_tests(test_src, &fixture.target)?;
+    Ok(cases
+        .iter()
+        .map(|case| run_one(&fixture, case))
+        .collect())
+}
+
+fn run_one(fixture: &FitshTestFixture, case: &FitshTestCase) -> FitshTestReport {
+    let mut report = FitshTestReport {
+        name: case.name.clone(),
+        passed: false,
+        gas: 0,
+        message: String::new(),
+    };
+    match execute_case(fixture, case, &mut report.gas) {
+        Ok(()) => report.passed = true,
+        Err(e) => report.message = e,
+    }
+    report
+}
+
+fn execute_case(fixture: &FitshTestFixture, case: &FitshTestCase, gas: &mut i64) -> Ret<()> {
+    let mut bench = ContractBench::new();
+    if let Some(height) = case.height {
+        bench.set_height(height);
+    }
+    bench.set_caller(fixture.deployer);
+    bench
+        .deploy(fixture.nonce, fixture.sto.clone(), fixture.argv.clone())
+        .map_err(|e| format!("deploy failed: {}", e))?;
+    bench.clear_logs();
+    for (addr, amt) in &case.balances {
+        bench.set_balance(addr, amt);
+    }
+    let caller = case.caller.unwrap_or(fixture.deployer);
+    bench.set_caller(caller);
+
+    let expect = &case.expect;
+    let res = bench.main_call(fixture.target.tx_addrs(caller), case.codes.clone());
+    match (res, &expect.fail) {
+        (Ok((used, _)), None) => *gas = used,
+        (Ok(_), Some(_)) => return Err("expected failure but call succeeded".to_owned()),
+        (Err(e), None) => return Err(e),
+        (Err(e), Some(msg)) => {
+            if !e.contains(msg.as_str()) {
+                return Err(format!("expected failure containing {:?} but got: {}", msg, e));
+            }
+            return Ok(());
+        }
+    }
+
+    if let Some(n) = expect.logs {
+        let got = bench.logs().len();
+        if got != n {
+            return Err(format!("expected {} logs but got {}", n, got));
+        }
+    }
+    let addr = fixture.target.addr;
+    for (key, want) in &expect.storage {
+        let got = bench.storage(&addr, key)?.unwrap_or(Value::Nil);
+        if !value_matches(want, &got) {
+            return Err(format!(
+                "storage {} expected {} but got {}",
+                key.to_string(),
+                want.to_string(),
+                got.to_string()
+            ));
+        }
+    }
+    for (addr, want) in &case.expect.balances {
+        let got = bench.balance(addr);
+        if got != *want {
+            return Err(format!(
+                "balance of {} expected {} but got {}",
+                addr.to_readable(),
+                want,
+                got
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Unsigned integers compare by value so `5` matches a stored `5u64`.
+fn value_matches(want: &Value, got: &Value) -> bool {
+    if want.is_uint() && got.is_uint() {
+        return want.extract_u128().ok() == got.extract_u128().ok();
+    }
+    want == got
+}
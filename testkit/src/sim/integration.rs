@@ -51,9 +51,11 @@ thread_local! {
     static TEST_SETUP_SCOPE: RefCell<Option<protocol::setup::TestSetupScopeGuard>> = const { RefCell::new(None) };
 }
 
-fn set_scoped_setup_guard(guard: protocol::setup::TestSetupScopeGuard) {
+fn set_scoped_setup_guard(install: impl FnOnce() -> protocol::setup::TestSetupScopeGuard) {
     TEST_SETUP_SCOPE.with(|cell| {
-        *cell.borrow_mut() = Some(guard);
+        // drop the previous guard first: its restore must not clobber the new scope
+        drop(cell.borrow_mut().take());
+        *cell.borrow_mut() = Some(install());
     });
 }
 
@@ -89,15 +91,15 @@ pub fn ensure_standard_protocol_setup_for_tests(
     if include_vm_extensions {
         vm::setup::register_protocol_extensions(&mut scoped);
     }
-    set_scoped_setup_guard(protocol::setup::install_test_scope(scoped));
+    set_scoped_setup_guard(|| protocol::setup::install_test_scope(scoped));
 }
 
 pub fn enable_mint_setup() {
-    set_scoped_setup_guard(scoped_setup(None));
+    set_scoped_setup_guard(|| scoped_setup(None));
 }
 
 pub fn set_vm_assigner(assigner: Option<protocol::setup::FnVmAssignFunc>) {
-    set_scoped_setup_guard(scoped_setup(assigner));
+    set_scoped_setup_guard(|| scoped_setup(assigner));
 }
 
 pub fn disable_vm_setup() {
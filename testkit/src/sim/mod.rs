@@ -1,7 +1,11 @@
 pub mod context;
+pub mod disk;
+pub mod contract;
+pub mod fitsh;
 pub mod integration;
 pub mod logs;
 pub mod miner_api;
+pub mod network;
 pub mod state;
 pub mod tx;
 pub mod vm;
@@ -0,0 +1,327 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Once};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use app::fullnode::{FctDiskDb, FullnodeBuilder, NilScaner, NilServer};
+use basis::component::*;
+use basis::config::*;
+use basis::interface::*;
+use chain::ChainEngine;
+use field::*;
+use mint::HacashMinter;
+use node::core::HacashNode;
+use node::light::LightNode;
+use node::memtxpool::MemTxPool;
+use node::p2p::{LinkConf, MemNet};
+use protocol::action::HacToTrs;
+use protocol::transaction::*;
+use sys::*;
+
+/*
+    Several full nodes in one process, wired by an in-memory network.
+
+    Every node is a real `FullnodeRuntime` (chain engine, minter, tx pool and
+    p2p node) on a dev-mode chain: no PoW, funded dev accounts, and blocks
+    mined only when the test calls `mine`. Nodes reach each other over one
+    `MemNet`, so tests control latency, partitions and message drops; drops
+    come from a seeded generator. Each node gets its own dev account as miner,
+    so blocks mined on different sides of a partition differ and fork.
+    Light nodes join the same network and only talk to the full nodes they
+    are given.
+*/
+
+const SIM_PORT: u16 = 3337;
+const CONNECT_WAIT: Duration = Duration::from_secs(10);
+
+fn install_sim_stack() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let mut setup = protocol::setup::new_standard_protocol_setup(x16rs::block_hash);
+        mint::setup::register_protocol_extensions(&mut setup);
+        vm::setup::register_protocol_extensions(&mut setup);
+        protocol::setup::install_once(setup);
+    });
+}
+
+struct SimNode {
+    addr: SocketAddr,
+    ini: IniObj,
+    hnoder: Option<Arc<dyn HNoder>>,
+    exiter: Exiter,
+    handle: Option<JoinHandle<Rerr>>,
+}
+
+struct SimLight {
+    light: Arc<dyn LightNoder>,
+    exiter: Exiter,
+    handle: Option<JoinHandle<()>>,
+}
+
+pub struct NetworkSim {
+    net: Arc<MemNet>,
+    diskdb: FctDiskDb,
+    root: PathBuf,
+    nodes: Vec<SimNode>,
+    lights: Vec<SimLight>,
+}
+
+impl NetworkSim {
+    /// `seed` drives message drops; `diskdb` opens each node's databases.
+    pub fn new<F>(seed: u64, diskdb: F) -> Self
+    where
+        F: Fn(&PathBuf) -> Box<dyn DiskDB> + Send + Sync + 'static,
+    {
+        static SIM_ID: AtomicU64 = AtomicU64::new(0);
+        install_sim_stack();
+        let id = SIM_ID.fetch_add(1, Ordering::Relaxed);
+        let root = std::env::temp_dir().join(format!("hacash_sim_{}_{}", std::process::id(), id));
+        let _ = std::fs::remove_dir_all(&root);
+        Self {
+            net: MemNet::new(seed),
+            diskdb: Arc::new(diskdb),
+            root,
+            nodes: vec![],
+            lights: vec![],
+        }
+    }
+
+    pub fn net(&self) -> &MemNet {
+        &self.net
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Start a node that dials `peers`, and wait until they are connected.
+    pub fn add_node(&mut self, peers: &[usize]) -> Ret<usize> {
+        let idx = self.nodes.len();
+        if idx >= DEV_ACCOUNTS {
+            return errf!("the simulator runs at most {} nodes", DEV_ACCOUNTS)
+        }
+        let addr = SocketAddr::from(([127, 0, 0, idx as u8 + 1], SIM_PORT));
+        let boots: Vec<String> = peers.iter().map(|p| self.nodes[*p].addr.to_string()).collect();
+        let mut ini = IniObj::new();
+        let mut set = |sec: &str, key: &str, val: String| {
+            ini.entry(sec.to_owned()).or_default().insert(key.to_owned(), Some(val));
+        };
+        set("default", "dev", "true".to_owned());
+        set("default", "data_dir", self.root.join(format!("node{}", idx)).display().to_string());
+        set("node", "name", format!("sim{}", idx));
+        set("node", "listen", SIM_PORT.to_string());
+        set("node", "boots", boots.join(","));
+        set("node", "encrypt", "false".to_owned());
+        set("node", "use_stable_nodes", "false".to_owned());
+        set("miner", "reward", dev_accounts()[idx].readable().to_owned());
+        set("miner", "message", format!("sim{}", idx));
+        set("server", "enable", "false".to_owned());
+        self.nodes.push(SimNode { addr, ini, hnoder: None, exiter: Exiter::new(), handle: None });
+        self.start_node(idx)?;
+        let want = peers.len();
+        if !self.wait_until(CONNECT_WAIT, |s| s.peer_count(idx) >= want) {
+            return errf!("sim node {} connected {} of {} peers", idx, self.peer_count(idx), want)
+        }
+        Ok(idx)
+    }
+
+    fn start_node(&mut self, idx: usize) -> Rerr {
+        let node = &mut self.nodes[idx];
+        let endpoint = self.net.endpoint(node.addr);
+        let diskdb = self.diskdb.clone();
+        let mut builder = FullnodeBuilder::from_ini(node.ini.clone());
+        builder
+            .scaner(Box::new(NilScaner {}))
+            .diskdb(move |dir| diskdb(dir))
+            .txpool(|cnf| Ok(Box::new(MemTxPool::new(cnf.lowest_fee_purity, vec![2000, 100], vec![true, false]))))
+            .minter(|ini| Ok(Box::new(HacashMinter::create(ini))))
+            .engine(|dbfn, cnf, minter, scaner| {
+                Ok(Box::new(ChainEngine::open(dbfn, cnf, minter, scaner, app::DB_VERSION)))
+            })
+            .hnoder(move |ini, txpool, engine| {
+                Ok(Box::new(HacashNode::open_on_memnet(ini, txpool, engine, endpoint.clone())))
+            })
+            .server(|_, _| Ok(Box::new(NilServer {})));
+        node.exiter = builder.exiter.clone();
+        let runtime = builder.build()?;
+        node.hnoder = Some(runtime.hnoder());
+        node.handle = Some(thread::spawn(move || runtime.run()));
+        Ok(())
+    }
+
+    /// Shut a node down.
+    pub fn stop_node(&mut self, idx: usize) -> Rerr {
+        let node = &mut self.nodes[idx];
+        node.hnoder = None;
+        node.exiter.exit();
+        match node.handle.take() {
+            Some(h) => h.join().map_err(|_| "sim node thread panicked".to_owned())?,
+            None => Ok(()),
+        }
+    }
+
+    /// Start a stopped node again with the same peers but empty data, so it
+    /// has to sync from scratch. Its old databases cannot be reopened in this
+    /// process: the engine and minter keep each other alive.
+    pub fn rejoin_node(&mut self, idx: usize) -> Rerr {
+        let node = &mut self.nodes[idx];
+        if node.handle.is_some() {
+            return errf!("sim node {} is running", idx)
+        }
+        let dir = ini_must(ini_section(&node.ini, "default"), "data_dir", "");
+        let dir = format!("{}r", dir);
+        node.ini.entry("default".to_owned()).or_default().insert("data_dir".to_owned(), Some(dir));
+        self.start_node(idx)
+    }
+
+    /// Start a light node whose full peers are `peers`.
+    pub fn add_light(&mut self, peers: &[usize]) -> Ret<usize> {
+        let idx = self.lights.len();
+        let addr = SocketAddr::from(([127, 0, 1, idx as u8 + 1], SIM_PORT));
+        let boots: Vec<String> = peers.iter().map(|p| self.nodes[*p].addr.to_string()).collect();
+        let mut ini = IniObj::new();
+        let mut set = |sec: &str, key: &str, val: String| {
+            ini.entry(sec.to_owned()).or_default().insert(key.to_owned(), Some(val));
+        };
+        set("default", "dev", "true".to_owned());
+        set("default", "data_dir", self.root.join(format!("light{}", idx)).display().to_string());
+        set("node", "name", format!("light{}", idx));
+        set("node", "boots", boots.join(","));
+        let minter: Arc<dyn Minter> = Arc::new(HacashMinter::create(&ini));
+        let light: Arc<dyn LightNoder> = Arc::new(LightNode::open_on_memnet(&ini, minter, self.net.endpoint(addr))?);
+        let exiter = Exiter::new();
+        let worker = exiter.worker();
+        let runner = light.clone();
+        let handle = thread::spawn(move || runner.start(worker));
+        self.lights.push(SimLight { light, exiter, handle: Some(handle) });
+        let want = peers.len();
+        if !self.wait_until(CONNECT_WAIT, |s| s.light(idx).all_peer_prints().len() >= want) {
+            return errf!("sim light node {} connected {} of {} peers", idx, self.light(idx).all_peer_prints().len(), want)
+        }
+        Ok(idx)
+    }
+
+    pub fn light(&self, idx: usize) -> Arc<dyn LightNoder> {
+        self.lights[idx].light.clone()
+    }
+
+    pub fn node(&self, idx: usize) -> Arc<dyn HNoder> {
+        self.nodes[idx].hnoder.clone().expect("sim node is stopped")
+    }
+
+    pub fn addr(&self, idx: usize) -> SocketAddr {
+        self.nodes[idx].addr
+    }
+
+    pub fn peer_count(&self, idx: usize) -> usize {
+        self.nodes[idx].hnoder.as_ref().map_or(0, |n| n.all_peer_prints().len())
+    }
+
+    /// Mine `count` blocks on one node; returns its new height.
+    pub fn mine(&self, idx: usize, count: usize) -> Ret<u64> {
+        let hnoder = self.node(idx);
+        let engine = hnoder.engine();
+        let mut height = self.height(idx);
+        for _ in 0..count {
+            height = mint::api::dev_mine_block(&engine, hnoder.as_ref())?;
+        }
+        Ok(height)
+    }
+
+    pub fn height(&self, idx: usize) -> u64 {
+        self.node(idx).engine().latest_block().height().uint()
+    }
+
+    pub fn head(&self, idx: usize) -> Hash {
+        self.node(idx).engine().latest_block().hash()
+    }
+
+    pub fn block_hash(&self, idx: usize, height: u64) -> Option<Hash> {
+        self.node(idx).engine().store().block_hash(&BlockHeight::from(height))
+    }
+
+    pub fn submit_tx(&self, idx: usize, tx: &TxPkg) -> Rerr {
+        self.node(idx).submit_transaction(tx, false, false)
+    }
+
+    pub fn has_tx(&self, idx: usize, hash: &Hash) -> bool {
+        self.node(idx).txpool().find(hash).is_some()
+    }
+
+    /// Latency and drop rate of every link without its own `link`.
+    pub fn set_default_link(&self, latency: Duration, drop_permille: u16) {
+        self.net.set_default_link(LinkConf { latency, drop_permille });
+    }
+
+    pub fn link(&self, a: usize, b: usize, latency: Duration, drop_permille: u16) {
+        self.net.set_link(self.addr(a), self.addr(b), LinkConf { latency, drop_permille });
+    }
+
+    /// Split the nodes into groups that cannot reach each other; unlisted
+    /// nodes join the first group.
+    pub fn partition(&self, groups: &[&[usize]]) {
+        let groups: Vec<Vec<SocketAddr>> = groups.iter()
+            .map(|g| g.iter().map(|i| self.addr(*i)).collect())
+            .collect();
+        self.net.partition(&groups);
+    }
+
+    pub fn heal(&self) {
+        self.net.heal();
+    }
+
+    /// Poll `cond` until it holds or `timeout` passes.
+    pub fn wait_until(&self, timeout: Duration, cond: impl Fn(&Self) -> bool) -> bool {
+        let end = Instant::now() + timeout;
+        loop {
+            if cond(self) {
+                return true
+            }
+            if Instant::now() >= end {
+                return false
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    /// Wait until every running node has the same head.
+    pub fn wait_synced(&self, timeout: Duration) -> bool {
+        self.wait_until(timeout, |s| {
+            let heads: Vec<Hash> = (0..s.len()).filter(|i| s.nodes[*i].hnoder.is_some()).map(|i| s.head(i)).collect();
+            heads.windows(2).all(|w| w[0] == w[1])
+        })
+    }
+}
+
+impl Drop for NetworkSim {
+    fn drop(&mut self) {
+        for l in self.lights.iter_mut() {
+            l.light.exit();
+            l.exiter.exit();
+            if let Some(h) = l.handle.take() {
+                let _ = h.join();
+            }
+        }
+        for i in 0..self.nodes.len() {
+            let _ = self.stop_node(i);
+        }
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// A signed HAC transfer from a dev account, ready to submit.
+pub fn dev_transfer(from: &Account, to: Address, hac: Amount, fee: Amount) -> TxPkg {
+    let mut tx = TransactionType2::new_by(Address::from(*from.address()), fee, curtimes());
+    let mut act = HacToTrs::new();
+    act.to = AddrOrPtr::from_addr(to);
+    act.hacash = hac;
+    tx.push_action(Box::new(act)).unwrap();
+    tx.fill_sign(from).unwrap();
+    build_tx_package(tx.serialize()).unwrap()
+}
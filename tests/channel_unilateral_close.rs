@@ -0,0 +1,196 @@
+use basis::component::Env;
+use basis::interface::*;
+use field::*;
+use mint::action::*;
+use mint::oprate::MintState;
+use protocol::context::ContextInst;
+use protocol::state::CoreState;
+use protocol::transaction::*;
+use sys::{Account, XRet};
+use testkit::sim::context::make_ctx_with_state;
+use testkit::sim::integration::enable_mint_setup;
+use testkit::sim::state::ForkableMemState;
+
+const OPEN_HEIGHT: u64 = protocol::upgrade::ONLINE_OPEN_HEIGHT;
+// ChannelOpen locks unilateral close for 5000 blocks
+const LOCK: u64 = 5000;
+
+struct Parties {
+    left_acc: Account,
+    right_acc: Account,
+    left: Address,
+    right: Address,
+    tx: TransactionType2,
+}
+
+fn parties() -> Parties {
+    enable_mint_setup();
+    let left_acc = Account::create_by("channel-unilateral-left").unwrap();
+    let right_acc = Account::create_by("channel-unilateral-right").unwrap();
+    let left = Address::from(*left_acc.address());
+    let right = Address::from(*right_acc.address());
+    let mut tx = TransactionType2::new_by(left, Amount::mei(1), 1_730_000_000);
+    tx.fill_sign(&left_acc).unwrap();
+    tx.fill_sign(&right_acc).unwrap();
+    Parties { left_acc, right_acc, left, right, tx }
+}
+
+fn make_ctx<'a>(tx: &'a dyn TransactionRead) -> ContextInst<'a> {
+    let mut env = Env::default();
+    env.chain.fast_sync = true;
+    env.block.height = OPEN_HEIGHT;
+    env.tx = create_tx_info(tx);
+    make_ctx_with_state(env, Box::new(ForkableMemState::default()), tx)
+}
+
+fn cid() -> ChannelId {
+    ChannelId::from([5u8; 16])
+}
+
+fn open_channel(ctx: &mut ContextInst, p: &Parties) {
+    for addr in [&p.left, &p.right] {
+        let mut state = CoreState::wrap(ctx.state());
+        state.balance_set(addr, &Balance::hac(Amount::mei(100)));
+    }
+    let mut open = ChannelOpen::new();
+    open.channel_id = cid();
+    open.left_bill = AddrHac { address: p.left, amount: Amount::mei(10) };
+    open.right_bill = AddrHac { address: p.right, amount: Amount::mei(10) };
+    open.execute(ctx).unwrap();
+}
+
+fn bill(p: &Parties, number: u64, left_mei: u64, right_mei: u64) -> SignedReconciliationBill {
+    let mut sbill = SignedReconciliationBill::new();
+    sbill.bill.channel_id = cid();
+    sbill.bill.reuse_version = Uint4::from(1);
+    sbill.bill.bill_auto_number = Uint8::from(number);
+    sbill.bill.left_bill.amount = Amount::mei(left_mei);
+    sbill.bill.right_bill.amount = Amount::mei(right_mei);
+    sbill.sign_left(&p.left_acc);
+    sbill.sign_right(&p.right_acc);
+    sbill
+}
+
+fn launch(ctx: &mut ContextInst, sbill: SignedReconciliationBill, assert_left: bool) -> XRet<Vec<u8>> {
+    let mut act = ChannelCloseLaunch::new();
+    act.assert_left = Bool::new(assert_left);
+    act.bill = sbill;
+    act.execute(ctx).map(|(_, r)| r)
+}
+
+fn settle(ctx: &mut ContextInst) -> XRet<Vec<u8>> {
+    let mut act = ChannelCloseSettle::new();
+    act.channel_id = cid();
+    act.execute(ctx).map(|(_, r)| r)
+}
+
+fn hac_mei(ctx: &mut ContextInst, addr: &Address) -> u64 {
+    CoreState::wrap(ctx.state()).balance(addr).unwrap_or_default().hacash.to_mei_u64().unwrap()
+}
+
+fn channel_status(ctx: &mut ContextInst) -> Uint1 {
+    MintState::wrap(ctx.state()).channel(&cid()).unwrap().status
+}
+
+#[test]
+fn unilateral_close_settles_launched_bill_after_challenge_period() {
+    let p = parties();
+    let mut ctx = make_ctx(p.tx.as_read());
+    open_channel(&mut ctx, &p);
+
+    ctx.env.block.height = OPEN_HEIGHT + 10;
+    launch(&mut ctx, bill(&p, 3, 15, 5), true).unwrap();
+    assert_eq!(channel_status(&mut ctx), CHANNEL_STATUS_CHALLENGING);
+    // no second launch
+    assert!(launch(&mut ctx, bill(&p, 4, 15, 5), true).is_err());
+
+    ctx.env.block.height = OPEN_HEIGHT + 10 + LOCK;
+    let err = settle(&mut ctx).unwrap_err();
+    assert!(err.contains("ends at height"), "{err}");
+
+    ctx.env.block.height = OPEN_HEIGHT + 11 + LOCK;
+    settle(&mut ctx).unwrap();
+    assert_eq!(channel_status(&mut ctx), CHANNEL_STATUS_FINAL_ARBITRATION_CLOSED);
+    assert_eq!(hac_mei(&mut ctx, &p.left), 90 + 15);
+    assert_eq!(hac_mei(&mut ctx, &p.right), 90 + 5);
+    assert!(MintState::wrap(ctx.state()).channel(&cid()).unwrap().if_challenging.if_value().is_none());
+}
+
+#[test]
+fn counterparty_vanishes_before_any_payment() {
+    let p = parties();
+    let mut ctx = make_ctx(p.tx.as_read());
+    open_channel(&mut ctx, &p);
+
+    // bill 0 must be the opening split, with no signatures needed
+    let mut opening = SignedReconciliationBill::new();
+    opening.bill.channel_id = cid();
+    opening.bill.reuse_version = Uint4::from(1);
+    opening.bill.left_bill.amount = Amount::mei(12);
+    opening.bill.right_bill.amount = Amount::mei(8);
+    let err = launch(&mut ctx, opening.clone(), true).unwrap_err();
+    assert!(err.contains("opening lock-in"), "{err}");
+    opening.bill.left_bill.amount = Amount::mei(10);
+    opening.bill.right_bill.amount = Amount::mei(10);
+    launch(&mut ctx, opening, true).unwrap();
+    assert_eq!(channel_status(&mut ctx), CHANNEL_STATUS_CHALLENGING);
+
+    ctx.env.block.height = OPEN_HEIGHT + 1 + LOCK;
+    settle(&mut ctx).unwrap();
+    assert_eq!(channel_status(&mut ctx), CHANNEL_STATUS_FINAL_ARBITRATION_CLOSED);
+    assert_eq!(hac_mei(&mut ctx, &p.left), 100);
+    assert_eq!(hac_mei(&mut ctx, &p.right), 100);
+}
+
+#[test]
+fn signed_bill_beats_a_launched_opening_bill() {
+    let p = parties();
+    let mut ctx = make_ctx(p.tx.as_read());
+    open_channel(&mut ctx, &p);
+
+    let mut opening = bill(&p, 0, 10, 10);
+    opening.left_sign = Sign::default();
+    opening.right_sign = Sign::default();
+    launch(&mut ctx, opening, true).unwrap();
+
+    let mut challenge = ChannelCloseChallenge::new();
+    challenge.bill = bill(&p, 1, 4, 16);
+    challenge.execute(&mut ctx).unwrap();
+    assert_eq!(hac_mei(&mut ctx, &p.left), 90 + 4);
+    assert_eq!(hac_mei(&mut ctx, &p.right), 90 + 16);
+}
+
+#[test]
+fn newer_bill_wins_the_challenge() {
+    let p = parties();
+    let mut ctx = make_ctx(p.tx.as_read());
+    open_channel(&mut ctx, &p);
+
+    // left launches with a stale bill in its favor
+    launch(&mut ctx, bill(&p, 7, 18, 2), true).unwrap();
+
+    let mut challenge = ChannelCloseChallenge::new();
+    challenge.bill = bill(&p, 7, 8, 12);
+    let err = challenge.execute(&mut ctx).unwrap_err();
+    assert!(err.contains("not newer"), "{err}");
+
+    // a bill the left side never signed
+    let mut forged = bill(&p, 9, 8, 12);
+    forged.sign_left(&p.right_acc);
+    challenge.bill = forged;
+    let err = challenge.execute(&mut ctx).unwrap_err();
+    assert!(err.contains("verification failed"), "{err}");
+
+    // splits that do not match the lock-in
+    challenge.bill = bill(&p, 9, 8, 13);
+    let err = challenge.execute(&mut ctx).unwrap_err();
+    assert!(err.contains("must match lock-in"), "{err}");
+
+    ctx.env.block.height = OPEN_HEIGHT + LOCK;
+    challenge.bill = bill(&p, 9, 8, 12);
+    challenge.execute(&mut ctx).unwrap();
+    assert_eq!(channel_status(&mut ctx), CHANNEL_STATUS_FINAL_ARBITRATION_CLOSED);
+    assert_eq!(hac_mei(&mut ctx, &p.left), 90 + 8);
+    assert_eq!(hac_mei(&mut ctx, &p.right), 90 + 12);
+    assert!(settle(&mut ctx).is_err());
+}
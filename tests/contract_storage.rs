@@ -0,0 +1,122 @@
+mod contract_storage {
+    use field::{Address, Amount};
+    use basis::interface::TransactionRead;
+    use testkit::sim::contract::ContractBench;
+    use vm::fitshc::compile;
+    use vm::machine::{build_call_codes, parse_sandbox_params};
+
+    const CONTRACT: &str = r##"
+pragma fitsh 1.0.0
+
+contract Keeper {
+    function external put(n: u64) -> u64 {
+        storage_new("short", n, 100)
+        storage_new("a_rather_long_storage_key_longer_than_a_hash", n, 20)
+        return 0
+    }
+    function external keep(p: u64) -> u64 {
+        storage_rent("short", p)
+        return 0
+    }
+}
+"##;
+
+    const LONG_KEY: &[u8] = b"a_rather_long_storage_key_longer_than_a_hash";
+
+    #[test]
+    fn index_lists_entries_and_renew_tx_extends_rent() {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("hacash_storage_index_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        vm::configure_storage_key_index(Box::new(db::DiskKV::open(&dir)));
+
+        let (contract, _, _, _) = compile(CONTRACT).unwrap();
+        let mut bench = ContractBench::new();
+        let caller = bench.caller();
+        let caddr = bench.deploy(1, contract.into_sto(), vec![]).unwrap();
+        let addr = caddr.clone().into_addr();
+        bench.set_balance(&caller, &Amount::mei(10));
+
+        let args = parse_sandbox_params("5:u64").unwrap();
+        let codes = build_call_codes("put", &args).unwrap();
+        bench.main_call(vec![caller, addr], codes).unwrap();
+
+        // nothing is indexed before the state is confirmed
+        let index = vm::storage_key_index().unwrap();
+        assert_eq!(index.count(&addr), 0);
+        bench.index_storage().unwrap();
+
+        // both keys are indexed, including the hashed long one
+        assert_eq!(index.count(&addr), 2);
+        let (keys, next) = index.page(&addr, 0, 1);
+        assert_eq!((keys.len(), next), (1, Some(1)));
+        assert_eq!(index.page(&addr, 1, 10).1, None);
+        assert_eq!(index.count(&Address::default()), 0);
+        // indexing the same writes again changes nothing
+        bench.index_storage().unwrap();
+        assert_eq!(index.count(&addr), 2);
+
+        // the expiry list runs across contracts, soonest first
+        let until = bench.height() + 1_000_000;
+        let (soon, more) = index.expiring((bench.height(), 0), until, 10);
+        assert_eq!(more, None);
+        assert_eq!(soon.len(), 2);
+        assert!(soon[0].0 < soon[1].0);
+        assert_eq!(index.entry_key(&soon[0].1), Some((addr, LONG_KEY.to_vec())));
+        assert_eq!(index.entry_key(&soon[1].1), Some((addr, b"short".to_vec())));
+        let (first, more) = index.expiring((bench.height(), 0), until, 1);
+        assert_eq!((first.len(), more), (1, Some((soon[1].0, 0))));
+
+        let entries = |bench: &ContractBench| {
+            let mut list = bench.storage_entries(&addr).unwrap();
+            list.sort_by(|a, b| a.key.cmp(&b.key));
+            list
+        };
+        let before = entries(&bench);
+        assert_eq!(before.len(), 2);
+        let (long, short) = (&before[0], &before[1]);
+        assert_eq!(long.key, LONG_KEY);
+        assert_eq!(short.key, b"short");
+        assert!(long.active && short.active);
+        assert!(long.live_blocks < short.live_blocks);
+        let hei = bench.height();
+        assert_eq!(short.expire_height(hei), hei + short.live_blocks);
+        assert_eq!(
+            short.purge_height(hei),
+            hei + short.live_blocks + short.recover_blocks
+        );
+
+        // the renew helper calls the contract's keep function
+        let tx = vm::action::build_storage_renew_tx(
+            caller,
+            &caddr,
+            "keep",
+            &parse_sandbox_params("10:u64").unwrap(),
+            Amount::mei(1),
+            protocol::context::TX_GAS_BUDGET_CAP_BYTE,
+            1730000000,
+        )
+        .unwrap();
+        assert_eq!(tx.addrs(), vec![caller, addr]);
+        assert_eq!(tx.actions().len(), 1);
+        let codes = build_call_codes("keep", &parse_sandbox_params("10:u64").unwrap()).unwrap();
+        bench.main_call(tx.addrs(), codes).unwrap();
+        let after = entries(&bench);
+        assert!(after[1].live_blocks > short.live_blocks);
+        assert_eq!(after[0].live_blocks, long.live_blocks);
+        // the renewal moves the short key later in the expiry list
+        bench.index_storage().unwrap();
+        let (later, _) = index.expiring((bench.height(), 0), until, 10);
+        assert_eq!(later[0], soon[0]);
+        assert!(later[1].0 > soon[1].0);
+
+        // once past recovery the long entry is pruned from the index
+        bench.set_height(long.purge_height(hei) + 1);
+        bench.index_storage().unwrap();
+        assert_eq!(index.count(&addr), 1);
+        assert_eq!(entries(&bench)[0].key, b"short");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
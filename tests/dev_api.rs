@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use basis::interface::*;
+use serde_json::Value;
+use testkit::sim::network::NetworkSim;
+
+fn call(sim: &NetworkSim, path: &str, query: &[(&str, &str)]) -> Value {
+    let hnoder = sim.node(0);
+    let ctx = ApiExecCtx {
+        engine: hnoder.engine(),
+        hnoder,
+        launch_time: 0,
+        miner_worker_notice_count: Arc::new(Mutex::new(0)),
+    };
+    let req = ApiRequest {
+        query: query.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        headers: HashMap::new(),
+        body: vec![],
+    };
+    let route = mint::api::service().routes().into_iter().find(|r| r.path == path).unwrap();
+    let ApiHandler::Sync(handler) = route.handler else {
+        panic!("{} is not a sync route", path)
+    };
+    serde_json::from_slice(&handler(&ctx, req).body).unwrap()
+}
+
+// one test, as the dev routes move the process clock
+#[test]
+fn dev_mine_and_warp_routes() {
+    let mut sim = NetworkSim::new(1, |dir| Box::new(db::DiskKV::open(dir)));
+    sim.add_node(&[]).unwrap();
+
+    let res = call(&sim, "/operate/dev/mine", &[("count", "0")]);
+    assert_eq!(res["ret"], 1);
+    let res = call(&sim, "/operate/dev/mine", &[("count", "3")]);
+    assert_eq!(res["heights"], serde_json::json!([1, 2, 3]));
+
+    // bad requests leave the clock alone
+    let warp = sys::time_warp();
+    let res = call(&sim, "/operate/dev/warp", &[("time", "100"), ("height", "20000")]);
+    assert_eq!(res["ret"], 1);
+    let res = call(&sim, "/operate/dev/warp", &[("time", &u64::MAX.to_string())]);
+    assert_eq!(res["ret"], 1);
+    assert_eq!(sys::time_warp(), warp);
+
+    let before = sys::curtimes();
+    let res = call(&sim, "/operate/dev/warp", &[("time", "3600"), ("height", "5")]);
+    assert_eq!(res["height"], 5);
+    assert!(res["time"].as_u64().unwrap() >= before + 3600);
+    assert!(sim.node(0).engine().latest_block().timestamp().uint() >= before + 3600);
+
+    // the clock saturates instead of wrapping
+    sys::warp_time(u64::MAX);
+    assert_eq!(sys::curtimes(), u64::MAX);
+}
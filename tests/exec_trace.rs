@@ -0,0 +1,63 @@
+mod exec_trace {
+    use field::Amount;
+    use protocol::context::{trace_begin, trace_finish};
+    use testkit::sim::contract::ContractBench;
+    use vm::fitshc::{FitshTestTarget, compile, compile_tests};
+
+    const CONTRACT: &str = r##"
+pragma fitsh 1.0.0
+
+contract Counter {
+    function external bump(n: u64) -> u64 {
+        log("Bump", n)
+        return n + 1
+    }
+}
+"##;
+
+    const RUN: &str = r##"
+pragma fitsh 1.0.0
+
+test traced {
+    run {
+        Counter.bump(4)
+        transfer_hac_to(1EuGe2GU8tDKnHLNfBsgyffx66buK7PP6g, mei_to_hac(1))
+    }
+}
+"##;
+
+    #[test]
+    fn trace_records_vm_calls_and_nested_actions() {
+        let (contract, _, _, name) = compile(CONTRACT).unwrap();
+        let mut bench = ContractBench::new();
+        let caller = bench.caller();
+        let caddr = bench.deploy(1, contract.into_sto(), vec![]).unwrap();
+        let target = FitshTestTarget {
+            name,
+            addr: caddr.clone().into_addr(),
+        };
+        let cases = compile_tests(RUN, &target).unwrap();
+        bench.set_balance(&caller, &Amount::mei(10));
+
+        trace_begin();
+        assert!(protocol::context::trace_enabled());
+        let res = bench.main_call(target.tx_addrs(caller), cases[0].codes.clone());
+        let steps = trace_finish();
+        res.unwrap();
+
+        let ops: Vec<(usize, &str)> = steps.iter().map(|s| (s.depth, s.op)).collect();
+        assert_eq!(ops.first(), Some(&(0, "vm.entry")));
+        let call = steps.iter().find(|s| s.op == "vm.call").unwrap();
+        assert_eq!(call.depth, 1);
+        assert!(call.detail.contains(&caddr.to_readable()), "{}", call.detail);
+        assert!(ops.contains(&(1, "vm.return")));
+        let act = steps.iter().position(|s| s.op == "action").unwrap();
+        assert_eq!(steps[act].depth, 1);
+        assert_eq!(steps[act + 1].op, "action.ok");
+        assert_eq!(steps.last().map(|s| (s.depth, s.op)), Some((0, "vm.return")));
+
+        // tracing is off again once finished
+        assert!(!protocol::context::trace_enabled());
+        assert!(trace_finish().is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+mod fitshc_lint {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    #[test]
+    fn lint_deny_fails_on_warnings() {
+        let src = r##"
+pragma fitsh 1.0.0
+
+contract Open {
+    function external set(v: u64) {
+        storage_new("k", v, 2)
+        end
+    }
+}
+"##;
+        let mut dir = std::env::temp_dir();
+        dir.push("hacash_fitshc_lint_tests");
+        let _ = fs::create_dir_all(&dir);
+        let file = dir.join("open.fitsh");
+        fs::write(&file, src).unwrap();
+
+        let exe = PathBuf::from(env!("CARGO_BIN_EXE_fitshc"));
+        let run = |deny: bool| {
+            let mut cmd = Command::new(&exe);
+            cmd.arg("lint").arg(&file);
+            if deny {
+                cmd.arg("--deny");
+            }
+            cmd.output().unwrap()
+        };
+
+        let out = run(false);
+        assert!(out.status.success());
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(
+            stdout.contains("[unguarded-storage-write] set"),
+            "{}",
+            stdout
+        );
+        assert!(stdout.contains("[short-rent-period] set"), "{}", stdout);
+
+        assert!(!run(true).status.success());
+    }
+}
@@ -0,0 +1,85 @@
+mod fitshc_test_runner {
+    use testkit::sim::fitsh::run_fitsh_tests;
+
+    const CONTRACT: &str = r##"
+pragma fitsh 1.0.0
+
+contract Counter {
+    function external bump(n: u64) -> u32 {
+        assert n > 0
+        var key = "count"
+        var val = storage_load(key)
+        if val is nil {
+            storage_new(key, n, 100)
+        } else {
+            storage_edit(key, n)
+        }
+        log("Bump", n)
+        return 0
+    }
+}
+"##;
+
+    const TESTS: &str = r##"
+pragma fitsh 1.0.0
+
+const STEP = 7
+
+test bump_stores_and_logs {
+    height: 10
+    run {
+        Counter.bump(STEP)
+    }
+    expect {
+        logs: 1
+        storage: ["count": 7]
+    }
+}
+
+test bump_zero_reverts {
+    run { Counter.bump(0) }
+    expect { fail: true }
+}
+
+test pays_from_caller_balance {
+    caller: 1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9
+    balance: [1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9: amount("10:248")]
+    run {
+        transfer_hac_to(1EuGe2GU8tDKnHLNfBsgyffx66buK7PP6g, mei_to_hac(3))
+    }
+    expect {
+        balance: [
+            1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9: amount("7:248")
+            1EuGe2GU8tDKnHLNfBsgyffx66buK7PP6g: amount("3:248")
+        ]
+    }
+}
+
+test wrong_expectation_fails {
+    run { Counter.bump(1) }
+    expect { storage: ["count": 2] }
+}
+"##;
+
+    #[test]
+    fn runs_fitsh_test_file_against_deployed_contract() {
+        let reports = run_fitsh_tests(CONTRACT, TESTS).unwrap();
+        let names: Vec<_> = reports.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "bump_stores_and_logs",
+                "bump_zero_reverts",
+                "pays_from_caller_balance",
+                "wrong_expectation_fails"
+            ]
+        );
+        for r in &reports[..3] {
+            assert!(r.passed, "{} failed: {}", r.name, r.message);
+        }
+        assert!(reports[0].gas > 0);
+        let bad = &reports[3];
+        assert!(!bad.passed);
+        assert!(bad.message.contains("storage"), "{}", bad.message);
+    }
+}
@@ -0,0 +1,158 @@
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use basis::config::dev_accounts;
+use field::*;
+use protocol::state::CoreStateRead;
+use testkit::sim::network::{NetworkSim, dev_transfer};
+
+const WAIT: Duration = Duration::from_secs(30);
+
+// the nodes share the process clock, which dev mining moves forward
+fn sim_guard() -> MutexGuard<'static, ()> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| Mutex::new(()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn new_sim(seed: u64) -> NetworkSim {
+    NetworkSim::new(seed, |dir| Box::new(db::DiskKV::open(dir)))
+}
+
+#[test]
+fn tx_propagates_and_lands_in_a_block() {
+    let _g = sim_guard();
+    let mut sim = new_sim(1);
+    sim.add_node(&[]).unwrap();
+    sim.add_node(&[0]).unwrap();
+    sim.add_node(&[1]).unwrap();
+    let accs = dev_accounts();
+    let to = Address::from(*accs[5].address());
+    let tx = dev_transfer(&accs[1], to, Amount::coin(10, 248), Amount::coin(1, 244));
+    let txhx = tx.hash();
+    sim.submit_tx(0, &tx).unwrap();
+    // two hops by inventory
+    assert!(sim.wait_until(WAIT, |s| s.has_tx(2, &txhx)));
+    sim.mine(2, 1).unwrap();
+    assert!(sim.wait_synced(WAIT));
+    assert!(sim.wait_until(WAIT, |s| (0..3).all(|i| !s.has_tx(i, &txhx))));
+}
+
+#[test]
+fn blocks_propagate_over_slow_lossy_links() {
+    let _g = sim_guard();
+    let mut sim = new_sim(2);
+    sim.set_default_link(Duration::from_millis(30), 200);
+    sim.add_node(&[]).unwrap();
+    sim.add_node(&[0]).unwrap();
+    sim.add_node(&[0, 1]).unwrap();
+    for _ in 0..6 {
+        sim.mine(0, 1).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    // a lost block shows up as a gap at the next one, which triggers a sync
+    sim.set_default_link(Duration::ZERO, 0);
+    sim.mine(0, 1).unwrap();
+    assert!(sim.wait_synced(WAIT));
+    assert_eq!(sim.height(2), 7);
+}
+
+#[test]
+fn partition_forks_then_reorgs_to_longer_side() {
+    let _g = sim_guard();
+    let mut sim = new_sim(3);
+    sim.add_node(&[]).unwrap();
+    sim.add_node(&[0]).unwrap();
+    sim.add_node(&[1]).unwrap();
+    sim.mine(0, 2).unwrap();
+    assert!(sim.wait_synced(WAIT));
+
+    // reorg depth 3, within the 4 unstable blocks
+    sim.partition(&[&[0], &[1, 2]]);
+    sim.mine(0, 3).unwrap();
+    sim.mine(1, 4).unwrap();
+    assert!(sim.wait_until(WAIT, |s| s.height(2) == 6));
+    assert_ne!(sim.block_hash(0, 3), sim.block_hash(1, 3));
+
+    sim.heal();
+    sim.mine(1, 1).unwrap();
+    assert!(sim.wait_synced(WAIT));
+    assert_eq!(sim.height(0), 7);
+    assert_eq!(sim.block_hash(0, 3), sim.block_hash(2, 3));
+}
+
+#[test]
+fn rejoined_node_syncs_from_scratch() {
+    let _g = sim_guard();
+    let mut sim = new_sim(4);
+    sim.add_node(&[]).unwrap();
+    sim.add_node(&[0]).unwrap();
+    sim.mine(0, 1).unwrap();
+    assert!(sim.wait_synced(WAIT));
+    sim.stop_node(1).unwrap();
+    sim.mine(0, 12).unwrap();
+    sim.rejoin_node(1).unwrap();
+    assert!(sim.wait_until(WAIT, |s| s.peer_count(1) == 1));
+    assert!(sim.wait_synced(WAIT));
+    assert_eq!(sim.height(1), 13);
+}
+
+#[test]
+fn light_node_follows_headers_and_proves_queries() {
+    let _g = sim_guard();
+    let mut sim = new_sim(5);
+    sim.add_node(&[]).unwrap();
+    sim.add_node(&[0]).unwrap();
+    sim.mine(0, 3).unwrap();
+    assert!(sim.wait_synced(WAIT));
+    let l = sim.add_light(&[0, 1]).unwrap();
+    assert!(sim.wait_until(WAIT, |s| s.light(l).latest() == (3, s.head(0))));
+
+    let accs = dev_accounts();
+    let to = Address::from(*accs[7].address());
+    let tx = dev_transfer(&accs[2], to, Amount::coin(10, 248), Amount::coin(1, 244));
+    sim.submit_tx(0, &tx).unwrap();
+    sim.mine(0, 1).unwrap();
+    assert!(sim.wait_synced(WAIT));
+    assert!(sim.wait_until(WAIT, |s| s.light(l).latest() == (4, s.head(0))));
+
+    let ltx = sim.light(l).transaction(&tx.hash()).unwrap();
+    assert_eq!((ltx.height, ltx.block_hash), (4, sim.head(0)));
+    assert!(sim.light(l).transaction(&Hash::from([1; 32])).is_err());
+
+    let bls = sim.light(l).balances(&[to]).unwrap();
+    let state = sim.node(1).engine().state();
+    let full = CoreStateRead::wrap(state.as_ref().as_ref()).balance(&to).unwrap_or_default();
+    assert_eq!((bls.height, bls.hash), (4, sim.head(0)));
+    assert_eq!(bls.list, vec![full]);
+}
+
+#[test]
+fn historical_state_below_root_reads_undo_logs() {
+    let _g = sim_guard();
+    let mut sim = new_sim(6);
+    sim.add_node(&[]).unwrap();
+    let accs = dev_accounts();
+    let miner = Address::from(*accs[0].address());
+    let to = Address::from(*accs[8].address());
+    sim.mine(0, 1).unwrap();
+    let tx = dev_transfer(&accs[3], to, Amount::coin(10, 248), Amount::coin(1, 244));
+    sim.submit_tx(0, &tx).unwrap();
+    sim.mine(0, 3).unwrap();
+    let engine = sim.node(0).engine();
+    let balances = |height: u64| {
+        let state = engine.historical_state(height).unwrap();
+        let state = CoreStateRead::wrap(state.as_ref());
+        (state.balance(&miner), state.balance(&to))
+    };
+    // all still inside the fork tree
+    let before: Vec<_> = (0..=4).map(balances).collect();
+    assert_eq!(before[1].1, before[0].1);
+    assert_ne!(before[2].1, before[1].1);
+    assert_ne!(before[4].0, before[3].0);
+
+    sim.mine(0, 10).unwrap();
+    let after: Vec<_> = (0..=4).map(balances).collect();
+    assert_eq!(after, before);
+}
@@ -28,3 +28,23 @@ impl ContractMainCall {
         Ok(s)
     }
 }
+
+/// Unsigned type-3 transaction in which `main` calls `function(args)` on `contract`.
+/// Persistent storage rent can only be paid from the owning contract's code, so
+/// renewing storage means calling that contract's renew function this way.
+pub fn build_storage_renew_tx(
+    main: Address,
+    contract: &ContractAddress,
+    function: &str,
+    args: &[crate::value::Value],
+    fee: Amount,
+    gas_max: u8,
+    timestamp: u64,
+) -> Ret<protocol::transaction::TransactionType3> {
+    let codes = crate::machine::build_call_codes(function, args)?;
+    let mut tx = protocol::transaction::TransactionType3::new_by(main, fee, timestamp);
+    tx.addrlist = AddrOrList::from_list(vec![main, contract.clone().into_addr()])?;
+    tx.gas_max = Uint1::from(gas_max);
+    tx.push_action(Box::new(ContractMainCall::from_bytecode(codes)?))?;
+    Ok(tx)
+}
@@ -0,0 +1,163 @@
+fn storage_entry_json(entry: &StorageEntry, curhei: u64) -> serde_json::Value {
+    json!({
+        "key": hex::encode(&entry.key),
+        "size": entry.size,
+        "active": entry.active,
+        "live_blocks": entry.live_blocks,
+        "recover_blocks": entry.recover_blocks,
+        "expire_height": entry.expire_height(curhei),
+        "purge_height": entry.purge_height(curhei),
+    })
+}
+
+/// Most index keys read by one storage request.
+const STORAGE_PAGE_MAX: usize = 200;
+
+/// One page of a contract's indexed storage, and the cursor of the next.
+fn contract_storage_page(
+    ctx: &ApiExecCtx,
+    req: &ApiRequest,
+    height: u64,
+) -> Result<(Address, Vec<StorageEntry>, Option<u64>), ApiResponse> {
+    let limit = req.query_usize("limit", 100).clamp(1, STORAGE_PAGE_MAX);
+    let cursor = req.query_u64("cursor", 0);
+    let Ok(addr) = req_addr(req.query("contract").unwrap_or("")) else {
+        return Err(api_error("contract address format invalid"));
+    };
+    if ContractAddress::from_addr(addr).is_err() {
+        return Err(api_error("contract address version error"));
+    }
+    let gst = GasExtra::new(height);
+    let cap = SpaceCap::new(height);
+    let staptr = ctx.engine.state();
+    let state = VMStateRead::wrap(staptr.as_ref().as_ref());
+    match state.storage_entries(&gst, &cap, height, &addr, cursor, limit) {
+        Ok((list, next)) => Ok((addr, list, next)),
+        Err(e) => Err(api_error(&e)),
+    }
+}
+
+fn contract_storage_list(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let height = ctx.engine.latest_block().height().uint();
+    let (addr, entries, next) = match contract_storage_page(ctx, &req, height) {
+        Ok(v) => v,
+        Err(res) => return res,
+    };
+    let list: Vec<_> = entries.iter().map(|e| storage_entry_json(e, height)).collect();
+    let total = storage_key_index().map_or(0, |idx| idx.count(&addr));
+    api_data_raw(format!(
+        r#""height":{},"total":{},"next":{},"list":{}"#,
+        height,
+        total,
+        json!(next),
+        serde_json::Value::from(list)
+    ))
+}
+
+/// Farthest ahead, in blocks, an expiry listing looks.
+const STORAGE_EXPIRE_BLOCKS_MAX: u64 = 1_000_000;
+
+/// Storage entries whose live rent runs out within `blocks`, soonest first,
+/// across all contracts or of `contract` only. A page reads up to `limit`
+/// index keys; follow `next` for the rest.
+fn contract_storage_expiring(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let height = ctx.engine.latest_block().height().uint();
+    let blocks = req.query_u64("blocks", 1000).min(STORAGE_EXPIRE_BLOCKS_MAX);
+    let limit = req.query_usize("limit", 100).clamp(1, STORAGE_PAGE_MAX);
+    let only = match req.query("contract").unwrap_or("") {
+        "" => None,
+        c => match req_addr(c) {
+            Ok(addr) if ContractAddress::from_addr(addr).is_ok() => Some(addr),
+            _ => return api_error("contract address format invalid"),
+        },
+    };
+    let from = match req.query("cursor").unwrap_or("") {
+        "" => (height, 0),
+        c => match c.split_once(':').map(|(h, i)| (h.parse::<u64>(), i.parse::<u64>())) {
+            Some((Ok(h), Ok(i))) => (h, i),
+            _ => return api_error("cursor must be like 120533:4"),
+        },
+    };
+    let index = match storage_key_index() {
+        Ok(index) => index,
+        Err(e) => return api_error(&e),
+    };
+    let gst = GasExtra::new(height);
+    let cap = SpaceCap::new(height);
+    let staptr = ctx.engine.state();
+    let state = VMStateRead::wrap(staptr.as_ref().as_ref());
+    let (keys, next) = index.expiring(from, height.saturating_add(blocks), limit);
+    let mut list = vec![];
+    for (_, sk) in keys {
+        let Some((cadr, key)) = index.entry_key(&sk) else {
+            continue;
+        };
+        if only.is_some_and(|a| a != cadr) {
+            continue;
+        }
+        if let Ok(Some(entry)) = state.storage_entry(&gst, &cap, height, &cadr, key)
+            && entry.active
+        {
+            let mut item = storage_entry_json(&entry, height);
+            item["contract"] = json!(cadr.to_readable());
+            list.push(item);
+        }
+    }
+    api_data_raw(format!(
+        r#""height":{},"blocks":{},"next":{},"list":{}"#,
+        height,
+        blocks,
+        json!(next.map(|(h, i)| format!("{}:{}", h, i))),
+        serde_json::Value::from(list)
+    ))
+}
+
+/// Build an unsigned renewal transaction calling the contract's rent function.
+fn contract_storage_renew_build(_ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let Ok(body) = serde_json::from_slice::<serde_json::Value>(&req.body) else {
+        return api_error("request body must be json");
+    };
+    let field = |k: &str| body.get(k).and_then(|v| v.as_str()).unwrap_or("");
+    let Ok(main) = req_addr(field("main_address")) else {
+        return api_error("main_address format invalid");
+    };
+    let Ok(addr) = req_addr(field("contract")) else {
+        return api_error("contract address format invalid");
+    };
+    let Ok(caddr) = ContractAddress::from_addr(addr) else {
+        return api_error("contract address version error");
+    };
+    let function = field("function").trim();
+    if function.is_empty() {
+        return api_error("function cannot be empty");
+    }
+    let args = match machine::parse_sandbox_params(field("params")) {
+        Ok(v) => v,
+        Err(e) => return api_error(&e),
+    };
+    let Ok(fee) = Amount::from(field("fee")) else {
+        return api_error("fee format invalid");
+    };
+    let gas_max = body
+        .get("gas_max")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(TX_GAS_BUDGET_CAP_BYTE as u64)
+        .min(u8::MAX as u64) as u8;
+    let timestamp = body
+        .get("timestamp")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_else(curtimes);
+    let tx = match crate::action::build_storage_renew_tx(
+        main, &caddr, function, &args, fee, gas_max, timestamp,
+    ) {
+        Ok(tx) => tx,
+        Err(e) => return api_error(&e),
+    };
+    api_data_raw(format!(
+        r#""hash":"{}","hash_with_fee":"{}","body":"{}","timestamp":{}"#,
+        tx.hash().to_hex(),
+        tx.hash_with_fee().to_hex(),
+        hex::encode(tx.serialize()),
+        timestamp
+    ))
+}
@@ -0,0 +1,221 @@
+static CONTRACT_SOURCE_DB: OnceLock<Box<dyn DiskDB>> = OnceLock::new();
+
+/// Install the local side DB holding verified contract sources.
+/// Without it the verify and source query APIs report the service as disabled.
+pub fn configure_contract_source_db(db: Box<dyn DiskDB>) {
+    let _ = CONTRACT_SOURCE_DB.set(db);
+}
+
+fn contract_source_db() -> Option<&'static dyn DiskDB> {
+    CONTRACT_SOURCE_DB.get().map(|d| d.as_ref())
+}
+
+/// Largest source `/submit/contract/verify` compiles.
+const CONTRACT_SOURCE_MAX: usize = 256 * 1024;
+/// Verifications compiled at the same time; more are refused, not queued.
+const CONTRACT_VERIFY_RUNNING_MAX: usize = 2;
+
+static CONTRACT_VERIFY_RUNNING: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// Compiler versions accepted by `/submit/contract/verify`: every version this
+/// fitshc compiles, i.e. the current major with an equal or older minor.
+fn fitshc_supported_versions() -> String {
+    let cur = crate::fitshc::state::FITSH_CURRENT_VERSION;
+    (0..=cur.minor).map(|m| format!("{}.{}.x", cur.major, m)).collect::<Vec<_>>().join(", ")
+}
+
+fn fitshc_version_supported(compiler: &str) -> bool {
+    let cur = crate::fitshc::state::FITSH_CURRENT_VERSION;
+    let nums: Vec<_> = compiler.trim_start_matches('v').split('.').map(|n| n.parse::<u16>()).collect();
+    match nums.as_slice() {
+        [Ok(major), Ok(minor), Ok(_)] => *major == cur.major && *minor <= cur.minor,
+        _ => false,
+    }
+}
+
+/// The version named by the source's `pragma fitsh`, which the compiler
+/// field defaults to.
+fn fitsh_pragma_version(source: &str) -> Option<&str> {
+    let words: Vec<_> = source.split_whitespace().take(3).collect();
+    match words.as_slice() {
+        ["pragma", "fitsh", ver] => Some(ver),
+        _ => None,
+    }
+}
+
+/// Compile `source` with fitshc `compiler` and check it reproduces `onchain` byte for byte.
+/// The revision is taken from the chain since edits bump it without changing the code.
+/// Returns the record to store: name, compiler, source, ABI and source maps.
+fn verify_contract_source(onchain: &ContractSto, source: &str, compiler: &str) -> Ret<serde_json::Value> {
+    if !fitshc_version_supported(compiler) {
+        return errf!(
+            "unsupported fitshc version '{}', supported versions: {}",
+            compiler,
+            fitshc_supported_versions()
+        );
+    }
+    let (contract, _, smaps, name) = crate::fitshc::compile(source)?;
+    let mut sto = contract.into_sto();
+    sto.metas.revision = onchain.metas.revision;
+    if sto.serialize() != onchain.serialize() {
+        return errf!("compiled bytecode does not match the on-chain contract");
+    }
+    let edition = onchain.calc_edition();
+
+    let mut abi = Vec::with_capacity(sto.userfuncs.length());
+    for func in sto.userfuncs.as_list() {
+        let sign = *func.sign;
+        let Some((fname, smap)) = smaps.iter().find(|(n, _)| calc_func_sign(n) == sign) else {
+            return errf!("function {} missing from source maps", hex::encode(sign));
+        };
+        let params = func.pmdf.param_types()?;
+        let names = smap.param_names().cloned().unwrap_or_default();
+        let params: Vec<_> = params
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| json!({"name": names.get(i), "type": ty.name()}))
+            .collect();
+        let ret = func.pmdf.output_type()?.map(|t| t.name());
+        abi.push(json!({
+            "name": fname,
+            "sign": hex::encode(sign),
+            "external": func.fncnf[0] & FnConf::External as u8 != 0,
+            "params": params,
+            "return": ret,
+        }));
+    }
+    let mut maps = Vec::with_capacity(smaps.len());
+    for (n, smap) in &smaps {
+        let map: serde_json::Value = serde_json::from_str(&smap.to_json()?)
+            .map_err(|e| e.to_string())?;
+        maps.push(json!({"name": n, "map": map}));
+    }
+    Ok(json!({
+        "name": name,
+        "compiler": compiler,
+        "revision": edition.revision.uint(),
+        "edition": edition.hash.to_hex(),
+        "source": source,
+        "abi": abi,
+        "source_maps": maps,
+    }))
+}
+
+fn contract_verify(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let Some(db) = contract_source_db() else {
+        return api_error("contract source service not enabled");
+    };
+    let Ok(body) = serde_json::from_slice::<serde_json::Value>(&req.body) else {
+        return api_error("request body must be json");
+    };
+    let field = |k: &str| body.get(k).and_then(|v| v.as_str()).unwrap_or("");
+    let Ok(addr) = req_addr(field("address")) else {
+        return api_error("contract address format invalid");
+    };
+    let Ok(caddr) = ContractAddress::from_addr(addr) else {
+        return api_error("contract address version error");
+    };
+    let source = field("source");
+    if source.is_empty() {
+        return api_error("source cannot be empty");
+    }
+    if source.len() > CONTRACT_SOURCE_MAX {
+        return api_error(&format!("source larger than {} bytes", CONTRACT_SOURCE_MAX));
+    }
+    let compiler = match field("compiler") {
+        "" => fitsh_pragma_version(source).unwrap_or(""),
+        c => c,
+    };
+    let height = ctx.engine.latest_block().height().uint();
+    let staptr = ctx.engine.state();
+    let Some(onchain) = VMStateRead::wrap(staptr.as_ref().as_ref()).contract(&caddr) else {
+        return api_error("contract not found");
+    };
+    struct RunningGuard;
+    impl Drop for RunningGuard {
+        fn drop(&mut self) {
+            CONTRACT_VERIFY_RUNNING.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+    if CONTRACT_VERIFY_RUNNING.fetch_add(1, std::sync::atomic::Ordering::SeqCst) >= CONTRACT_VERIFY_RUNNING_MAX {
+        CONTRACT_VERIFY_RUNNING.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+        return api_error("contract verify busy, retry later");
+    }
+    let _running = RunningGuard;
+    let mut record = match verify_contract_source(&onchain, source, compiler) {
+        Ok(r) => r,
+        Err(e) => return api_error(&e),
+    };
+    record["address"] = json!(addr.to_readable());
+    record["height"] = json!(height);
+    db.save(&caddr.serialize(), record.to_string().as_bytes());
+    api_data_raw(format!(
+        r#""verified":true,"name":{},"edition":{}"#,
+        record["name"], record["edition"]
+    ))
+}
+
+fn contract_source(ctx: &ApiExecCtx, req: ApiRequest) -> ApiResponse {
+    let Some(db) = contract_source_db() else {
+        return api_error("contract source service not enabled");
+    };
+    let Ok(addr) = req_addr(req.query("address").unwrap_or("")) else {
+        return api_error("contract address format invalid");
+    };
+    let Ok(caddr) = ContractAddress::from_addr(addr) else {
+        return api_error("contract address version error");
+    };
+    let Some(raw) = db.read(&caddr.serialize()) else {
+        return api_error("contract source not verified");
+    };
+    let Ok(record) = serde_json::from_slice::<serde_json::Value>(&raw) else {
+        return api_error("contract source record broken");
+    };
+    // edits after verification leave the stored source stale
+    let staptr = ctx.engine.state();
+    let current = VMStateRead::wrap(staptr.as_ref().as_ref())
+        .contract_edition(&caddr)
+        .map(|e| e.hash.to_hex());
+    let latest = current.as_deref() == record["edition"].as_str();
+    api_data_raw(format!(r#""latest":{},"contract":{}"#, latest, record))
+}
+
+#[cfg(test)]
+mod contract_verify_tests {
+    use super::*;
+
+    const SRC: &str = r##"
+pragma fitsh 1.0.0
+
+contract Echo {
+    function external echo(n: u64) -> u64 {
+        return n
+    }
+}
+"##;
+
+    #[test]
+    fn verify_matches_onchain_and_builds_abi() {
+        let (contract, ..) = crate::fitshc::compile(SRC).unwrap();
+        let mut onchain = contract.into_sto();
+        onchain.metas.revision = Uint2::from(3);
+        let rec = verify_contract_source(&onchain, SRC, "1.0.0").unwrap();
+        assert_eq!(rec["name"], "Echo");
+        assert_eq!(rec["revision"], 3);
+        assert_eq!(rec["abi"][0]["name"], "echo");
+        assert_eq!(rec["abi"][0]["external"], true);
+        assert_eq!(rec["abi"][0]["params"][0]["name"], "n");
+        assert_eq!(rec["abi"][0]["params"][0]["type"], "u64");
+        assert_eq!(rec["abi"][0]["return"], "u64");
+
+        let other = SRC.replace("return n", "return n + 1");
+        let err = verify_contract_source(&onchain, &other, "1.0.0").unwrap_err();
+        assert!(err.contains("does not match"), "{}", err);
+        let err = verify_contract_source(&onchain, SRC, "0.9.0").unwrap_err();
+        assert_eq!(err, "unsupported fitshc version '0.9.0', supported versions: 1.0.x");
+        assert!(verify_contract_source(&onchain, SRC, "1.0.3").is_ok());
+        assert!(verify_contract_source(&onchain, SRC, "v1.0.0").is_ok());
+        assert_eq!(fitsh_pragma_version(SRC), Some("1.0.0"));
+        assert_eq!(fitsh_pragma_version("contract A {}"), None);
+    }
+}
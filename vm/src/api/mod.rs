@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use basis::component::*;
 use basis::interface::*;
@@ -10,6 +10,8 @@ use serde_json::json;
 use sys::*;
 
 use crate::ContractAddress;
+use crate::ContractSto;
+use crate::{StorageEntry, storage_key_index};
 use crate::VMStateRead;
 use crate::VmLog;
 use crate::machine;
@@ -21,6 +23,8 @@ include!("common.rs");
 include!("routes.rs");
 
 include!("contract_sandbox_call.rs");
+include!("contract_storage.rs");
+include!("contract_verify.rs");
 include!("debug.rs");
 include!("vm_logs_read.rs");
 include!("vm_logs_del.rs");
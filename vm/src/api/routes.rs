@@ -1,9 +1,52 @@
 
 fn routes() -> Vec<ApiRoute> {
+    use ApiType::*;
     vec![
-        ApiRoute::get("/query/contract/sandboxcall", contract_sandbox_call),
-        ApiRoute::debug_get("contract/storage", debug_contract_storage),
-        ApiRoute::get("/query/contract/logs", vm_logs_read),
-        ApiRoute::get("/operate/contract/logs/delete", vm_logs_del),
+        ApiRoute::get("/query/contract/sandboxcall", contract_sandbox_call)
+            .summary("Call a contract function on the latest state without a transaction")
+            .param_must("contract", String, "contract address")
+            .param_must("function", String, "")
+            .param("params", String, "call arguments")
+            .param("caller", String, "caller address"),
+        ApiRoute::debug_get("contract/storage", debug_contract_storage)
+            .summary("Raw contract storage value")
+            .param_must("contract", String, "contract address")
+            .param("key", String, "")
+            .param("kind", String, "storage (default)"),
+        ApiRoute::get("/query/contract/storage/list", contract_storage_list)
+            .summary("Storage entries of a contract")
+            .param_must("contract", String, "contract address")
+            .param("cursor", Integer, "next of the previous page, 0 by default")
+            .param("limit", Integer, "100 by default, 200 at most"),
+        ApiRoute::get("/query/contract/storage/expiring", contract_storage_expiring)
+            .summary("Storage entries expiring soon, by expire height")
+            .param("contract", String, "only entries of this contract")
+            .param("blocks", Integer, "within this many blocks, 1000 by default")
+            .param("cursor", String, "next of the previous page, height:position")
+            .param("limit", Integer, "index keys read, 100 by default, 200 at most"),
+        ApiRoute::post("/create/contract/storage/renew", contract_storage_renew_build)
+            .summary("Build a storage renew transaction")
+            .body_json("{main_address, contract, fee, timestamp, ...}")
+            .returns("hash_with_fee", String, "")
+            .returns("body", String, "transaction hex"),
+        ApiRoute::get("/query/contract/logs", vm_logs_read)
+            .summary("Contract logs of a block")
+            .param("height", Integer, "")
+            .param("index", Integer, "first log index")
+            .param("address", String, "only logs of this contract")
+            .param("topic0", String, "hex")
+            .param("topic1", String, "hex")
+            .param("topic2", String, "hex")
+            .param("topic3", String, "hex"),
+        ApiRoute::post("/submit/contract/verify", contract_verify)
+            .summary("Verify a contract source against its deployed code")
+            .body_json("{address, source, compiler (default: the source pragma version), edition, name}"),
+        ApiRoute::get("/query/contract/source", contract_source)
+            .summary("Verified source of a contract")
+            .param_must("address", String, "contract address"),
+        ApiRoute::get("/operate/contract/logs/delete", vm_logs_del)
+            .summary("Delete the stored contract logs of a block")
+            .param_must("height", Integer, "")
+            .param("auth", String, "delete auth, when the node sets one"),
     ]
 }
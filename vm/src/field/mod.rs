@@ -23,4 +23,5 @@ include!{"func.rs"}
 include!{"contract.rs"}
 include!{"status.rs"}
 include!{"storage.rs"}
-include!{"state.rs"}
\ No newline at end of file
+include!{"state.rs"}
+include!{"storage_index.rs"}
\ No newline at end of file
@@ -164,13 +164,29 @@ impl VMState<'_> {
         Ok(ValueKey::from(k))
     }
 
+    /// Hand the unhashed key of a hashed storage entry to the storage key
+    /// index; nothing is written to the state.
+    fn skeep(&mut self, cadr: &Address, k: &Value, sk: &ValueKey) {
+        let Ok(k) = k.extract_key_bytes() else {
+            return;
+        };
+        let raw = [cadr.to_vec(), k].concat();
+        if raw.len() > Hash::SIZE {
+            note_storage_raw_key(&sk.serialize(), raw);
+        }
+    }
+
+    fn sremove(&mut self, sk: &ValueKey) {
+        self.ctrtkvdb_del(sk);
+    }
+
     fn sfetch(&mut self, curhei: u64, gst: &GasExtra, sk: &ValueKey) -> VmrtRes<Option<ValueSto>> {
         let Some(mut v) = self.ctrtkvdb(sk) else {
             return Ok(None);
         };
         v.settle(curhei, gst)?;
         if v.is_absent() {
-            self.ctrtkvdb_del(sk);
+            self.sremove(sk);
             return Ok(None);
         }
         self.ctrtkvdb_set(sk, &v);
@@ -233,6 +249,7 @@ impl VMState<'_> {
         let live_credit = period_credit(unit, period, cap.storage_period)?;
         let vobj = ValueSto::new(curhei, v, live_credit, 0)?;
         self.ctrtkvdb_set(&sk, &vobj);
+        self.skeep(cadr, &k, &sk);
         let gas = gst
             .storage_key_cost
             .saturating_add(u64_to_i64_sat(unit).saturating_mul(period as i64));
@@ -283,6 +300,7 @@ impl VMState<'_> {
             "edit recover credit overflow",
         )?);
         self.ctrtkvdb_set(&sk, &old);
+        self.skeep(cadr, &k, &sk);
         let fee = u64_to_i64_sat(unit).saturating_mul(gst.storage_edit_mul);
         let rebate = refund_for_live_credit(trimmed_live, cap.storage_period);
         Ok((fee, rebate))
@@ -324,6 +342,7 @@ impl VMState<'_> {
         v.live_credit = Uint4::from(ValueSto::credit_u32(next_credit, "rent credit overflow")?);
         v.charge = BlockHeight::from(curhei);
         self.ctrtkvdb_set(&sk, &v);
+        self.skeep(cadr, &k, &sk);
         Ok(u64_to_i64_sat(unit).saturating_mul(period as i64))
     }
 
@@ -366,6 +385,7 @@ impl VMState<'_> {
         )?);
         v.charge = BlockHeight::from(curhei);
         self.ctrtkvdb_set(&sk, &v);
+        self.skeep(cadr, &k, &sk);
         Ok(u64_to_i64_sat(unit)
             .saturating_mul(period as i64)
             .saturating_div(3))
@@ -385,11 +405,11 @@ impl VMState<'_> {
         };
         v.settle(curhei, gst)?;
         if v.is_absent() {
-            self.ctrtkvdb_del(&sk);
+            self.sremove(&sk);
             return Ok(0);
         }
         let refund = refund_for_live_credit(v.live_credit.uint() as u64, cap.storage_period);
-        self.ctrtkvdb_del(&sk);
+        self.sremove(&sk);
         let refund = refund
             .checked_add(gst.storage_key_cost)
             .ok_or_else(|| ItrErr::new(StorageError, "delete refund overflow"))?;
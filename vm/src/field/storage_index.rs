@@ -0,0 +1,513 @@
+/*
+    Local, non-consensus index of persistent storage keys per contract.
+
+    State keys longer than a hash are stored hashed (see `VMState::skey`).
+    While a block runs the VM hands the unhashed keys to a bounded cache in
+    memory, never to the state. The index is fed the state writes of each
+    block that became the root, taking unhashed keys from that cache, so it
+    only holds keys of committed storage, and drops a key when the block
+    deletes it. Entries that expire without being touched are dropped by
+    `prune` a few at a time. Before its first block the scaner runs
+    `backfill`, which scans the storage already in the state DB once; hashed
+    keys written before the index was on cannot be traced to a contract and
+    stay out.
+
+    Keys:
+      height                 last indexed block
+      b                      backfill done
+      c ++ contract          key count of a contract, u64
+      e ++ contract ++ seq   state key, seq from 0, no holes
+      g                      key count of all contracts, u64
+      g ++ seq               state key
+      r ++ state key         contract ++ seq ++ global seq
+      k ++ state key         unhashed key, for hashed state keys
+      p                      next global seq to prune
+      xn ++ bucket           key count expiring in a bucket of heights
+      xc ++ height           key count expiring at a height
+      xe ++ height ++ seq    state key, seq from 0, no holes
+      xr ++ state key        height ++ seq
+*/
+static STORAGE_KEY_INDEX: OnceLock<StorageKeyIndex> = OnceLock::new();
+
+/// Install the storage key index DB. Without it nothing is recorded.
+pub fn configure_storage_key_index(db: Box<dyn DiskDB>) {
+    let _ = STORAGE_KEY_INDEX.set(StorageKeyIndex { db });
+}
+
+pub fn storage_key_index() -> Ret<&'static StorageKeyIndex> {
+    STORAGE_KEY_INDEX.get().ok_or_else(|| "storage key index not enabled".to_owned())
+}
+
+/// Storage entries checked for expiry per block.
+pub const STORAGE_INDEX_PRUNE_STEP: u64 = 32;
+
+/// Unhashed keys kept in memory until their block is confirmed. Blocks are
+/// confirmed a few behind the tip, well within this.
+const STORAGE_RAW_CACHE_MAX: usize = 1 << 16;
+
+/// Expire heights grouped per bucket, so a listing skips empty ranges.
+const STORAGE_EXPIRE_BUCKET: u64 = 1000;
+
+const SKEY_PREFIX: u8 = 205;
+
+#[derive(Default)]
+struct StorageRawKeys {
+    keys: HashMap<Vec<u8>, Vec<u8>>,
+    order: VecDeque<Vec<u8>>,
+}
+
+static STORAGE_RAW_KEYS: LazyLock<Mutex<StorageRawKeys>> = LazyLock::new(Default::default);
+
+/// Remember the unhashed key of hashed state key `sk` for the index, if one
+/// is configured. Node local, it costs no gas and writes no state.
+pub(crate) fn note_storage_raw_key(sk: &[u8], raw: Vec<u8>) {
+    if STORAGE_KEY_INDEX.get().is_none() {
+        return;
+    }
+    let mut cache = STORAGE_RAW_KEYS.lock().unwrap_or_else(|e| e.into_inner());
+    if cache.keys.insert(sk.to_vec(), raw).is_none() {
+        cache.order.push_back(sk.to_vec());
+    }
+    while cache.order.len() > STORAGE_RAW_CACHE_MAX {
+        if let Some(old) = cache.order.pop_front() {
+            cache.keys.remove(&old);
+        }
+    }
+}
+
+fn cached_storage_raw_key(sk: &[u8]) -> Option<Vec<u8>> {
+    let cache = STORAGE_RAW_KEYS.lock().unwrap_or_else(|e| e.into_inner());
+    cache.keys.get(sk).cloned()
+}
+
+pub struct StorageKeyIndex {
+    db: Box<dyn DiskDB>,
+}
+
+/// Writes of one block, read back before they reach the disk.
+struct StorageIndexBatch<'a> {
+    db: &'a dyn DiskDB,
+    mem: basis::component::MemKV,
+}
+
+impl StorageIndexBatch<'_> {
+    fn get(&self, k: &[u8]) -> Option<Vec<u8>> {
+        match self.mem.get(&k.to_vec()) {
+            Some(v) => v.clone(),
+            None => self.db.read(k),
+        }
+    }
+
+    fn get_u64(&self, k: &[u8]) -> u64 {
+        self.get(k).map_or(0, |v| u64::from_be_bytes(v.try_into().unwrap_or_default()))
+    }
+
+    fn put(&mut self, k: Vec<u8>, v: Vec<u8>) {
+        self.mem.put(k, v)
+    }
+
+    fn del(&mut self, k: Vec<u8>) {
+        self.mem.del(k)
+    }
+}
+
+fn sidx_key(pre: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+    let mut k = pre.to_vec();
+    for p in parts {
+        k.extend_from_slice(p);
+    }
+    k
+}
+
+fn sidx_u64(v: Option<Vec<u8>>) -> u64 {
+    v.map_or(0, |v| u64::from_be_bytes(v.try_into().unwrap_or_default()))
+}
+
+impl StorageKeyIndex {
+    /// Last indexed block height, zero when nothing is indexed.
+    pub fn height(&self) -> u64 {
+        sidx_u64(self.db.read(b"height"))
+    }
+
+    /// Record the storage writes of block `height`, which became the root
+    /// with state `sta`.
+    pub fn apply(&self, height: u64, sta: &dyn State) -> Rerr {
+        let gst = GasExtra::new(height);
+        let mut batch = StorageIndexBatch { db: self.db.as_ref(), mem: basis::component::MemKV::new() };
+        for (k, v) in sta.as_mem() {
+            if k.first() == Some(&SKEY_PREFIX) {
+                Self::write(&mut batch, &gst, &k[1..], v.as_deref());
+            }
+        }
+        Self::prune(&mut batch, sta, height, STORAGE_INDEX_PRUNE_STEP);
+        batch.put(b"height".to_vec(), height.to_be_bytes().to_vec());
+        self.db.write(&batch.mem);
+        Ok(())
+    }
+
+    /// Index the storage already in the state DB of `sta` at `height`, once.
+    pub fn backfill(&self, height: u64, sta: &dyn State) -> Rerr {
+        if self.db.read(b"b").is_some() {
+            return Ok(());
+        }
+        let gst = GasExtra::new(height);
+        let mut batch = StorageIndexBatch { db: self.db.as_ref(), mem: basis::component::MemKV::new() };
+        let mut untraced = 0usize;
+        sta.disk().for_each(&mut |k, v| {
+            if k.first() == Some(&SKEY_PREFIX) && !Self::write(&mut batch, &gst, &k[1..], Some(v)) {
+                untraced += 1;
+            }
+            true
+        })?;
+        if untraced > 0 {
+            println!("[Storage Index] {} hashed storage keys written before the index cannot be listed.", untraced);
+        }
+        batch.put(b"b".to_vec(), vec![1]);
+        self.db.write(&batch.mem);
+        Ok(())
+    }
+
+    /// Answers false for a present key of unknown contract, left out.
+    fn write(batch: &mut StorageIndexBatch, gst: &GasExtra, sk: &[u8], v: Option<&[u8]>) -> bool {
+        let listed = batch.get(&sidx_key(b"r", &[sk]));
+        match (v, listed) {
+            (Some(v), listed) => {
+                if listed.is_none() {
+                    let Some((cadr, raw)) = Self::owner(batch, sk) else {
+                        return false;
+                    };
+                    if raw.len() > Hash::SIZE {
+                        batch.put(sidx_key(b"k", &[sk]), raw);
+                    }
+                    Self::insert(batch, &cadr, sk);
+                }
+                if let Some(hei) = Self::expire_of(gst, v) {
+                    Self::expire_at(batch, sk, hei);
+                }
+            }
+            (None, Some(at)) => Self::remove(batch, sk, &at),
+            _ => {}
+        }
+        true
+    }
+
+    /// The contract a state key belongs to, and its unhashed key.
+    fn owner(batch: &StorageIndexBatch, sk: &[u8]) -> Option<(Address, Vec<u8>)> {
+        let raw = match batch.get(&sidx_key(b"k", &[sk])).or_else(|| cached_storage_raw_key(sk)) {
+            Some(raw) => raw,
+            None if sk.len() <= Hash::SIZE => sk.to_vec(),
+            None => return None,
+        };
+        if raw.len() < Address::SIZE {
+            return None;
+        }
+        let cadr = Address::from(<[u8; Address::SIZE]>::try_from(&raw[..Address::SIZE]).ok()?);
+        ContractAddress::from_addr(cadr).ok().map(|_| (cadr, raw))
+    }
+
+    /// Last height the stored entry `v` can be read at without more rent.
+    fn expire_of(gst: &GasExtra, v: &[u8]) -> Option<u64> {
+        let sto = ValueSto::build(v).ok()?;
+        let live = sto.live_rest_blocks(gst).ok()?;
+        Some(sto.charge.uint().saturating_add(live))
+    }
+
+    fn insert(batch: &mut StorageIndexBatch, cadr: &Address, sk: &[u8]) {
+        let ck = sidx_key(b"c", &[cadr.as_bytes()]);
+        let cseq = batch.get_u64(&ck);
+        let gseq = batch.get_u64(b"g");
+        batch.put(sidx_key(b"e", &[cadr.as_bytes(), &cseq.to_be_bytes()]), sk.to_vec());
+        batch.put(sidx_key(b"g", &[&gseq.to_be_bytes()]), sk.to_vec());
+        batch.put(ck, (cseq + 1).to_be_bytes().to_vec());
+        batch.put(b"g".to_vec(), (gseq + 1).to_be_bytes().to_vec());
+        let at = sidx_key(cadr.as_bytes(), &[&cseq.to_be_bytes(), &gseq.to_be_bytes()]);
+        batch.put(sidx_key(b"r", &[sk]), at);
+    }
+
+    // Lists stay without holes: the last entry moves into the place freed.
+    fn remove(batch: &mut StorageIndexBatch, sk: &[u8], at: &[u8]) {
+        const A: usize = Address::SIZE;
+        if at.len() != A + 16 {
+            return;
+        }
+        let cadr = &at[..A];
+        let cseq = u64::from_be_bytes(at[A..A + 8].try_into().unwrap());
+        let gseq = u64::from_be_bytes(at[A + 8..].try_into().unwrap());
+        batch.del(sidx_key(b"r", &[sk]));
+        batch.del(sidx_key(b"k", &[sk]));
+        if let Some(xat) = batch.get(&sidx_key(b"xr", &[sk])) {
+            Self::expire_drop(batch, sk, &xat);
+        }
+        // contract list
+        let ck = sidx_key(b"c", &[cadr]);
+        let clast = batch.get_u64(&ck).saturating_sub(1);
+        let clast_k = sidx_key(b"e", &[cadr, &clast.to_be_bytes()]);
+        if clast != cseq && let Some(moved) = batch.get(&clast_k) {
+            batch.put(sidx_key(b"e", &[cadr, &cseq.to_be_bytes()]), moved.clone());
+            Self::relocate(batch, b"r", &moved, |a| a[A..A + 8].copy_from_slice(&cseq.to_be_bytes()));
+        }
+        batch.del(clast_k);
+        match clast {
+            0 => batch.del(ck),
+            n => batch.put(ck, n.to_be_bytes().to_vec()),
+        }
+        // global list
+        let glast = batch.get_u64(b"g").saturating_sub(1);
+        let glast_k = sidx_key(b"g", &[&glast.to_be_bytes()]);
+        if glast != gseq && let Some(moved) = batch.get(&glast_k) {
+            batch.put(sidx_key(b"g", &[&gseq.to_be_bytes()]), moved.clone());
+            Self::relocate(batch, b"r", &moved, |a| a[A + 8..].copy_from_slice(&gseq.to_be_bytes()));
+        }
+        batch.del(glast_k);
+        batch.put(b"g".to_vec(), glast.to_be_bytes().to_vec());
+    }
+
+    fn relocate(batch: &mut StorageIndexBatch, pre: &[u8], sk: &[u8], f: impl FnOnce(&mut Vec<u8>)) {
+        let rk = sidx_key(pre, &[sk]);
+        if let Some(mut at) = batch.get(&rk) {
+            f(&mut at);
+            batch.put(rk, at);
+        }
+    }
+
+    /// File `sk` under expire height `hei`, leaving its previous height.
+    fn expire_at(batch: &mut StorageIndexBatch, sk: &[u8], hei: u64) {
+        if let Some(at) = batch.get(&sidx_key(b"xr", &[sk])) {
+            if at.get(..8) == Some(&hei.to_be_bytes()[..]) {
+                return;
+            }
+            Self::expire_drop(batch, sk, &at);
+        }
+        let hk = hei.to_be_bytes();
+        let ck = sidx_key(b"xc", &[&hk]);
+        let seq = batch.get_u64(&ck);
+        batch.put(sidx_key(b"xe", &[&hk, &seq.to_be_bytes()]), sk.to_vec());
+        batch.put(ck, (seq + 1).to_be_bytes().to_vec());
+        let nk = sidx_key(b"xn", &[&(hei / STORAGE_EXPIRE_BUCKET).to_be_bytes()]);
+        let n = batch.get_u64(&nk);
+        batch.put(nk, (n + 1).to_be_bytes().to_vec());
+        batch.put(sidx_key(b"xr", &[sk]), sidx_key(&hk, &[&seq.to_be_bytes()]));
+    }
+
+    fn expire_drop(batch: &mut StorageIndexBatch, sk: &[u8], at: &[u8]) {
+        if at.len() != 16 {
+            return;
+        }
+        let hk = &at[..8];
+        let seq = u64::from_be_bytes(at[8..].try_into().unwrap());
+        batch.del(sidx_key(b"xr", &[sk]));
+        let ck = sidx_key(b"xc", &[hk]);
+        let last = batch.get_u64(&ck).saturating_sub(1);
+        let last_k = sidx_key(b"xe", &[hk, &last.to_be_bytes()]);
+        if last != seq && let Some(moved) = batch.get(&last_k) {
+            batch.put(sidx_key(b"xe", &[hk, &seq.to_be_bytes()]), moved.clone());
+            Self::relocate(batch, b"xr", &moved, |a| a[8..].copy_from_slice(&seq.to_be_bytes()));
+        }
+        batch.del(last_k);
+        match last {
+            0 => batch.del(ck),
+            n => batch.put(ck, n.to_be_bytes().to_vec()),
+        }
+        let hei = u64::from_be_bytes(hk.try_into().unwrap());
+        let nk = sidx_key(b"xn", &[&(hei / STORAGE_EXPIRE_BUCKET).to_be_bytes()]);
+        match batch.get_u64(&nk).saturating_sub(1) {
+            0 => batch.del(nk),
+            n => batch.put(nk, n.to_be_bytes().to_vec()),
+        }
+    }
+
+    /// Drop up to `step` entries, in turn, that expired without being touched.
+    fn prune(batch: &mut StorageIndexBatch, sta: &dyn State, height: u64, step: u64) {
+        let gst = GasExtra::new(height);
+        let vmsta = VMStateRead::wrap(sta);
+        let mut seq = batch.get_u64(b"p");
+        for _ in 0..step {
+            let total = batch.get_u64(b"g");
+            if total == 0 {
+                break;
+            }
+            seq %= total;
+            let Some(sk) = batch.get(&sidx_key(b"g", &[&seq.to_be_bytes()])) else {
+                break;
+            };
+            let live = vmsta.ctrtkvdb(&ValueKey::from(sk.clone())).is_some_and(|mut v| {
+                v.settle(height, &gst).is_ok() && !v.is_absent()
+            });
+            match (live, batch.get(&sidx_key(b"r", &[&sk]))) {
+                (false, Some(at)) => Self::remove(batch, &sk, &at), // the last one moved here
+                _ => seq += 1,
+            }
+        }
+        batch.put(b"p".to_vec(), seq.to_be_bytes().to_vec());
+    }
+
+    /// The contract and the contract key of indexed state key `sk`.
+    pub fn entry_key(&self, sk: &[u8]) -> Option<(Address, Vec<u8>)> {
+        let at = self.db.read(&sidx_key(b"r", &[sk]))?;
+        let cadr = Address::from(<[u8; Address::SIZE]>::try_from(at.get(..Address::SIZE)?).ok()?);
+        let raw = self.db.read(&sidx_key(b"k", &[sk])).unwrap_or_else(|| sk.to_vec());
+        Some((cadr, raw.get(Address::SIZE..)?.to_vec()))
+    }
+
+    /// Up to `limit` state keys whose live rent runs out from height
+    /// `from.0`, position `from.1`, up to height `until`, by expire height;
+    /// and where the rest starts, if any.
+    pub fn expiring(&self, from: (u64, u64), until: u64, limit: usize) -> (Vec<(u64, Vec<u8>)>, Option<(u64, u64)>) {
+        let (mut hei, mut seq) = from;
+        let mut keys = vec![];
+        while hei <= until {
+            let bucket = hei / STORAGE_EXPIRE_BUCKET;
+            if sidx_u64(self.db.read(&sidx_key(b"xn", &[&bucket.to_be_bytes()]))) == 0 {
+                hei = (bucket + 1) * STORAGE_EXPIRE_BUCKET;
+                seq = 0;
+                continue;
+            }
+            let hk = hei.to_be_bytes();
+            let count = sidx_u64(self.db.read(&sidx_key(b"xc", &[&hk])));
+            while seq < count {
+                if keys.len() >= limit {
+                    return (keys, Some((hei, seq)));
+                }
+                if let Some(sk) = self.db.read(&sidx_key(b"xe", &[&hk, &seq.to_be_bytes()])) {
+                    keys.push((hei, sk));
+                }
+                seq += 1;
+            }
+            hei += 1;
+            seq = 0;
+        }
+        (keys, None)
+    }
+
+    /// Number of keys indexed for `cadr`.
+    pub fn count(&self, cadr: &Address) -> u64 {
+        sidx_u64(self.db.read(&sidx_key(b"c", &[cadr.as_bytes()])))
+    }
+
+    /// Up to `limit` state keys of `cadr` from position `cursor`, and the
+    /// cursor of the rest, if any.
+    pub fn page(&self, cadr: &Address, cursor: u64, limit: usize) -> (Vec<Vec<u8>>, Option<u64>) {
+        let total = self.count(cadr);
+        let end = total.min(cursor.saturating_add(limit as u64));
+        let keys = (cursor..end)
+            .filter_map(|i| self.db.read(&sidx_key(b"e", &[cadr.as_bytes(), &i.to_be_bytes()])))
+            .collect();
+        (keys, maybe!(end < total, Some(end), None))
+    }
+}
+
+/// Feeds confirmed blocks to the storage key index, if one is configured;
+/// everything else goes to the wrapped scaner.
+pub struct StorageKeyIndexScaner {
+    inner: Box<dyn Scaner>,
+}
+
+impl StorageKeyIndexScaner {
+    pub fn wrap(inner: Box<dyn Scaner>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Scaner for StorageKeyIndexScaner {
+    fn init(&mut self, ini: &IniObj) -> Rerr {
+        self.inner.init(ini)
+    }
+
+    fn exit(&self) {
+        self.inner.exit()
+    }
+
+    fn start(&self, worker: Worker) {
+        self.inner.start(worker)
+    }
+
+    fn serve(&self, worker: Worker) {
+        self.inner.serve(worker)
+    }
+
+    fn roll(&self, blk: Arc<dyn Block>, sta: Arc<Box<dyn State>>, disk: Arc<dyn DiskDB>) {
+        if let Ok(index) = storage_key_index()
+            && let Err(e) = index
+                .backfill(blk.height().uint(), sta.as_ref().as_ref())
+                .and_then(|_| index.apply(blk.height().uint(), sta.as_ref().as_ref()))
+        {
+            println!("[Storage Index] block {} failed: {}", blk.height().uint(), e);
+        }
+        self.inner.roll(blk, sta, disk)
+    }
+
+    fn api_services(&self) -> Vec<Arc<dyn ApiService>> {
+        self.inner.api_services()
+    }
+}
+
+/// Live view of one persistent storage entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageEntry {
+    pub key: Vec<u8>,
+    pub value: Value,
+    pub size: usize,
+    pub live_blocks: u64,
+    pub recover_blocks: u64,
+    pub active: bool,
+}
+
+impl StorageEntry {
+    /// Last height at which the entry can still be read or edited.
+    pub fn expire_height(&self, curhei: u64) -> u64 {
+        curhei.saturating_add(self.live_blocks)
+    }
+
+    /// Height after which the entry can no longer be recovered with rent.
+    pub fn purge_height(&self, curhei: u64) -> u64 {
+        self.expire_height(curhei).saturating_add(self.recover_blocks)
+    }
+}
+
+impl VMStateRead<'_> {
+    /// Indexed storage entries of `cadr` still present at `curhei`, up to
+    /// `limit` from `cursor`, and the cursor of the rest.
+    pub fn storage_entries(
+        &self,
+        gst: &GasExtra,
+        cap: &SpaceCap,
+        curhei: u64,
+        cadr: &Address,
+        cursor: u64,
+        limit: usize,
+    ) -> Ret<(Vec<StorageEntry>, Option<u64>)> {
+        let index = storage_key_index()?;
+        let (sks, next) = index.page(cadr, cursor, limit);
+        let mut list = vec![];
+        for sk in sks {
+            let Some((_, key)) = index.entry_key(&sk) else {
+                continue;
+            };
+            if let Some(entry) = self.storage_entry(gst, cap, curhei, cadr, key)? {
+                list.push(entry);
+            }
+        }
+        Ok((list, next))
+    }
+
+    pub fn storage_entry(
+        &self,
+        gst: &GasExtra,
+        cap: &SpaceCap,
+        curhei: u64,
+        cadr: &Address,
+        key: Vec<u8>,
+    ) -> Ret<Option<StorageEntry>> {
+        let got = self
+            .debug_storage_get(gst, cap, curhei, cadr, &Value::Bytes(key.clone()))
+            .map_err(|e| e.to_string())?;
+        Ok(got.map(|(value, live, recover, active, _)| StorageEntry {
+            key,
+            size: value.val_size(),
+            value,
+            live_blocks: live,
+            recover_blocks: recover,
+            active,
+        }))
+    }
+}
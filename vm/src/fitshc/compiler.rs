@@ -14,10 +14,14 @@ pub type FitshCompileOutput = (
     String,
 );
 
-pub fn compile_with_warnings(code: &str) -> Ret<(FitshCompileOutput, Vec<String>)> {
+/// Parse and compile `code`, keeping each body's IR when `lint` is set.
+pub(super) fn parse_source(code: &str, lint: bool) -> Ret<ParseState> {
     let tkr = Tokenizer::new(code.as_bytes());
     let tokens = tkr.parse().map_err(|e| e.to_string())?;
     let mut state = ParseState::new(tokens);
+    if lint {
+        state.lint_units = Some(Vec::new());
+    }
 
     parse_top_level(&mut state)?;
     if state.idx != state.max {
@@ -26,7 +30,11 @@ pub fn compile_with_warnings(code: &str) -> Ret<(FitshCompileOutput, Vec<String>
             state.current().cloned()
         );
     }
+    Ok(state)
+}
 
+pub fn compile_with_warnings(code: &str) -> Ret<(FitshCompileOutput, Vec<String>)> {
+    let mut state = parse_source(code, false)?;
     let warnings = std::mem::take(&mut state.warnings);
     Ok((
         (
@@ -0,0 +1,521 @@
+use std::collections::HashSet;
+
+use super::compiler::parse_source;
+use crate::IRNode;
+use crate::ir::*;
+use crate::lang::ir_literal_value;
+use crate::native::NativeFunc;
+use crate::rt::Bytecode::*;
+use crate::rt::*;
+use sys::Ret;
+
+/// Storage created or rented for fewer periods than this expires within days.
+pub const LINT_MIN_RENT_PERIODS: u128 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintRule {
+    UnreachableCode,
+    UnguardedStorageWrite,
+    UncheckedCallResult,
+    Reentrancy,
+    ShortRentPeriod,
+    UnboundedLoop,
+}
+
+impl LintRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UnreachableCode => "unreachable-code",
+            Self::UnguardedStorageWrite => "unguarded-storage-write",
+            Self::UncheckedCallResult => "unchecked-call-result",
+            Self::Reentrancy => "reentrancy",
+            Self::ShortRentPeriod => "short-rent-period",
+            Self::UnboundedLoop => "unbounded-loop",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LintWarning {
+    pub rule: LintRule,
+    /// Function name, or `abstract::Name` for abstract hooks.
+    pub func: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.rule.name(), self.func, self.message)
+    }
+}
+
+/// IR of one compiled body, kept by the parser for lint passes.
+#[derive(Debug, Clone)]
+pub struct LintUnit {
+    pub name: String,
+    pub external: bool,
+    pub irnodes: IRNodeArray,
+}
+
+/// Compile `code` and lint every function and abstract body.
+pub fn lint(code: &str) -> Ret<Vec<LintWarning>> {
+    let state = parse_source(code, true)?;
+    Ok(lint_units(&state.lint_units.unwrap_or_default()))
+}
+
+pub fn lint_units(units: &[LintUnit]) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    for unit in units {
+        let mut walker = LintWalker {
+            unit,
+            caller_checked: contains(&unit.irnodes, &is_caller_check),
+            external_call_seen: false,
+            storage_locals: HashSet::new(),
+            reported: HashSet::new(),
+            warnings: &mut warnings,
+        };
+        walker.walk(&unit.irnodes);
+    }
+    warnings
+}
+
+struct LintWalker<'a> {
+    unit: &'a LintUnit,
+    caller_checked: bool,
+    external_call_seen: bool,
+    /// Local slots last assigned from a storage read.
+    storage_locals: HashSet<u8>,
+    /// Rules reported at most once per body.
+    reported: HashSet<LintRule>,
+    warnings: &'a mut Vec<LintWarning>,
+}
+
+impl LintWalker<'_> {
+    fn warn(&mut self, rule: LintRule, message: impl Into<String>) {
+        self.warnings.push(LintWarning {
+            rule,
+            func: self.unit.name.clone(),
+            message: message.into(),
+        });
+    }
+
+    fn warn_once(&mut self, rule: LintRule, message: &str) {
+        if self.reported.insert(rule) {
+            self.warn(rule, message);
+        }
+    }
+
+    /// Post-order walk, so call arguments and storage operands are seen before
+    /// the instruction itself, matching execution order.
+    fn walk(&mut self, node: &dyn IRNode) {
+        let op: Bytecode = std_mem_transmute!(node.bytecode());
+        match op {
+            IRBLOCK | IRBLOCKR => {
+                if let Some(arr) = node.as_any().downcast_ref::<IRNodeArray>() {
+                    return self.walk_block(arr);
+                }
+            }
+            IRIF | IRIFR => {
+                if let Some(ifn) = node.as_any().downcast_ref::<IRNodeTriple>() {
+                    return self.walk_if(ifn);
+                }
+            }
+            IRWHILE => {
+                if let Some(wn) = node.as_any().downcast_ref::<IRNodeDouble>() {
+                    return self.walk_while(wn);
+                }
+            }
+            PUT => {
+                if let Some(put) = node.as_any().downcast_ref::<IRNodeParam1Single>() {
+                    self.walk(&*put.subx);
+                    if self.reads_storage(&*put.subx) {
+                        self.storage_locals.insert(put.para);
+                    } else {
+                        self.storage_locals.remove(&put.para);
+                    }
+                    return;
+                }
+            }
+            _ => {}
+        }
+        for sub in children(node) {
+            self.walk(sub);
+        }
+        match op {
+            SNEW | SEDIT | SDEL | SRENT | SRECV => self.check_storage_write(op, node),
+            _ => {
+                if is_external_edit_call(node) {
+                    self.external_call_seen = true;
+                }
+            }
+        }
+    }
+
+    fn walk_block(&mut self, arr: &IRNodeArray) {
+        let mut jumped = false;
+        for sub in arr.subs.iter() {
+            if jumped && !is_trivial(&**sub) {
+                self.warn(
+                    LintRule::UnreachableCode,
+                    "code after break, continue, return or abort never runs",
+                );
+                jumped = false;
+            }
+            if arr.inst == IRBLOCK && is_external_call(&**sub) {
+                self.warn(
+                    LintRule::UncheckedCallResult,
+                    "return value of external call is discarded",
+                );
+            }
+            self.walk(&**sub);
+            if is_loop_exit(unwrap(&**sub)) || matches!(leaf_op(&**sub), Some(IRCONTINUE)) {
+                jumped = true;
+            }
+        }
+    }
+
+    fn walk_if(&mut self, ifn: &IRNodeTriple) {
+        self.walk(&*ifn.subx);
+        match literal_bool(&*ifn.subx) {
+            Some(true) => {
+                if !is_trivial(&*ifn.subz) {
+                    self.warn(
+                        LintRule::UnreachableCode,
+                        "else branch never runs: condition is always true",
+                    );
+                }
+                self.walk(&*ifn.suby);
+            }
+            Some(false) => {
+                self.warn(
+                    LintRule::UnreachableCode,
+                    "if branch never runs: condition is always false",
+                );
+                self.walk(&*ifn.subz);
+            }
+            None => {
+                self.walk(&*ifn.suby);
+                self.walk(&*ifn.subz);
+            }
+        }
+    }
+
+    fn walk_while(&mut self, wn: &IRNodeDouble) {
+        match literal_bool(&*wn.subx) {
+            Some(false) => {
+                self.warn(
+                    LintRule::UnreachableCode,
+                    "loop body never runs: condition is always false",
+                );
+                return;
+            }
+            Some(true) if !contains(&*wn.suby, &is_loop_exit) => {
+                self.warn(
+                    LintRule::UnboundedLoop,
+                    "loop has no exit and runs until gas is exhausted",
+                );
+            }
+            _ => {
+                if self.reads_storage(&*wn.subx) {
+                    self.warn(
+                        LintRule::UnboundedLoop,
+                        "loop bound depends on storage and its gas cost grows with stored data",
+                    );
+                }
+            }
+        }
+        self.walk(&*wn.subx);
+        self.walk(&*wn.suby);
+    }
+
+    fn check_storage_write(&mut self, op: Bytecode, node: &dyn IRNode) {
+        if self.unit.external && !self.caller_checked {
+            self.warn_once(
+                LintRule::UnguardedStorageWrite,
+                "external function writes storage without checking tx_main_addr or a signature",
+            );
+        }
+        if self.external_call_seen {
+            self.warn_once(
+                LintRule::Reentrancy,
+                "storage is written after an external call; update state before calling out",
+            );
+        }
+        let period = match op {
+            SNEW => node
+                .as_any()
+                .downcast_ref::<IRNodeTriple>()
+                .map(|n| &n.subz),
+            SRENT => node
+                .as_any()
+                .downcast_ref::<IRNodeDouble>()
+                .map(|n| &n.suby),
+            _ => None,
+        };
+        let short = period
+            .and_then(|p| literal_uint(&**p))
+            .filter(|n| *n < LINT_MIN_RENT_PERIODS);
+        if let Some(n) = short {
+            self.warn(
+                LintRule::ShortRentPeriod,
+                format!(
+                    "storage rented for {} period(s), below the recommended {}",
+                    n, LINT_MIN_RENT_PERIODS
+                ),
+            );
+        }
+    }
+
+    fn reads_storage(&self, node: &dyn IRNode) -> bool {
+        let locals = &self.storage_locals;
+        contains(node, &|n: &dyn IRNode| match local_get_slot(n) {
+            Some(slot) => locals.contains(&slot),
+            None => {
+                let op: Bytecode = std_mem_transmute!(n.bytecode());
+                matches!(op, SLOAD | SSTAT)
+            }
+        })
+    }
+}
+
+/// Direct sub-nodes of any composite IR node.
+fn children(node: &dyn IRNode) -> Vec<&dyn IRNode> {
+    let any = node.as_any();
+    macro_rules! subs {
+        ($($ty:ty => [$($f:ident),+]),+ $(,)?) => {
+            $(if let Some(n) = any.downcast_ref::<$ty>() {
+                return vec![$(&*n.$f),+];
+            })+
+        };
+    }
+    subs! {
+        IRNodeWrapOne => [node],
+        IRNodeSingle => [subx],
+        IRNodeParamsSingle => [subx],
+        IRNodeParam1Single => [subx],
+        IRNodeParam2Single => [subx],
+        IRNodeDouble => [subx, suby],
+        IRNodeParam1Double => [subx, suby],
+        IRNodeTriple => [subx, suby, subz],
+        IRNodeParam1Triple => [subx, suby, subz],
+        IRNodeQuad => [subx, suby, subz, subw],
+        IRNodeParam1Quad => [subx, suby, subz, subw],
+        IRNodeQuint => [suba, subb, subc, subd, sube],
+    }
+    if let Some(arr) = any.downcast_ref::<IRNodeArray>() {
+        return arr.subs.iter().map(|s| &**s).collect();
+    }
+    vec![]
+}
+
+fn contains(node: &dyn IRNode, pred: &dyn Fn(&dyn IRNode) -> bool) -> bool {
+    pred(node) || children(node).into_iter().any(|n| contains(n, pred))
+}
+
+fn unwrap(node: &dyn IRNode) -> &dyn IRNode {
+    match node.as_any().downcast_ref::<IRNodeWrapOne>() {
+        Some(wrap) => unwrap(&*wrap.node),
+        None => node,
+    }
+}
+
+fn leaf_op(node: &dyn IRNode) -> Option<Bytecode> {
+    unwrap(node)
+        .as_any()
+        .downcast_ref::<IRNodeLeaf>()
+        .map(|l| l.inst)
+}
+
+fn is_trivial(node: &dyn IRNode) -> bool {
+    let node = unwrap(node);
+    node.as_any().downcast_ref::<IRNodeEmpty>().is_some()
+        || matches!(leaf_op(node), Some(NOP))
+        || node
+            .as_any()
+            .downcast_ref::<IRNodeArray>()
+            .is_some_and(|arr| arr.subs.iter().all(|s| is_trivial(&**s)))
+}
+
+fn is_loop_exit(node: &dyn IRNode) -> bool {
+    let op: Bytecode = std_mem_transmute!(node.bytecode());
+    matches!(op, IRBREAK | RET | END | ERR | ABT)
+}
+
+fn literal_bool(node: &dyn IRNode) -> Option<bool> {
+    ir_literal_value(unwrap(node)).ok()??.extract_bool().ok()
+}
+
+fn literal_uint(node: &dyn IRNode) -> Option<u128> {
+    ir_literal_value(unwrap(node)).ok()??.extract_u128().ok()
+}
+
+fn local_get_slot(node: &dyn IRNode) -> Option<u8> {
+    let any = node.as_any();
+    if let Some(leaf) = any.downcast_ref::<IRNodeLeaf>() {
+        return match leaf.inst {
+            GET0 => Some(0),
+            GET1 => Some(1),
+            GET2 => Some(2),
+            GET3 => Some(3),
+            _ => None,
+        };
+    }
+    any.downcast_ref::<IRNodeParam1>()
+        .filter(|p| p.inst == GET)
+        .map(|p| p.para)
+}
+
+fn call_spec(node: &dyn IRNode) -> Option<CallSpec> {
+    let call = unwrap(node).as_any().downcast_ref::<IRNodeParamsSingle>()?;
+    decode_user_call_site(call.inst, &call.para).ok()
+}
+
+/// Invocation of another contract that returns a value.
+fn is_external_call(node: &dyn IRNode) -> bool {
+    matches!(
+        call_spec(node),
+        Some(CallSpec::Invoke {
+            target: CallTarget::Ext(_) | CallTarget::Use(_),
+            ..
+        })
+    )
+}
+
+/// Invocation of another contract that may write state and call back.
+fn is_external_edit_call(node: &dyn IRNode) -> bool {
+    matches!(
+        call_spec(node),
+        Some(CallSpec::Invoke {
+            target: CallTarget::Ext(_),
+            effect: EffectMode::Edit,
+            ..
+        })
+    )
+}
+
+fn is_caller_check(node: &dyn IRNode) -> bool {
+    let (inst, para) = if let Some(p) = node.as_any().downcast_ref::<IRNodeParam1>() {
+        (p.inst, p.para)
+    } else if let Some(p) = node.as_any().downcast_ref::<IRNodeParam1Single>() {
+        (p.inst, p.para)
+    } else {
+        return false;
+    };
+    match inst {
+        ACTENV => search_act_name_by_id(para, &ACTION_ENV_DEFS) == "tx_main_addr",
+        ACTVIEW => search_act_name_by_id(para, &ACTION_VIEW_DEFS) == "check_signature",
+        NTFUNC => para == NativeFunc::idx_verify_signature,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod lint_tests {
+    use super::*;
+
+    fn lint_body(body: &str) -> Vec<LintWarning> {
+        let src = format!(
+            "pragma fitsh 1.0.0\ncontract T {{\n library [ Lib: emqjNS9PscqdBpMtnC3Jfuc4mvZUPYTPS ]\n{}\n}}",
+            body
+        );
+        lint(&src).unwrap()
+    }
+
+    fn rules(ws: &[LintWarning]) -> Vec<LintRule> {
+        ws.iter().map(|w| w.rule).collect()
+    }
+
+    #[test]
+    fn guarded_function_is_clean() {
+        let ws = lint_body(
+            r#"function external set(v: u64) {
+                assert tx_main_addr() == emqjNS9PscqdBpMtnC3Jfuc4mvZUPYTPS
+                storage_edit("k", v)
+                end
+            }"#,
+        );
+        assert!(ws.is_empty(), "{:?}", ws);
+    }
+
+    #[test]
+    fn flags_unguarded_write_with_short_rent() {
+        let ws = lint_body(
+            r#"function external set(v: u64) {
+                storage_new("k", v, 2)
+                storage_edit("k", v)
+                end
+            }"#,
+        );
+        assert_eq!(
+            rules(&ws),
+            [LintRule::UnguardedStorageWrite, LintRule::ShortRentPeriod]
+        );
+        assert_eq!(ws[0].func, "set");
+    }
+
+    #[test]
+    fn flags_discarded_call_result_and_reentrancy() {
+        let ws = lint_body(
+            r#"function pay(v: u64) {
+                Lib.send(v)
+                storage_edit("paid", v)
+                end
+            }"#,
+        );
+        assert_eq!(
+            rules(&ws),
+            [LintRule::UncheckedCallResult, LintRule::Reentrancy]
+        );
+    }
+
+    #[test]
+    fn flags_storage_bound_and_endless_loops() {
+        let ws = lint_body(
+            r#"function scan() {
+                var n = storage_load("n")
+                var i = 0
+                while i < n { i += 1 }
+                while true { i += 1 }
+                end
+            }"#,
+        );
+        assert_eq!(
+            rules(&ws),
+            [LintRule::UnboundedLoop, LintRule::UnboundedLoop]
+        );
+    }
+
+    #[test]
+    fn flags_dead_branches() {
+        let ws = lint_body(
+            r#"function f(v: u64) -> u64 {
+                if false { v = 1 }
+                while false { v = 2 }
+                return v
+            }"#,
+        );
+        assert_eq!(
+            rules(&ws),
+            [LintRule::UnreachableCode, LintRule::UnreachableCode]
+        );
+    }
+
+    #[test]
+    fn flags_code_after_return() {
+        // the parser refuses such source, so append to compiled IR instead
+        let src = "pragma fitsh 1.0.0\ncontract T {\n function f(v: u64) -> u64 { return v }\n}";
+        let mut units = parse_source(src, true).unwrap().lint_units.unwrap();
+        assert!(lint_units(&units).is_empty());
+        let body = &mut units[0].irnodes;
+        let ret = body.subs.last().unwrap().clone();
+        body.subs.push(ret);
+        assert_eq!(rules(&lint_units(&units)), [LintRule::UnreachableCode]);
+    }
+
+    #[test]
+    fn plain_compile_keeps_no_lint_units() {
+        let src = "pragma fitsh 1.0.0\ncontract T {\n function f() { end }\n}";
+        assert!(parse_source(src, false).unwrap().lint_units.is_none());
+        assert_eq!(parse_source(src, true).unwrap().lint_units.unwrap().len(), 1);
+    }
+}
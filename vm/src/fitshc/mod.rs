@@ -1,9 +1,13 @@
 pub mod compile_body;
 pub mod compiler;
+pub mod lint;
 pub mod parse_deploy;
 pub mod parse_func;
+pub mod parse_test;
 pub mod parse_top;
 pub mod state;
 
 pub use compile_body::{CompiledCode, compile_body};
 pub use compiler::{compile, compile_with_warnings};
+pub use lint::{LintRule, LintWarning, lint};
+pub use parse_test::{FitshTestCase, FitshTestExpect, FitshTestTarget, compile_tests};
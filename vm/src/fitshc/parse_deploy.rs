@@ -74,7 +74,7 @@ pub fn parse_deploy(state: &mut ParseState) -> Ret<DeployInfo> {
     Ok(info)
 }
 
-fn parse_amount_ctor(state: &mut ParseState) -> Ret<Amount> {
+pub(super) fn parse_amount_ctor(state: &mut ParseState) -> Ret<Amount> {
     match state.current() {
         Some(Identifier(id)) if id == "amount" => state.advance(),
         _ => return errf!("expected amount(\"...\") at protocol_cost"),
@@ -1,4 +1,5 @@
 use super::compile_body::{CompiledCode, compile_body};
+use super::lint::LintUnit;
 use super::state::ParseState;
 use crate::Token::*;
 use crate::contract::Func;
@@ -129,6 +130,13 @@ pub fn parse_function(state: &mut ParseState, consume_kw: bool) -> Ret<(Func, So
     // Compile body using shared compile function
     let (irnodes, compiled, source_map) =
         compile_body(body_tokens, args, &state.libs, &state.consts, is_ircode)?;
+    if let Some(units) = state.lint_units.as_mut() {
+        units.push(LintUnit {
+            name: name.clone(),
+            external: is_external,
+            irnodes: irnodes.clone(),
+        });
+    }
 
     func = match compiled {
         CompiledCode::IrCode(_) => func.irnode(irnodes)?,
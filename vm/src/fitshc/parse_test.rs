@@ -0,0 +1,354 @@
+use super::parse_deploy::parse_amount_ctor;
+use super::parse_func::parse_func_body_tokens;
+use super::parse_top::{parse_required_pragma, parse_top_const};
+use super::state::ParseState;
+use crate::IRNode;
+use crate::Token::*;
+use crate::lang::{Syntax, Tokenizer};
+use crate::rt::*;
+use crate::value::Value;
+use dyn_clone::clone_box;
+use field::Amount;
+use std::collections::HashSet;
+use sys::*;
+use sys::{Ret, errf};
+
+/// Contract under test, as seen from a test body.
+/// The contract is bound by name as lib index 1 (tx address slot after the caller).
+#[derive(Debug, Clone)]
+pub struct FitshTestTarget {
+    pub name: String,
+    pub addr: field::Address,
+}
+
+impl FitshTestTarget {
+    /// Transaction address list the compiled test body expects at runtime.
+    pub fn tx_addrs(&self, caller: field::Address) -> Vec<field::Address> {
+        vec![caller, self.addr]
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FitshTestExpect {
+    /// `Some("")` expects any failure; otherwise the error must contain the text.
+    pub fail: Option<String>,
+    pub logs: Option<usize>,
+    pub storage: Vec<(Value, Value)>,
+    pub balances: Vec<(field::Address, Amount)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FitshTestCase {
+    pub name: String,
+    pub height: Option<u64>,
+    pub caller: Option<field::Address>,
+    pub balances: Vec<(field::Address, Amount)>,
+    pub codes: Vec<u8>,
+    pub expect: FitshTestExpect,
+}
+
+/// Compile a `*_test.fitsh` file:
+///
+/// ```fitsh
+/// pragma fitsh 1.0.0
+/// test transfer_ok {
+///     height: 100
+///     caller: 1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9
+///     balance: [1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9: amount("10:248")]
+///     run { Token.transfer(1EuGe2GU8tDKnHLNfBsgyffx66buK7PP6g, 5) }
+///     expect { logs: 1, storage: ["total": 5] }
+/// }
+/// ```
+pub fn compile_tests(code: &str, target: &FitshTestTarget) -> Ret<Vec<FitshTestCase>> {
+    let tokens = Tokenizer::new(code.as_bytes())
+        .parse()
+        .map_err(|e| e.to_string())?;
+    let mut state = ParseState::new(tokens);
+    parse_required_pragma(&mut state)?;
+
+    let mut cases = Vec::new();
+    let mut names = HashSet::new();
+    loop {
+        state.skip_soft_separators();
+        match state.current() {
+            None => break,
+            Some(Keyword(KwTy::Const)) => parse_top_const(&mut state)?,
+            Some(Identifier(id)) if id == "test" => {
+                let case = parse_test_case(&mut state, target)?;
+                if !names.insert(case.name.clone()) {
+                    return errf!("duplicate test '{}'", case.name);
+                }
+                cases.push(case);
+            }
+            Some(token) => return errf!("unexpected token in test file: {:?}", token),
+        }
+    }
+    if cases.is_empty() {
+        return errf!("no test found");
+    }
+    Ok(cases)
+}
+
+fn expect_colon(state: &mut ParseState, key: &str) -> Rerr {
+    if let Some(Keyword(KwTy::Colon)) = state.current() {
+        state.advance();
+        return Ok(());
+    }
+    errf!("expected ':' after test field '{}'", key)
+}
+
+fn parse_key(state: &mut ParseState, seen: &mut HashSet<String>, ctx: &str) -> Ret<String> {
+    let Some(Identifier(key)) = state.current() else {
+        return errf!("expected {} field name but got {:?}", ctx, state.current());
+    };
+    let key = key.clone();
+    state.advance();
+    if !seen.insert(key.clone()) {
+        return errf!("duplicate {} field '{}'", ctx, key);
+    }
+    Ok(key)
+}
+
+fn parse_test_case(state: &mut ParseState, target: &FitshTestTarget) -> Ret<FitshTestCase> {
+    state.advance(); // consume test
+    let Some(Identifier(name)) = state.current() else {
+        return errf!("expected test name after 'test'");
+    };
+    let name = name.clone();
+    state.advance();
+    state.eat_partition('{')?;
+
+    let mut case = FitshTestCase {
+        name,
+        height: None,
+        caller: None,
+        balances: Vec::new(),
+        codes: Vec::new(),
+        expect: FitshTestExpect::default(),
+    };
+    let mut body = None;
+    let mut seen = HashSet::new();
+    loop {
+        state.skip_soft_separators();
+        if let Some(Partition('}')) = state.current() {
+            state.advance();
+            break;
+        }
+        let key = parse_key(state, &mut seen, "test")?;
+        match key.as_str() {
+            "height" => {
+                expect_colon(state, &key)?;
+                case.height = Some(parse_u64(state, &key)?);
+            }
+            "caller" => {
+                expect_colon(state, &key)?;
+                case.caller = Some(parse_address(state)?);
+            }
+            "balance" => {
+                expect_colon(state, &key)?;
+                case.balances = parse_balance_list(state)?;
+            }
+            "run" => body = Some(parse_func_body_tokens(state)?),
+            "expect" => case.expect = parse_expect(state)?,
+            _ => return errf!("unknown test field '{}'", key),
+        }
+    }
+    let Some(body) = body else {
+        return errf!("test '{}' missing run block", case.name);
+    };
+    case.codes = compile_test_body(body, target, &state.consts)
+        .map_err(|e| format!("test '{}' compile error: {}", case.name, e))?;
+    Ok(case)
+}
+
+fn parse_expect(state: &mut ParseState) -> Ret<FitshTestExpect> {
+    state.eat_partition('{')?;
+    let mut expect = FitshTestExpect::default();
+    let mut seen = HashSet::new();
+    loop {
+        state.skip_soft_separators();
+        if let Some(Partition('}')) = state.current() {
+            state.advance();
+            break;
+        }
+        let key = parse_key(state, &mut seen, "expect")?;
+        expect_colon(state, &key)?;
+        match key.as_str() {
+            "fail" => {
+                expect.fail = match state.current() {
+                    Some(Keyword(KwTy::True)) => Some(String::new()),
+                    Some(Keyword(KwTy::False)) => None,
+                    Some(Bytes(msg)) => Some(String::from_utf8_lossy(msg).into_owned()),
+                    _ => return errf!("expect fail must be bool or string"),
+                };
+                state.advance();
+            }
+            "logs" => expect.logs = Some(parse_u64(state, &key)? as usize),
+            "storage" => expect.storage = parse_storage_list(state)?,
+            "balance" => expect.balances = parse_balance_list(state)?,
+            _ => return errf!("unknown expect field '{}'", key),
+        }
+    }
+    Ok(expect)
+}
+
+fn parse_u64(state: &mut ParseState, key: &str) -> Ret<u64> {
+    let n = match state.current() {
+        Some(Integer(n)) | Some(IntegerWithSuffix(n, _)) => *n,
+        _ => return errf!("expected integer at '{}'", key),
+    };
+    let n = u64::try_from(n).map_err(|_| format!("{} overflow: {}", key, n))?;
+    state.advance();
+    Ok(n)
+}
+
+fn parse_address(state: &mut ParseState) -> Ret<field::Address> {
+    let addr = match state.current() {
+        Some(Address(a)) => *a,
+        Some(Identifier(a)) => field::Address::from_readable(a).map_err(|e| e.to_string())?,
+        other => return errf!("expected address but got {:?}", other),
+    };
+    state.advance();
+    Ok(addr)
+}
+
+fn parse_balance_list(state: &mut ParseState) -> Ret<Vec<(field::Address, Amount)>> {
+    state.eat_partition('[')?;
+    let mut list = Vec::new();
+    loop {
+        state.skip_soft_separators();
+        if let Some(Partition(']')) = state.current() {
+            state.advance();
+            break;
+        }
+        let addr = parse_address(state)?;
+        expect_colon(state, "balance")?;
+        list.push((addr, parse_amount_ctor(state)?));
+    }
+    Ok(list)
+}
+
+fn parse_storage_list(state: &mut ParseState) -> Ret<Vec<(Value, Value)>> {
+    state.eat_partition('[')?;
+    let mut list = Vec::new();
+    loop {
+        state.skip_soft_separators();
+        if let Some(Partition(']')) = state.current() {
+            state.advance();
+            break;
+        }
+        let key = parse_literal_value(state)?;
+        expect_colon(state, "storage")?;
+        list.push((key, parse_literal_value(state)?));
+    }
+    Ok(list)
+}
+
+/// Expectation literal. Integers keep their suffix type, or take the
+/// narrowest uint type like `push_num`; the runner compares uints by value.
+fn parse_literal_value(state: &mut ParseState) -> Ret<Value> {
+    let v = match state.current() {
+        Some(Keyword(KwTy::Nil)) => Value::Nil,
+        Some(Keyword(KwTy::True)) => Value::Bool(true),
+        Some(Keyword(KwTy::False)) => Value::Bool(false),
+        Some(Integer(n)) => match *n {
+            0..=255 => Value::U8(*n as u8),
+            256..=65535 => Value::U16(*n as u16),
+            65536..=4294967295 => Value::U32(*n as u32),
+            4294967296..=18446744073709551615 => Value::U64(*n as u64),
+            _ => Value::U128(*n),
+        },
+        Some(IntegerWithSuffix(n, kw)) => {
+            let n = *n;
+            let overflow = || format!("literal {} overflow", n);
+            match kw {
+                KwTy::U8 => Value::U8(u8::try_from(n).map_err(|_| overflow())?),
+                KwTy::U16 => Value::U16(u16::try_from(n).map_err(|_| overflow())?),
+                KwTy::U32 => Value::U32(u32::try_from(n).map_err(|_| overflow())?),
+                KwTy::U64 => Value::U64(u64::try_from(n).map_err(|_| overflow())?),
+                KwTy::U128 => Value::U128(n),
+                _ => return errf!("literal suffix invalid"),
+            }
+        }
+        Some(Character(c)) => Value::U8(*c),
+        Some(Bytes(b)) => Value::Bytes(b.clone()),
+        Some(Address(a)) => Value::Address(*a),
+        other => return errf!("expected literal but got {:?}", other),
+    };
+    state.advance();
+    Ok(v)
+}
+
+fn compile_test_body(
+    mut body_tokens: Vec<Token>,
+    target: &FitshTestTarget,
+    consts: &[(String, Box<dyn IRNode>)],
+) -> Ret<Vec<u8>> {
+    // a test body falling through its last statement passes
+    body_tokens.push(Keyword(KwTy::End));
+    let libs = vec![(target.name.clone(), 1u8, Some(target.addr))];
+    let mut syntax = Syntax::new(body_tokens).with_libs(libs);
+    if !consts.is_empty() {
+        let const_nodes = consts
+            .iter()
+            .map(|(name, node)| (name.clone(), clone_box(node.as_ref())))
+            .collect();
+        syntax = syntax.with_consts(const_nodes);
+    }
+    let (irnodes, _) = syntax.parse()?;
+    let codes = irnodes.codegen().map_err(|e| e.to_string())?;
+    verify_bytecodes(&codes).map_err(|e| e.to_string())?;
+    Ok(codes)
+}
+
+#[cfg(test)]
+mod parse_test_tests {
+    use super::*;
+
+    fn target() -> FitshTestTarget {
+        FitshTestTarget {
+            name: "Token".to_owned(),
+            addr: field::Address::from_readable("emqjNS9PscqdBpMtnC3Jfuc4mvZUPYTPS").unwrap(),
+        }
+    }
+
+    #[test]
+    fn parses_fields_and_expectations() {
+        let src = r#"
+            pragma fitsh 1.0.0
+            test works {
+                height: 20
+                caller: 1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9
+                balance: [1MzNY1oA3kfgYi75zquj3SRUPYztzXHzK9: amount("1:248")]
+                run { Token.total() }
+                expect { logs: 2, storage: ["k": 7u64], fail: "denied" }
+            }
+        "#;
+        let cases = compile_tests(src, &target()).unwrap();
+        assert_eq!(cases.len(), 1);
+        let case = &cases[0];
+        assert_eq!(case.height, Some(20));
+        assert_eq!(case.balances.len(), 1);
+        assert_eq!(case.expect.logs, Some(2));
+        assert_eq!(
+            case.expect.storage,
+            vec![(Value::Bytes(b"k".to_vec()), Value::U64(7))]
+        );
+        assert_eq!(case.expect.fail.as_deref(), Some("denied"));
+        assert!(!case.codes.is_empty());
+    }
+
+    #[test]
+    fn rejects_duplicate_test_names() {
+        let src = "pragma fitsh 1.0.0 test a { run { end } } test a { run { end } }";
+        let err = compile_tests(src, &target()).unwrap_err();
+        assert!(err.contains("duplicate test 'a'"), "{err}");
+    }
+
+    #[test]
+    fn rejects_test_without_run_block() {
+        let src = "pragma fitsh 1.0.0 test a { height: 1 }";
+        let err = compile_tests(src, &target()).unwrap_err();
+        assert!(err.contains("missing run block"), "{err}");
+    }
+}
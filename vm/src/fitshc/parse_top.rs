@@ -1,4 +1,5 @@
 use super::compile_body::{CompiledCode, compile_body};
+use super::lint::LintUnit;
 use super::parse_deploy::parse_deploy;
 use super::parse_func::{
     parse_func_body_tokens, parse_func_sig, parse_function, parse_optional_code_modifier,
@@ -38,7 +39,7 @@ pub fn parse_top_level(state: &mut ParseState) -> Ret<()> {
     Ok(())
 }
 
-fn parse_required_pragma(state: &mut ParseState) -> Rerr {
+pub(super) fn parse_required_pragma(state: &mut ParseState) -> Rerr {
     if !matches!(state.current(), Some(Keyword(KwTy::Pragma))) {
         return errf!(
             "expected 'pragma fitsh {}' at file start",
@@ -128,40 +129,43 @@ fn parse_optional_const_type(state: &mut ParseState) -> Ret<Option<ValueTy>> {
     Ok(Some(ty))
 }
 
-fn parse_contract_body_item(state: &mut ParseState) -> Ret<()> {
-    state.skip_soft_separators();
-    match state.current() {
-        Some(Keyword(KwTy::Const)) => {
-            // Parse top-level const: const NAME = VALUE
-            state.advance(); // consume 'const'
-            let name = if let Some(Identifier(n)) = state.current() {
-                n.clone()
-            } else {
-                return errf!("expected const name after 'const'");
-            };
-            state.advance();
-            let explicit_ty = parse_optional_const_type(state)?;
+pub(super) fn parse_top_const(state: &mut ParseState) -> Rerr {
+    // Parse top-level const: const NAME = VALUE
+    state.advance(); // consume 'const'
+    let name = if let Some(Identifier(n)) = state.current() {
+        n.clone()
+    } else {
+        return errf!("expected const name after 'const'");
+    };
+    state.advance();
+    let explicit_ty = parse_optional_const_type(state)?;
 
-            // Expect '='
-            if let Some(Keyword(KwTy::Assign)) = state.current() {
-                state.advance();
-            } else {
-                return errf!("expected '=' after const name");
-            }
+    // Expect '='
+    if let Some(Keyword(KwTy::Assign)) = state.current() {
+        state.advance();
+    } else {
+        return errf!("expected '=' after const name");
+    }
 
-            // Parse the const value
-            let token = state
-                .current()
-                .cloned()
-                .ok_or_else(|| "expected const value but got EOF".to_string())?;
-            state.advance();
-            let literal = crate::lang::parse_const_literal(token, explicit_ty)?;
+    // Parse the const value
+    let token = state
+        .current()
+        .cloned()
+        .ok_or_else(|| "expected const value but got EOF".to_string())?;
+    state.advance();
+    let literal = crate::lang::parse_const_literal(token, explicit_ty)?;
 
-            if state.consts.iter().any(|(n, _)| n == &name) {
-                return errf!("duplicate const '{}'", name);
-            }
-            state.consts.push((name, literal.node));
-        }
+    if state.consts.iter().any(|(n, _)| n == &name) {
+        return errf!("duplicate const '{}'", name);
+    }
+    state.consts.push((name, literal.node));
+    Ok(())
+}
+
+fn parse_contract_body_item(state: &mut ParseState) -> Ret<()> {
+    state.skip_soft_separators();
+    match state.current() {
+        Some(Keyword(KwTy::Const)) => parse_top_const(state)?,
         Some(Keyword(KwTy::Deploy)) => {
             if state.deploy.is_some() {
                 return errf!("duplicate deploy block");
@@ -242,7 +246,7 @@ fn parse_contract_body_item(state: &mut ParseState) -> Ret<()> {
             }
 
             // compile abstract body using shared compile function
-            let (_irnodes, compiled, source_map) = compile_body(
+            let (irnodes, compiled, source_map) = compile_body(
                 body_tokens,
                 args.clone(),
                 &state.libs,
@@ -258,6 +262,13 @@ fn parse_contract_body_item(state: &mut ParseState) -> Ret<()> {
                 return errf!("duplicate abstract '{}'", name);
             }
             state.contract = state.contract.clone().syst(abst);
+            if let Some(units) = state.lint_units.as_mut() {
+                units.push(LintUnit {
+                    name: format!("abstract::{}", name),
+                    external: false,
+                    irnodes,
+                });
+            }
             state
                 .source_maps
                 .push((format!("abstract::{}", name), source_map));
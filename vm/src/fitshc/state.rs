@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use super::lint::LintUnit;
 use super::parse_deploy::DeployInfo;
 use crate::IRNode;
 use crate::Token::*;
@@ -22,6 +23,8 @@ pub struct ParseState {
     /// Top-level constants injected into each compiled body.
     pub consts: Vec<(String, Box<dyn IRNode>)>,
     pub warnings: Vec<String>,
+    /// Compiled body IR, collected only when `lint` asks for it.
+    pub lint_units: Option<Vec<LintUnit>>,
     pub version: Option<FitshVersion>,
     pub userfunc_signs: HashSet<[u8; 4]>,
     pub abst_signs: HashSet<u8>,
@@ -68,6 +71,7 @@ impl ParseState {
             source_maps: Vec::new(),
             consts: Vec::new(),
             warnings: Vec::new(),
+            lint_units: None,
             version: None,
             userfunc_signs: HashSet::new(),
             abst_signs: HashSet::new(),
@@ -9,6 +9,7 @@ impl CallFrame {
         param: Option<Value>,
     ) -> VmrtRes<Value> {
         use CallExit::*;
+        let tracing = protocol::context::trace_enabled();
         macro_rules! curr { () => { self.frames.last().unwrap() }; }
         macro_rules! curr_mut { () => { self.frames.last_mut().unwrap() }; }
         macro_rules! prepare_and_push {
@@ -24,6 +25,9 @@ impl CallFrame {
             ($retv:expr) => {{
                 let mut retv = $retv;
                 curr!().check_output_type(&mut retv, &r.warm.space_cap)?;
+                if tracing {
+                    protocol::context::trace_leave("vm.return", retv.to_string());
+                }
                 self.pop().unwrap().reclaim(r);
                 loop {
                     let is_tail = match self.frames.last() {
@@ -38,6 +42,9 @@ impl CallFrame {
                         .last()
                         .unwrap()
                         .check_output_type(&mut retv, &r.warm.space_cap)?;
+                    if tracing {
+                        protocol::context::trace_leave("vm.return", retv.to_string());
+                    }
                     self.pop().unwrap().reclaim(r);
                 }
             }};
@@ -45,6 +52,17 @@ impl CallFrame {
 
         assert!(self.len() == 0);
         let height = host.height();
+        if tracing {
+            protocol::context::trace_enter(
+                "vm.entry",
+                format!(
+                    "{:?} {:?} context {}",
+                    exec.entry,
+                    exec.effect,
+                    bindings.context_addr.to_readable()
+                ),
+            );
+        }
 
         exec.ensure_call_depth(&r.warm.space_cap)?;
         let mut root = self.increase(r)?;
@@ -68,6 +86,9 @@ impl CallFrame {
                     curr_mut!().oprnds.peek()?.check_container_cap(&r.warm.space_cap)?;
                     let mut plan = r.plan_user_call(host, &spec, &curr_bindings)?;
                     plan.next_bindings.intent_scope = curr!().intent_state.current_scope();
+                    if tracing {
+                        trace_user_call(&spec, &plan.next_bindings, curr_mut!().oprnds.peek()?);
+                    }
 
                     match spec {
                         CallSpec::Splice { .. } => {
@@ -112,6 +133,9 @@ impl CallFrame {
                         retv = curr_mut!().pop_value()?;
                     }
                     if matches!(exit, Abort | Throw) {
+                        if tracing {
+                            protocol::context::trace_leave("vm.throw", retv.to_string());
+                        }
                         return itr_err_fmt!(ThrowAbort, "VM return failed: {}", retv);
                     }
                     settle_return!(retv);
@@ -120,3 +144,19 @@ impl CallFrame {
         }
     }
 }
+
+fn trace_user_call(spec: &CallSpec, next: &FrameBindings, param: &Value) {
+    let owner = next
+        .code_contract
+        .as_ref()
+        .map(|c| c.to_readable())
+        .unwrap_or_default();
+    let detail = format!("{} {} argv {}", owner, hex::encode(spec.selector()), param);
+    match spec {
+        // splice runs inside the current frame, so it does not nest
+        CallSpec::Splice { .. } => protocol::context::trace_push("vm.splice", detail),
+        CallSpec::Invoke { effect, .. } => {
+            protocol::context::trace_enter("vm.call", format!("{:?} {}", effect, detail));
+        }
+    }
+}
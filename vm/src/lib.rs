@@ -30,7 +30,23 @@ use std::sync::OnceLock;
 static RUNTIME_POOL_INSTANCE: OnceLock<RuntimePool> = OnceLock::new();
 
 pub fn global_runtime_pool() -> &'static RuntimePool {
-    RUNTIME_POOL_INSTANCE.get_or_init(|| RuntimePool::new())
+    RUNTIME_POOL_INSTANCE.get_or_init(|| {
+        basis::metrics::register_collector(export_contract_cache_metrics);
+        RuntimePool::new()
+    })
+}
+
+fn export_contract_cache_metrics() {
+    use basis::metrics::*;
+    let st = global_runtime_pool().contract_cache().stats();
+    gauge_set("hacash_vm_contract_cache_enabled", &[], st.enabled as u8 as f64);
+    gauge_set("hacash_vm_contract_cache_max_bytes", &[], st.max_bytes as f64);
+    gauge_set("hacash_vm_contract_cache_used_bytes", &[], st.used_bytes as f64);
+    gauge_set("hacash_vm_contract_cache_entries", &[], st.entries as f64);
+    counter_set("hacash_vm_contract_cache_hits_total", &[], st.hits);
+    counter_set("hacash_vm_contract_cache_misses_total", &[], st.misses);
+    counter_set("hacash_vm_contract_cache_inserts_total", &[], st.inserts);
+    counter_set("hacash_vm_contract_cache_evicts_total", &[], st.evicts);
 }
 
 /// Configure the contract cache pool.